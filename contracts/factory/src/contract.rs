@@ -1,20 +1,22 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, ensure, to_json_binary, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Order, Reply,
-    ReplyOn, Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, WasmMsg,
+    attr, ensure, to_json_binary, Binary, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Order, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult,
+    WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::Bound;
 use cw_utils::parse_instantiate_response_data;
 use itertools::Itertools;
 
 use astroport::asset::{addr_opt_validate, AssetInfo, PairInfo};
-use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner};
 use astroport::factory::{
-    Config, ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, MigrateMsg, PairConfig,
-    PairType, PairsResponse, QueryMsg, TrackerConfig,
+    Config, ConfigResponse, ExecuteMsg, FeeInfoResponse, GlobalStatsResponse, InstantiateMsg,
+    MigrateMsg, PairConfig, PairType, PairsResponse, QueryMsg, SwapPathResponse, TrackerConfig,
+    WhitelistEntry, WhitelistResponse, MIN_ADMIN_TRANSFER_DELAY,
 };
 use astroport::incentives::ExecuteMsg::DeactivatePool;
 use astroport::pair::InstantiateMsg as PairInstantiateMsg;
@@ -23,8 +25,9 @@ use crate::error::ContractError;
 use crate::migration::migrate_pair_configs;
 use crate::querier::query_pair_info;
 use crate::state::{
-    check_asset_infos, pair_key, read_pairs, TmpPairInfo, CONFIG, OWNERSHIP_PROPOSAL, PAIRS,
-    PAIR_CONFIGS, TMP_PAIR_INFO, TRACKER_CONFIG,
+    calc_range_start, check_asset_infos, pair_key, read_pairs, AdminProposal, TmpPairInfo,
+    ADMIN_PROPOSAL, CONFIG, OWNERSHIP_PROPOSAL, PAIRS, PAIR_ASSETS, PAIR_CONFIGS, TMP_PAIR_INFO,
+    TRACKER_CONFIG,
 };
 
 /// Contract name that is used for migration.
@@ -33,6 +36,12 @@ const CONTRACT_NAME: &str = "astroport-factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// A `reply` call code ID used in a sub-message.
 const INSTANTIATE_PAIR_REPLY_ID: u64 = 1;
+/// The maximum number of pairs in a route returned by [`QueryMsg::SwapPath`]
+const MAX_SWAP_PATH_HOPS: usize = 3;
+/// The default number of pairs queried by [`QueryMsg::GlobalStats`]
+const DEFAULT_GLOBAL_STATS_PAIR_LIMIT: u32 = 30;
+/// The maximum number of pairs queried by [`QueryMsg::GlobalStats`]
+const MAX_GLOBAL_STATS_PAIR_LIMIT: u32 = 100;
 
 /// Creates a new contract with the specified parameters packed in the `msg` variable.
 ///
@@ -117,6 +126,8 @@ pub struct UpdateConfig {
 ///             generator_address,
 ///         }** Updates general contract parameters.
 ///
+/// * **ExecuteMsg::SetFeeRecipient { recipient }** Sets the address governance fees are sent to.
+///
 /// * **ExecuteMsg::UpdatePairConfig { config }** Updates a pair type
 /// * configuration or creates a new pair type if a [`Custom`] name is used (which hasn't been used before).
 ///
@@ -129,11 +140,19 @@ pub struct UpdateConfig {
 /// * **ExecuteMsg::Deregister { asset_infos }** Removes an existing pair from the factory.
 /// * The asset information is for the assets that are traded in the pair.
 ///
-/// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Creates a request to change contract ownership.
+/// * **ExecuteMsg::UpdatePairKey { old_asset_infos }** Registers the calling pair under its current assets.
+///
+/// * **ExecuteMsg::ProposeNewOwner { owner, expires_in }** Starts a time-locked admin transfer with the minimum delay.
+///
+/// * **ExecuteMsg::DropOwnershipProposal {}** Removes the pending admin transfer.
+///
+/// * **ExecuteMsg::ClaimOwnership {}** Accepts the pending admin transfer after its delay.
+///
+/// * **ExecuteMsg::ProposeAdmin { new_admin, delay_seconds }** Starts a time-locked admin transfer.
 ///
-/// * **ExecuteMsg::DropOwnershipProposal {}** Removes a request to change contract ownership.
+/// * **ExecuteMsg::AcceptAdmin {}** Accepts the pending admin transfer after its delay.
 ///
-/// * **ExecuteMsg::ClaimOwnership {}** Claims contract ownership.
+/// * **ExecuteMsg::DropAdminProposal {}** Removes the pending admin transfer.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -159,6 +178,7 @@ pub fn execute(
                 coin_registry_address,
             },
         ),
+        ExecuteMsg::SetFeeRecipient { recipient } => set_fee_recipient(deps, info, recipient),
         ExecuteMsg::UpdatePairConfig { config } => execute_update_pair_config(deps, info, config),
         ExecuteMsg::CreatePair {
             pair_type,
@@ -166,42 +186,116 @@ pub fn execute(
             init_params,
         } => execute_create_pair(deps, info, env, pair_type, asset_infos, init_params),
         ExecuteMsg::Deregister { asset_infos } => deregister(deps, info, asset_infos),
-        ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
-            let config = CONFIG.load(deps.storage)?;
-
-            propose_new_owner(
-                deps,
-                info,
-                env,
-                owner,
-                expires_in,
-                config.owner,
-                OWNERSHIP_PROPOSAL,
-            )
-            .map_err(Into::into)
-        }
-        ExecuteMsg::DropOwnershipProposal {} => {
-            let config = CONFIG.load(deps.storage)?;
-
-            drop_ownership_proposal(deps, info, config.owner, OWNERSHIP_PROPOSAL)
-                .map_err(Into::into)
-        }
-        ExecuteMsg::ClaimOwnership {} => {
-            claim_ownership(deps, info, env, OWNERSHIP_PROPOSAL, |deps, new_owner| {
-                CONFIG
-                    .update::<_, StdError>(deps.storage, |mut v| {
-                        v.owner = new_owner;
-                        Ok(v)
-                    })
-                    .map(|_| ())
-            })
-            .map_err(Into::into)
+        ExecuteMsg::UpdatePairKey { old_asset_infos } => {
+            update_pair_key(deps, info, old_asset_infos)
         }
         ExecuteMsg::UpdateTrackerConfig {
             tracker_code_id,
             token_factory_addr,
         } => update_tracker_config(deps, info, tracker_code_id, token_factory_addr),
+        // The legacy ownership messages go through the time-locked admin transfer
+        ExecuteMsg::ProposeNewOwner { owner, .. } => {
+            propose_admin(deps, env, info, owner, MIN_ADMIN_TRANSFER_DELAY)
+        }
+        ExecuteMsg::DropOwnershipProposal {} => drop_admin_proposal(deps, info),
+        ExecuteMsg::ClaimOwnership {} => accept_admin(deps, env, info),
+        ExecuteMsg::ProposeAdmin {
+            new_admin,
+            delay_seconds,
+        } => propose_admin(deps, env, info, new_admin, delay_seconds),
+        ExecuteMsg::AcceptAdmin {} => accept_admin(deps, env, info),
+        ExecuteMsg::DropAdminProposal {} => drop_admin_proposal(deps, info),
+    }
+}
+
+/// Starts a time-locked admin transfer replacing the pending one.
+///
+/// * **new_admin** the proposed admin.
+///
+/// * **delay_seconds** the delay after which `new_admin` can accept the transfer.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn propose_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_admin: String,
+    delay_seconds: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if delay_seconds < MIN_ADMIN_TRANSFER_DELAY {
+        return Err(ContractError::AdminTransferDelayTooShort {
+            min: MIN_ADMIN_TRANSFER_DELAY,
+        });
     }
+
+    let proposal = AdminProposal {
+        new_admin: deps.api.addr_validate(&new_admin)?,
+        accepts_at: env.block.time.seconds() + delay_seconds,
+    };
+    ADMIN_PROPOSAL.save(deps.storage, &proposal)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "propose_admin"),
+        attr("new_admin", new_admin),
+        attr("accepts_at", proposal.accepts_at.to_string()),
+    ]))
+}
+
+/// Accepts the pending admin transfer once its delay has passed.
+///
+/// ## Executor
+/// Only the proposed admin can execute this.
+pub fn accept_admin(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let proposal = ADMIN_PROPOSAL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingAdmin {})?;
+
+    if info.sender != proposal.new_admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if env.block.time.seconds() < proposal.accepts_at {
+        return Err(ContractError::AdminTransferLocked {
+            accepts_at: proposal.accepts_at,
+        });
+    }
+
+    CONFIG.update::<_, StdError>(deps.storage, |mut config| {
+        config.owner = proposal.new_admin.clone();
+        Ok(config)
+    })?;
+    ADMIN_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "accept_admin"),
+        attr("new_admin", proposal.new_admin),
+    ]))
+}
+
+/// Removes the pending admin transfer.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn drop_admin_proposal(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ADMIN_PROPOSAL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoPendingAdmin {})?;
+    ADMIN_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new().add_attribute("action", "drop_admin_proposal"))
 }
 
 /// Updates general contract settings.
@@ -249,6 +343,39 @@ pub fn execute_update_config(
     Ok(Response::new().add_attribute("action", "update_config"))
 }
 
+/// Sets the address governance fees are sent to.
+/// The recipient balances are queried to make sure the address is an active account able to receive funds.
+///
+/// * **recipient** is the new fee recipient.
+///
+/// ## Executor
+/// Only the owner can execute this.
+pub fn set_fee_recipient(
+    deps: DepsMut,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+
+    // Permission check
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    deps.querier
+        .query_all_balances(&recipient)
+        .map_err(|_| ContractError::FeeRecipientNotActive(recipient.to_string()))?;
+
+    config.fee_address = Some(recipient.clone());
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "set_fee_recipient")
+        .add_attribute("recipient", recipient))
+}
+
 /// Updates a pair type's configuration.
 ///
 /// * **pair_config** is an object of type [`PairConfig`] that contains the pair type information to update.
@@ -319,7 +446,13 @@ pub fn execute_create_pair(
     }
 
     let pair_key = pair_key(&asset_infos);
-    TMP_PAIR_INFO.save(deps.storage, &TmpPairInfo { pair_key })?;
+    TMP_PAIR_INFO.save(
+        deps.storage,
+        &TmpPairInfo {
+            pair_key,
+            asset_infos: asset_infos.clone(),
+        },
+    )?;
 
     let sub_msg: Vec<SubMsg> = vec![SubMsg {
         id: INSTANTIATE_PAIR_REPLY_ID,
@@ -371,6 +504,7 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
             let pair_contract = deps.api.addr_validate(&init_response.contract_address)?;
 
             PAIRS.save(deps.storage, &tmp.pair_key, &pair_contract)?;
+            PAIR_ASSETS.save(deps.storage, &tmp.pair_key, &tmp.asset_infos)?;
 
             Ok(Response::new().add_attributes(vec![
                 attr("action", "register"),
@@ -402,6 +536,7 @@ pub fn deregister(
 
     let pair_addr = PAIRS.load(deps.storage, &pair_key(&asset_infos))?;
     PAIRS.remove(deps.storage, &pair_key(&asset_infos));
+    PAIR_ASSETS.remove(deps.storage, &pair_key(&asset_infos));
 
     let mut messages: Vec<CosmosMsg> = vec![];
     if let Some(generator) = config.generator_address {
@@ -423,6 +558,44 @@ pub fn deregister(
     ]))
 }
 
+/// Registers a pair under its current assets after one of them was replaced.
+///
+/// * **old_asset_infos** the assets the pair was registered with.
+///
+/// ## Executor
+/// Only the pair registered under `old_asset_infos` can execute this.
+pub fn update_pair_key(
+    deps: DepsMut,
+    info: MessageInfo,
+    old_asset_infos: Vec<AssetInfo>,
+) -> Result<Response, ContractError> {
+    let old_key = pair_key(&old_asset_infos);
+    let pair_addr = PAIRS
+        .may_load(deps.storage, &old_key)?
+        .ok_or(ContractError::Unauthorized {})?;
+
+    if info.sender != pair_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let pair_info = query_pair_info(&deps.querier, &pair_addr)?;
+    let new_key = pair_key(&pair_info.asset_infos);
+    if PAIRS.has(deps.storage, &new_key) {
+        return Err(ContractError::PairWasRegistered {});
+    }
+
+    PAIRS.remove(deps.storage, &old_key);
+    PAIRS.save(deps.storage, &new_key, &pair_addr)?;
+    PAIR_ASSETS.remove(deps.storage, &old_key);
+    PAIR_ASSETS.save(deps.storage, &new_key, &pair_info.asset_infos)?;
+
+    Ok(Response::new().add_attributes(vec![
+        attr("action", "update_pair_key"),
+        attr("pair_contract_addr", pair_addr),
+        attr("asset_infos", pair_info.asset_infos.iter().join(", ")),
+    ]))
+}
+
 pub fn update_tracker_config(
     deps: DepsMut,
     info: MessageInfo,
@@ -465,6 +638,16 @@ pub fn update_tracker_config(
 /// * **QueryMsg::FeeInfo { pair_type }** Returns the fee structure (total and maker fees) for a specific pair type.
 ///
 /// * **QueryMsg::BlacklistedPairTypes {}** Returns a vector that contains blacklisted pair types (pair types that cannot get ASTRO emissions).
+///
+/// * **QueryMsg::Whitelist {}** Returns whitelisted pair code IDs using a custom [`WhitelistResponse`] structure.
+///
+/// * **QueryMsg::IsWhitelisted { code_id }** Returns whether a pair code ID is allowed to create pairs.
+///
+/// * **QueryMsg::SwapPath { from, to }** Returns the shortest route between two assets using a custom
+/// [`SwapPathResponse`] structure.
+///
+/// * **QueryMsg::GlobalStats { start_after, pair_limit }** Returns aggregated pair statistics using a custom
+/// [`GlobalStatsResponse`] structure.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -476,6 +659,15 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::FeeInfo { pair_type } => to_json_binary(&query_fee_info(deps, pair_type)?),
         QueryMsg::BlacklistedPairTypes {} => to_json_binary(&query_blacklisted_pair_types(deps)?),
         QueryMsg::TrackerConfig {} => to_json_binary(&query_tracker_config(deps)?),
+        QueryMsg::Whitelist {} => to_json_binary(&query_whitelist(deps)?),
+        QueryMsg::IsWhitelisted { code_id } => {
+            to_json_binary(&query_is_whitelisted(deps, code_id)?)
+        }
+        QueryMsg::SwapPath { from, to } => to_json_binary(&query_swap_path(deps, from, to)?),
+        QueryMsg::GlobalStats {
+            start_after,
+            pair_limit,
+        } => to_json_binary(&query_global_stats(deps, start_after, pair_limit)?),
     }
 }
 
@@ -496,9 +688,185 @@ pub fn query_blacklisted_pair_types(deps: Deps) -> StdResult<Vec<PairType>> {
         .collect()
 }
 
+/// Returns whitelisted pair code IDs using a custom [`WhitelistResponse`] structure.
+pub fn query_whitelist(deps: Deps) -> StdResult<WhitelistResponse> {
+    let entries = PAIR_CONFIGS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (_, pair_config) = item?;
+            Ok(WhitelistEntry {
+                code_id: pair_config.code_id,
+                pair_type: pair_config.pair_type,
+                allowed: !pair_config.is_disabled,
+                max_total_fee_bps: pair_config.total_fee_bps,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(WhitelistResponse { entries })
+}
+
+/// Returns whether any enabled pair type uses the given code ID.
+pub fn query_is_whitelisted(deps: Deps, code_id: u64) -> StdResult<bool> {
+    Ok(query_whitelist(deps)?
+        .entries
+        .iter()
+        .any(|entry| entry.code_id == code_id && entry.allowed))
+}
+
+/// Finds the shortest route of at most [`MAX_SWAP_PATH_HOPS`] pairs between two assets
+/// with a breadth-first search over all registered pairs. The graph is built from the pair assets
+/// kept in the factory storage, so only the pairs on the found route are queried.
+///
+/// * **from** is the asset to swap from.
+///
+/// * **to** is the asset to swap to.
+pub fn query_swap_path(deps: Deps, from: AssetInfo, to: AssetInfo) -> StdResult<SwapPathResponse> {
+    if from.equal(&to) {
+        return Err(StdError::generic_err("Swap path assets must be different"));
+    }
+
+    let pairs = PAIRS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (key, pair_addr) = item?;
+            // Pairs registered before the assets were stored have to be queried
+            let asset_infos = match PAIR_ASSETS.may_load(deps.storage, &key)? {
+                Some(asset_infos) => asset_infos,
+                None => query_pair_info(&deps.querier, &pair_addr)?.asset_infos,
+            };
+            Ok((pair_addr, asset_infos))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    // Each queue item holds the last reached asset and the pairs used to reach it
+    // together with the asset received from each of them
+    let mut queue = VecDeque::from([(from.clone(), vec![])]);
+    let mut visited = vec![from];
+    let mut path: Option<Vec<(usize, AssetInfo)>> = None;
+
+    'search: while let Some((asset, hops)) = queue.pop_front() {
+        if hops.len() == MAX_SWAP_PATH_HOPS {
+            continue;
+        }
+
+        for (ind, (_, asset_infos)) in pairs.iter().enumerate() {
+            if !asset_infos.contains(&asset) {
+                continue;
+            }
+
+            for next in asset_infos.iter() {
+                if visited.contains(next) {
+                    continue;
+                }
+
+                let mut next_hops = hops.clone();
+                next_hops.push((ind, next.clone()));
+
+                if next.equal(&to) {
+                    path = Some(next_hops);
+                    break 'search;
+                }
+
+                visited.push(next.clone());
+                queue.push_back((next.clone(), next_hops));
+            }
+        }
+    }
+
+    let path = path.ok_or_else(|| {
+        StdError::generic_err(
+            ContractError::NoPathFound {
+                max_hops: MAX_SWAP_PATH_HOPS,
+            }
+            .to_string(),
+        )
+    })?;
+
+    let mut hops = Vec::with_capacity(path.len());
+    let mut intermediate_assets = Vec::with_capacity(path.len());
+    let mut fee_ratio = Decimal::one();
+    for (ind, asset) in path {
+        let pair_info = query_pair_info(&deps.querier, &pairs[ind].0)?;
+        let total_fee_bps = PAIR_CONFIGS
+            .may_load(deps.storage, pair_info.pair_type.to_string())?
+            .map(|pair_config| pair_config.total_fee_bps)
+            .unwrap_or_default();
+        fee_ratio *= Decimal::one() - Decimal::from_ratio(total_fee_bps, 10000u16);
+
+        hops.push(pair_info);
+        intermediate_assets.push(asset);
+    }
+    // The asset received from the last hop is the target one
+    intermediate_assets.pop();
+
+    Ok(SwapPathResponse {
+        hops,
+        intermediate_assets,
+        total_fee_rate: Decimal::one() - fee_ratio,
+    })
+}
+
+/// Returns aggregated statistics over the registered pairs.
+///
+/// * **start_after** the pair after which the pairs are queried to build `pairs_by_type`.
+///
+/// * **pair_limit** caps the number of pairs whose info is queried to build `pairs_by_type`.
+/// `total_pairs` always counts all registered pairs.
+///
+/// The factory has no price oracle, so USD TVL and volume are not available.
+pub fn query_global_stats(
+    deps: Deps,
+    start_after: Option<Vec<AssetInfo>>,
+    pair_limit: Option<u32>,
+) -> StdResult<GlobalStatsResponse> {
+    let total_pairs = PAIRS
+        .keys(deps.storage, None, None, Order::Ascending)
+        .count() as u32;
+
+    let limit = pair_limit
+        .unwrap_or(DEFAULT_GLOBAL_STATS_PAIR_LIMIT)
+        .min(MAX_GLOBAL_STATS_PAIR_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+    // One more pair is read to find out whether there is a next page
+    let pairs = PAIRS
+        .range(
+            deps.storage,
+            start.as_deref().map(Bound::exclusive),
+            None,
+            Order::Ascending,
+        )
+        .take(limit + 1)
+        .collect::<StdResult<Vec<_>>>()?;
+    let has_more = pairs.len() > limit;
+
+    let mut pairs_by_type: Vec<(PairType, u32)> = vec![];
+    let mut last_pair = None;
+    for (_, pair_addr) in pairs.into_iter().take(limit) {
+        let pair_info = query_pair_info(&deps.querier, pair_addr)?;
+        match pairs_by_type
+            .iter_mut()
+            .find(|(ty, _)| *ty == pair_info.pair_type)
+        {
+            Some((_, count)) => *count += 1,
+            None => pairs_by_type.push((pair_info.pair_type, 1)),
+        }
+        last_pair = Some(pair_info.asset_infos);
+    }
+
+    Ok(GlobalStatsResponse {
+        total_pairs,
+        total_tvl_usd: None,
+        total_24h_volume_usd: None,
+        pairs_by_type,
+        next_start_after: last_pair.filter(|_| has_more),
+    })
+}
+
 /// Returns general contract parameters using a custom [`ConfigResponse`] structure.
 pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config = CONFIG.load(deps.storage)?;
+    let admin_proposal = ADMIN_PROPOSAL.may_load(deps.storage)?;
     let resp = ConfigResponse {
         owner: config.owner,
         token_code_id: config.token_code_id,
@@ -510,6 +878,8 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         generator_address: config.generator_address,
         whitelist_code_id: config.whitelist_code_id,
         coin_registry_address: config.coin_registry_address,
+        pending_admin: admin_proposal.as_ref().map(|p| p.new_admin.clone()),
+        pending_admin_accepts_at: admin_proposal.map(|p| p.accepts_at),
     };
 
     Ok(resp)
@@ -596,6 +966,9 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, Co
         _ => return Err(ContractError::MigrationError {}),
     }
 
+    // Untimed ownership proposals can't be claimed anymore
+    OWNERSHIP_PROPOSAL.remove(deps.storage);
+
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::new()
@@ -36,4 +36,19 @@ pub enum ContractError {
 
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
+
+    #[error("Fee recipient {0} is not an active account")]
+    FeeRecipientNotActive(String),
+
+    #[error("No swap path found within {max_hops} hops")]
+    NoPathFound { max_hops: usize },
+
+    #[error("Admin transfer delay must be at least {min} seconds")]
+    AdminTransferDelayTooShort { min: u64 },
+
+    #[error("No pending admin transfer")]
+    NoPendingAdmin {},
+
+    #[error("Admin transfer can be accepted from {accepts_at}")]
+    AdminTransferLocked { accepts_at: u64 },
 }
@@ -1,9 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_json, to_json_binary, Coin, Empty, OwnedDeps, Querier, QuerierResult, QueryRequest,
-    SystemError, SystemResult, WasmQuery,
+    from_json, to_json_binary, BankQuery, Coin, Empty, OwnedDeps, Querier, QuerierResult,
+    QueryRequest, SystemError, SystemResult, WasmQuery,
 };
 
 use astroport::asset::PairInfo;
@@ -28,6 +28,7 @@ pub fn mock_dependencies(
 pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     astroport_pair_querier: AstroportPairQuerier,
+    inactive_accounts: HashSet<String>,
 }
 
 #[derive(Clone, Default)]
@@ -87,6 +88,14 @@ impl WasmMockQuerier {
                     }
                     _ => panic!("DO NOT ENTER HERE")
             }
+            QueryRequest::Bank(BankQuery::AllBalances { address })
+                if self.inactive_accounts.contains(address) =>
+            {
+                SystemResult::Err(SystemError::InvalidRequest {
+                    error: format!("Account {address} does not exist"),
+                    request: to_json_binary(request).unwrap(),
+                })
+            }
             _ => self.base.handle_query(request),
         }
     }
@@ -97,6 +106,7 @@ impl WasmMockQuerier {
         WasmMockQuerier {
             base,
             astroport_pair_querier: AstroportPairQuerier::default(),
+            inactive_accounts: HashSet::new(),
         }
     }
 
@@ -104,4 +114,9 @@ impl WasmMockQuerier {
     pub fn with_astroport_pairs(&mut self, pairs: &[(&String, &PairInfo)]) {
         self.astroport_pair_querier = AstroportPairQuerier::new(pairs);
     }
+
+    // Configure the accounts whose balances can't be queried
+    pub fn with_inactive_accounts(&mut self, accounts: &[&str]) {
+        self.inactive_accounts = accounts.iter().map(|addr| addr.to_string()).collect();
+    }
 }
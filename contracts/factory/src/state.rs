@@ -11,6 +11,7 @@ use astroport::factory::{Config, PairConfig, TrackerConfig};
 #[cw_serde]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
+    pub asset_infos: Vec<AssetInfo>,
 }
 
 /// Saves a pair's key
@@ -22,6 +23,22 @@ pub const CONFIG: Item<Config> = Item::new("config");
 /// Saves created pairs (from olders to latest)
 pub const PAIRS: Map<&[u8], Addr> = Map::new("pair_info");
 
+/// Saves the assets of created pairs under the same keys as [`PAIRS`].
+/// Not set for pairs registered before this map was introduced
+pub const PAIR_ASSETS: Map<&[u8], Vec<AssetInfo>> = Map::new("pair_assets");
+
+/// This structure describes a pending time-locked admin transfer.
+#[cw_serde]
+pub struct AdminProposal {
+    /// The proposed admin
+    pub new_admin: Addr,
+    /// The timestamp (in seconds) from which the proposed admin can accept the transfer
+    pub accepts_at: u64,
+}
+
+/// Stores the pending admin transfer
+pub const ADMIN_PROPOSAL: Item<AdminProposal> = Item::new("admin_proposal");
+
 /// Track config for tracking contract
 pub const TRACKER_CONFIG: Item<TrackerConfig> = Item::new("tracker_config");
 
@@ -90,7 +107,7 @@ pub fn read_pairs(
 ///
 /// `start_after` is an [`Option`] type that accepts [`AssetInfo`] elements.
 /// It is the token pair which we use to determine the start index for a range when returning data for multiple pairs
-fn calc_range_start(start_after: Option<Vec<AssetInfo>>) -> Option<Vec<u8>> {
+pub(crate) fn calc_range_start(start_after: Option<Vec<AssetInfo>>) -> Option<Vec<u8>> {
     start_after.map(|ref asset| {
         let mut key = pair_key(asset);
         key.push(1);
@@ -112,7 +129,7 @@ pub(crate) fn check_asset_infos(
         .map_err(Into::into)
 }
 
-/// Stores the latest contract ownership transfer proposal
+/// This state key isn't used anymore. It's cleared on migration as ownership transfers are time-locked now
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
 /// This state key isn't used anymore but left for backward compatability with old pairs
@@ -13,6 +13,7 @@ use crate::{
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::factory::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, PairConfig, PairType, PairsResponse, QueryMsg,
+    MIN_ADMIN_TRANSFER_DELAY,
 };
 
 use crate::contract::reply;
@@ -221,6 +222,59 @@ fn update_config() {
     assert_eq!(res, ContractError::Unauthorized {});
 }
 
+#[test]
+fn set_fee_recipient() {
+    let mut deps = mock_dependencies(&[]);
+    let owner = "owner0000";
+
+    let msg = InstantiateMsg {
+        pair_configs: vec![],
+        token_code_id: 123u64,
+        fee_address: None,
+        owner: owner.to_string(),
+        generator_address: None,
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+        tracker_config: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info(owner, &[]);
+    instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::SetFeeRecipient {
+        recipient: String::from("maker"),
+    };
+
+    // Unauthorized err
+    let info = mock_info("addr0000", &[]);
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // The recipient must be an active account
+    deps.querier.with_inactive_accounts(&["inactive"]);
+    let info = mock_info(owner, &[]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::SetFeeRecipient {
+            recipient: String::from("inactive"),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::FeeRecipientNotActive("inactive".to_string())
+    );
+
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let query_res = query(deps.as_ref(), env, QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_json(&query_res).unwrap();
+    assert_eq!(config_res.fee_address, Some(Addr::unchecked("maker")));
+}
+
 #[test]
 fn update_owner() {
     let mut deps = mock_dependencies(&[]);
@@ -247,52 +301,75 @@ fn update_owner() {
 
     // New owner
     let env = mock_env();
-    let msg = ExecuteMsg::ProposeNewOwner {
-        owner: new_owner.clone(),
-        expires_in: 100, // seconds
+    let msg = ExecuteMsg::ProposeAdmin {
+        new_admin: new_owner.clone(),
+        delay_seconds: MIN_ADMIN_TRANSFER_DELAY,
     };
 
     let info = mock_info(new_owner.as_str(), &[]);
 
     // Unauthorized check
     let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
-    assert_eq!(err.to_string(), "Generic error: Unauthorized");
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    // Claim before proposal
+    // Accept before proposal
     let info = mock_info(new_owner.as_str(), &[]);
-    execute(
-        deps.as_mut(),
-        env.clone(),
-        info,
-        ExecuteMsg::ClaimOwnership {},
-    )
-    .unwrap_err();
+    let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AcceptAdmin {}).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingAdmin {});
 
     // Propose new owner
     let info = mock_info(owner, &[]);
-    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap();
     assert_eq!(0, res.messages.len());
 
-    // Unauthorized ownership claim
-    let info = mock_info("invalid_addr", &[]);
+    // Drop the proposal
+    let info = mock_info(new_owner.as_str(), &[]);
     let err = execute(
         deps.as_mut(),
         env.clone(),
         info,
-        ExecuteMsg::ClaimOwnership {},
+        ExecuteMsg::DropAdminProposal {},
     )
     .unwrap_err();
-    assert_eq!(err.to_string(), "Generic error: Unauthorized");
+    assert_eq!(err, ContractError::Unauthorized {});
 
-    // Claim ownership
-    let info = mock_info(new_owner.as_str(), &[]);
-    let res = execute(
+    let info = mock_info(owner, &[]);
+    execute(
         deps.as_mut(),
         env.clone(),
         info,
-        ExecuteMsg::ClaimOwnership {},
+        ExecuteMsg::DropAdminProposal {},
     )
     .unwrap();
+
+    let info = mock_info(new_owner.as_str(), &[]);
+    let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AcceptAdmin {}).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingAdmin {});
+
+    // Propose new owner again
+    let info = mock_info(owner, &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // The transfer is time-locked
+    let info = mock_info(new_owner.as_str(), &[]);
+    let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AcceptAdmin {}).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::AdminTransferLocked {
+            accepts_at: env.block.time.seconds() + MIN_ADMIN_TRANSFER_DELAY
+        }
+    );
+
+    // Unauthorized acceptance
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(MIN_ADMIN_TRANSFER_DELAY);
+    let info = mock_info("invalid_addr", &[]);
+    let err = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AcceptAdmin {}).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Accept the transfer
+    let info = mock_info(new_owner.as_str(), &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, ExecuteMsg::AcceptAdmin {}).unwrap();
     assert_eq!(0, res.messages.len());
 
     // Let's query the state
@@ -2,12 +2,15 @@
 
 mod factory_helper;
 
-use cosmwasm_std::{attr, Addr, StdError};
+use std::str::FromStr;
+
+use cosmwasm_std::{attr, Addr, Decimal, StdError};
 
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::factory::{
-    ConfigResponse, ExecuteMsg, FeeInfoResponse, InstantiateMsg, PairConfig, PairType, QueryMsg,
-    TrackerConfig,
+    ConfigResponse, ExecuteMsg, FeeInfoResponse, GlobalStatsResponse, InstantiateMsg, PairConfig,
+    PairType, QueryMsg, SwapPathResponse, TrackerConfig, WhitelistEntry, WhitelistResponse,
+    MIN_ADMIN_TRANSFER_DELAY,
 };
 
 use crate::factory_helper::{instantiate_token, FactoryHelper};
@@ -83,6 +86,31 @@ fn proper_initialization() {
     assert_eq!(123, config_res.token_code_id);
     assert_eq!(pair_configs, config_res.pair_configs);
     assert_eq!(owner, config_res.owner);
+
+    let whitelist: WhitelistResponse = app
+        .wrap()
+        .query_wasm_smart(&factory_instance, &QueryMsg::Whitelist {})
+        .unwrap();
+    assert_eq!(
+        whitelist.entries,
+        vec![WhitelistEntry {
+            code_id: 321,
+            pair_type: PairType::Xyk {},
+            allowed: true,
+            max_total_fee_bps: 100,
+        }]
+    );
+
+    let is_whitelisted: bool = app
+        .wrap()
+        .query_wasm_smart(&factory_instance, &QueryMsg::IsWhitelisted { code_id: 321 })
+        .unwrap();
+    assert!(is_whitelisted);
+    let is_whitelisted: bool = app
+        .wrap()
+        .query_wasm_smart(&factory_instance, &QueryMsg::IsWhitelisted { code_id: 322 })
+        .unwrap();
+    assert!(!is_whitelisted);
 }
 
 #[test]
@@ -260,71 +288,172 @@ fn test_create_pair() {
 }
 
 #[test]
-fn check_update_owner() {
+fn check_time_locked_admin_transfer() {
     let mut app = mock_app();
     let owner = Addr::unchecked("owner");
+    let new_admin = Addr::unchecked("new_admin");
     let helper = FactoryHelper::init(&mut app, &owner);
 
-    let new_owner = String::from("new_owner");
-
-    // New owner
-    let msg = ExecuteMsg::ProposeNewOwner {
-        owner: new_owner.clone(),
-        expires_in: 100, // seconds
+    let propose_msg = |delay_seconds| ExecuteMsg::ProposeAdmin {
+        new_admin: new_admin.to_string(),
+        delay_seconds,
     };
 
-    // Unauthed check
     let err = app
         .execute_contract(
             Addr::unchecked("not_owner"),
             helper.factory.clone(),
-            &msg,
+            &propose_msg(MIN_ADMIN_TRANSFER_DELAY),
             &[],
         )
         .unwrap_err();
-    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
 
-    // Claim before proposal
     let err = app
         .execute_contract(
-            Addr::unchecked(new_owner.clone()),
+            owner.clone(),
             helper.factory.clone(),
-            &ExecuteMsg::ClaimOwnership {},
+            &propose_msg(MIN_ADMIN_TRANSFER_DELAY - 1),
             &[],
         )
         .unwrap_err();
     assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Ownership proposal not found"
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AdminTransferDelayTooShort {
+            min: MIN_ADMIN_TRANSFER_DELAY
+        }
     );
 
-    // Propose new owner
-    app.execute_contract(Addr::unchecked("owner"), helper.factory.clone(), &msg, &[])
+    let err = app
+        .execute_contract(
+            new_admin.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::AcceptAdmin {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NoPendingAdmin {}
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &propose_msg(MIN_ADMIN_TRANSFER_DELAY),
+        &[],
+    )
+    .unwrap();
+
+    // Only the owner can drop the pending transfer
+    let err = app
+        .execute_contract(
+            new_admin.clone(),
+            helper.factory.clone(),
+            &ExecuteMsg::DropAdminProposal {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::DropAdminProposal {},
+        &[],
+    )
+    .unwrap();
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.pending_admin, None);
+
+    app.execute_contract(
+        owner.clone(),
+        helper.factory.clone(),
+        &propose_msg(MIN_ADMIN_TRANSFER_DELAY),
+        &[],
+    )
+    .unwrap();
+
+    let accepts_at = app.block_info().time.seconds() + MIN_ADMIN_TRANSFER_DELAY;
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
         .unwrap();
+    assert_eq!(res.owner, owner);
+    assert_eq!(res.pending_admin, Some(new_admin.clone()));
+    assert_eq!(res.pending_admin_accepts_at, Some(accepts_at));
 
-    // Claim from invalid addr
+    // The transfer can't be accepted before the delay has passed
     let err = app
         .execute_contract(
-            Addr::unchecked("invalid_addr"),
+            new_admin.clone(),
             helper.factory.clone(),
-            &ExecuteMsg::ClaimOwnership {},
+            &ExecuteMsg::AcceptAdmin {},
             &[],
         )
         .unwrap_err();
-    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AdminTransferLocked { accepts_at }
+    );
 
-    // Drop ownership proposal
+    app.update_block(|block| block.time = block.time.plus_seconds(MIN_ADMIN_TRANSFER_DELAY));
+
+    // Only the proposed admin can accept the transfer
     let err = app
         .execute_contract(
-            Addr::unchecked(new_owner.clone()),
+            Addr::unchecked("not_new_admin"),
             helper.factory.clone(),
-            &ExecuteMsg::DropOwnershipProposal {},
+            &ExecuteMsg::AcceptAdmin {},
             &[],
         )
         .unwrap_err();
-    // new_owner is not an owner yet
-    assert_eq!(err.root_cause().to_string(), "Generic error: Unauthorized");
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(
+        new_admin.clone(),
+        helper.factory.clone(),
+        &ExecuteMsg::AcceptAdmin {},
+        &[],
+    )
+    .unwrap();
+
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.owner, new_admin);
+    assert_eq!(res.pending_admin, None);
+    assert_eq!(res.pending_admin_accepts_at, None);
+}
+
+#[test]
+fn check_legacy_ownership_transfer_is_time_locked() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let new_owner = Addr::unchecked("new_owner");
+    let helper = FactoryHelper::init(&mut app, &owner);
+
+    let propose_msg = ExecuteMsg::ProposeNewOwner {
+        owner: new_owner.to_string(),
+        expires_in: 100,
+    };
 
+    app.execute_contract(owner.clone(), helper.factory.clone(), &propose_msg, &[])
+        .unwrap();
     app.execute_contract(
         owner.clone(),
         helper.factory.clone(),
@@ -333,37 +462,53 @@ fn check_update_owner() {
     )
     .unwrap();
 
-    // Try to claim ownership
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.pending_admin, None);
+
+    app.execute_contract(owner.clone(), helper.factory.clone(), &propose_msg, &[])
+        .unwrap();
+
+    // The proposal gets the minimum delay, expires_in is ignored
+    let accepts_at = app.block_info().time.seconds() + MIN_ADMIN_TRANSFER_DELAY;
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.pending_admin, Some(new_owner.clone()));
+    assert_eq!(res.pending_admin_accepts_at, Some(accepts_at));
+
     let err = app
         .execute_contract(
-            Addr::unchecked(new_owner.clone()),
+            new_owner.clone(),
             helper.factory.clone(),
             &ExecuteMsg::ClaimOwnership {},
             &[],
         )
         .unwrap_err();
     assert_eq!(
-        err.root_cause().to_string(),
-        "Generic error: Ownership proposal not found"
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AdminTransferLocked { accepts_at }
     );
 
-    // Propose new owner again
-    app.execute_contract(Addr::unchecked("owner"), helper.factory.clone(), &msg, &[])
-        .unwrap();
-    // Claim ownership
+    app.update_block(|block| block.time = block.time.plus_seconds(MIN_ADMIN_TRANSFER_DELAY));
+
     app.execute_contract(
-        Addr::unchecked(new_owner.clone()),
+        new_owner.clone(),
         helper.factory.clone(),
         &ExecuteMsg::ClaimOwnership {},
         &[],
     )
     .unwrap();
 
-    // Let's query the contract state
-    let msg = QueryMsg::Config {};
-    let res: ConfigResponse = app.wrap().query_wasm_smart(&helper.factory, &msg).unwrap();
-
-    assert_eq!(res.owner, new_owner)
+    let res: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&helper.factory, &QueryMsg::Config {})
+        .unwrap();
+    assert_eq!(res.owner, new_owner);
+    assert_eq!(res.pending_admin, None);
 }
 
 #[test]
@@ -484,3 +629,146 @@ fn tracker_config() {
     assert_eq!(tracker_config.token_factory_addr, "token_factory_addr");
     assert_eq!(tracker_config.code_id, 64);
 }
+
+#[test]
+fn swap_path() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["tokenA", "tokenB", "tokenC", "tokenD", "tokenE"]
+        .into_iter()
+        .map(|name| instantiate_token(&mut app, helper.cw20_token_code_id, &owner, name, None))
+        .collect::<Vec<_>>();
+    let asset_info = |ind: usize| AssetInfo::Token {
+        contract_addr: tokens[ind].clone(),
+    };
+
+    // A - B - C - D - E
+    for ind in 0..4 {
+        helper
+            .create_pair(
+                &mut app,
+                &owner,
+                PairType::Xyk {},
+                [&tokens[ind], &tokens[ind + 1]],
+                None,
+            )
+            .unwrap();
+    }
+
+    let res: SwapPathResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::SwapPath {
+                from: asset_info(0),
+                to: asset_info(3),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.hops.len(), 3);
+    assert_eq!(res.hops[0].asset_infos, vec![asset_info(0), asset_info(1)]);
+    assert_eq!(res.hops[2].asset_infos, vec![asset_info(2), asset_info(3)]);
+    assert_eq!(res.intermediate_assets, vec![asset_info(1), asset_info(2)]);
+    // 1% fee on each of three hops
+    assert_eq!(res.total_fee_rate, Decimal::from_str("0.029701").unwrap());
+
+    // The route works in the opposite direction too
+    let res: SwapPathResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::SwapPath {
+                from: asset_info(2),
+                to: asset_info(1),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.hops.len(), 1);
+    assert!(res.intermediate_assets.is_empty());
+    assert_eq!(res.total_fee_rate, Decimal::percent(1));
+
+    // Four hops exceed the limit
+    let err = app
+        .wrap()
+        .query_wasm_smart::<SwapPathResponse>(
+            &helper.factory,
+            &QueryMsg::SwapPath {
+                from: asset_info(0),
+                to: asset_info(4),
+            },
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("No swap path found within 3 hops"));
+}
+
+#[test]
+fn global_stats() {
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let tokens = ["tokenA", "tokenB", "tokenC", "tokenD"]
+        .into_iter()
+        .map(|name| instantiate_token(&mut app, helper.cw20_token_code_id, &owner, name, None))
+        .collect::<Vec<_>>();
+
+    for ind in 0..3 {
+        helper
+            .create_pair(
+                &mut app,
+                &owner,
+                PairType::Xyk {},
+                [&tokens[ind], &tokens[ind + 1]],
+                None,
+            )
+            .unwrap();
+    }
+
+    let res: GlobalStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::GlobalStats {
+                start_after: None,
+                pair_limit: None,
+            },
+        )
+        .unwrap();
+    assert_eq!(res.total_pairs, 3);
+    assert_eq!(res.pairs_by_type, vec![(PairType::Xyk {}, 3)]);
+    assert_eq!(res.total_tvl_usd, None);
+    assert_eq!(res.total_24h_volume_usd, None);
+    assert_eq!(res.next_start_after, None);
+
+    // The limit caps the queried pairs but not the total count
+    let res: GlobalStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::GlobalStats {
+                start_after: None,
+                pair_limit: Some(2),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.total_pairs, 3);
+    assert_eq!(res.pairs_by_type, vec![(PairType::Xyk {}, 2)]);
+    assert!(res.next_start_after.is_some());
+
+    // The remaining pairs are queried on the next page
+    let res: GlobalStatsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &QueryMsg::GlobalStats {
+                start_after: res.next_start_after,
+                pair_limit: Some(2),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.total_pairs, 3);
+    assert_eq!(res.pairs_by_type, vec![(PairType::Xyk {}, 1)]);
+    assert_eq!(res.next_start_after, None);
+}
@@ -5,42 +5,60 @@ use std::vec;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, coin, ensure_eq, from_json, to_json_binary, wasm_execute, Addr, Binary, Coin, CosmosMsg,
-    CustomMsg, CustomQuery, Decimal, Decimal256, Deps, DepsMut, Empty, Env, Fraction, MessageInfo,
-    QuerierWrapper, Reply, Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult,
-    Uint128, Uint256, Uint64, WasmMsg,
+    attr, coin, ensure, ensure_eq, from_json, to_json_binary, wasm_execute, Addr, BankMsg, Binary,
+    Coin, CosmosMsg, CustomMsg, CustomQuery, Decimal, Decimal256, Deps, DepsMut, Env, Event,
+    MessageInfo, Order, QuerierWrapper, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    SubMsgResponse, SubMsgResult, Uint128, Uint256, Uint64, WasmMsg,
 };
-use cw2::set_contract_version;
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{AllowanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
 use cw_utils::{
-    one_coin, parse_reply_instantiate_data, MsgInstantiateContractResponse, PaymentError,
+    nonpayable, one_coin, parse_reply_instantiate_data, MsgInstantiateContractResponse,
+    PaymentError,
 };
 
 use astroport::asset::{
-    addr_opt_validate, check_swap_parameters, Asset, AssetInfo, CoinsExt, PairInfo,
-    MINIMUM_LIQUIDITY_AMOUNT,
+    addr_opt_validate, check_swap_parameters, compute_pool_shares, native_asset_info, token_asset,
+    Asset, AssetInfo, AssetInfoExt, CoinsExt, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
 use astroport::common::LP_SUBDENOM;
 use astroport::factory::PairType;
+use astroport::gas_estimates::XYK_SWAP;
 use astroport::incentives::ExecuteMsg as IncentiveExecuteMsg;
+use astroport::observation::try_dec256_into_dec;
+use astroport::oracle::QueryMsg as OracleQueryMsg;
 use astroport::pair::{
-    ConfigResponse, FeeShareConfig, ReplyIds, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams,
-    DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FEE_SHARE_BPS,
+    assert_max_spread, assert_min_out, calculate_price_impact, effective_spread_amount,
+    AccumulatedFeesResponse, CheckSwapLimitsResponse, CumulativePricesResponse, Cw20HookMsg,
+    DepthLevel, ExecuteMsg, FlashLoanCallbackMsg, InstantiateMsg, IsPoolDepletedResponse,
+    LiquidityDepthResponse, MaxSwapableResponse, MigrateMsg, MinSwapAmountResponse,
+    NextPriceResponse, PoolResponse, QueryMsg, ReserveRatioResponse, ReverseSimulationResponse,
+    SimulationResponse, SlippageValidationResponse, SwapCallbackMsg, MAX_DEPTH_LEVELS,
+    RESERVE_IMBALANCE_THRESHOLD, TWAP_PRECISION,
 };
 use astroport::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
+    ArbProtectionConfig, ConfigResponse, FeeShareConfig, ReplyIds, XYKPoolConfig, XYKPoolParams,
+    XYKPoolUpdateParams, DEFAULT_SLIPPAGE, LIQUIDITY_REQUEST_LOCK_PERIOD, MAX_ALLOWED_SLIPPAGE,
+    MAX_FEE_SHARE_BPS, MAX_FLASH_LOAN_FEE_BPS, MAX_VOLUME_LIMIT_DECAY_BLOCKS,
 };
 use astroport::querier::{
-    query_factory_config, query_fee_info, query_native_supply, query_tracker_config,
+    query_factory_config, query_fee_info, query_native_supply, query_tracker_config, FeeInfo,
 };
 use astroport::token_factory::{
     tf_before_send_hook_msg, tf_burn_msg, tf_create_denom_msg, tf_mint_msg, MsgCreateDenomResponse,
 };
-use astroport::{tokenfactory_tracker, U256};
+use astroport::tokenfactory_tracker;
+use astroport_pair_common::state::CODE_VERSION;
+use astroport_pair_common::utils::{
+    accumulate_block_volume, assert_code_version, replace_asset_messages, transfer_lp_and_stake,
+};
 
 use crate::error::ContractError;
-use crate::state::{Config, BALANCES, CONFIG};
+use crate::state::{
+    Config, LiquidityRequest, LiquidityRequestFulfillment, ACCUMULATED_FEES, BALANCES, CONFIG,
+    CONFIG_FROZEN, CUMULATIVE_VOLUMES, ESCROWED_BOUNTIES, EXTERNAL_LIQUIDITY, FLASH_LOAN,
+    LAST_FULL_WITHDRAWAL_TS, LIQUIDITY_REQUESTS, NEXT_LIQUIDITY_REQUEST_ID, PAUSED,
+};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "astroport-pair";
@@ -59,8 +77,8 @@ pub fn instantiate(
         return Err(StdError::generic_err("asset_infos must contain exactly two elements").into());
     }
 
-    msg.asset_infos[0].check(deps.api)?;
-    msg.asset_infos[1].check(deps.api)?;
+    msg.asset_infos[0].validate(deps.api)?;
+    msg.asset_infos[1].validate(deps.api)?;
 
     if msg.asset_infos[0] == msg.asset_infos[1] {
         return Err(ContractError::DoublingAssets {});
@@ -74,6 +92,7 @@ pub fn instantiate(
     }
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
 
     let config = Config {
         pair_info: PairInfo {
@@ -89,6 +108,12 @@ pub fn instantiate(
         track_asset_balances,
         fee_share: None,
         tracker_addr: None,
+        arb_protection: None,
+        flash_loan_fee: Decimal::zero(),
+        global_max_slippage: None,
+        max_swap_volume_per_block: None,
+        volume_limit_decay_blocks: 0,
+        pause_guardian: None,
     };
 
     if track_asset_balances {
@@ -193,6 +218,45 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                 .add_message(set_hook_msg)
                 .add_attribute("tracker_contract", contract_address))
         }
+        ReplyIds::FlashLoan => {
+            // The receiver has to return the borrowed assets plus fees within its callback
+            let expected_balances = FLASH_LOAN.load(deps.storage)?;
+            FLASH_LOAN.remove(deps.storage);
+
+            let config = CONFIG.load(deps.storage)?;
+            let pools = config
+                .pair_info
+                .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+
+            for (pool, expected) in pools.iter().zip(expected_balances.iter()) {
+                if pool.amount < expected.amount {
+                    return Err(ContractError::FlashLoanNotRepaid {
+                        asset: pool.info.to_string(),
+                        expected: expected.amount,
+                        actual: pool.amount,
+                    });
+                }
+            }
+
+            // The fees stay in the pool, so the tracked reserves have to be refreshed
+            if config.track_asset_balances {
+                for pool in &pools {
+                    BALANCES.save(deps.storage, &pool.info, &pool.amount, env.block.height)?;
+                }
+            }
+
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "flash_loan_repaid"),
+                attr(
+                    "assets",
+                    pools
+                        .iter()
+                        .map(|asset| asset.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            ]))
+        }
     }
 }
 
@@ -210,18 +274,61 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
 ///             auto_stake,
 ///             receiver,
 ///            min_lp_to_receive,
+///            request_id,
 ///         }** Provides liquidity in the pair with the specified input parameters.
 ///
+/// * **ExecuteMsg::ProvideLiquiditySingle { offer, slippage_tolerance, receiver }** Provides liquidity
+/// with a single native asset, swapping part of it to the other pool asset.
+///
 /// * **ExecuteMsg::Swap {
 ///             offer_asset,
 ///             belief_price,
 ///             max_spread,
+///             min_out,
 ///             to,
+///             callback,
 ///         }** Performs a swap operation with the specified parameters.
 /// * **ExecuteMsg::WithdrawLiquidity {
 ///            assets,
 ///           min_assets_to_receive,
 ///       }** Withdraws liquidity from the pool.
+///
+/// * **ExecuteMsg::TransferLpAndStake {
+///             recipient,
+///             amount,
+///             generator,
+///         }** Stakes the attached LP tokens in the Incentives contract on behalf of the recipient.
+///
+/// * **ExecuteMsg::DonateToPool { assets }** Donates assets to the pool without minting LP tokens.
+///
+/// * **ExecuteMsg::FreezeConfig {}** Permanently freezes the pair configuration.
+///
+/// * **ExecuteMsg::Sweep { recipient, assets }** Sends tokens which are not part of the pool to the recipient.
+///
+/// * **ExecuteMsg::RequestLiquidityProvision { amount, deadline }** Creates a liquidity request
+/// escrowing the attached tokens as a bounty.
+///
+/// * **ExecuteMsg::CancelLiquidityRequest { request_id }** Cancels a liquidity request and refunds the bounty.
+///
+/// * **ExecuteMsg::ClaimLiquidityRequest { request_id }** Releases the LP tokens locked by a fulfilled
+/// liquidity request together with the bounty.
+///
+/// * **ExecuteMsg::FlashLoan { assets, receiver, msg }** Lends pool assets to the receiver within
+/// a single transaction.
+///
+/// * **ExecuteMsg::SetFlashLoanFee { fee }** Sets the fee charged on flash loans.
+///
+/// * **ExecuteMsg::SetSlippageTolerance { max_slippage }** Sets the cap on provide liquidity slippage tolerances.
+///
+/// * **ExecuteMsg::RegisterExternalLiquidity { external_contract, amount }** Registers liquidity
+/// held by an external contract on behalf of the pool.
+///
+/// * **ExecuteMsg::Pause {}** Halts swaps, liquidity provision and withdrawals.
+///
+/// * **ExecuteMsg::Unpause {}** Resumes the operations halted by a pause.
+///
+/// * **ExecuteMsg::ReplaceAsset { old_asset, new_asset }** Replaces a pool asset with a new one
+/// and sends the old asset balance to the caller.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -229,6 +336,28 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Reject messages if the stored code version differs from the compiled one
+    assert_code_version::<ContractError>(deps.storage, CONTRACT_VERSION)?;
+
+    // The pool reserves are incomplete until the ongoing flash loan is repaid
+    if FLASH_LOAN.exists(deps.storage) {
+        return Err(ContractError::FlashLoanInProgress {});
+    }
+
+    // Pool operations are halted until the factory owner unpauses the pair
+    if matches!(
+        msg,
+        ExecuteMsg::Receive(_)
+            | ExecuteMsg::ProvideLiquidity { .. }
+            | ExecuteMsg::ProvideLiquiditySingle { .. }
+            | ExecuteMsg::Swap { .. }
+            | ExecuteMsg::WithdrawLiquidity { .. }
+            | ExecuteMsg::FlashLoan { .. }
+    ) && PAUSED.may_load(deps.storage)?.unwrap_or_default()
+    {
+        return Err(ContractError::ContractPaused {});
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ProvideLiquidity {
@@ -237,6 +366,7 @@ pub fn execute(
             auto_stake,
             receiver,
             min_lp_to_receive,
+            request_id,
         } => provide_liquidity(
             deps,
             env,
@@ -246,12 +376,29 @@ pub fn execute(
             auto_stake,
             receiver,
             min_lp_to_receive,
+            request_id,
+            None,
+            None,
         ),
+        ExecuteMsg::ProvideLiquiditySingle {
+            offer,
+            slippage_tolerance,
+            receiver,
+        } => {
+            offer.info.check(deps.api)?;
+            if !offer.is_native_token() {
+                return Err(ContractError::NonSupported {});
+            }
+
+            provide_liquidity_single(deps, env, info, offer, slippage_tolerance, receiver)
+        }
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
+            min_out,
             to,
+            callback,
             ..
         } => {
             offer_asset.info.check(deps.api)?;
@@ -269,7 +416,9 @@ pub fn execute(
                 offer_asset,
                 belief_price,
                 max_spread,
+                min_out,
                 to_addr,
+                callback,
             )
         }
         ExecuteMsg::UpdateConfig { params } => update_config(deps, info, params),
@@ -277,6 +426,52 @@ pub fn execute(
             assets,
             min_assets_to_receive,
         } => withdraw_liquidity(deps, env, info, assets, min_assets_to_receive),
+        ExecuteMsg::TransferLpAndStake {
+            recipient,
+            amount,
+            generator,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            transfer_lp_and_stake(
+                deps.api,
+                info,
+                &config.pair_info.liquidity_token,
+                recipient,
+                amount,
+                generator,
+            )
+        }
+        ExecuteMsg::DonateToPool { assets } => donate_to_pool(deps, env, info, assets),
+        ExecuteMsg::FreezeConfig {} => freeze_config(deps, env, info),
+        ExecuteMsg::Sweep { recipient, assets } => sweep(deps, env, info, recipient, assets),
+        ExecuteMsg::RequestLiquidityProvision { amount, deadline } => {
+            request_liquidity_provision(deps, env, info, amount, deadline)
+        }
+        ExecuteMsg::CancelLiquidityRequest { request_id } => {
+            cancel_liquidity_request(deps, info, request_id)
+        }
+        ExecuteMsg::ClaimLiquidityRequest { request_id } => {
+            claim_liquidity_request(deps, env, request_id)
+        }
+        ExecuteMsg::FlashLoan {
+            assets,
+            receiver,
+            msg,
+        } => flash_loan(deps, info, assets, receiver, msg),
+        ExecuteMsg::SetFlashLoanFee { fee } => set_flash_loan_fee(deps, info, fee),
+        ExecuteMsg::SetSlippageTolerance { max_slippage } => {
+            set_slippage_tolerance(deps, info, max_slippage)
+        }
+        ExecuteMsg::RegisterExternalLiquidity {
+            external_contract,
+            amount,
+        } => register_external_liquidity(deps, info, external_contract, amount),
+        ExecuteMsg::Pause {} => pause(deps, info),
+        ExecuteMsg::Unpause {} => unpause(deps, info),
+        ExecuteMsg::ReplaceAsset {
+            old_asset,
+            new_asset,
+        } => replace_asset(deps, env, info, old_asset, new_asset),
         _ => Err(ContractError::NonSupported {}),
     }
 }
@@ -294,20 +489,17 @@ pub fn receive_cw20(
         Cw20HookMsg::Swap {
             belief_price,
             max_spread,
+            min_out,
             to,
             ..
         } => {
             // Only asset contract can execute this message
-            let mut authorized = false;
             let config = CONFIG.load(deps.storage)?;
-
-            for pool in config.pair_info.asset_infos {
-                if let AssetInfo::Token { contract_addr, .. } = &pool {
-                    if contract_addr == info.sender {
-                        authorized = true;
-                    }
-                }
-            }
+            let authorized = config
+                .pair_info
+                .asset_infos
+                .iter()
+                .any(|asset_info| asset_info.token_contract() == Some(&info.sender));
 
             if !authorized {
                 return Err(ContractError::Unauthorized {});
@@ -327,10 +519,90 @@ pub fn receive_cw20(
                 },
                 belief_price,
                 max_spread,
+                min_out,
                 to_addr,
+                None,
             )
         }
+        Cw20HookMsg::StakeAndProvide {
+            counterpart,
+            slippage_tolerance,
+            generator,
+        } => stake_and_provide(
+            deps,
+            env,
+            info.clone(),
+            token_asset(info.sender, cw20_msg.amount),
+            Addr::unchecked(cw20_msg.sender),
+            counterpart,
+            slippage_tolerance,
+            generator,
+        ),
+    }
+}
+
+/// Provides liquidity using the CW20 tokens received by the pair together with the counterpart asset
+/// and stakes the minted LP tokens in the Incentives contract on behalf of the CW20 sender.
+///
+/// * **info** the message info of the CW20 hook call.
+///
+/// * **received** CW20 tokens sent to the pair.
+///
+/// * **sender** address which sent the CW20 tokens.
+///
+/// * **counterpart** the other pool asset. CW20 counterparts are pulled from the sender.
+/// A native counterpart must be attached to the hook call.
+///
+/// * **slippage_tolerance** is an optional parameter which is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+///
+/// * **generator** Incentives contract address.
+///
+/// NOTE - the sender should approve the pair contract to pull CW20 counterpart tokens.
+#[allow(clippy::too_many_arguments)]
+pub fn stake_and_provide(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    received: Asset,
+    sender: Addr,
+    counterpart: Asset,
+    slippage_tolerance: Option<Decimal>,
+    generator: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    // Only asset contract can execute this message
+    if !config.pair_info.asset_infos.contains(&received.info) {
+        return Err(ContractError::Unauthorized {});
     }
+
+    if counterpart.info.equal(&received.info)
+        || !config.pair_info.asset_infos.contains(&counterpart.info)
+    {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let generator = deps.api.addr_validate(&generator)?;
+
+    counterpart.assert_sent_native_token_balance(&info)?;
+
+    provide_liquidity(
+        deps,
+        env,
+        MessageInfo {
+            sender,
+            funds: info.funds,
+        },
+        vec![received.clone(), counterpart],
+        slippage_tolerance,
+        None,
+        None,
+        None,
+        None,
+        Some(received),
+        Some(generator),
+    )
 }
 
 /// Provides liquidity in the pair with the specified input parameters.
@@ -347,6 +619,14 @@ pub fn receive_cw20(
 /// If no custom receiver is specified, the pair will mint LP tokens for the function caller.
 ///
 /// * **min_lp_to_receive** is an optional parameter which specifies the minimum amount of LP tokens to receive.
+///
+/// * **request_id** is an optional liquidity request fulfilled by this provision. The minted LP tokens
+/// are locked in the pair for [`LIQUIDITY_REQUEST_LOCK_PERIOD`] and can then be claimed by the receiver
+/// together with the request bounty.
+///
+/// * **received** CW20 tokens which were already transferred to the pair by a CW20 hook.
+///
+/// * **generator** the Incentives contract to stake the minted LP tokens in. Overrides **auto_stake**.
 /// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
 #[allow(clippy::too_many_arguments)]
 pub fn provide_liquidity(
@@ -358,8 +638,12 @@ pub fn provide_liquidity(
     auto_stake: Option<bool>,
     receiver: Option<String>,
     min_lp_to_receive: Option<Uint128>,
+    request_id: Option<u64>,
+    received: Option<Asset>,
+    generator: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
+    let slippage_tolerance = clamp_slippage_tolerance(&config, slippage_tolerance)?;
 
     let mut pools = config
         .pair_info
@@ -374,8 +658,12 @@ pub fn provide_liquidity(
 
     let mut messages = vec![];
     for (i, pool) in pools.iter_mut().enumerate() {
+        let already_received = received
+            .as_ref()
+            .map_or(false, |received| received.info.equal(&pool.info));
+
         // If the asset is a token contract, then we need to execute a TransferFrom msg to receive assets
-        if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+        if let (AssetInfo::Token { contract_addr, .. }, false) = (&pool.info, already_received) {
             messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: contract_addr.to_string(),
                 msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
@@ -386,7 +674,7 @@ pub fn provide_liquidity(
                 funds: vec![],
             }));
         } else {
-            // If the asset is native token, the pool balance is already increased
+            // If the asset is native token or was received via a CW20 hook, the pool balance is already increased
             // To calculate the total amount of deposits properly, we should subtract the user deposit from the pool
             pool.amount = pool.amount.checked_sub(deposits[i])?;
         }
@@ -415,7 +703,247 @@ pub fn provide_liquidity(
         ));
     }
 
-    // Mint LP tokens for the sender or for the receiver (if set)
+    // LP tokens are minted for the sender or for the receiver (if set)
+    let receiver = addr_opt_validate(deps.api, &receiver)?.unwrap_or_else(|| info.sender.clone());
+
+    // Lock the LP tokens minted by a liquidity request fulfillment, so the bounty can't be collected
+    // by providing and withdrawing liquidity right away
+    let mut fulfilled_event = None;
+    if let Some(request_id) = request_id {
+        let mut request = LIQUIDITY_REQUESTS
+            .may_load(deps.storage, request_id)?
+            .ok_or(ContractError::LiquidityRequestNotFound { request_id })?;
+
+        if request.fulfillment.is_some() {
+            return Err(ContractError::LiquidityRequestFulfilled { request_id });
+        }
+
+        if auto_stake || generator.is_some() {
+            return Err(ContractError::LiquidityRequestAutoStake {});
+        }
+
+        if env.block.time.seconds() > request.deadline {
+            return Err(ContractError::LiquidityRequestExpired { request_id });
+        }
+
+        if share < request.amount {
+            return Err(ContractError::LiquidityRequestNotMet {
+                requested: request.amount,
+                minted: share,
+            });
+        }
+
+        let unlocks_at = env.block.time.seconds() + LIQUIDITY_REQUEST_LOCK_PERIOD;
+        request.fulfillment = Some(LiquidityRequestFulfillment {
+            provider: receiver.clone(),
+            lp_amount: share,
+            unlocks_at,
+        });
+        LIQUIDITY_REQUESTS.save(deps.storage, request_id, &request)?;
+
+        fulfilled_event = Some(
+            Event::new("liquidity_request_fulfilled")
+                .add_attribute("request_id", request_id.to_string())
+                .add_attribute("provider", &receiver)
+                .add_attribute("lp_amount", share)
+                .add_attribute("unlocks_at", unlocks_at.to_string()),
+        );
+        messages.extend(mint_liquidity_token_message(
+            deps.querier,
+            &config,
+            &env.contract.address,
+            &env.contract.address,
+            share,
+            false,
+        )?);
+    } else if let Some(generator) = generator {
+        // Mint LP tokens for the pair contract and stake them in the given Incentives contract
+        let lp_coin = coin(share.u128(), config.pair_info.liquidity_token.to_string());
+        messages.extend(tf_mint_msg(
+            &env.contract.address,
+            lp_coin.clone(),
+            &env.contract.address,
+        ));
+        messages.push(
+            wasm_execute(
+                generator,
+                &IncentiveExecuteMsg::Deposit {
+                    recipient: Some(receiver.to_string()),
+                },
+                vec![lp_coin],
+            )?
+            .into(),
+        );
+    } else {
+        messages.extend(mint_liquidity_token_message(
+            deps.querier,
+            &config,
+            &env.contract.address,
+            &receiver,
+            share,
+            auto_stake,
+        )?);
+    }
+
+    if config.track_asset_balances {
+        for (i, pool) in pools.iter().enumerate() {
+            BALANCES.save(
+                deps.storage,
+                &pool.info,
+                &pool.amount.checked_add(deposits[i])?,
+                env.block.height,
+            )?;
+        }
+    }
+
+    // Accumulate prices for the assets in the pool
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_events(fulfilled_event)
+        .add_attributes(vec![
+            attr("action", "provide_liquidity"),
+            attr("sender", info.sender),
+            attr("receiver", receiver),
+            attr("assets", format!("{}, {}", assets[0], assets[1])),
+            attr("share", share),
+        ]))
+}
+
+/// Provides liquidity with a single native asset. The part of `offer` computed by
+/// [`compute_single_sided_swap_amount`] is swapped within the pool and the rest of the deposit
+/// is provided together with the swap return.
+///
+/// * **offer** the native asset to provide.
+///
+/// * **slippage_tolerance** the maximum allowed shortfall of minted LP tokens compared to a balanced
+/// provision of the same value. It is also used as the price slippage tolerance of the provision.
+/// Both are capped by the pair-level slippage limit.
+///
+/// * **receiver** address that receives LP tokens. If this address isn't specified, the function will default to the caller.
+pub fn provide_liquidity_single(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    offer: Asset,
+    slippage_tolerance: Option<Decimal>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    offer.assert_sent_native_token_balance(&info)?;
+    if offer.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut config = CONFIG.load(deps.storage)?;
+    let slippage_tolerance = clamp_slippage_tolerance(&config, slippage_tolerance)?;
+
+    // The deposit is already part of the contract balance
+    let initial_pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?
+        .into_iter()
+        .map(|mut p| {
+            if p.info.equal(&offer.info) {
+                p.amount = p.amount.checked_sub(offer.amount)?;
+            }
+            Ok(p)
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let (offer_ind, ask_ind) = if offer.info.equal(&initial_pools[0].info) {
+        (0, 1)
+    } else if offer.info.equal(&initial_pools[1].info) {
+        (1, 0)
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    };
+
+    let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    if total_share.is_zero() {
+        return Err(ContractError::SingleSidedProvideToEmptyPool {});
+    }
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )
+    .map_err(|err| ContractError::from_std_with_context(err, "during swap fee calculation"))?;
+
+    let swap_amount = compute_single_sided_swap_amount(
+        initial_pools[offer_ind].amount,
+        offer.amount,
+        fee_info.total_fee_rate,
+    )?;
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        initial_pools[offer_ind].amount,
+        initial_pools[ask_ind].amount,
+        swap_amount,
+        fee_info.total_fee_rate,
+    )
+    .map_err(|err| ContractError::from_std_with_context(err, "during swap amount calculation"))?;
+
+    // The internal swap is subject to the same limits as regular swaps
+    accumulate_block_volume::<ContractError>(
+        deps.storage,
+        env.block.height,
+        config.max_swap_volume_per_block,
+        config.volume_limit_decay_blocks,
+        &offer.info,
+        swap_amount,
+    )?;
+
+    if let Some(arb_protection) = &config.arb_protection {
+        assert_arb_protection(
+            deps.querier,
+            arb_protection,
+            &initial_pools[offer_ind],
+            &initial_pools[ask_ind],
+            swap_amount,
+            return_amount,
+        )?;
+    }
+
+    let (mut messages, maker_fee_amount, fee_share_amount) = distribute_commission(
+        deps.storage,
+        &config,
+        &fee_info,
+        &initial_pools[ask_ind].info,
+        commission_amount,
+    )?;
+
+    // Pool state after the internal swap
+    let mut pools = initial_pools.clone();
+    pools[offer_ind].amount = pools[offer_ind].amount.checked_add(swap_amount)?;
+    pools[ask_ind].amount = pools[ask_ind]
+        .amount
+        .checked_sub(return_amount + maker_fee_amount + fee_share_amount)?;
+
+    let mut deposits = [Uint128::zero(); 2];
+    deposits[offer_ind] = offer.amount - swap_amount;
+    deposits[ask_ind] = return_amount;
+
+    let share = calculate_shares(&deposits, &pools, total_share, slippage_tolerance)?;
+
+    // A balanced provision of the same value deposits half of the offer amount
+    let balanced_share = total_share.multiply_ratio(
+        offer.amount,
+        initial_pools[offer_ind].amount * Uint128::new(2),
+    );
+    let slippage_tolerance = slippage_tolerance.unwrap_or(Decimal::from_str(DEFAULT_SLIPPAGE)?);
+    let min_share = balanced_share * (Decimal::one() - slippage_tolerance);
+    if share < min_share {
+        return Err(ContractError::ProvideSlippageViolation(share, min_share));
+    }
+
     let receiver = addr_opt_validate(deps.api, &receiver)?.unwrap_or_else(|| info.sender.clone());
     messages.extend(mint_liquidity_token_message(
         deps.querier,
@@ -423,7 +951,7 @@ pub fn provide_liquidity(
         &env.contract.address,
         &receiver,
         share,
-        auto_stake,
+        false,
     )?);
 
     if config.track_asset_balances {
@@ -437,10 +965,19 @@ pub fn provide_liquidity(
         }
     }
 
-    // Accumulate prices for the assets in the pool
-    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
-        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
-    {
+    let mut volumes = CUMULATIVE_VOLUMES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    volumes[offer_ind] += Uint256::from(swap_amount);
+    volumes[ask_ind] += Uint256::from(return_amount);
+    CUMULATIVE_VOLUMES.save(deps.storage, &volumes)?;
+
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) = accumulate_prices(
+        env,
+        &config,
+        initial_pools[0].amount,
+        initial_pools[1].amount,
+    )? {
         config.price0_cumulative_last = price0_cumulative_new;
         config.price1_cumulative_last = price1_cumulative_new;
         config.block_time_last = block_time;
@@ -448,10 +985,14 @@ pub fn provide_liquidity(
     }
 
     Ok(Response::new().add_messages(messages).add_attributes(vec![
-        attr("action", "provide_liquidity"),
+        attr("action", "provide_liquidity_single"),
         attr("sender", info.sender),
         attr("receiver", receiver),
-        attr("assets", format!("{}, {}", assets[0], assets[1])),
+        attr("offer_asset", offer.to_string()),
+        attr("swap_amount", swap_amount),
+        attr("return_amount", return_amount),
+        attr("spread_amount", spread_amount),
+        attr("commission_amount", commission_amount),
         attr("share", share),
     ]))
 }
@@ -504,38 +1045,141 @@ where
     }
 }
 
-/// Withdraw liquidity from the pool.
-pub fn withdraw_liquidity(
+/// Donates assets to the pool. No LP tokens are minted, so the donation is distributed among existing LPs.
+///
+/// * **assets** is an array with assets to donate. Each asset must be one of the pool assets.
+pub fn donate_to_pool(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
     assets: Vec<Asset>,
-    min_assets_to_receive: Option<Vec<Asset>>,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage).unwrap();
-
-    let Coin { amount, denom } = one_coin(&info)?;
+    let mut config = CONFIG.load(deps.storage)?;
 
-    ensure_eq!(
-        denom,
-        config.pair_info.liquidity_token,
-        PaymentError::MissingDenom(config.pair_info.liquidity_token.to_string())
-    );
+    for asset in &assets {
+        asset.info.check(deps.api)?;
+        if asset.amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+    }
 
-    let (pools, total_share) = pool_info(deps.querier, &config)?;
+    info.funds
+        .assert_coins_properly_sent(&assets, &config.pair_info.asset_infos)?;
 
-    let refund_assets = if assets.is_empty() {
-        // Usual withdraw (balanced)
-        get_share_in_assets(&pools, amount, total_share)
-    } else {
-        return Err(StdError::generic_err("Imbalanced withdraw is currently disabled").into());
-    };
+    // Donations to an empty pool would be captured by the first liquidity provider
+    let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+    if total_share.is_zero() {
+        return Err(ContractError::DonationToEmptyPool {});
+    }
 
-    ensure_min_assets_to_receive(&config, refund_assets.clone(), min_assets_to_receive)?;
+    let mut pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
 
-    if config.track_asset_balances {
-        for (i, pool) in pools.iter().enumerate() {
-            BALANCES.save(
+    let mut messages = vec![];
+    let mut donations = [Uint128::zero(); 2];
+    for (i, pool) in pools.iter_mut().enumerate() {
+        if let Some(asset) = assets.iter().find(|asset| asset.info == pool.info) {
+            donations[i] = asset.amount;
+
+            if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+                messages.push(
+                    wasm_execute(
+                        contract_addr,
+                        &Cw20ExecuteMsg::TransferFrom {
+                            owner: info.sender.to_string(),
+                            recipient: env.contract.address.to_string(),
+                            amount: asset.amount,
+                        },
+                        vec![],
+                    )?
+                    .into(),
+                );
+            } else {
+                // Native tokens are already added to the pool balance
+                pool.amount = pool.amount.checked_sub(asset.amount)?;
+            }
+        }
+    }
+
+    if config.track_asset_balances {
+        for (i, pool) in pools.iter().enumerate() {
+            BALANCES.save(
+                deps.storage,
+                &pool.info,
+                &pool.amount.checked_add(donations[i])?,
+                env.block.height,
+            )?;
+        }
+    }
+
+    // Accumulate prices for the assets in the pool before the donation changes them
+    if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
+        accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
+    {
+        config.price0_cumulative_last = price0_cumulative_new;
+        config.price1_cumulative_last = price1_cumulative_new;
+        config.block_time_last = block_time;
+        CONFIG.save(deps.storage, &config)?;
+    }
+
+    let donated = assets
+        .iter()
+        .map(|asset| asset.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_event(Event::new("donation").add_attributes([
+            attr("donor", info.sender.to_string()),
+            attr("assets", &donated),
+        ]))
+        .add_attributes([
+            attr("action", "donate_to_pool"),
+            attr("sender", info.sender),
+            attr("assets", donated),
+        ]))
+}
+
+/// Withdraw liquidity from the pool.
+pub fn withdraw_liquidity(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<Asset>,
+    min_assets_to_receive: Option<Vec<Asset>>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage).unwrap();
+
+    let Coin { amount, denom } = one_coin(&info)?;
+
+    ensure_eq!(
+        denom,
+        config.pair_info.liquidity_token,
+        PaymentError::MissingDenom(config.pair_info.liquidity_token.to_string())
+    );
+
+    let (pools, total_share) = pool_info(deps.querier, &config)?;
+
+    let refund_assets = if assets.is_empty() {
+        // Usual withdraw (balanced)
+        get_share_in_assets(&pools, amount, total_share)
+    } else {
+        return Err(StdError::generic_err("Imbalanced withdraw is currently disabled").into());
+    };
+
+    ensure_min_assets_to_receive(&config, refund_assets.clone(), min_assets_to_receive)?;
+
+    // The minimum liquidity is locked in the contract forever, so the pool is considered depleted
+    // once it is the only LP supply left
+    if total_share.checked_sub(amount)? <= MINIMUM_LIQUIDITY_AMOUNT {
+        LAST_FULL_WITHDRAWAL_TS.save(deps.storage, &env.block.time.seconds())?;
+    }
+
+    if config.track_asset_balances {
+        for (i, pool) in pools.iter().enumerate() {
+            BALANCES.save(
                 deps.storage,
                 &pool.info,
                 &(pool.amount - refund_assets[i].amount),
@@ -610,8 +1254,12 @@ pub fn get_share_in_assets(pools: &[Asset], amount: Uint128, total_share: Uint12
 ///
 /// * **max_spread** sets the maximum spread of the swap operation.
 ///
+/// * **min_out** sets the minimum amount of ask assets to receive.
+///
 /// * **to** sets the recipient of the swap operation.
 ///
+/// * **callback** optional message sent back to the sender together with the swap result.
+///
 /// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
 #[allow(clippy::too_many_arguments)]
 pub fn swap(
@@ -622,7 +1270,9 @@ pub fn swap(
     offer_asset: Asset,
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
+    min_out: Option<Uint128>,
     to: Option<Addr>,
+    callback: Option<Binary>,
 ) -> Result<Response, ContractError> {
     offer_asset.assert_sent_native_token_balance(&info)?;
 
@@ -659,7 +1309,8 @@ pub fn swap(
         &deps.querier,
         &config.factory_addr,
         config.pair_info.pair_type.clone(),
-    )?;
+    )
+    .map_err(|err| ContractError::from_std_with_context(err, "during swap fee calculation"))?;
 
     let offer_amount = offer_asset.amount;
 
@@ -668,7 +1319,8 @@ pub fn swap(
         ask_pool.amount,
         offer_amount,
         fee_info.total_fee_rate,
-    )?;
+    )
+    .map_err(|err| ContractError::from_std_with_context(err, "during swap amount calculation"))?;
 
     // Check the max spread limit (if it was specified)
     assert_max_spread(
@@ -678,6 +1330,26 @@ pub fn swap(
         return_amount + commission_amount,
         spread_amount,
     )?;
+    assert_min_out(min_out, return_amount)?;
+    accumulate_block_volume::<ContractError>(
+        deps.storage,
+        env.block.height,
+        config.max_swap_volume_per_block,
+        config.volume_limit_decay_blocks,
+        &offer_asset.info,
+        offer_amount,
+    )?;
+
+    if let Some(arb_protection) = &config.arb_protection {
+        assert_arb_protection(
+            deps.querier,
+            arb_protection,
+            &offer_pool,
+            &ask_pool,
+            offer_amount,
+            return_amount,
+        )?;
+    }
 
     let return_asset = Asset {
         info: ask_pool.info.clone(),
@@ -690,43 +1362,14 @@ pub fn swap(
         messages.push(return_asset.into_msg(receiver.clone())?)
     }
 
-    // If this pool is configured to share fees, calculate the amount to send
-    // to the receiver and add the transfer message
-    // The calculation works as follows: We take the share percentage first,
-    // and the remainder is then split between LPs and maker
-    let mut fees_commission_amount = commission_amount;
-    let mut fee_share_amount = Uint128::zero();
-    if let Some(fee_share) = config.fee_share.clone() {
-        // Calculate the fee share amount from the full commission amount
-        let share_fee_rate = Decimal::from_ratio(fee_share.bps, 10000u16);
-        fee_share_amount = fees_commission_amount * share_fee_rate;
-
-        if !fee_share_amount.is_zero() {
-            // Subtract the fee share amount from the commission
-            fees_commission_amount = fees_commission_amount.saturating_sub(fee_share_amount);
-
-            // Build send message for the shared amount
-            let fee_share_msg = Asset {
-                info: ask_pool.info.clone(),
-                amount: fee_share_amount,
-            }
-            .into_msg(fee_share.recipient)?;
-            messages.push(fee_share_msg);
-        }
-    }
-
-    // Compute the Maker fee
-    let mut maker_fee_amount = Uint128::zero();
-    if let Some(fee_address) = fee_info.fee_address {
-        if let Some(f) = calculate_maker_fee(
-            &ask_pool.info,
-            fees_commission_amount,
-            fee_info.maker_fee_rate,
-        ) {
-            maker_fee_amount = f.amount;
-            messages.push(f.into_msg(fee_address)?);
-        }
-    }
+    let (fee_messages, maker_fee_amount, fee_share_amount) = distribute_commission(
+        deps.storage,
+        &config,
+        &fee_info,
+        &ask_pool.info,
+        commission_amount,
+    )?;
+    messages.extend(fee_messages);
 
     if config.track_asset_balances {
         BALANCES.save(
@@ -743,6 +1386,19 @@ pub fn swap(
         )?;
     }
 
+    // Accumulate swapped volumes for the assets in the pool
+    let mut volumes = CUMULATIVE_VOLUMES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let (offer_ind, ask_ind) = if offer_pool.info.equal(&pools[0].info) {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    volumes[offer_ind] += Uint256::from(offer_amount);
+    volumes[ask_ind] += Uint256::from(return_amount);
+    CUMULATIVE_VOLUMES.save(deps.storage, &volumes)?;
+
     // Accumulate prices for the assets in the pool
     if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
         accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
@@ -753,10 +1409,23 @@ pub fn swap(
         CONFIG.save(deps.storage, &config)?;
     }
 
+    // The callback is executed after the return asset was sent to the receiver
+    if let Some(msg) = callback {
+        messages.push(
+            SwapCallbackMsg {
+                return_amount,
+                offer_asset: offer_asset.clone(),
+                msg,
+            }
+            .into_cosmos_msg(&sender)?,
+        );
+    }
+
     Ok(Response::new()
         .add_messages(
             // 1. send collateral tokens from the contract to a user
             // 2. send inactive commission fees to the Maker contract
+            // 3. execute the swap callback on the sender (if specified)
             messages,
         )
         .add_attributes(vec![
@@ -774,6 +1443,64 @@ pub fn swap(
         ]))
 }
 
+/// Splits the swap commission into the fee share and the Maker fee and builds the transfer messages.
+/// The Maker fee is also recorded in [`ACCUMULATED_FEES`].
+/// Returns the messages together with the Maker fee and the fee share amounts.
+///
+/// * **ask_info** the asset in which the commission is taken.
+///
+/// * **commission_amount** total commission of the swap.
+fn distribute_commission(
+    storage: &mut dyn Storage,
+    config: &Config,
+    fee_info: &FeeInfo,
+    ask_info: &AssetInfo,
+    commission_amount: Uint128,
+) -> StdResult<(Vec<CosmosMsg>, Uint128, Uint128)> {
+    let mut messages = vec![];
+
+    // If this pool is configured to share fees, calculate the amount to send
+    // to the receiver and add the transfer message
+    // The calculation works as follows: We take the share percentage first,
+    // and the remainder is then split between LPs and maker
+    let mut fees_commission_amount = commission_amount;
+    let mut fee_share_amount = Uint128::zero();
+    if let Some(fee_share) = config.fee_share.clone() {
+        // Calculate the fee share amount from the full commission amount
+        let share_fee_rate = Decimal::from_ratio(fee_share.bps, 10000u16);
+        fee_share_amount = fees_commission_amount * share_fee_rate;
+
+        if !fee_share_amount.is_zero() {
+            // Subtract the fee share amount from the commission
+            fees_commission_amount = fees_commission_amount.saturating_sub(fee_share_amount);
+
+            // Build send message for the shared amount
+            let fee_share_msg = Asset {
+                info: ask_info.clone(),
+                amount: fee_share_amount,
+            }
+            .into_msg(fee_share.recipient)?;
+            messages.push(fee_share_msg);
+        }
+    }
+
+    // Compute the Maker fee
+    let mut maker_fee_amount = Uint128::zero();
+    if let Some(fee_address) = fee_info.fee_address.clone() {
+        if let Some(f) =
+            calculate_maker_fee(ask_info, fees_commission_amount, fee_info.maker_fee_rate)
+        {
+            maker_fee_amount = f.amount;
+            ACCUMULATED_FEES.update(storage, ask_info, |fees| -> StdResult<_> {
+                Ok(fees.unwrap_or_default().checked_add(f.amount)?)
+            })?;
+            messages.push(f.into_msg(fee_address)?);
+        }
+    }
+
+    Ok((messages, maker_fee_amount, fee_share_amount))
+}
+
 /// Updates the pool configuration with the specified parameters in the `params` variable.
 ///
 /// * **params** new parameter values.
@@ -789,6 +1516,10 @@ pub fn update_config(
         return Err(ContractError::Unauthorized {});
     }
 
+    if CONFIG_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::ConfigFrozen {});
+    }
+
     let mut response = Response::default();
 
     match from_json::<XYKPoolUpdateParams>(&params)? {
@@ -802,7 +1533,12 @@ pub fn update_config(
 
             // Ensure the fee share isn't 0 and doesn't exceed the maximum allowed value
             if fee_share_bps == 0 || fee_share_bps > MAX_FEE_SHARE_BPS {
-                return Err(ContractError::FeeShareOutOfBounds {});
+                return Err(ContractError::InvalidFeeRate {
+                    rate: Decimal::from_ratio(fee_share_bps, 10000u16),
+                    field: "fee_share_bps".to_string(),
+                    min: Decimal::from_ratio(1u16, 10000u16),
+                    max: Decimal::from_ratio(MAX_FEE_SHARE_BPS, 10000u16),
+                });
             }
 
             // Set sharing config
@@ -830,197 +1566,1444 @@ pub fn update_config(
                 .attributes
                 .push(attr("action", "disable_fee_share"));
         }
+        XYKPoolUpdateParams::EnableArbProtection {
+            arb_oracle,
+            arb_threshold,
+            arb_tolerance,
+        } => {
+            // Ensure both the threshold and the tolerance are within (0, 1)
+            if [arb_threshold, arb_tolerance]
+                .iter()
+                .any(|value| value.is_zero() || *value >= Decimal::one())
+            {
+                return Err(ContractError::ArbProtectionParamsOutOfBounds {});
+            }
+
+            config.arb_protection = Some(ArbProtectionConfig {
+                oracle: deps.api.addr_validate(&arb_oracle)?,
+                threshold: arb_threshold,
+                tolerance: arb_tolerance,
+            });
+            CONFIG.save(deps.storage, &config)?;
+
+            response.attributes.extend([
+                attr("action", "enable_arb_protection"),
+                attr("arb_oracle", arb_oracle),
+                attr("arb_threshold", arb_threshold.to_string()),
+                attr("arb_tolerance", arb_tolerance.to_string()),
+            ]);
+        }
+        XYKPoolUpdateParams::DisableArbProtection => {
+            config.arb_protection = None;
+            CONFIG.save(deps.storage, &config)?;
+            response
+                .attributes
+                .push(attr("action", "disable_arb_protection"));
+        }
+        XYKPoolUpdateParams::SetFlashLoanFee { fee } => {
+            validate_flash_loan_fee(fee)?;
+
+            config.flash_loan_fee = fee;
+            CONFIG.save(deps.storage, &config)?;
+
+            response.attributes.extend([
+                attr("action", "set_flash_loan_fee"),
+                attr("flash_loan_fee", fee.to_string()),
+            ]);
+        }
+        XYKPoolUpdateParams::SetSwapVolumeLimit {
+            max_swap_volume_per_block,
+            volume_limit_decay_blocks,
+        } => {
+            ensure!(
+                volume_limit_decay_blocks <= MAX_VOLUME_LIMIT_DECAY_BLOCKS,
+                ContractError::VolumeLimitDecayBlocksTooLarge {
+                    max: MAX_VOLUME_LIMIT_DECAY_BLOCKS
+                }
+            );
+
+            config.max_swap_volume_per_block = max_swap_volume_per_block;
+            config.volume_limit_decay_blocks = volume_limit_decay_blocks;
+            CONFIG.save(deps.storage, &config)?;
+
+            response.attributes.extend([
+                attr("action", "set_swap_volume_limit"),
+                attr(
+                    "max_swap_volume_per_block",
+                    max_swap_volume_per_block
+                        .map(|limit| limit.to_string())
+                        .unwrap_or_else(|| "none".to_string()),
+                ),
+                attr(
+                    "volume_limit_decay_blocks",
+                    volume_limit_decay_blocks.to_string(),
+                ),
+            ]);
+        }
+        XYKPoolUpdateParams::SetPauseGuardian { pause_guardian } => {
+            config.pause_guardian = addr_opt_validate(deps.api, &pause_guardian)?;
+            CONFIG.save(deps.storage, &config)?;
+
+            response.attributes.extend([
+                attr("action", "set_pause_guardian"),
+                attr(
+                    "pause_guardian",
+                    pause_guardian.unwrap_or_else(|| "none".to_string()),
+                ),
+            ]);
+        }
     }
 
     Ok(response)
 }
 
-/// Accumulate token prices for the assets in the pool.
-/// Note that this function shifts **block_time** when any of the token prices is zero in order to not
-/// fill an accumulator with a null price for that period.
+/// Sets the fee charged on flash loans. Only the factory owner can execute this.
 ///
-/// * **x** is the balance of asset\[\0] in the pool.
+/// * **fee** new flash loan fee relative to the borrowed amount.
+pub fn set_flash_loan_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee: Decimal,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if CONFIG_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::ConfigFrozen {});
+    }
+
+    validate_flash_loan_fee(fee)?;
+
+    config.flash_loan_fee = fee;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_flash_loan_fee"),
+        attr("flash_loan_fee", fee.to_string()),
+    ]))
+}
+
+/// Sets the cap on provide liquidity slippage tolerances. Higher tolerances are clamped down to it.
+/// Only the factory owner can execute this.
 ///
-/// * **y** is the balance of asset\[\1] in the pool.
-pub fn accumulate_prices(
-    env: Env,
+/// * **max_slippage** new slippage tolerance cap, at most [`MAX_ALLOWED_SLIPPAGE`].
+pub fn set_slippage_tolerance(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_slippage: Decimal,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if CONFIG_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::ConfigFrozen {});
+    }
+
+    if max_slippage > Decimal::from_str(MAX_ALLOWED_SLIPPAGE)? {
+        return Err(ContractError::AllowedSpreadAssertion {});
+    }
+
+    config.global_max_slippage = Some(max_slippage);
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes([
+        attr("action", "set_slippage_tolerance"),
+        attr("global_max_slippage", max_slippage.to_string()),
+    ]))
+}
+
+/// Clamps a user provided slippage tolerance to the pair-level cap if one is set.
+/// A missing tolerance falls back to [`DEFAULT_SLIPPAGE`] before clamping.
+fn clamp_slippage_tolerance(
     config: &Config,
-    x: Uint128,
-    y: Uint128,
-) -> StdResult<Option<(Uint128, Uint128, u64)>> {
-    let block_time = env.block.time.seconds();
-    if block_time <= config.block_time_last {
-        return Ok(None);
+    slippage_tolerance: Option<Decimal>,
+) -> Result<Option<Decimal>, ContractError> {
+    match config.global_max_slippage {
+        Some(max_slippage) => Ok(Some(
+            slippage_tolerance
+                .unwrap_or(Decimal::from_str(DEFAULT_SLIPPAGE)?)
+                .min(max_slippage),
+        )),
+        None => Ok(slippage_tolerance),
     }
+}
 
-    // We have to shift block_time when any price is zero in order to not fill an accumulator with a null price for that period
-    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+/// Registers liquidity held by `external_contract` on behalf of the pool.
+/// Zero amounts remove the record. Only the factory owner can execute this.
+///
+/// * **amount** external liquidity amounts in the pool asset order.
+pub fn register_external_liquidity(
+    deps: DepsMut,
+    info: MessageInfo,
+    external_contract: String,
+    amount: [Uint128; 2],
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
 
-    let mut pcl0 = config.price0_cumulative_last;
-    let mut pcl1 = config.price1_cumulative_last;
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if CONFIG_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::ConfigFrozen {});
+    }
+
+    let external_contract = deps.api.addr_validate(&external_contract)?;
+    if amount.iter().all(Uint128::is_zero) {
+        EXTERNAL_LIQUIDITY.remove(deps.storage, &external_contract);
+    } else {
+        EXTERNAL_LIQUIDITY.save(deps.storage, &external_contract, &amount)?;
+    }
+
+    Ok(Response::new().add_attributes([
+        attr("action", "register_external_liquidity"),
+        attr("external_contract", external_contract),
+        attr("amount0", amount[0]),
+        attr("amount1", amount[1]),
+    ]))
+}
+
+fn validate_flash_loan_fee(fee: Decimal) -> Result<(), ContractError> {
+    let max_fee = Decimal::from_ratio(MAX_FLASH_LOAN_FEE_BPS, 10000u16);
+    if fee > max_fee {
+        return Err(ContractError::InvalidFeeRate {
+            rate: fee,
+            field: "flash_loan_fee".to_string(),
+            min: Decimal::zero(),
+            max: max_fee,
+        });
+    }
+
+    Ok(())
+}
+
+/// Lends pool assets to `receiver` and calls it with [`FlashLoanCallbackMsg`].
+/// The reply to the callback checks that the pool holds the lent assets plus fees again,
+/// otherwise the whole transaction is reverted.
+///
+/// * **assets** assets to lend. They must be pool assets.
+///
+/// * **receiver** contract receiving the assets and the callback.
+///
+/// * **msg** message passed to the receiver in the callback.
+pub fn flash_loan(
+    deps: DepsMut,
+    info: MessageInfo,
+    assets: Vec<Asset>,
+    receiver: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+
+    let receiver = deps.api.addr_validate(&receiver)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    if assets.is_empty() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut expected_balances = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+    let mut fees = vec![];
+
+    for asset in &assets {
+        if asset.amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let pool = expected_balances
+            .iter_mut()
+            .find(|pool| pool.info.equal(&asset.info))
+            .ok_or(ContractError::AssetMismatch {})?;
+
+        if asset.amount > pool.amount {
+            return Err(StdError::generic_err(format!(
+                "Not enough {} in the pool to lend {}",
+                asset.info, asset.amount
+            ))
+            .into());
+        }
+
+        // Fees are rounded up in favor of the pool
+        let fee = asset
+            .info
+            .with_balance(asset.amount.mul_ceil(config.flash_loan_fee));
+        pool.amount += fee.amount;
+        fees.push(fee);
+    }
+
+    FLASH_LOAN.save(deps.storage, &expected_balances)?;
+
+    let mut messages = assets
+        .iter()
+        .map(|asset| asset.clone().into_msg(&receiver).map(SubMsg::new))
+        .collect::<StdResult<Vec<_>>>()?;
+    messages.push(SubMsg::reply_on_success(
+        FlashLoanCallbackMsg {
+            assets: assets.clone(),
+            fees: fees.clone(),
+            msg,
+        }
+        .into_cosmos_msg(&receiver)?,
+        ReplyIds::FlashLoan as u64,
+    ));
+
+    Ok(Response::new().add_submessages(messages).add_attributes([
+        attr("action", "flash_loan"),
+        attr("sender", info.sender),
+        attr("receiver", receiver),
+        attr(
+            "assets",
+            assets
+                .iter()
+                .map(|asset| asset.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        attr(
+            "fees",
+            fees.iter()
+                .map(|asset| asset.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    ]))
+}
+
+/// Replaces a pool asset with a new one. Only the factory owner can execute this.
+/// The pair must already hold at least the same reserve of the new asset as it holds of the old one.
+/// The old asset reserve is sent to the caller and the factory is asked to register the pair
+/// under its new assets.
+///
+/// * **old_asset** the pool asset to replace.
+///
+/// * **new_asset** the asset that takes its place.
+pub fn replace_asset(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    old_asset: AssetInfo,
+    new_asset: AssetInfo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if CONFIG_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::ConfigFrozen {});
+    }
+
+    new_asset.check(deps.api)?;
+
+    if !config.pair_info.asset_infos.contains(&old_asset) {
+        return Err(ContractError::InvalidAsset(old_asset.to_string()));
+    }
+    if config.pair_info.asset_infos.contains(&new_asset) {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    let old_reserve = old_asset.query_pool(&deps.querier, &env.contract.address)?;
+    let new_reserve = new_asset.query_pool(&deps.querier, &env.contract.address)?;
+
+    if new_reserve < old_reserve {
+        return Err(ContractError::InsufficientReplacementReserve {
+            asset: new_asset.to_string(),
+            actual: new_reserve,
+        });
+    }
+
+    ACCUMULATED_FEES.remove(deps.storage, &old_asset);
+
+    let old_asset_infos = config.pair_info.asset_infos.clone();
+    for asset_info in config.pair_info.asset_infos.iter_mut() {
+        if *asset_info == old_asset {
+            *asset_info = new_asset.clone();
+        }
+    }
+
+    if config.track_asset_balances {
+        BALANCES.save(deps.storage, &old_asset, &Uint128::zero(), env.block.height)?;
+        BALANCES.save(deps.storage, &new_asset, &new_reserve, env.block.height)?;
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    let messages = replace_asset_messages(
+        &config.factory_addr,
+        old_asset_infos,
+        old_asset.clone(),
+        old_reserve,
+        &info.sender,
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        attr("action", "replace_asset"),
+        attr("old_asset", old_asset.to_string()),
+        attr("new_asset", new_asset.to_string()),
+        attr("reserve", new_reserve),
+    ]))
+}
+
+/// Permanently freezes the pair configuration. Only the factory owner can execute this.
+/// Once frozen, [`update_config`], [`set_flash_loan_fee`], [`set_slippage_tolerance`],
+/// [`register_external_liquidity`], [`replace_asset`] and [`sweep`] are rejected with
+/// [`ContractError::ConfigFrozen`].
+pub fn freeze_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if CONFIG_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::ConfigFrozen {});
+    }
+
+    CONFIG_FROZEN.save(deps.storage, &true)?;
+
+    Ok(Response::new()
+        .add_event(Event::new("config-frozen").add_attribute("pair", env.contract.address))
+        .add_attribute("action", "freeze_config"))
+}
+
+/// Halts swaps, liquidity provision and withdrawals. Only the factory owner or the pause
+/// guardian can execute this.
+pub fn pause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    if info.sender != factory_config.owner && Some(&info.sender) != config.pause_guardian.as_ref() {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PAUSED.save(deps.storage, &true)?;
+
+    Ok(Response::new().add_attribute("action", "pause"))
+}
+
+/// Resumes the operations halted by [`pause`]. Only the factory owner can execute this.
+pub fn unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    PAUSED.save(deps.storage, &false)?;
+
+    Ok(Response::new().add_attribute("action", "unpause"))
+}
+
+/// Sends tokens which are not part of the pool to the `recipient`. Only the factory owner
+/// can execute this.
+///
+/// * **assets** the assets to sweep. If not set, all native balances except the pool assets
+/// and the LP token are swept.
+pub fn sweep(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    assets: Option<Vec<AssetInfo>>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    if info.sender != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if CONFIG_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+        return Err(ContractError::ConfigFrozen {});
+    }
+
+    let recipient = deps.api.addr_validate(&recipient)?;
+
+    // The LP token is excluded as the pair holds the minimum liquidity amount itself
+    let mut protected_assets = config.pair_info.asset_infos.clone();
+    protected_assets.push(AssetInfo::NativeToken {
+        denom: config.pair_info.liquidity_token.clone(),
+    });
+
+    let balances = match assets {
+        Some(assets) => assets
+            .into_iter()
+            .map(|asset_info| {
+                let asset_info = asset_info.to_checked(deps.api)?;
+                if protected_assets.contains(&asset_info) {
+                    return Err(ContractError::SweepPoolAsset {
+                        asset: asset_info.to_string(),
+                    });
+                }
+                let amount = asset_info.query_pool(&deps.querier, &env.contract.address)?;
+                Ok(asset_info.with_balance(amount))
+            })
+            .collect::<Result<Vec<_>, ContractError>>()?,
+        None => deps
+            .querier
+            .query_all_balances(&env.contract.address)?
+            .into_iter()
+            .map(|coin| native_asset_info(coin.denom).with_balance(coin.amount))
+            .filter(|asset| !protected_assets.contains(&asset.info))
+            .collect(),
+    };
+
+    let mut response = Response::new();
+    for mut asset in balances {
+        // Liquidity request bounties are escrowed on behalf of the requesters
+        if let AssetInfo::NativeToken { denom } = &asset.info {
+            let escrowed_amount = ESCROWED_BOUNTIES
+                .may_load(deps.storage, denom)?
+                .unwrap_or_default();
+            asset.amount = asset.amount.saturating_sub(escrowed_amount);
+        }
+        if asset.amount.is_zero() {
+            continue;
+        }
+
+        response = response
+            .add_event(
+                Event::new("sweep")
+                    .add_attribute("asset", asset.info.to_string())
+                    .add_attribute("amount", asset.amount)
+                    .add_attribute("recipient", &recipient),
+            )
+            .add_message(asset.into_msg(&recipient)?);
+    }
+
+    Ok(response.add_attribute("action", "sweep"))
+}
+
+/// Creates a liquidity request escrowing the attached native tokens as a bounty for the
+/// liquidity provider who fulfills it.
+///
+/// * **amount** the minimum amount of LP tokens a provision has to mint to fulfill the request.
+///
+/// * **deadline** the timestamp (in seconds) after which the request can no longer be fulfilled.
+pub fn request_liquidity_provision(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    amount: Uint128,
+    deadline: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    if info.funds.is_empty() {
+        return Err(ContractError::MissingLiquidityRequestBounty {});
+    }
+
+    // The bounty is held by the pair, so pool assets would be counted as pool reserves
+    for coin in &info.funds {
+        let asset_info = native_asset_info(coin.denom.clone());
+        if coin.denom == config.pair_info.liquidity_token
+            || config.pair_info.asset_infos.contains(&asset_info)
+        {
+            return Err(ContractError::InvalidLiquidityRequestBounty {
+                asset: asset_info.to_string(),
+            });
+        }
+    }
+
+    if deadline <= env.block.time.seconds() {
+        return Err(ContractError::InvalidLiquidityRequestDeadline {});
+    }
+
+    let request_id = NEXT_LIQUIDITY_REQUEST_ID
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    NEXT_LIQUIDITY_REQUEST_ID.save(deps.storage, &(request_id + 1))?;
+
+    LIQUIDITY_REQUESTS.save(
+        deps.storage,
+        request_id,
+        &LiquidityRequest {
+            requester: info.sender.clone(),
+            amount,
+            deadline,
+            bounty: info.funds.clone(),
+            fulfillment: None,
+        },
+    )?;
+
+    for coin in &info.funds {
+        ESCROWED_BOUNTIES.update::<_, StdError>(deps.storage, &coin.denom, |escrowed| {
+            Ok(escrowed.unwrap_or_default().checked_add(coin.amount)?)
+        })?;
+    }
+
+    Ok(Response::new()
+        .add_event(
+            Event::new("liquidity_request")
+                .add_attribute("request_id", request_id.to_string())
+                .add_attribute("requester", info.sender)
+                .add_attribute("amount", amount)
+                .add_attribute("deadline", deadline.to_string())
+                .add_attribute("bounty", coins_to_string(&info.funds)),
+        )
+        .add_attribute("action", "request_liquidity_provision"))
+}
+
+/// Cancels an unfulfilled liquidity request and refunds the bounty. Only the requester can execute this.
+pub fn cancel_liquidity_request(
+    deps: DepsMut,
+    info: MessageInfo,
+    request_id: u64,
+) -> Result<Response, ContractError> {
+    let request = LIQUIDITY_REQUESTS
+        .may_load(deps.storage, request_id)?
+        .ok_or(ContractError::LiquidityRequestNotFound { request_id })?;
+
+    if info.sender != request.requester {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if request.fulfillment.is_some() {
+        return Err(ContractError::LiquidityRequestFulfilled { request_id });
+    }
+
+    LIQUIDITY_REQUESTS.remove(deps.storage, request_id);
+    release_escrowed_bounty(deps.storage, &request.bounty)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: request.requester.to_string(),
+            amount: request.bounty,
+        })
+        .add_attributes([
+            attr("action", "cancel_liquidity_request"),
+            attr("request_id", request_id.to_string()),
+        ]))
+}
+
+/// Sends the LP tokens locked by a fulfilled liquidity request and the request bounty to the
+/// liquidity provider. Anyone can execute this once the lock period is over.
+pub fn claim_liquidity_request(
+    deps: DepsMut,
+    env: Env,
+    request_id: u64,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let request = LIQUIDITY_REQUESTS
+        .may_load(deps.storage, request_id)?
+        .ok_or(ContractError::LiquidityRequestNotFound { request_id })?;
+
+    let fulfillment = request
+        .fulfillment
+        .ok_or(ContractError::LiquidityRequestNotFulfilled { request_id })?;
+
+    if env.block.time.seconds() < fulfillment.unlocks_at {
+        return Err(ContractError::LiquidityRequestLocked {
+            request_id,
+            unlocks_at: fulfillment.unlocks_at,
+        });
+    }
+
+    LIQUIDITY_REQUESTS.remove(deps.storage, request_id);
+    release_escrowed_bounty(deps.storage, &request.bounty)?;
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: fulfillment.provider.to_string(),
+            amount: vec![coin(
+                fulfillment.lp_amount.u128(),
+                config.pair_info.liquidity_token,
+            )],
+        })
+        .add_message(BankMsg::Send {
+            to_address: fulfillment.provider.to_string(),
+            amount: request.bounty.clone(),
+        })
+        .add_attributes([
+            attr("action", "claim_liquidity_request"),
+            attr("request_id", request_id.to_string()),
+            attr("provider", fulfillment.provider),
+            attr("lp_amount", fulfillment.lp_amount),
+            attr("bounty", coins_to_string(&request.bounty)),
+        ]))
+}
+
+/// Removes a paid out or refunded bounty from the escrowed totals.
+fn release_escrowed_bounty(storage: &mut dyn Storage, bounty: &[Coin]) -> StdResult<()> {
+    for coin in bounty {
+        let escrowed = ESCROWED_BOUNTIES.load(storage, &coin.denom)?;
+        ESCROWED_BOUNTIES.save(storage, &coin.denom, &escrowed.checked_sub(coin.amount)?)?;
+    }
+
+    Ok(())
+}
+
+fn coins_to_string(coins: &[Coin]) -> String {
+    coins
+        .iter()
+        .map(|coin| coin.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Accumulate token prices for the assets in the pool.
+/// Note that this function shifts **block_time** when any of the token prices is zero in order to not
+/// fill an accumulator with a null price for that period.
+///
+/// * **x** is the balance of asset\[\0] in the pool.
+///
+/// * **y** is the balance of asset\[\1] in the pool.
+pub fn accumulate_prices(
+    env: Env,
+    config: &Config,
+    x: Uint128,
+    y: Uint128,
+) -> StdResult<Option<(Uint128, Uint128, u64)>> {
+    let block_time = env.block.time.seconds();
+    if block_time <= config.block_time_last {
+        return Ok(None);
+    }
+
+    // We have to shift block_time when any price is zero in order to not fill an accumulator with a null price for that period
+    let time_elapsed = Uint128::from(block_time - config.block_time_last);
+
+    let mut pcl0 = config.price0_cumulative_last;
+    let mut pcl1 = config.price1_cumulative_last;
+
+    if !x.is_zero() && !y.is_zero() {
+        let price_precision = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
+        pcl0 = config.price0_cumulative_last.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(y, x),
+        );
+        pcl1 = config.price1_cumulative_last.wrapping_add(
+            time_elapsed
+                .checked_mul(price_precision)?
+                .multiply_ratio(x, y),
+        );
+    };
+
+    Ok(Some((pcl0, pcl1, block_time)))
+}
+
+/// Calculates the amount of fees the Maker contract gets according to specified pair parameters.
+/// Returns a [`None`] if the Maker fee is zero, otherwise returns a [`Asset`] struct with the specified attributes.
+///
+/// * **pool_info** contains information about the pool asset for which the commission will be calculated.
+///
+/// * **commission_amount** is the total amount of fees charged for a swap.
+///
+/// * **maker_commission_rate** is the percentage of fees that go to the Maker contract.
+pub fn calculate_maker_fee(
+    pool_info: &AssetInfo,
+    commission_amount: Uint128,
+    maker_commission_rate: Decimal,
+) -> Option<Asset> {
+    let maker_fee: Uint128 = commission_amount * maker_commission_rate;
+    if maker_fee.is_zero() {
+        return None;
+    }
+
+    Some(Asset {
+        info: pool_info.clone(),
+        amount: maker_fee,
+    })
+}
+
+/// Exposes all the queries available in the contract.
+///
+/// ## Queries
+/// * **QueryMsg::Pair {}** Returns information about the pair in an object of type [`PairInfo`].
+///
+/// * **QueryMsg::Pool {}** Returns information about the amount of assets in the pair contract as
+/// well as the amount of LP tokens issued using an object of type [`PoolResponse`].
+///
+/// * **QueryMsg::Share { amount }** Returns the amount of assets that could be withdrawn from the pool
+/// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
+///
+/// * **QueryMsg::Simulation { offer_asset }** Returns the result of a swap simulation using a [`SimulationResponse`] object.
+///
+/// * **QueryMsg::ReverseSimulation { ask_asset }** Returns the result of a reverse swap simulation  using
+/// a [`ReverseSimulationResponse`] object.
+///
+/// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
+/// pool using a [`CumulativePricesResponse`] object.
+///
+/// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
+///
+/// * **QueryMsg::AssetBalanceAt { asset_info, block_height }** Returns the balance of the specified asset that was in the pool
+/// just preceeding the moment of the specified block height creation.
+/// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amount of assets that could be withdrawn from the pool
+/// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
+/// * **QueryMsg::SimulateProvide { msg }** Simulates the liquidity provision in the pair contract.
+/// * **QueryMsg::ExecPrice { offer_asset }** Returns the execution price for the specified offer asset.
+/// * **QueryMsg::Slippage { offer_asset, slippage_tolerance }** Checks whether a swap would succeed
+/// with the given slippage tolerance using a [`SlippageValidationResponse`] object.
+/// * **QueryMsg::TokenAllowance { owner, spender }** Returns the allowance of the pool CW20 token
+/// using an [`AllowanceResponse`] object.
+/// * **QueryMsg::MinSwapAmount {}** Returns the minimum viable swap amount of each pool asset
+/// using a [`MinSwapAmountResponse`] object.
+/// * **QueryMsg::ReserveRatio {}** Returns the share of each pool asset in the total pool value
+/// using a [`ReserveRatioResponse`] object.
+/// * **QueryMsg::NextPriceWithSize { offer_asset }** Returns the price impact curve of a swap
+/// using a [`NextPriceResponse`] object.
+///
+/// * **QueryMsg::IsPoolDepleted {}** Returns whether all liquidity has been withdrawn from the pool
+/// using an [`IsPoolDepletedResponse`] object.
+///
+/// * **QueryMsg::MaxSwapable { ask_asset }** Returns the largest swap into the given asset which keeps
+/// the pool reserve above the minimum liquidity using a [`MaxSwapableResponse`] object.
+///
+/// * **QueryMsg::CheckSwapLimits { offer_asset, belief_price, max_spread }** Checks whether a swap
+/// would pass the max spread check using a [`CheckSwapLimitsResponse`] object.
+///
+/// * **QueryMsg::AccumulatedFees {}** Returns the protocol fees sent to the Maker using an
+/// [`AccumulatedFeesResponse`] object.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Pair {} => to_json_binary(&CONFIG.load(deps.storage)?.pair_info),
+        QueryMsg::Pool {} => to_json_binary(&query_pool(deps)?),
+        QueryMsg::Share { amount } => to_json_binary(&query_share(deps, amount)?),
+        QueryMsg::Simulation { offer_asset, .. } => {
+            to_json_binary(&query_simulation(deps, offer_asset)?)
+        }
+        QueryMsg::ReverseSimulation { ask_asset, .. } => {
+            to_json_binary(&query_reverse_simulation(deps, ask_asset)?)
+        }
+        QueryMsg::CumulativePrices {} => to_json_binary(&query_cumulative_prices(deps, env)?),
+        QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
+        QueryMsg::AssetBalanceAt {
+            asset_info,
+            block_height,
+        } => to_json_binary(&query_asset_balances_at(deps, asset_info, block_height)?),
+        QueryMsg::SimulateWithdraw { lp_amount } => to_json_binary(&query_share(deps, lp_amount)?),
+        QueryMsg::SimulateProvide {
+            assets,
+            slippage_tolerance,
+        } => to_json_binary(&query_simulate_provide(deps, assets, slippage_tolerance)?),
+        QueryMsg::ExecPrice { offer_asset } => {
+            to_json_binary(&query_exec_price(deps, offer_asset)?)
+        }
+        QueryMsg::Slippage {
+            offer_asset,
+            slippage_tolerance,
+        } => to_json_binary(&query_slippage(deps, offer_asset, slippage_tolerance)?),
+        QueryMsg::TokenAllowance { owner, spender } => {
+            to_json_binary(&query_token_allowance(deps, owner, spender)?)
+        }
+        QueryMsg::MinSwapAmount {} => to_json_binary(&query_min_swap_amount(deps)?),
+        QueryMsg::ReserveRatio {} => to_json_binary(&query_reserve_ratio(deps)?),
+        QueryMsg::NextPriceWithSize { offer_asset } => {
+            to_json_binary(&query_next_price_with_size(deps, offer_asset)?)
+        }
+        QueryMsg::IsPoolDepleted {} => to_json_binary(&query_is_pool_depleted(deps)?),
+        QueryMsg::LiquidityDepth { price_step, levels } => {
+            to_json_binary(&query_liquidity_depth(deps, price_step, levels)?)
+        }
+        QueryMsg::MaxSwapable { ask_asset } => {
+            to_json_binary(&query_max_swapable(deps, ask_asset)?)
+        }
+        QueryMsg::CheckSwapLimits {
+            offer_asset,
+            belief_price,
+            max_spread,
+        } => to_json_binary(&query_check_swap_limits(
+            deps,
+            offer_asset,
+            belief_price,
+            max_spread,
+        )?),
+        QueryMsg::AccumulatedFees {} => to_json_binary(&query_accumulated_fees(deps)?),
+        _ => Err(StdError::generic_err("Query is not supported")),
+    }
+}
+
+/// Returns the amounts of assets in the pair contract as well as the amount of LP
+/// tokens currently minted in an object of type [`PoolResponse`].
+pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let (assets, total_share) = pool_info(deps.querier, &config)?;
+
+    let external_liquidity = EXTERNAL_LIQUIDITY
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| {
+            let (addr, [amount0, amount1]) = item?;
+            Ok((
+                addr,
+                [
+                    config.pair_info.asset_infos[0].with_balance(amount0),
+                    config.pair_info.asset_infos[1].with_balance(amount1),
+                ],
+            ))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let resp = PoolResponse {
+        assets,
+        total_share,
+        external_liquidity,
+    };
+
+    Ok(resp)
+}
+
+/// Returns the amount of assets that could be withdrawn from the pool using a specific amount of LP tokens.
+/// The result is returned in a vector that contains objects of type [`Asset`].
+///
+/// * **amount** is the amount of LP tokens for which we calculate associated amounts of assets.
+pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
+    let config = CONFIG.load(deps.storage)?;
+    let (pools, total_share) = pool_info(deps.querier, &config)?;
+    let refund_assets = get_share_in_assets(&pools, amount, total_share);
+
+    Ok(refund_assets)
+}
+
+/// Returns the allowance granted by `owner` to `spender` for the CW20 token in the pool.
+/// Fails if the pool doesn't hold exactly one CW20 token.
+///
+/// * **owner** address that granted the allowance.
+///
+/// * **spender** address that is allowed to spend the tokens.
+pub fn query_token_allowance(
+    deps: Deps,
+    owner: String,
+    spender: String,
+) -> StdResult<AllowanceResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let tokens: Vec<&Addr> = config
+        .pair_info
+        .asset_infos
+        .iter()
+        .filter_map(AssetInfo::token_contract)
+        .collect();
+
+    match tokens.as_slice() {
+        [token] => deps
+            .querier
+            .query_wasm_smart(*token, &Cw20QueryMsg::Allowance { owner, spender }),
+        [] => Err(StdError::generic_err("The pair doesn't hold CW20 tokens")),
+        _ => Err(StdError::generic_err(
+            "The pair holds several CW20 tokens, query the token contract directly",
+        )),
+    }
+}
+
+/// Returns information about a swap simulation in a [`SimulationResponse`] object.
+///
+/// * **offer_asset** is the asset to swap as well as an amount of the said asset.
+pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    ensure_not_depleted(deps, &config)?;
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+
+    let offer_pool: Asset;
+    let ask_pool: Asset;
+    if offer_asset.info.equal(&pools[0].info) {
+        offer_pool = pools[0].clone();
+        ask_pool = pools[1].clone();
+    } else if offer_asset.info.equal(&pools[1].info) {
+        offer_pool = pools[1].clone();
+        ask_pool = pools[0].clone();
+    } else {
+        return Err(StdError::generic_err(
+            "Given offer asset does not belong in the pair",
+        ));
+    }
+
+    // Get fee info from the factory contract
+    let fee_info = query_fee_info(
+        &deps.querier,
+        config.factory_addr,
+        config.pair_info.pair_type,
+    )?;
+
+    let (return_amount, spread_amount, commission_amount) = compute_swap(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        fee_info.total_fee_rate,
+    )?;
+
+    let price_impact = calculate_price_impact(
+        Decimal256::checked_from_ratio(ask_pool.amount, offer_pool.amount).unwrap_or_default(),
+        Decimal256::from_ratio(offer_asset.amount, 1u8),
+        Decimal256::from_ratio(return_amount, 1u8),
+    )?;
+
+    Ok(SimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+        price_impact,
+        gas_estimate: XYK_SWAP.for_offer(&offer_asset.info),
+    })
+}
+
+/// Returns the execution price of a swap defined as `offer_amount / return_amount`
+/// where `return_amount` is the amount received after the fee is deducted.
+/// Unlike the spot price it accounts for both the spread and the fee of the given offer amount.
+///
+/// * **offer_asset** is the asset to swap as well as an amount of the said asset.
+pub fn query_exec_price(deps: Deps, offer_asset: Asset) -> StdResult<Decimal> {
+    let SimulationResponse { return_amount, .. } = query_simulation(deps, offer_asset.clone())?;
+
+    if return_amount.is_zero() {
+        return Err(StdError::generic_err(
+            "Offer amount is too small to calculate the execution price",
+        ));
+    }
+
+    Ok(
+        Decimal::checked_from_ratio(offer_asset.amount, return_amount)
+            .map_err(ContractError::from)?,
+    )
+}
+
+/// Returns execution prices for offer amounts of 1, 10, 100, ... up to `offer_asset.amount`
+/// in a [`NextPriceResponse`] object.
+///
+/// * **offer_asset** is the asset to swap as well as the largest amount to price.
+pub fn query_next_price_with_size(deps: Deps, offer_asset: Asset) -> StdResult<NextPriceResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+
+    let (offer_pool, ask_pool) = if offer_asset.info.equal(&pools[0].info) {
+        (&pools[0], &pools[1])
+    } else if offer_asset.info.equal(&pools[1].info) {
+        (&pools[1], &pools[0])
+    } else {
+        return Err(StdError::generic_err(
+            "Given offer asset does not belong in the pair",
+        ));
+    };
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        config.factory_addr,
+        config.pair_info.pair_type,
+    )?;
+
+    let price_levels = compute_price_levels(
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        fee_info.total_fee_rate,
+    )?;
+
+    Ok(NextPriceResponse { price_levels })
+}
+
+/// Returns the pool depth at `levels` prices spaced by `price_step` around the current price.
+pub fn query_liquidity_depth(
+    deps: Deps,
+    price_step: Decimal,
+    levels: u8,
+) -> StdResult<LiquidityDepthResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+
+    let (bids, asks) =
+        compute_liquidity_depth(pools[0].amount, pools[1].amount, price_step, levels)?;
+
+    Ok(LiquidityDepthResponse { bids, asks })
+}
+
+/// Calculates the amounts needed to move the price of the first asset to `price * (1 ± price_step * i)`
+/// for `i` in `0..levels`. In a constant product pool moving the price by a factor `r` scales
+/// the offer pool by `sqrt(r)`, thus the amount is `pool * |sqrt(r)^±1 - 1|`.
+/// The commission retained by the pool is not taken into account.
+/// Bid levels at or below zero price are omitted.
+pub fn compute_liquidity_depth(
+    pool0: Uint128,
+    pool1: Uint128,
+    price_step: Decimal,
+    levels: u8,
+) -> StdResult<(Vec<DepthLevel>, Vec<DepthLevel>)> {
+    if price_step.is_zero() {
+        return Err(StdError::generic_err(
+            "Price step must be greater than zero",
+        ));
+    }
+    if levels > MAX_DEPTH_LEVELS {
+        return Err(StdError::generic_err(format!(
+            "Number of levels must not exceed {MAX_DEPTH_LEVELS}"
+        )));
+    }
+    if pool0.is_zero() || pool1.is_zero() {
+        return Err(StdError::generic_err("One of the pools is empty"));
+    }
+
+    let price = Decimal256::from_ratio(pool1, pool0);
+    let price_step = Decimal256::from(price_step);
+
+    let mut bids = vec![];
+    let mut asks = vec![];
+    for i in 0..levels {
+        let shift = price_step.checked_mul(Decimal256::from_ratio(i, 1u8))?;
+
+        // Bids sell the first asset into the pool
+        if shift < Decimal256::one() {
+            let ratio = Decimal256::one() - shift;
+            let size =
+                Uint256::from(pool0) * (Decimal256::one() / ratio.sqrt() - Decimal256::one());
+            bids.push(DepthLevel {
+                price: try_dec256_into_dec(price * ratio)?,
+                cumulative_size: size.try_into()?,
+            });
+        }
+
+        // Asks buy the first asset from the pool with the second one
+        let ratio = Decimal256::one() + shift;
+        let size = Uint256::from(pool1) * (ratio.sqrt() - Decimal256::one());
+        asks.push(DepthLevel {
+            price: try_dec256_into_dec(price.checked_mul(ratio)?)?,
+            cumulative_size: size.try_into()?,
+        });
+    }
+
+    Ok((bids, asks))
+}
+
+/// Returns the largest offer amount which keeps the `ask_asset` reserve at or above
+/// [`MINIMUM_LIQUIDITY_AMOUNT`], together with the amount it returns.
+///
+/// * **ask_asset** is the asset to receive from the swap.
+pub fn query_max_swapable(deps: Deps, ask_asset: AssetInfo) -> StdResult<MaxSwapableResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    ensure_not_depleted(deps, &config)?;
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+
+    let (offer_pool, ask_pool) = if ask_asset.equal(&pools[0].info) {
+        (pools[1].amount, pools[0].amount)
+    } else if ask_asset.equal(&pools[1].info) {
+        (pools[0].amount, pools[1].amount)
+    } else {
+        return Err(StdError::generic_err(
+            "Given ask asset doesn't belong to pairs",
+        ));
+    };
+
+    let fee_info = query_fee_info(
+        &deps.querier,
+        config.factory_addr,
+        config.pair_info.pair_type,
+    )?;
+
+    let (max_offer, max_return) =
+        compute_max_swapable(offer_pool, ask_pool, fee_info.total_fee_rate)?;
+
+    Ok(MaxSwapableResponse {
+        max_offer,
+        max_return,
+        effective_price: Decimal::checked_from_ratio(max_offer, max_return).unwrap_or_default(),
+    })
+}
+
+/// Calculates the largest offer amount after which the ask pool still holds at least
+/// [`MINIMUM_LIQUIDITY_AMOUNT`] before the commission is retained, i.e.
+/// `offer_pool + max_offer <= offer_pool * ask_pool / MINIMUM_LIQUIDITY_AMOUNT`.
+/// Returns the offer amount and the return amount net of the commission.
+pub fn compute_max_swapable(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    commission_rate: Decimal,
+) -> StdResult<(Uint128, Uint128)> {
+    if offer_pool.is_zero() || ask_pool.is_zero() {
+        return Err(StdError::generic_err("One of the pools is empty"));
+    }
+    if ask_pool <= MINIMUM_LIQUIDITY_AMOUNT {
+        return Ok((Uint128::zero(), Uint128::zero()));
+    }
+
+    let max_offer: Uint128 = (offer_pool.full_mul(ask_pool)
+        / Uint256::from(MINIMUM_LIQUIDITY_AMOUNT)
+        - Uint256::from(offer_pool))
+    .try_into()?;
+    if max_offer.is_zero() {
+        return Ok((Uint128::zero(), Uint128::zero()));
+    }
+
+    let (max_return, ..) = compute_swap(offer_pool, ask_pool, max_offer, commission_rate)?;
+
+    Ok((max_offer, max_return))
+}
+
+/// Calculates execution prices for offer amounts growing by a factor of 10 from 1 up to `max_offer_amount`.
+/// The last level always uses `max_offer_amount`. Amounts which return nothing are skipped.
+pub fn compute_price_levels(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    max_offer_amount: Uint128,
+    commission_rate: Decimal,
+) -> StdResult<Vec<(Uint128, Decimal)>> {
+    if max_offer_amount.is_zero() {
+        return Err(StdError::generic_err(
+            "Offer amount must be greater than zero",
+        ));
+    }
 
-    if !x.is_zero() && !y.is_zero() {
-        let price_precision = Uint128::from(10u128.pow(TWAP_PRECISION.into()));
-        pcl0 = config.price0_cumulative_last.wrapping_add(
-            time_elapsed
-                .checked_mul(price_precision)?
-                .multiply_ratio(y, x),
-        );
-        pcl1 = config.price1_cumulative_last.wrapping_add(
-            time_elapsed
-                .checked_mul(price_precision)?
-                .multiply_ratio(x, y),
-        );
-    };
+    let mut sizes = vec![];
+    let mut size = Uint128::one();
+    while size < max_offer_amount {
+        sizes.push(size);
+        size = match size.checked_mul(10u128.into()) {
+            Ok(size) => size,
+            Err(_) => break,
+        };
+    }
+    sizes.push(max_offer_amount);
 
-    Ok(Some((pcl0, pcl1, block_time)))
+    sizes
+        .into_iter()
+        .filter_map(
+            |size| match compute_swap(offer_pool, ask_pool, size, commission_rate) {
+                Ok((return_amount, ..)) if return_amount.is_zero() => None,
+                Ok((return_amount, ..)) => Some(
+                    Decimal::checked_from_ratio(size, return_amount)
+                        .map(|price| (size, price))
+                        .map_err(|err| StdError::from(ContractError::from(err))),
+                ),
+                Err(err) => Some(Err(err)),
+            },
+        )
+        .collect()
 }
 
-/// Calculates the amount of fees the Maker contract gets according to specified pair parameters.
-/// Returns a [`None`] if the Maker fee is zero, otherwise returns a [`Asset`] struct with the specified attributes.
+/// Checks whether a swap would pass the slippage check with the given tolerance.
+/// The slippage is calculated the same way as in [`assert_max_spread`] when no belief price is specified.
 ///
-/// * **pool_info** contains information about the pool asset for which the commission will be calculated.
-///
-/// * **commission_amount** is the total amount of fees charged for a swap.
+/// * **offer_asset** is the asset to swap as well as an amount of the said asset.
 ///
-/// * **maker_commission_rate** is the percentage of fees that go to the Maker contract.
-pub fn calculate_maker_fee(
-    pool_info: &AssetInfo,
-    commission_amount: Uint128,
-    maker_commission_rate: Decimal,
-) -> Option<Asset> {
-    let maker_fee: Uint128 = commission_amount * maker_commission_rate;
-    if maker_fee.is_zero() {
-        return None;
+/// * **slippage_tolerance** is the maximum slippage the swap may have.
+pub fn query_slippage(
+    deps: Deps,
+    offer_asset: Asset,
+    slippage_tolerance: Decimal,
+) -> StdResult<SlippageValidationResponse> {
+    if slippage_tolerance > Decimal::from_str(MAX_ALLOWED_SLIPPAGE)? {
+        return Err(StdError::generic_err(
+            ContractError::AllowedSpreadAssertion {}.to_string(),
+        ));
     }
 
-    Some(Asset {
-        info: pool_info.clone(),
-        amount: maker_fee,
+    let SimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+        ..
+    } = query_simulation(deps, offer_asset)?;
+
+    // The amount of ask assets the swap would return at the current pool price
+    let ideal_return = return_amount + commission_amount + spread_amount;
+    let actual_slippage =
+        Decimal::checked_from_ratio(spread_amount, ideal_return).map_err(ContractError::from)?;
+    let min_return =
+        (ideal_return * (Decimal::one() - slippage_tolerance)).saturating_sub(commission_amount);
+
+    Ok(SlippageValidationResponse {
+        would_succeed: actual_slippage <= slippage_tolerance,
+        actual_slippage,
+        min_return,
     })
 }
 
-/// Exposes all the queries available in the contract.
+/// Runs the max spread check of the swap handler against a simulated swap without executing it.
 ///
-/// ## Queries
-/// * **QueryMsg::Pair {}** Returns information about the pair in an object of type [`PairInfo`].
-///
-/// * **QueryMsg::Pool {}** Returns information about the amount of assets in the pair contract as
-/// well as the amount of LP tokens issued using an object of type [`PoolResponse`].
-///
-/// * **QueryMsg::Share { amount }** Returns the amount of assets that could be withdrawn from the pool
-/// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
-///
-/// * **QueryMsg::Simulation { offer_asset }** Returns the result of a swap simulation using a [`SimulationResponse`] object.
-///
-/// * **QueryMsg::ReverseSimulation { ask_asset }** Returns the result of a reverse swap simulation  using
-/// a [`ReverseSimulationResponse`] object.
-///
-/// * **QueryMsg::CumulativePrices {}** Returns information about cumulative prices for the assets in the
-/// pool using a [`CumulativePricesResponse`] object.
+/// * **offer_asset** is the asset to swap as well as an amount of the said asset.
 ///
-/// * **QueryMsg::Config {}** Returns the configuration for the pair contract using a [`ConfigResponse`] object.
+/// * **belief_price** belief price used in the swap.
 ///
-/// * **QueryMsg::AssetBalanceAt { asset_info, block_height }** Returns the balance of the specified asset that was in the pool
-/// just preceeding the moment of the specified block height creation.
-/// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amount of assets that could be withdrawn from the pool
-/// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
-/// * **QueryMsg::SimulateProvide { msg }** Simulates the liquidity provision in the pair contract.
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::Pair {} => to_json_binary(&CONFIG.load(deps.storage)?.pair_info),
-        QueryMsg::Pool {} => to_json_binary(&query_pool(deps)?),
-        QueryMsg::Share { amount } => to_json_binary(&query_share(deps, amount)?),
-        QueryMsg::Simulation { offer_asset, .. } => {
-            to_json_binary(&query_simulation(deps, offer_asset)?)
-        }
-        QueryMsg::ReverseSimulation { ask_asset, .. } => {
-            to_json_binary(&query_reverse_simulation(deps, ask_asset)?)
-        }
-        QueryMsg::CumulativePrices {} => to_json_binary(&query_cumulative_prices(deps, env)?),
-        QueryMsg::Config {} => to_json_binary(&query_config(deps)?),
-        QueryMsg::AssetBalanceAt {
-            asset_info,
-            block_height,
-        } => to_json_binary(&query_asset_balances_at(deps, asset_info, block_height)?),
-        QueryMsg::SimulateWithdraw { lp_amount } => to_json_binary(&query_share(deps, lp_amount)?),
-        QueryMsg::SimulateProvide {
-            assets,
-            slippage_tolerance,
-        } => to_json_binary(&query_simulate_provide(deps, assets, slippage_tolerance)?),
-        _ => Err(StdError::generic_err("Query is not supported")),
+/// * **max_spread** max spread allowed so that the swap can be executed successfully.
+pub fn query_check_swap_limits(
+    deps: Deps,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+) -> StdResult<CheckSwapLimitsResponse> {
+    if max_spread.unwrap_or(Decimal::from_str(DEFAULT_SLIPPAGE)?)
+        > Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?
+    {
+        return Err(StdError::generic_err(
+            ContractError::AllowedSpreadAssertion {}.to_string(),
+        ));
     }
-}
 
-/// Returns the amounts of assets in the pair contract as well as the amount of LP
-/// tokens currently minted in an object of type [`PoolResponse`].
-pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
-    let config = CONFIG.load(deps.storage)?;
-    let (assets, total_share) = pool_info(deps.querier, &config)?;
+    let offer_amount = offer_asset.amount;
+    let SimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+        ..
+    } = query_simulation(deps, offer_asset)?;
 
-    let resp = PoolResponse {
-        assets,
-        total_share,
-    };
+    // The swap handler checks the return amount before the commission is deducted
+    let gross_return = return_amount + commission_amount;
+    let checked_spread =
+        effective_spread_amount(belief_price, offer_amount, gross_return, spread_amount)?;
 
-    Ok(resp)
+    Ok(CheckSwapLimitsResponse {
+        would_succeed: assert_max_spread(
+            belief_price,
+            max_spread,
+            offer_amount,
+            gross_return,
+            spread_amount,
+        )
+        .is_ok(),
+        expected_spread: Decimal::checked_from_ratio(checked_spread, gross_return + checked_spread)
+            .unwrap_or_default(),
+        expected_return: return_amount,
+    })
 }
 
-/// Returns the amount of assets that could be withdrawn from the pool using a specific amount of LP tokens.
-/// The result is returned in a vector that contains objects of type [`Asset`].
-///
-/// * **amount** is the amount of LP tokens for which we calculate associated amounts of assets.
-pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
+/// Returns the protocol fees the pool sent to the Maker in an [`AccumulatedFeesResponse`] object.
+pub fn query_accumulated_fees(deps: Deps) -> StdResult<AccumulatedFeesResponse> {
     let config = CONFIG.load(deps.storage)?;
-    let (pools, total_share) = pool_info(deps.querier, &config)?;
-    let refund_assets = get_share_in_assets(&pools, amount, total_share);
 
-    Ok(refund_assets)
+    let fees = config
+        .pair_info
+        .asset_infos
+        .into_iter()
+        .map(|asset_info| {
+            let amount = ACCUMULATED_FEES
+                .may_load(deps.storage, &asset_info)?
+                .unwrap_or_default();
+            Ok(asset_info.with_balance(amount))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AccumulatedFeesResponse { fees })
 }
 
-/// Returns information about a swap simulation in a [`SimulationResponse`] object.
-///
-/// * **offer_asset** is the asset to swap as well as an amount of the said asset.
-pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationResponse> {
+/// Returns the minimum amount of each pool asset which can be swapped for a non-zero return
+/// in a [`MinSwapAmountResponse`] object.
+pub fn query_min_swap_amount(deps: Deps) -> StdResult<MinSwapAmountResponse> {
     let config = CONFIG.load(deps.storage)?;
 
     let pools = config
         .pair_info
         .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
-    if offer_asset.info.equal(&pools[0].info) {
-        offer_pool = pools[0].clone();
-        ask_pool = pools[1].clone();
-    } else if offer_asset.info.equal(&pools[1].info) {
-        offer_pool = pools[1].clone();
-        ask_pool = pools[0].clone();
-    } else {
+    let min_amount = |offer_ind: usize| -> StdResult<Asset> {
+        let amount = compute_min_swap_amount(pools[offer_ind].amount, pools[1 - offer_ind].amount)?;
+        Ok(Asset {
+            info: pools[offer_ind].info.clone(),
+            amount,
+        })
+    };
+
+    Ok(MinSwapAmountResponse {
+        min_amounts: vec![min_amount(0)?, min_amount(1)?],
+    })
+}
+
+/// Returns whether all user liquidity has been withdrawn from the pool in an [`IsPoolDepletedResponse`] object.
+/// The pool is depleted if only the locked minimum liquidity remains after a withdrawal.
+pub fn query_is_pool_depleted(deps: Deps) -> StdResult<IsPoolDepletedResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let last_withdrawal_ts = LAST_FULL_WITHDRAWAL_TS.may_load(deps.storage)?;
+    let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?;
+
+    Ok(IsPoolDepletedResponse {
+        is_depleted: last_withdrawal_ts.is_some() && total_share <= MINIMUM_LIQUIDITY_AMOUNT,
+        last_withdrawal_ts,
+    })
+}
+
+/// Returns [`ContractError::PoolDepleted`] if all user liquidity has been withdrawn from the pool.
+fn ensure_not_depleted(deps: Deps, config: &Config) -> StdResult<()> {
+    if LAST_FULL_WITHDRAWAL_TS.may_load(deps.storage)?.is_some()
+        && query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?
+            <= MINIMUM_LIQUIDITY_AMOUNT
+    {
         return Err(StdError::generic_err(
-            "Given offer asset does not belong in the pair",
+            ContractError::PoolDepleted {}.to_string(),
         ));
     }
 
-    // Get fee info from the factory contract
-    let fee_info = query_fee_info(
-        &deps.querier,
-        config.factory_addr,
-        config.pair_info.pair_type,
-    )?;
+    Ok(())
+}
 
-    let (return_amount, spread_amount, commission_amount) = compute_swap(
-        offer_pool.amount,
-        ask_pool.amount,
-        offer_asset.amount,
-        fee_info.total_fee_rate,
-    )?;
+/// Returns the share of each pool asset in the total pool value in a [`ReserveRatioResponse`] object.
+/// If arbitrage protection is enabled, the second asset reserve is valued in units of the first asset
+/// using the protection oracle. Otherwise raw reserves are compared.
+pub fn query_reserve_ratio(deps: Deps) -> StdResult<ReserveRatioResponse> {
+    let config = CONFIG.load(deps.storage)?;
 
-    Ok(SimulationResponse {
-        return_amount,
-        spread_amount,
-        commission_amount,
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+
+    let value_1 = match &config.arb_protection {
+        Some(arb_protection) if !pools[1].amount.is_zero() => deps
+            .querier
+            .query_wasm_smart::<Vec<(AssetInfo, Uint256)>>(
+                &arb_protection.oracle,
+                &OracleQueryMsg::Consult {
+                    token: pools[1].info.clone(),
+                    amount: pools[1].amount,
+                },
+            )?
+            .into_iter()
+            .find_map(|(info, amount)| info.equal(&pools[0].info).then_some(amount))
+            .ok_or_else(|| StdError::generic_err("Oracle doesn't provide a price for the asset"))?
+            .try_into()?,
+        _ => pools[1].amount,
+    };
+
+    compute_reserve_ratio(pools[0].amount, value_1)
+}
+
+/// Calculates the share of each pool asset given both reserves valued in the same units.
+pub fn compute_reserve_ratio(
+    value_0: Uint128,
+    value_1: Uint128,
+) -> StdResult<ReserveRatioResponse> {
+    let total = value_0.checked_add(value_1)?;
+    if total.is_zero() {
+        return Err(StdError::generic_err("Pool is empty"));
+    }
+
+    let ratio_0 = Decimal::from_ratio(value_0, total);
+    let ratio_1 = Decimal::one() - ratio_0;
+
+    Ok(ReserveRatioResponse {
+        ratio_0,
+        ratio_1,
+        is_balanced: ratio_0.abs_diff(Decimal::percent(50)) < RESERVE_IMBALANCE_THRESHOLD,
     })
 }
 
@@ -1034,6 +3017,8 @@ pub fn query_reverse_simulation(
 ) -> StdResult<ReverseSimulationResponse> {
     let config = CONFIG.load(deps.storage)?;
 
+    ensure_not_depleted(deps, &config)?;
+
     let pools = config
         .pair_info
         .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
@@ -1066,10 +3051,17 @@ pub fn query_reverse_simulation(
         fee_info.total_fee_rate,
     )?;
 
+    let price_impact = calculate_price_impact(
+        Decimal256::checked_from_ratio(ask_pool.amount, offer_pool.amount).unwrap_or_default(),
+        Decimal256::from_ratio(offer_amount, 1u8),
+        Decimal256::from_ratio(ask_asset.amount, 1u8),
+    )?;
+
     Ok(ReverseSimulationResponse {
         offer_amount,
         spread_amount,
         commission_amount,
+        price_impact,
     })
 }
 
@@ -1101,10 +3093,17 @@ pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePric
         ),
     ];
 
+    let [volume0_cumulative_last, volume1_cumulative_last] = CUMULATIVE_VOLUMES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
     let resp = CumulativePricesResponse {
         assets,
         total_share,
         cumulative_prices,
+        volume0_cumulative_last,
+        volume1_cumulative_last,
+        geo_twap: None,
     };
 
     Ok(resp)
@@ -1121,10 +3120,19 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         params: Some(to_json_binary(&XYKPoolConfig {
             track_asset_balances: config.track_asset_balances,
             fee_share: config.fee_share,
+            arb_protection: config.arb_protection,
+            flash_loan_fee: config.flash_loan_fee,
+            global_max_slippage: config.global_max_slippage,
+            max_swap_volume_per_block: config.max_swap_volume_per_block,
+            volume_limit_decay_blocks: config.volume_limit_decay_blocks,
+            pause_guardian: config.pause_guardian,
         })?),
         owner: factory_config.owner,
         factory_addr: config.factory_addr,
         tracker_addr: config.tracker_addr,
+        liquidity_concentration: None,
+        is_paused: PAUSED.may_load(deps.storage)?.unwrap_or_default(),
+        estimated_annual_fee_rate: None,
     })
 }
 
@@ -1146,12 +3154,10 @@ fn query_simulate_provide(
         .pair_info
         .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
 
-    let deposits = get_deposits_from_assets(deps, &assets, &pools)
-        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let deposits = get_deposits_from_assets(deps, &assets, &pools)?;
 
     let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?;
-    let share = calculate_shares(&deposits, &pools, total_share, slippage_tolerance)
-        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let share = calculate_shares(&deposits, &pools, total_share, slippage_tolerance)?;
 
     Ok(share)
 }
@@ -1210,6 +3216,71 @@ pub fn compute_swap(
     ))
 }
 
+/// Returns the part of a single-sided deposit which has to be swapped so that the rest of the deposit
+/// and the swap return match the pool ratio after the swap. The commission is taken from the swap return
+/// and stays in the pool, thus the swap amount `s` solves
+/// `s^2 + s * ((2 - fee) * offer_pool - fee * offer_amount) - offer_amount * offer_pool = 0`.
+///
+/// * **offer_pool** total amount of offer assets in the pool.
+///
+/// * **offer_amount** amount of the single-sided deposit.
+///
+/// * **commission_rate** total amount of fees charged for the swap.
+pub fn compute_single_sided_swap_amount(
+    offer_pool: Uint128,
+    offer_amount: Uint128,
+    commission_rate: Decimal,
+) -> StdResult<Uint128> {
+    let offer_pool = Decimal256::from_ratio(offer_pool, 1u8);
+    let offer_amount = Decimal256::from_ratio(offer_amount, 1u8);
+    let commission_rate = Decimal256::from(commission_rate);
+    let two = Decimal256::from_ratio(2u8, 1u8);
+
+    // The linear coefficient may be negative, so its terms are kept apart
+    let b_pos = (two - commission_rate).checked_mul(offer_pool)?;
+    let b_neg = commission_rate.checked_mul(offer_amount)?;
+    let b_abs = b_pos.abs_diff(b_neg);
+
+    let discriminant = b_abs.checked_mul(b_abs)?.checked_add(
+        Decimal256::from_ratio(4u8, 1u8)
+            .checked_mul(offer_amount)?
+            .checked_mul(offer_pool)?,
+    )?;
+    let root = discriminant.sqrt();
+
+    let swap_amount = if b_pos >= b_neg {
+        (root - b_abs) / two
+    } else {
+        (root + b_abs) / two
+    };
+
+    Ok((swap_amount.atomics() / Decimal256::one().atomics()).try_into()?)
+}
+
+/// Returns the minimum amount of offer assets which can be swapped for a non-zero return.
+/// The commission is rounded down and the fee rate is always below 100%, so any swap which returns
+/// at least one unit of ask assets before the commission is deducted still returns a non-zero amount.
+/// Thus the minimum doesn't depend on the fee rate.
+///
+/// * **offer_pool** total amount of offer assets in the pool.
+///
+/// * **ask_pool** total amount of ask assets in the pool.
+pub fn compute_min_swap_amount(offer_pool: Uint128, ask_pool: Uint128) -> StdResult<Uint128> {
+    if offer_pool.is_zero() || ask_pool <= Uint128::one() {
+        return Err(StdError::generic_err("Pool liquidity is too low to swap"));
+    }
+
+    // ask_pool * offer_amount / (offer_pool + offer_amount) >= 1
+    // <=> offer_amount >= offer_pool / (ask_pool - 1)
+    let denominator = ask_pool - Uint128::one();
+    let min_amount = offer_pool / denominator;
+    if (offer_pool % denominator).is_zero() {
+        Ok(min_amount)
+    } else {
+        Ok(min_amount + Uint128::one())
+    }
+}
+
 /// Returns an amount of offer assets for a specified amount of ask assets.
 ///
 /// * **offer_pool** total amount of offer assets in the pool.
@@ -1270,13 +3341,10 @@ pub fn calculate_shares(
 ) -> Result<Uint128, ContractError> {
     let share = if total_share.is_zero() {
         // Initial share = collateral amount
-        let share = Uint128::new(
-            (U256::from(deposits[0].u128()) * U256::from(deposits[1].u128()))
-                .integer_sqrt()
-                .as_u128(),
-        )
-        .checked_sub(MINIMUM_LIQUIDITY_AMOUNT)
-        .map_err(|_| ContractError::MinimumLiquidityAmountError {})?;
+        let share =
+            compute_pool_shares(*deposits, [pools[0].amount, pools[1].amount], total_share)?
+                .checked_sub(MINIMUM_LIQUIDITY_AMOUNT)
+                .map_err(|_| ContractError::MinimumLiquidityAmountError {})?;
 
         // share cannot become zero after minimum liquidity subtraction
         if share.is_zero() {
@@ -1293,10 +3361,7 @@ pub fn calculate_shares(
         // == deposit_0 * total_share / pool_0
         // 2. sqrt(deposit_1 * exchange_rate_1_to_0 * deposit_1) * (total_share / sqrt(pool_1 * pool_1))
         // == deposit_1 * total_share / pool_1
-        std::cmp::min(
-            deposits[0].multiply_ratio(total_share, pools[0].amount),
-            deposits[1].multiply_ratio(total_share, pools[1].amount),
-        )
+        compute_pool_shares(*deposits, [pools[0].amount, pools[1].amount], total_share)?
     };
     Ok(share)
 }
@@ -1337,47 +3402,58 @@ pub fn get_deposits_from_assets(
     Ok(deposits)
 }
 
-/// If `belief_price` and `max_spread` are both specified, we compute a new spread,
-/// otherwise we just use the swap spread to check `max_spread`.
+/// Validates a swap against the oracle price if the swap moves the pool price by more than
+/// the configured threshold.
 ///
-/// * **belief_price** belief price used in the swap.
+/// * **arb_protection** arbitrage protection config.
 ///
-/// * **max_spread** max spread allowed so that the swap can be executed successfully.
+/// * **offer_pool** total amount of offer assets in the pool before the swap.
 ///
-/// * **offer_amount** amount of assets to swap.
+/// * **ask_pool** total amount of ask assets in the pool before the swap.
 ///
-/// * **return_amount** amount of assets to receive from the swap.
+/// * **offer_amount** amount of offer assets to swap.
 ///
-/// * **spread_amount** spread used in the swap.
-pub fn assert_max_spread(
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
+/// * **return_amount** amount of ask assets to receive from the swap.
+pub fn assert_arb_protection(
+    querier: QuerierWrapper,
+    arb_protection: &ArbProtectionConfig,
+    offer_pool: &Asset,
+    ask_pool: &Asset,
     offer_amount: Uint128,
     return_amount: Uint128,
-    spread_amount: Uint128,
 ) -> Result<(), ContractError> {
-    let default_spread = Decimal::from_str(DEFAULT_SLIPPAGE)?;
-    let max_allowed_spread = Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?;
+    // Pool prices are denominated in ask assets per one offer asset
+    let price_before = Decimal256::checked_from_ratio(ask_pool.amount, offer_pool.amount)?;
+    let price_after = Decimal256::checked_from_ratio(
+        ask_pool.amount.checked_sub(return_amount)?,
+        offer_pool.amount.checked_add(offer_amount)?,
+    )?;
 
-    let max_spread = max_spread.unwrap_or(default_spread);
-    if max_spread.gt(&max_allowed_spread) {
-        return Err(ContractError::AllowedSpreadAssertion {});
+    let price_move = price_before
+        .abs_diff(price_after)
+        .checked_div(price_before)?;
+    if price_move <= Decimal256::from(arb_protection.threshold) {
+        return Ok(());
     }
 
-    if let Some(belief_price) = belief_price {
-        let expected_return = offer_amount
-            * belief_price
-                .inv()
-                .ok_or_else(|| StdError::generic_err("Belief price must not be zero!"))?;
-        let spread_amount = expected_return.saturating_sub(return_amount);
+    let oracle_return = querier
+        .query_wasm_smart::<Vec<(AssetInfo, Uint256)>>(
+            &arb_protection.oracle,
+            &OracleQueryMsg::Consult {
+                token: offer_pool.info.clone(),
+                amount: offer_amount,
+            },
+        )?
+        .into_iter()
+        .find_map(|(info, amount)| info.equal(&ask_pool.info).then_some(amount))
+        .ok_or_else(|| StdError::generic_err("Oracle doesn't provide a price for the ask asset"))?;
+    let oracle_price = Decimal256::checked_from_ratio(oracle_return, offer_amount)?;
 
-        if return_amount < expected_return
-            && Decimal::from_ratio(spread_amount, expected_return) > max_spread
-        {
-            return Err(ContractError::MaxSpreadAssertion {});
-        }
-    } else if Decimal::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
-        return Err(ContractError::MaxSpreadAssertion {});
+    if oracle_price.is_zero()
+        || price_after.abs_diff(oracle_price) / oracle_price
+            > Decimal256::from(arb_protection.tolerance)
+    {
+        return Err(ContractError::ArbProtectionTriggered {});
     }
 
     Ok(())
@@ -1422,8 +3498,46 @@ pub fn assert_slippage_tolerance(
 
 /// Manages the contract migration.
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
-    unimplemented!("No safe path available for migration from cw20 to tokenfactory LP tokens")
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_version = get_contract_version(deps.storage)?;
+
+    if let Some(from_version) = msg.from_version {
+        ensure_eq!(
+            from_version,
+            contract_version.version,
+            ContractError::IncompatibleMigrationSource {
+                expected: from_version,
+                actual: contract_version.version,
+            }
+        );
+    }
+
+    ensure_eq!(
+        msg.to_version,
+        CONTRACT_VERSION,
+        ContractError::IncompatibleMigrationTarget {
+            expected: msg.to_version,
+            actual: CONTRACT_VERSION.to_string(),
+        }
+    );
+
+    // Versions prior to 2.0.0 use CW20 LP tokens and can not be migrated
+    match contract_version.contract.as_ref() {
+        "astroport-pair" => match contract_version.version.as_ref() {
+            "2.0.0" | "2.0.1" | "2.0.2" => {}
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        _ => return Err(ContractError::MigrationError {}),
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
+
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", &contract_version.contract)
+        .add_attribute("previous_contract_version", &contract_version.version)
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION))
 }
 
 /// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
@@ -1,5 +1,7 @@
-use astroport::{asset::MINIMUM_LIQUIDITY_AMOUNT, pair::MAX_FEE_SHARE_BPS};
-use cosmwasm_std::{OverflowError, StdError, Uint128};
+use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
+use astroport::pair::{MinOutNotMet, SpreadAssertionError};
+use astroport_pair_common::error::{BlockVolumeLimitExceeded, LpAmountMismatch, VersionMismatch};
+use cosmwasm_std::{CheckedFromRatioError, Decimal, OverflowError, StdError, Uint128};
 use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
@@ -15,9 +17,21 @@ pub enum ContractError {
     #[error("{0}")]
     ParseReplyError(#[from] ParseReplyError),
 
+    #[error("{0}")]
+    CheckedFromRatioError(#[from] CheckedFromRatioError),
+
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Contract version mismatch: expected {expected}, got {got}")]
+    VersionMismatch { expected: String, got: String },
+
+    #[error("Pair configuration is frozen")]
+    ConfigFrozen {},
+
+    #[error("Pool asset {asset} can not be swept")]
+    SweepPoolAsset { asset: String },
+
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
@@ -30,6 +44,9 @@ pub enum ContractError {
     #[error("Operation exceeds max spread limit")]
     MaxSpreadAssertion {},
 
+    #[error("Swap returns {actual} which is less than the minimum output {expected}")]
+    MinOutNotMet { expected: Uint128, actual: Uint128 },
+
     #[error("Provided spread amount exceeds allowed limit")]
     AllowedSpreadAssertion {},
 
@@ -67,14 +84,127 @@ pub enum ContractError {
     #[error("Failed to migrate the contract")]
     MigrationError {},
 
+    #[error("Can't migrate from version {actual}, expected {expected}")]
+    IncompatibleMigrationSource { expected: String, actual: String },
+
+    #[error("Can't migrate to version {expected}, the new code has version {actual}")]
+    IncompatibleMigrationTarget { expected: String, actual: String },
+
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
 
+    #[error("{field} of {rate} is out of bounds: must be between {min} and {max}")]
+    InvalidFeeRate {
+        rate: Decimal,
+        field: String,
+        min: Decimal,
+        max: Decimal,
+    },
+
+    #[error("Sent {sent} LP tokens but expected {expected}")]
+    LpAmountMismatch { sent: Uint128, expected: Uint128 },
+
+    #[error("Arbitrage protection threshold and tolerance must be within (0, 1)")]
+    ArbProtectionParamsOutOfBounds {},
+
+    #[error("Post-swap price deviates from the oracle price by more than the allowed tolerance")]
+    ArbProtectionTriggered {},
+
+    #[error("Can not donate to a pool without liquidity")]
+    DonationToEmptyPool {},
+
+    #[error("Can not provide a single asset to a pool without liquidity")]
+    SingleSidedProvideToEmptyPool {},
+
+    #[error("Pool is depleted: all liquidity has been withdrawn")]
+    PoolDepleted {},
+
+    #[error("A liquidity request requires a native token bounty")]
+    MissingLiquidityRequestBounty {},
+
+    #[error("Pool asset {asset} can not be used as a liquidity request bounty")]
+    InvalidLiquidityRequestBounty { asset: String },
+
+    #[error("Liquidity request deadline must be in the future")]
+    InvalidLiquidityRequestDeadline {},
+
+    #[error("Liquidity request {request_id} not found")]
+    LiquidityRequestNotFound { request_id: u64 },
+
+    #[error("Liquidity request {request_id} has expired")]
+    LiquidityRequestExpired { request_id: u64 },
+
+    #[error("Liquidity request requires {requested} LP tokens to be minted, got {minted}")]
+    LiquidityRequestNotMet { requested: Uint128, minted: Uint128 },
+
+    #[error("Liquidity request {request_id} is already fulfilled")]
+    LiquidityRequestFulfilled { request_id: u64 },
+
+    #[error("Liquidity request {request_id} is not fulfilled yet")]
+    LiquidityRequestNotFulfilled { request_id: u64 },
+
+    #[error("LP tokens of liquidity request {request_id} are locked until {unlocks_at}")]
+    LiquidityRequestLocked { request_id: u64, unlocks_at: u64 },
+
+    #[error("Auto staking is not supported when fulfilling a liquidity request")]
+    LiquidityRequestAutoStake {},
+
+    #[error("Operation is not allowed while a flash loan is in progress")]
+    FlashLoanInProgress {},
+
+    #[error("Contract is paused")]
+    ContractPaused {},
+
     #[error(
-        "Fee share is 0 or exceeds maximum allowed value of {} bps",
-        MAX_FEE_SHARE_BPS
+        "Flash loan of {asset} was not repaid: expected pool balance {expected}, got {actual}"
     )]
-    FeeShareOutOfBounds {},
+    FlashLoanNotRepaid {
+        asset: String,
+        expected: Uint128,
+        actual: Uint128,
+    },
+
+    #[error("Swap volume {volume} exceeds the per block limit of {limit}")]
+    BlockVolumeLimitExceeded { volume: Uint128, limit: Uint128 },
+
+    #[error("Volume limit decay blocks must not exceed {max}")]
+    VolumeLimitDecayBlocksTooLarge { max: u64 },
+
+    #[error("The asset {0} does not belong to the pair")]
+    InvalidAsset(String),
+
+    #[error("Insufficient {asset} reserve for replacement: {actual}")]
+    InsufficientReplacementReserve { asset: String, actual: Uint128 },
+
+    #[error("Error {context}: {source}")]
+    WithContext {
+        source: Box<ContractError>,
+        context: String,
+    },
+}
+
+impl ContractError {
+    /// Wraps a [`StdError`] with a description of where it occurred.
+    pub fn from_std_with_context(err: StdError, ctx: &str) -> Self {
+        propagate_error_context(err.into(), ctx.to_string())
+    }
+}
+
+/// Wraps an error with a description of where it occurred, e.g. "during swap fee calculation".
+pub fn propagate_error_context(err: ContractError, context: String) -> ContractError {
+    ContractError::WithContext {
+        source: Box::new(err),
+        context,
+    }
+}
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
 }
 
 impl From<OverflowError> for ContractError {
@@ -82,3 +212,49 @@ impl From<OverflowError> for ContractError {
         StdError::from(o).into()
     }
 }
+
+impl From<LpAmountMismatch> for ContractError {
+    fn from(err: LpAmountMismatch) -> Self {
+        ContractError::LpAmountMismatch {
+            sent: err.sent,
+            expected: err.expected,
+        }
+    }
+}
+
+impl From<SpreadAssertionError> for ContractError {
+    fn from(err: SpreadAssertionError) -> Self {
+        match err {
+            SpreadAssertionError::Std(err) => ContractError::Std(err),
+            SpreadAssertionError::AllowedSpread => ContractError::AllowedSpreadAssertion {},
+            SpreadAssertionError::MaxSpread => ContractError::MaxSpreadAssertion {},
+        }
+    }
+}
+
+impl From<VersionMismatch> for ContractError {
+    fn from(err: VersionMismatch) -> Self {
+        ContractError::VersionMismatch {
+            expected: err.expected,
+            got: err.got,
+        }
+    }
+}
+
+impl From<MinOutNotMet> for ContractError {
+    fn from(err: MinOutNotMet) -> Self {
+        ContractError::MinOutNotMet {
+            expected: err.expected,
+            actual: err.actual,
+        }
+    }
+}
+
+impl From<BlockVolumeLimitExceeded> for ContractError {
+    fn from(err: BlockVolumeLimitExceeded) -> Self {
+        ContractError::BlockVolumeLimitExceeded {
+            volume: err.volume,
+            limit: err.limit,
+        }
+    }
+}
@@ -3,12 +3,14 @@ use std::collections::HashMap;
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
     from_json, to_json_binary, Addr, Coin, Empty, OwnedDeps, Querier, QuerierResult, QueryRequest,
-    SystemError, SystemResult, Uint128, WasmQuery,
+    SystemError, SystemResult, Uint128, Uint256, WasmQuery,
 };
-use cw20::{BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
+use cw20::{AllowanceResponse, BalanceResponse, Cw20QueryMsg, Expiration, TokenInfoResponse};
 
+use astroport::asset::{native_asset_info, token_asset_info};
 use astroport::factory::FeeInfoResponse;
 use astroport::factory::QueryMsg::FeeInfo;
+use astroport::oracle::QueryMsg as OracleQueryMsg;
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies.
 /// This uses the Astroport CustomQuerier.
@@ -92,6 +94,20 @@ impl WasmMockQuerier {
                         ),
                         _ => panic!("DO NOT ENTER HERE"),
                     }
+                } else if contract_addr == "oracle" {
+                    // The oracle always quotes pool assets 1:1
+                    match from_json(&msg).unwrap() {
+                        OracleQueryMsg::Consult { amount, .. } => SystemResult::Ok(
+                            to_json_binary(&vec![
+                                (native_asset_info("uusd".to_string()), Uint256::from(amount)),
+                                (
+                                    token_asset_info(Addr::unchecked("asset0000")),
+                                    Uint256::from(amount),
+                                ),
+                            ])
+                            .into(),
+                        ),
+                    }
                 } else {
                     match from_json(&msg).unwrap() {
                         Cw20QueryMsg::TokenInfo {} => {
@@ -139,6 +155,23 @@ impl WasmMockQuerier {
                                 to_json_binary(&BalanceResponse { balance: *balance }).into(),
                             )
                         }
+                        Cw20QueryMsg::Allowance { owner, spender } => {
+                            // Only addr0000 has approved tokens for the pair
+                            let allowance = if owner == "addr0000" && spender == MOCK_CONTRACT_ADDR
+                            {
+                                Uint128::new(100)
+                            } else {
+                                Uint128::zero()
+                            };
+
+                            SystemResult::Ok(
+                                to_json_binary(&AllowanceResponse {
+                                    allowance,
+                                    expires: Expiration::Never {},
+                                })
+                                .into(),
+                            )
+                        }
                         _ => panic!("DO NOT ENTER HERE"),
                     }
                 }
@@ -1,10 +1,10 @@
 use astroport::{
-    asset::{AssetInfo, PairInfo},
-    pair::FeeShareConfig,
+    asset::{Asset, AssetInfo, PairInfo},
+    pair::{ArbProtectionConfig, FeeShareConfig},
 };
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
-use cw_storage_plus::{Item, SnapshotMap};
+use cosmwasm_std::{Addr, Coin, Decimal, Uint128, Uint256};
+use cw_storage_plus::{Item, Map, SnapshotMap};
 
 /// This structure stores the main config parameters for a constant product pair contract.
 #[cw_serde]
@@ -25,11 +25,86 @@ pub struct Config {
     pub fee_share: Option<FeeShareConfig>,
     /// Stores the tracker contract address
     pub tracker_addr: Option<Addr>,
+    /// The config for oracle backed arbitrage protection
+    #[serde(default)]
+    pub arb_protection: Option<ArbProtectionConfig>,
+    /// The fee charged on flash loans relative to the borrowed amount
+    #[serde(default)]
+    pub flash_loan_fee: Decimal,
+    /// The cap applied to user provided slippage tolerances
+    #[serde(default)]
+    pub global_max_slippage: Option<Decimal>,
+    /// The maximum swap volume of each asset (in that asset's units) accepted within a block
+    #[serde(default)]
+    pub max_swap_volume_per_block: Option<Uint128>,
+    /// The number of blocks over which previously swapped volume is linearly released
+    #[serde(default)]
+    pub volume_limit_decay_blocks: u64,
+    /// The address which is allowed to pause the pair in addition to the factory owner
+    #[serde(default)]
+    pub pause_guardian: Option<Addr>,
 }
 
 /// Stores the config struct at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Whether the pair configuration is permanently frozen
+pub const CONFIG_FROZEN: Item<bool> = Item::new("config_frozen");
+
+/// Whether swaps, liquidity provision and withdrawals are halted
+pub const PAUSED: Item<bool> = Item::new("paused");
+
+/// Total swapped volume of each pool asset
+pub const CUMULATIVE_VOLUMES: Item<[Uint256; 2]> = Item::new("cumulative_volumes");
+
+/// The pool balances expected once the ongoing flash loan is repaid (pre-loan balances plus fees)
+pub const FLASH_LOAN: Item<Vec<Asset>> = Item::new("flash_loan");
+
+/// Liquidity held by external contracts on behalf of the pool, in the pool asset order
+pub const EXTERNAL_LIQUIDITY: Map<&Addr, [Uint128; 2]> = Map::new("external_liquidity");
+
+/// Protocol fees sent to the Maker by asset
+pub const ACCUMULATED_FEES: Map<&AssetInfo, Uint128> = Map::new("accumulated_fees");
+
+/// The last timestamp when all user liquidity was withdrawn from the pool
+pub const LAST_FULL_WITHDRAWAL_TS: Item<u64> = Item::new("last_full_withdrawal_ts");
+
+/// This structure describes an open request for liquidity provision.
+#[cw_serde]
+pub struct LiquidityRequest {
+    /// The address which created the request and escrowed the bounty
+    pub requester: Addr,
+    /// The minimum amount of LP tokens a provision has to mint to fulfill the request
+    pub amount: Uint128,
+    /// The timestamp (in seconds) after which the request can no longer be fulfilled
+    pub deadline: u64,
+    /// The tokens paid to the liquidity provider fulfilling the request
+    pub bounty: Vec<Coin>,
+    /// Set once the request is fulfilled. The bounty stays escrowed until the fulfillment is claimed
+    #[serde(default)]
+    pub fulfillment: Option<LiquidityRequestFulfillment>,
+}
+
+/// This structure describes the fulfillment of a liquidity request.
+#[cw_serde]
+pub struct LiquidityRequestFulfillment {
+    /// The address receiving the locked LP tokens and the bounty
+    pub provider: Addr,
+    /// The amount of LP tokens minted by the fulfilling provision. They are held by the pair until `unlocks_at`
+    pub lp_amount: Uint128,
+    /// The timestamp (in seconds) after which the LP tokens and the bounty can be claimed
+    pub unlocks_at: u64,
+}
+
+/// Open and unclaimed liquidity requests by their identifiers
+pub const LIQUIDITY_REQUESTS: Map<u64, LiquidityRequest> = Map::new("liquidity_requests");
+
+/// Total liquidity request bounties escrowed by the pair by denom
+pub const ESCROWED_BOUNTIES: Map<&str, Uint128> = Map::new("escrowed_bounties");
+
+/// The identifier assigned to the next liquidity request
+pub const NEXT_LIQUIDITY_REQUEST_ID: Item<u64> = Item::new("next_liquidity_request_id");
+
 /// Stores asset balances to query them later at any block height
 pub const BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
     "balances",
@@ -1,29 +1,40 @@
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coin, to_json_binary, Addr, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Decimal,
-    DepsMut, Env, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgResponse, SubMsgResult,
-    Timestamp, Uint128, WasmMsg,
+    attr, coin, to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal, DepsMut,
+    QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, SubMsgResponse,
+    SubMsgResult, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_utils::PaymentError;
 use proptest::prelude::*;
 use prost::Message;
 
-use astroport::asset::{Asset, AssetInfo, PairInfo};
+use astroport::asset::{Asset, AssetInfo, PairInfo, MINIMUM_LIQUIDITY_AMOUNT};
 use astroport::common::LP_SUBDENOM;
 use astroport::factory::PairType;
+use astroport::gas_estimates::XYK_SWAP;
+use astroport::incentives::ExecuteMsg as IncentiveExecuteMsg;
 use astroport::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse, TWAP_PRECISION,
+    assert_max_spread, ArbProtectionConfig, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg,
+    PoolResponse, ReverseSimulationResponse, SimulationResponse, SwapCallbackExecuteMsg,
+    SwapCallbackMsg, TWAP_PRECISION,
+};
+use astroport::test_utils::{
+    make_cw20_asset, make_native_asset, mock_env_with_block_time, MockPairSetup,
 };
 use astroport::token_factory::{MsgBurn, MsgCreateDenom, MsgCreateDenomResponse, MsgMint};
+use astroport_pair_common::state::CODE_VERSION;
 
 use crate::contract::compute_offer_amount;
 use crate::contract::reply;
 use crate::contract::{
-    accumulate_prices, assert_max_spread, compute_swap, execute, instantiate, query_pool,
-    query_reverse_simulation, query_share, query_simulation,
+    accumulate_prices, assert_arb_protection, compute_liquidity_depth, compute_max_swapable,
+    compute_min_swap_amount, compute_price_levels, compute_reserve_ratio,
+    compute_single_sided_swap_amount, compute_swap, execute, instantiate, migrate,
+    query_check_swap_limits, query_exec_price, query_pool, query_reverse_simulation, query_share,
+    query_simulation, query_slippage, query_token_allowance,
 };
-use crate::error::ContractError;
+use crate::error::{propagate_error_context, ContractError};
 use crate::mock_querier::mock_dependencies;
 use crate::state::{Config, CONFIG};
 
@@ -182,6 +193,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env();
@@ -307,6 +319,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -387,6 +400,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env();
@@ -451,6 +465,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -493,6 +508,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -535,6 +551,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -576,6 +593,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -607,6 +625,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
     let info = mock_info(
         "addr0001",
@@ -637,6 +656,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
     let info = mock_info(
         "addr0001",
@@ -649,6 +669,201 @@ fn provide_liquidity() {
     assert_eq!(err, ContractError::AllowedSpreadAssertion {});
 }
 
+#[test]
+fn stake_and_provide() {
+    let mut deps = mock_dependencies(&[]);
+
+    // Received CW20 tokens are already in the pool
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("asset0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(2000))],
+        ),
+        (
+            &String::from("asset0001"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(0))],
+        ),
+    ]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .with_asset(make_cw20_asset("asset0001", 0u128))
+        .instantiate_msg();
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let denom = format!("factory/{}/{}", env.contract.address, "share/astroport");
+
+    // Store the liquidity token
+    store_liquidity_token(deps.as_mut(), 1, denom.to_string());
+
+    let hook_msg = |counterpart: Asset| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::new(2000),
+            msg: to_json_binary(&Cw20HookMsg::StakeAndProvide {
+                counterpart,
+                slippage_tolerance: None,
+                generator: "generator".to_string(),
+            })
+            .unwrap(),
+        })
+    };
+
+    let counterpart = Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0001"),
+        },
+        amount: Uint128::new(2000),
+    };
+
+    // Only pool tokens can call the hook
+    let info = mock_info("random0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        hook_msg(counterpart.clone()),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+
+    // Counterpart must belong to the pair
+    let info = mock_info("asset0000", &[]);
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        hook_msg(Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(2000),
+        }),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::AssetMismatch {});
+
+    let res = execute(deps.as_mut(), env.clone(), info, hook_msg(counterpart)).unwrap();
+
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(WasmMsg::Execute {
+            contract_addr: "asset0001".to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "addr0000".to_string(),
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::new(2000),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+    // 1000 = sqrt(2000 * 2000) - MINIMUM_LIQUIDITY_AMOUNT
+    assert_eq!(
+        res.messages.last().unwrap(),
+        &SubMsg::new(WasmMsg::Execute {
+            contract_addr: "generator".to_string(),
+            msg: to_json_binary(&IncentiveExecuteMsg::Deposit {
+                recipient: Some("addr0000".to_string()),
+            })
+            .unwrap(),
+            funds: vec![coin(1000u128, denom)],
+        })
+    );
+}
+
+#[test]
+fn stake_and_provide_native_counterpart() {
+    // The attached native counterpart is already in the pool
+    let mut deps = mock_dependencies(&[coin(2000u128, "uusd")]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(2000))],
+    )]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .with_asset(make_native_asset("uusd", 0u128))
+        .instantiate_msg();
+
+    let hook_msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::new(2000),
+        msg: to_json_binary(&Cw20HookMsg::StakeAndProvide {
+            counterpart: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::new(2000),
+            },
+            slippage_tolerance: None,
+            generator: "generator".to_string(),
+        })
+        .unwrap(),
+    });
+
+    let env = mock_env();
+    let denom = format!("factory/{}/{}", env.contract.address, "share/astroport");
+
+    instantiate(deps.as_mut(), env.clone(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, denom.to_string());
+
+    // The native counterpart must be attached to the hook call
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("asset0000", &[]),
+        hook_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("No funds sent"))
+    );
+
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("asset0000", &[coin(1000u128, "uusd")]),
+        hook_msg.clone(),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err(
+            "Native token balance mismatch between the argument and the transferred"
+        ))
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("asset0000", &[coin(2000u128, "uusd")]),
+        hook_msg,
+    )
+    .unwrap();
+
+    // Neither asset is pulled from the sender
+    assert!(!res.messages.iter().any(|msg| matches!(
+        &msg.msg,
+        CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) if contract_addr == "asset0000"
+    )));
+    // 1000 = sqrt(2000 * 2000) - MINIMUM_LIQUIDITY_AMOUNT
+    assert_eq!(
+        res.messages.last().unwrap(),
+        &SubMsg::new(WasmMsg::Execute {
+            contract_addr: "generator".to_string(),
+            msg: to_json_binary(&IncentiveExecuteMsg::Deposit {
+                recipient: Some("addr0000".to_string()),
+            })
+            .unwrap(),
+            funds: vec![coin(1000u128, denom)],
+        })
+    );
+}
+
 #[test]
 fn withdraw_liquidity() {
     let mut deps = mock_dependencies(&[Coin {
@@ -788,6 +1003,184 @@ fn withdraw_liquidity() {
     );
 }
 
+#[test]
+fn transfer_lp_and_stake() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
+
+    instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let denom = format!("factory/{}/{}", env.contract.address, "share/astroport");
+    store_liquidity_token(deps.as_mut(), 1, denom.to_string());
+
+    let msg = ExecuteMsg::TransferLpAndStake {
+        recipient: "addr0001".to_string(),
+        amount: Uint128::new(100u128),
+        generator: "generator".to_string(),
+    };
+
+    // Wrong denom
+    let info = mock_info("addr0000", &[coin(100u128, "uusd")]);
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::PaymentError(PaymentError::MissingDenom(denom.clone()))
+    );
+
+    // Attached amount doesn't match the requested one
+    let info = mock_info("addr0000", &[coin(50u128, denom.clone())]);
+    let err = execute(deps.as_mut(), env.clone(), info, msg.clone()).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::LpAmountMismatch {
+            sent: Uint128::new(50u128),
+            expected: Uint128::new(100u128),
+        }
+    );
+
+    let info = mock_info("addr0000", &[coin(100u128, denom.clone())]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("generator"),
+            msg: to_json_binary(&IncentiveExecuteMsg::Deposit {
+                recipient: Some("addr0001".to_string()),
+            })
+            .unwrap(),
+            funds: vec![coin(100u128, denom)],
+        })]
+    );
+}
+
+#[test]
+fn donate_to_pool() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::new(150u128),
+    }]);
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &Uint128::new(100u128))],
+    )]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
+
+    instantiate(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let denom = format!("factory/{}/{}", env.contract.address, "share/astroport");
+    store_liquidity_token(deps.as_mut(), 1, denom.to_string());
+
+    let msg = ExecuteMsg::DonateToPool {
+        assets: vec![
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::new(50u128),
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked("asset0000"),
+                },
+                amount: Uint128::new(20u128),
+            },
+        ],
+    };
+    let info = mock_info("addr0000", &[coin(50u128, "uusd")]);
+
+    // The pool has no liquidity yet
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::DonationToEmptyPool {});
+
+    deps.querier.with_balance(&[(
+        &String::from("addr0000"),
+        &[coin(100u128, denom.to_string())],
+    )]);
+
+    // Only pool assets can be donated
+    let err = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::DonateToPool {
+            assets: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                amount: Uint128::new(50u128),
+            }],
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("Asset uluna is not in the pool"))
+    );
+
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(WasmMsg::Execute {
+            contract_addr: String::from("asset0000"),
+            msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: String::from("addr0000"),
+                recipient: String::from(MOCK_CONTRACT_ADDR),
+                amount: Uint128::new(20u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })]
+    );
+    assert_eq!(res.events.len(), 1);
+    assert_eq!(res.events[0].ty, "donation");
+    assert_eq!(
+        res.events[0].attributes,
+        vec![
+            attr("donor", "addr0000"),
+            attr("assets", "50uusd, 20asset0000"),
+        ]
+    );
+}
+
+#[test]
+fn execute_rejects_version_mismatch() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    // Simulate a code upgrade which didn't go through the migration entry point
+    CODE_VERSION
+        .save(deps.as_mut().storage, &"0.0.1".to_string())
+        .unwrap();
+
+    let msg = ExecuteMsg::DonateToPool { assets: vec![] };
+    let err = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::VersionMismatch {
+            expected: env!("CARGO_PKG_VERSION").to_string(),
+            got: "0.0.1".to_string(),
+        }
+    );
+}
+
 #[test]
 fn try_native_to_token() {
     let total_share = Uint128::new(30000000000u128);
@@ -844,7 +1237,9 @@ fn try_native_to_token() {
         ask_asset_info: None,
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
+        min_out: None,
         to: None,
+        callback: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -909,6 +1304,9 @@ fn try_native_to_token() {
     assert_eq!(expected_return_amount, simulation_res.return_amount);
     assert_eq!(expected_commission_amount, simulation_res.commission_amount);
     assert_eq!(expected_spread_amount, simulation_res.spread_amount);
+    // 0.050476189999999999 = 1 - (949523810 / 1500000000) / (20000000000 / 30000000000)
+    assert_eq!(simulation_res.price_impact, Decimal::raw(50476189999999999));
+    assert_eq!(simulation_res.gas_estimate, XYK_SWAP.native);
 
     // Check reverse simulation result
     let err = query_reverse_simulation(
@@ -994,81 +1392,352 @@ fn try_native_to_token() {
 }
 
 #[test]
-fn try_token_to_native() {
-    let total_share = Uint128::new(20000000000u128);
-    let asset_pool_amount = Uint128::new(30000000000u128);
-    let collateral_pool_amount = Uint128::new(20000000000u128);
+fn swap_with_callback() {
+    let asset_pool_amount = Uint128::new(20000000000u128);
+    let collateral_pool_amount = Uint128::new(30000000000u128);
     let offer_amount = Uint128::new(1500000000u128);
 
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: collateral_pool_amount,
+        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
     }]);
-    deps.querier.with_token_balances(&[
-        (
-            &String::from("liquidity0000"),
-            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
-        ),
-        (
-            &String::from("asset0000"),
-            &[(
-                &String::from(MOCK_CONTRACT_ADDR),
-                &(asset_pool_amount + offer_amount),
-            )],
-        ),
-    ]);
 
-    let msg = InstantiateMsg {
-        asset_infos: vec![
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: Addr::unchecked("asset0000"),
-            },
-        ],
-        token_code_id: 10u64,
-        factory_addr: String::from("factory"),
-        init_params: None,
-    };
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &asset_pool_amount)],
+    )]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // We can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
 
     // Store liquidity token
     store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
 
-    // Unauthorized access; can not execute swap directly for token swap
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::Token {
-                contract_addr: Addr::unchecked("asset0000"),
-            },
-            amount: offer_amount,
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
         },
+        amount: offer_amount,
+    };
+    let callback = to_json_binary(&"do_something").unwrap();
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: offer_asset.clone(),
         ask_asset_info: None,
         belief_price: None,
-        max_spread: None,
-        to: None,
+        max_spread: Some(Decimal::percent(50)),
+        min_out: None,
+        to: Some("addr0001".to_string()),
+        callback: Some(callback.clone()),
     };
     let env = mock_env_with_block_time(1000);
-    let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    assert_eq!(res, ContractError::Cw20DirectSwap {});
+    let info = mock_info("router0000", &[coin(offer_amount.u128(), "uusd")]);
 
-    // Normal sell
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: String::from("addr0000"),
-        amount: offer_amount,
-        msg: to_json_binary(&Cw20HookMsg::Swap {
-            ask_asset_info: None,
-            belief_price: None,
-            max_spread: Some(Decimal::percent(50)),
-            to: None,
-        })
-        .unwrap(),
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+
+    // 952380952 = 20000000000 - (30000000000 * 20000000000) / (30000000000 + 1500000000)
+    let expected_ret_amount = Uint128::new(952_380_952u128);
+    let expected_commission_amount = expected_ret_amount.multiply_ratio(3u128, 1000u128); // 0.3%
+    let expected_return_amount = expected_ret_amount - expected_commission_amount;
+
+    // The callback is the last message and is sent to the swap initiator
+    assert_eq!(
+        res.messages.last().unwrap(),
+        &SubMsg::new(WasmMsg::Execute {
+            contract_addr: "router0000".to_string(),
+            msg: to_json_binary(&SwapCallbackExecuteMsg::SwapCallback(SwapCallbackMsg {
+                return_amount: expected_return_amount,
+                offer_asset,
+                msg: callback,
+            }))
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+}
+
+#[test]
+fn swap_with_arb_protection() {
+    let pool_amount = Uint128::new(1_000_000000u128);
+    let big_offer_amount = Uint128::new(100_000000u128);
+    let small_offer_amount = Uint128::new(1_000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount + big_offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+    )]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    CONFIG
+        .update(deps.as_mut().storage, |mut config| -> StdResult<_> {
+            config.arb_protection = Some(ArbProtectionConfig {
+                oracle: Addr::unchecked("oracle"),
+                threshold: Decimal::percent(1),
+                tolerance: Decimal::percent(5),
+            });
+            Ok(config)
+        })
+        .unwrap();
+
+    let swap_msg = |amount: Uint128| ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount,
+        },
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        min_out: None,
+        to: None,
+        callback: None,
+    };
+
+    // The swap moves the price by ~17% while the oracle quotes 1:1
+    let info = mock_info("addr0000", &[coin(big_offer_amount.u128(), "uusd")]);
+    let err = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info,
+        swap_msg(big_offer_amount),
+    )
+    .unwrap_err();
+    assert_eq!(err, ContractError::ArbProtectionTriggered {});
+
+    // Swaps below the threshold are not validated against the oracle
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[coin((pool_amount + small_offer_amount).u128(), "uusd")],
+    )]);
+    let info = mock_info("addr0000", &[coin(small_offer_amount.u128(), "uusd")]);
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info,
+        swap_msg(small_offer_amount),
+    )
+    .unwrap();
+}
+
+#[test]
+fn arb_protection_empty_pool() {
+    let deps = mock_dependencies(&[]);
+    let arb_protection = ArbProtectionConfig {
+        oracle: Addr::unchecked("oracle"),
+        threshold: Decimal::percent(1),
+        tolerance: Decimal::percent(5),
+    };
+    let offer_pool = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::zero(),
+    };
+    let ask_pool = Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked("asset0000"),
+        },
+        amount: Uint128::new(1000),
+    };
+
+    // Empty pools return an error instead of panicking on division by zero
+    assert_arb_protection(
+        QuerierWrapper::new(&deps.querier),
+        &arb_protection,
+        &offer_pool,
+        &ask_pool,
+        Uint128::new(100),
+        Uint128::new(10),
+    )
+    .unwrap_err();
+    assert_arb_protection(
+        QuerierWrapper::new(&deps.querier),
+        &arb_protection,
+        &Asset {
+            amount: Uint128::new(1000),
+            ..offer_pool
+        },
+        &Asset {
+            amount: Uint128::zero(),
+            ..ask_pool
+        },
+        Uint128::new(100),
+        Uint128::zero(),
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn swap_with_min_out() {
+    let pool_amount = Uint128::new(1_000_000000u128);
+    let offer_amount = Uint128::new(1_000000u128);
+
+    let mut deps = mock_dependencies(&[coin(pool_amount.u128(), "uusd")]);
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &pool_amount)],
+    )]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: offer_amount,
+    };
+    let SimulationResponse { return_amount, .. } =
+        query_simulation(deps.as_ref(), offer_asset.clone()).unwrap();
+
+    // User deposit must be pre-applied
+    deps.querier.with_balance(&[(
+        &String::from(MOCK_CONTRACT_ADDR),
+        &[coin((pool_amount + offer_amount).u128(), "uusd")],
+    )]);
+
+    let swap_msg = |min_out: Uint128| ExecuteMsg::Swap {
+        offer_asset: offer_asset.clone(),
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        min_out: Some(min_out),
+        to: None,
+        callback: None,
+    };
+    let info = mock_info("addr0000", &[coin(offer_amount.u128(), "uusd")]);
+
+    // The swap passes the max spread check but returns less than expected
+    let err = execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info.clone(),
+        swap_msg(return_amount + Uint128::one()),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::MinOutNotMet {
+            expected: return_amount + Uint128::one(),
+            actual: return_amount,
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env_with_block_time(1000),
+        info,
+        swap_msg(return_amount),
+    )
+    .unwrap();
+}
+
+#[test]
+fn try_token_to_native() {
+    let total_share = Uint128::new(20000000000u128);
+    let asset_pool_amount = Uint128::new(30000000000u128);
+    let collateral_pool_amount = Uint128::new(20000000000u128);
+    let offer_amount = Uint128::new(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &String::from("liquidity0000"),
+            &[(&String::from(MOCK_CONTRACT_ADDR), &total_share)],
+        ),
+        (
+            &String::from("asset0000"),
+            &[(
+                &String::from(MOCK_CONTRACT_ADDR),
+                &(asset_pool_amount + offer_amount),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+        ],
+        token_code_id: 10u64,
+        factory_addr: String::from("factory"),
+        init_params: None,
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // We can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, "liquidity0000".to_string());
+
+    // Unauthorized access; can not execute swap directly for token swap
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: Addr::unchecked("asset0000"),
+            },
+            amount: offer_amount,
+        },
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        min_out: None,
+        to: None,
+        callback: None,
+    };
+    let env = mock_env_with_block_time(1000);
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(res, ContractError::Cw20DirectSwap {});
+
+    // Normal sell
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: String::from("addr0000"),
+        amount: offer_amount,
+        msg: to_json_binary(&Cw20HookMsg::Swap {
+            ask_asset_info: None,
+            belief_price: None,
+            max_spread: Some(Decimal::percent(50)),
+            min_out: None,
+            to: None,
+        })
+        .unwrap(),
     });
     let env = mock_env_with_block_time(1000);
     let info = mock_info("asset0000", &[]);
@@ -1189,6 +1858,7 @@ fn try_token_to_native() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: None,
         })
         .unwrap(),
@@ -1298,18 +1968,8 @@ fn test_query_pool() {
     assert_eq!(
         res.assets,
         [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: asset_0_amount
-            },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: Addr::unchecked("asset0000"),
-                },
-                amount: asset_1_amount
-            }
+            make_native_asset("uusd", asset_0_amount),
+            make_cw20_asset("asset0000", asset_1_amount)
         ]
     );
     assert_eq!(res.total_share, total_share_amount);
@@ -1367,6 +2027,274 @@ fn test_query_share() {
     assert_eq!(res[1].amount, Uint128::new(500));
 }
 
+#[test]
+fn test_query_token_allowance() {
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    let res = query_token_allowance(
+        deps.as_ref(),
+        "addr0000".to_string(),
+        MOCK_CONTRACT_ADDR.to_string(),
+    )
+    .unwrap();
+    assert_eq!(res.allowance, Uint128::new(100));
+
+    let res = query_token_allowance(
+        deps.as_ref(),
+        "addr0001".to_string(),
+        MOCK_CONTRACT_ADDR.to_string(),
+    )
+    .unwrap();
+    assert_eq!(res.allowance, Uint128::zero());
+}
+
+#[test]
+fn test_query_exec_price() {
+    let total_share_amount = Uint128::from(1_000_000000u128);
+    let asset_0_amount = Uint128::from(1_000_000000u128);
+    let asset_1_amount = Uint128::from(1_000_000000u128);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: asset_0_amount,
+    }]);
+
+    let denom = format!("factory/{}/{}", env.contract.address, "share/astroport");
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &asset_1_amount)],
+    )]);
+
+    deps.querier.with_balance(&[(
+        &"addr0000".to_string(),
+        &[coin(total_share_amount.u128(), denom.clone())],
+    )]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
+
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, denom.to_string());
+
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(1_000000),
+    };
+
+    let simulation = query_simulation(deps.as_ref(), offer_asset.clone()).unwrap();
+    let price = query_exec_price(deps.as_ref(), offer_asset.clone()).unwrap();
+    assert_eq!(
+        price,
+        Decimal::from_ratio(offer_asset.amount, simulation.return_amount)
+    );
+    // The fee makes the price worse than the one computed from the pre-fee return
+    let gross_return = simulation.return_amount + simulation.commission_amount;
+    assert!(price > Decimal::from_ratio(offer_asset.amount, gross_return));
+    // The spread and the fee make the execution price worse than the 1:1 pool ratio
+    assert!(price > Decimal::one());
+
+    // Offer amount is too small to get anything back
+    let err = query_exec_price(
+        deps.as_ref(),
+        Asset {
+            info: offer_asset.info,
+            amount: Uint128::new(1),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Offer amount is too small to calculate the execution price")
+    );
+}
+
+#[test]
+fn test_query_slippage() {
+    let total_share_amount = Uint128::from(1_000_000000u128);
+    let asset_0_amount = Uint128::from(1_000_000000u128);
+    let asset_1_amount = Uint128::from(1_000_000000u128);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: asset_0_amount,
+    }]);
+
+    let denom = format!("factory/{}/{}", env.contract.address, "share/astroport");
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &asset_1_amount)],
+    )]);
+
+    deps.querier.with_balance(&[(
+        &"addr0000".to_string(),
+        &[coin(total_share_amount.u128(), denom.clone())],
+    )]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
+
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, denom.to_string());
+
+    let offer_asset = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(amount),
+    };
+
+    // Small swaps barely move the price
+    let res = query_slippage(deps.as_ref(), offer_asset(1_000000), Decimal::percent(1)).unwrap();
+    assert!(res.would_succeed);
+    assert!(res.actual_slippage < Decimal::permille(1));
+
+    // 100_000000 = 100 * 1000 / (1000 + 100) ~ 90.909090, so the slippage is ~9%
+    let res = query_slippage(deps.as_ref(), offer_asset(100_000000), Decimal::percent(5)).unwrap();
+    assert!(!res.would_succeed);
+    assert_eq!(
+        res.actual_slippage,
+        Decimal::from_ratio(9_090910u128, 100_000000u128)
+    );
+    // 95_000000 minus 0.3% commission of 90_909090
+    assert_eq!(res.min_return, Uint128::new(95_000000 - 272727));
+
+    let err =
+        query_slippage(deps.as_ref(), offer_asset(1_000000), Decimal::percent(51)).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(ContractError::AllowedSpreadAssertion {}.to_string())
+    );
+}
+
+#[test]
+fn test_query_check_swap_limits() {
+    let total_share_amount = Uint128::from(1_000_000000u128);
+    let asset_0_amount = Uint128::from(1_000_000000u128);
+    let asset_1_amount = Uint128::from(1_000_000000u128);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: asset_0_amount,
+    }]);
+
+    let denom = format!("factory/{}/{}", env.contract.address, "share/astroport");
+
+    deps.querier.with_token_balances(&[(
+        &String::from("asset0000"),
+        &[(&String::from(MOCK_CONTRACT_ADDR), &asset_1_amount)],
+    )]);
+
+    deps.querier.with_balance(&[(
+        &"addr0000".to_string(),
+        &[coin(total_share_amount.u128(), denom.clone())],
+    )]);
+
+    let msg = MockPairSetup::new()
+        .with_asset(make_native_asset("uusd", 0u128))
+        .with_asset(make_cw20_asset("asset0000", 0u128))
+        .instantiate_msg();
+
+    instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // Store liquidity token
+    store_liquidity_token(deps.as_mut(), 1, denom.to_string());
+
+    let offer_asset = |amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::new(amount),
+    };
+
+    // Small swaps pass the default max spread
+    let res = query_check_swap_limits(deps.as_ref(), offer_asset(1_000000), None, None).unwrap();
+    assert!(res.would_succeed);
+    assert!(res.expected_spread < Decimal::permille(1));
+    let sim = query_simulation(deps.as_ref(), offer_asset(1_000000)).unwrap();
+    assert_eq!(res.expected_return, sim.return_amount);
+
+    // 100_000000 = 100 * 1000 / (1000 + 100) ~ 90.909090, so the spread is ~9%
+    let res = query_check_swap_limits(
+        deps.as_ref(),
+        offer_asset(100_000000),
+        None,
+        Some(Decimal::percent(5)),
+    )
+    .unwrap();
+    assert!(!res.would_succeed);
+    assert_eq!(
+        res.expected_spread,
+        Decimal::from_ratio(9_090910u128, 100_000000u128)
+    );
+    let res = query_check_swap_limits(
+        deps.as_ref(),
+        offer_asset(100_000000),
+        None,
+        Some(Decimal::percent(10)),
+    )
+    .unwrap();
+    assert!(res.would_succeed);
+
+    // A belief price far above the pool price makes the spread exceed the limit
+    let res = query_check_swap_limits(
+        deps.as_ref(),
+        offer_asset(1_000000),
+        Some(Decimal::percent(50)),
+        Some(Decimal::percent(10)),
+    )
+    .unwrap();
+    assert!(!res.would_succeed);
+
+    let err = query_check_swap_limits(
+        deps.as_ref(),
+        offer_asset(1_000000),
+        None,
+        Some(Decimal::percent(51)),
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err(ContractError::AllowedSpreadAssertion {}.to_string())
+    );
+    let err = query_check_swap_limits(
+        deps.as_ref(),
+        offer_asset(1_000000),
+        Some(Decimal::zero()),
+        None,
+    )
+    .unwrap_err();
+    assert_eq!(err, StdError::generic_err("Belief price must not be zero!"));
+}
+
 #[test]
 fn test_accumulate_prices() {
     struct Case {
@@ -1466,6 +2394,12 @@ fn test_accumulate_prices() {
                 track_asset_balances: false,
                 fee_share: None,
                 tracker_addr: None,
+                arb_protection: None,
+                flash_loan_fee: Decimal::zero(),
+                global_max_slippage: None,
+                max_swap_volume_per_block: None,
+                volume_limit_decay_blocks: 0,
+                pause_guardian: None,
             },
             Uint128::new(case.x_amount),
             Uint128::new(case.y_amount),
@@ -1488,16 +2422,6 @@ fn test_accumulate_prices() {
     }
 }
 
-fn mock_env_with_block_time(time: u64) -> Env {
-    let mut env = mock_env();
-    env.block = BlockInfo {
-        height: 1,
-        time: Timestamp::from_seconds(time),
-        chain_id: "columbus".to_string(),
-    };
-    env
-}
-
 #[test]
 fn compute_swap_rounding() {
     let offer_pool = Uint128::from(5_000_000_000_000_u128);
@@ -1513,6 +2437,82 @@ fn compute_swap_rounding() {
     );
 }
 
+#[test]
+fn compute_min_swap_amount_rounding() {
+    let offer_pool = Uint128::from(5_000_000_000_000_u128);
+    let ask_pool = Uint128::from(1_000_000_000_u128);
+    let commission_rate = Decimal::permille(3);
+
+    let min_amount = compute_min_swap_amount(offer_pool, ask_pool).unwrap();
+    assert_eq!(min_amount, Uint128::new(5001));
+
+    let (return_amount, ..) =
+        compute_swap(offer_pool, ask_pool, min_amount, commission_rate).unwrap();
+    assert_eq!(return_amount, Uint128::one());
+    let (return_amount, ..) = compute_swap(
+        offer_pool,
+        ask_pool,
+        min_amount - Uint128::one(),
+        commission_rate,
+    )
+    .unwrap();
+    assert_eq!(return_amount, Uint128::zero());
+
+    let err = compute_min_swap_amount(offer_pool, Uint128::one()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Pool liquidity is too low to swap"
+    );
+}
+
+#[test]
+fn compute_price_levels_curve() {
+    let pool = Uint128::new(1_000_000);
+    let commission_rate = Decimal::permille(3);
+
+    let levels = compute_price_levels(pool, pool, Uint128::new(500_000), commission_rate).unwrap();
+    let sizes = levels
+        .iter()
+        .map(|(size, _)| size.u128())
+        .collect::<Vec<_>>();
+    // A single unit returns nothing after rounding
+    assert_eq!(sizes, vec![10, 100, 1_000, 10_000, 100_000, 500_000]);
+    assert_eq!(
+        levels.last().unwrap().1,
+        Decimal::from_ratio(500_000u128, 332_334u128)
+    );
+
+    // Once rounding is negligible, larger trades are priced worse
+    assert!(levels[2..].windows(2).all(|w| w[0].1 < w[1].1));
+
+    let err = compute_price_levels(pool, pool, Uint128::zero(), commission_rate).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Offer amount must be greater than zero"
+    );
+}
+
+#[test]
+fn compute_reserve_ratio_balance() {
+    let res = compute_reserve_ratio(Uint128::new(1_000), Uint128::new(1_000)).unwrap();
+    assert_eq!(res.ratio_0, Decimal::percent(50));
+    assert_eq!(res.ratio_1, Decimal::percent(50));
+    assert!(res.is_balanced);
+
+    let res = compute_reserve_ratio(Uint128::new(590), Uint128::new(410)).unwrap();
+    assert_eq!(res.ratio_0, Decimal::percent(59));
+    assert_eq!(res.ratio_1, Decimal::percent(41));
+    assert!(res.is_balanced);
+
+    // Exactly at the threshold the pool is considered imbalanced
+    let res = compute_reserve_ratio(Uint128::new(400), Uint128::new(600)).unwrap();
+    assert_eq!(res.ratio_0, Decimal::percent(40));
+    assert!(!res.is_balanced);
+
+    let err = compute_reserve_ratio(Uint128::zero(), Uint128::zero()).unwrap_err();
+    assert_eq!(err.to_string(), "Generic error: Pool is empty");
+}
+
 proptest! {
     #[test]
     fn compute_swap_overflow_test(
@@ -1536,6 +2536,34 @@ proptest! {
     }
 }
 
+#[test]
+fn error_context_is_propagated() {
+    let err = propagate_error_context(
+        StdError::generic_err("One of the pools is empty").into(),
+        "during swap amount calculation".to_string(),
+    );
+    assert_eq!(
+        err.to_string(),
+        "Error during swap amount calculation: Generic error: One of the pools is empty"
+    );
+    assert_eq!(
+        std::error::Error::source(&err).unwrap().to_string(),
+        "Generic error: One of the pools is empty"
+    );
+}
+
+#[test]
+fn std_error_context() {
+    let err = ContractError::from_std_with_context(
+        StdError::generic_err("Pair not found"),
+        "during swap fee calculation",
+    );
+    assert_eq!(
+        err.to_string(),
+        "Error during swap fee calculation: Generic error: Pair not found"
+    );
+}
+
 #[test]
 fn ensure_useful_error_messages_are_given_on_swaps() {
     const OFFER: Uint128 = Uint128::new(1_000_000_000000);
@@ -1606,3 +2634,169 @@ fn ensure_useful_error_messages_are_given_on_swaps() {
     );
     compute_offer_amount(OFFER, ASK, AMOUNT, DZERO).unwrap();
 }
+
+#[test]
+fn single_sided_swap_amount() {
+    let pool = Uint128::new(1_000_000_000000);
+
+    // Without fees the swap amount is sqrt(R^2 + a * R) - R
+    let swap_amount =
+        compute_single_sided_swap_amount(pool, Uint128::new(440_000_000000), Decimal::zero())
+            .unwrap();
+    assert_eq!(swap_amount, Uint128::new(200_000_000000));
+
+    // With fees the rest of the deposit and the swap return still match the pool ratio
+    let offer_amount = Uint128::new(123_456_789000);
+    let fee = Decimal::permille(3);
+    let swap_amount = compute_single_sided_swap_amount(pool, offer_amount, fee).unwrap();
+    let (return_amount, _, _) = compute_swap(pool, pool, swap_amount, fee).unwrap();
+
+    let deposit_ratio = Decimal::from_ratio(offer_amount - swap_amount, return_amount);
+    let pool_ratio = Decimal::from_ratio(pool + swap_amount, pool - return_amount);
+    assert!(deposit_ratio.abs_diff(pool_ratio) < Decimal::from_ratio(1u8, 1_000_000u32));
+
+    assert_eq!(
+        compute_single_sided_swap_amount(pool, Uint128::zero(), fee).unwrap(),
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn liquidity_depth() {
+    let pool = Uint128::new(1_000_000_000000);
+
+    let err = compute_liquidity_depth(pool, pool, Decimal::zero(), 10).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Price step must be greater than zero")
+    );
+    let err = compute_liquidity_depth(pool, pool, Decimal::percent(1), 51).unwrap_err();
+    assert_eq!(
+        err,
+        StdError::generic_err("Number of levels must not exceed 50")
+    );
+
+    let (bids, asks) = compute_liquidity_depth(pool, pool, Decimal::percent(1), 50).unwrap();
+    assert_eq!(bids.len(), 50);
+    assert_eq!(asks.len(), 50);
+    assert_eq!(bids[0].price, Decimal::one());
+    assert_eq!(bids[0].cumulative_size, Uint128::zero());
+    assert_eq!(asks[0].cumulative_size, Uint128::zero());
+    for (prev, next) in bids.iter().zip(bids.iter().skip(1)) {
+        assert!(next.price < prev.price);
+        assert!(next.cumulative_size > prev.cumulative_size);
+    }
+    for (prev, next) in asks.iter().zip(asks.iter().skip(1)) {
+        assert!(next.price > prev.price);
+        assert!(next.cumulative_size > prev.cumulative_size);
+    }
+
+    // Moving the price by 21% up and 75% down scales the offer pool by 1.1 and 2 respectively
+    let (bids, asks) = compute_liquidity_depth(pool, pool, Decimal::percent(21), 2).unwrap();
+    assert_eq!(asks[1].cumulative_size, Uint128::new(100_000_000000));
+    let (bids_75, _) = compute_liquidity_depth(pool, pool, Decimal::percent(75), 2).unwrap();
+    assert_eq!(bids_75[1].cumulative_size, pool);
+    assert_eq!(bids_75[1].price, Decimal::percent(25));
+
+    // Bids at or below zero price are omitted
+    assert_eq!(bids.len(), 2);
+    let (bids, asks) = compute_liquidity_depth(pool, pool, Decimal::percent(50), 4).unwrap();
+    assert_eq!(bids.len(), 2);
+    assert_eq!(asks.len(), 4);
+}
+
+#[test]
+fn max_swapable() {
+    let pool = Uint128::new(1_000_000);
+
+    // The ask pool keeps exactly the minimum liquidity before the commission
+    let (max_offer, max_return) = compute_max_swapable(pool, pool, Decimal::zero()).unwrap();
+    assert_eq!(max_offer, Uint128::new(999_000_000));
+    assert_eq!(max_return, Uint128::new(999_000));
+
+    let (max_offer, max_return) = compute_max_swapable(pool, pool, Decimal::permille(3)).unwrap();
+    assert_eq!(max_offer, Uint128::new(999_000_000));
+    assert_eq!(max_return, Uint128::new(996_003));
+
+    // Nothing can be swapped out of a pool holding the minimum liquidity
+    let (max_offer, max_return) =
+        compute_max_swapable(pool, MINIMUM_LIQUIDITY_AMOUNT, Decimal::zero()).unwrap();
+    assert_eq!(max_offer, Uint128::zero());
+    assert_eq!(max_return, Uint128::zero());
+
+    let err = compute_max_swapable(Uint128::zero(), pool, Decimal::zero()).unwrap_err();
+    assert_eq!(err, StdError::generic_err("One of the pools is empty"));
+}
+
+#[test]
+fn migrate_from_previous_version() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = |from_version: Option<&str>| MigrateMsg {
+        from_version: from_version.map(ToString::to_string),
+        to_version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+
+    // Versions with CW20 LP tokens can not be migrated
+    cw2::set_contract_version(deps.as_mut().storage, "astroport-pair", "1.5.1").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), msg(None)).unwrap_err();
+    assert_eq!(err, ContractError::MigrationError {});
+
+    cw2::set_contract_version(deps.as_mut().storage, "astroport-pair", "2.0.2").unwrap();
+    let err = migrate(deps.as_mut(), mock_env(), msg(Some("2.0.1"))).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::IncompatibleMigrationSource {
+            expected: "2.0.1".to_string(),
+            actual: "2.0.2".to_string(),
+        }
+    );
+
+    let err = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            from_version: None,
+            to_version: "2.0.2".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::IncompatibleMigrationTarget {
+            expected: "2.0.2".to_string(),
+            actual: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+
+    migrate(deps.as_mut(), mock_env(), msg(Some("2.0.2"))).unwrap();
+    let version = cw2::get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.contract, "astroport-pair");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        CODE_VERSION.load(deps.as_ref().storage).unwrap(),
+        env!("CARGO_PKG_VERSION")
+    );
+
+    // Configs stored before the new fields were added are still readable
+    let legacy_config = r#"{
+        "pair_info": {
+            "asset_infos": [
+                { "native_token": { "denom": "uusd" } },
+                { "token": { "contract_addr": "asset0000" } }
+            ],
+            "contract_addr": "cosmos2contract",
+            "liquidity_token": "lp",
+            "pair_type": { "xyk": {} }
+        },
+        "factory_addr": "factory",
+        "block_time_last": 0,
+        "price0_cumulative_last": "0",
+        "price1_cumulative_last": "0",
+        "track_asset_balances": false,
+        "fee_share": null,
+        "tracker_addr": null
+    }"#;
+    let config: Config = cosmwasm_std::from_json(legacy_config).unwrap();
+    assert_eq!(config.arb_protection, None);
+    assert_eq!(config.volume_limit_decay_blocks, 0);
+}
@@ -1,14 +1,19 @@
 #![cfg(not(tarpaulin_include))]
 
-use astroport::asset::{native_asset_info, Asset, AssetInfo, PairInfo, MINIMUM_LIQUIDITY_AMOUNT};
+use astroport::asset::{
+    native_asset_info, Asset, AssetInfo, AssetInfoExt, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
+};
 use astroport::factory::{
+    Config as FactoryConfig, ConfigResponse as FactoryConfigResponse,
     ExecuteMsg as FactoryExecuteMsg, InstantiateMsg as FactoryInstantiateMsg, PairConfig, PairType,
     QueryMsg as FactoryQueryMsg, TrackerConfig,
 };
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FeeShareConfig,
-    InstantiateMsg, PoolResponse, QueryMsg, XYKPoolConfig, XYKPoolParams, XYKPoolUpdateParams,
-    MAX_FEE_SHARE_BPS, TWAP_PRECISION,
+    AccumulatedFeesResponse, ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg,
+    FeeShareConfig, FlashLoanCallbackExecuteMsg, InstantiateMsg, IsPoolDepletedResponse,
+    PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse, XYKPoolConfig,
+    XYKPoolParams, XYKPoolUpdateParams, LIQUIDITY_REQUEST_LOCK_PERIOD, MAX_FEE_SHARE_BPS,
+    MAX_FLASH_LOAN_FEE_BPS, MAX_VOLUME_LIMIT_DECAY_BLOCKS, TWAP_PRECISION,
 };
 use astroport::token::InstantiateMsg as TokenInstantiateMsg;
 use astroport::tokenfactory_tracker::{
@@ -22,8 +27,8 @@ use astroport_pair::error::ContractError;
 
 use astroport::common::LP_SUBDENOM;
 use cosmwasm_std::{
-    attr, coin, to_json_binary, Addr, Coin, Decimal, DepsMut, Empty, Env, MessageInfo, Response,
-    StdResult, Uint128, Uint64,
+    attr, coin, from_json, to_json_binary, Addr, BankMsg, Binary, Coin, Decimal, DepsMut, Empty,
+    Env, Event, MessageInfo, Response, StdResult, Uint128, Uint256, Uint64,
 };
 use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg, MinterResponse};
 
@@ -479,12 +484,21 @@ fn test_provide_and_withdraw_liquidity() {
                 to_json_binary(&XYKPoolConfig {
                     track_asset_balances: false,
                     fee_share: None,
+                    arb_protection: None,
+                    flash_loan_fee: Decimal::zero(),
+                    global_max_slippage: None,
+                    max_swap_volume_per_block: None,
+                    volume_limit_decay_blocks: 0,
+                    pause_guardian: None,
                 })
                 .unwrap()
             ),
             owner,
             factory_addr: config.factory_addr,
             tracker_addr: config.tracker_addr,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     )
 }
@@ -515,6 +529,7 @@ fn provide_liquidity_msg(
         auto_stake: None,
         receiver,
         min_lp_to_receive,
+        request_id: None,
     };
 
     let coins = [
@@ -705,6 +720,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: Some(user.to_string()),
         })
         .unwrap(),
@@ -738,6 +754,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -751,6 +768,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: Some(user.to_string()),
         })
         .unwrap(),
@@ -871,6 +889,206 @@ fn test_if_twap_is_calculated_correctly_when_pool_idles() {
     assert_eq!(twap1 / price_precision, Uint128::new(129600)); //   1.5 * ELAPSED_SECONDS
 }
 
+#[test]
+fn test_cumulative_volumes() {
+    let owner = Addr::unchecked("owner");
+    let user1 = Addr::unchecked("user1");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+        ],
+    );
+
+    app.send_tokens(
+        owner.clone(),
+        user1.clone(),
+        &[coin(2000000_000000, "uusd"), coin(2000000_000000, "uluna")],
+    )
+    .unwrap();
+
+    let pair_instance = instantiate_pair(&mut app, &user1);
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000000_000000),
+        Uint128::new(1000000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(user1.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let cpr: CumulativePricesResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::CumulativePrices {})
+        .unwrap();
+    assert_eq!(cpr.volume0_cumulative_last, Uint256::zero());
+    assert_eq!(cpr.volume1_cumulative_last, Uint256::zero());
+
+    for denom in ["uusd", "uluna"] {
+        let msg = ExecuteMsg::Swap {
+            offer_asset: native_asset_info(denom.to_string()).with_balance(1_000000u128),
+            ask_asset_info: None,
+            belief_price: None,
+            max_spread: None,
+            min_out: None,
+            to: None,
+            callback: None,
+        };
+        app.execute_contract(
+            user1.clone(),
+            pair_instance.clone(),
+            &msg,
+            &[coin(1_000000, denom)],
+        )
+        .unwrap();
+    }
+
+    // Both the offered and the returned amounts are accumulated
+    let cpr: CumulativePricesResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::CumulativePrices {})
+        .unwrap();
+    assert_eq!(cpr.volume0_cumulative_last, Uint256::from(2_000000u128));
+    assert_eq!(cpr.volume1_cumulative_last, Uint256::from(1_999999u128));
+}
+
+#[test]
+fn test_is_pool_depleted() {
+    let owner = Addr::unchecked("owner");
+    let user1 = Addr::unchecked("user1");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+        ],
+    );
+
+    app.send_tokens(
+        owner.clone(),
+        user1.clone(),
+        &[coin(2000000_000000, "uusd"), coin(2000000_000000, "uluna")],
+    )
+    .unwrap();
+
+    let pair_instance = instantiate_pair(&mut app, &user1);
+    let lp_denom = format!("factory/{pair_instance}/{LP_SUBDENOM}");
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000000_000000),
+        Uint128::new(1000000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(user1.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let res: IsPoolDepletedResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::IsPoolDepleted {})
+        .unwrap();
+    assert_eq!(
+        res,
+        IsPoolDepletedResponse {
+            is_depleted: false,
+            last_withdrawal_ts: None
+        }
+    );
+
+    // Withdraw all user liquidity
+    let lp_amount = app.wrap().query_balance(&user1, &lp_denom).unwrap();
+    app.execute_contract(
+        user1.clone(),
+        pair_instance.clone(),
+        &ExecuteMsg::WithdrawLiquidity {
+            assets: vec![],
+            min_assets_to_receive: None,
+        },
+        &[lp_amount],
+    )
+    .unwrap();
+
+    let res: IsPoolDepletedResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::IsPoolDepleted {})
+        .unwrap();
+    assert_eq!(
+        res,
+        IsPoolDepletedResponse {
+            is_depleted: true,
+            last_withdrawal_ts: Some(app.block_info().time.seconds())
+        }
+    );
+
+    let offer_asset = native_asset_info("uusd".to_string()).with_balance(1_000000u128);
+    let err = app
+        .wrap()
+        .query_wasm_smart::<SimulationResponse>(
+            &pair_instance,
+            &QueryMsg::Simulation {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: None,
+            },
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&ContractError::PoolDepleted {}.to_string()));
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<ReverseSimulationResponse>(
+            &pair_instance,
+            &QueryMsg::ReverseSimulation {
+                offer_asset_info: None,
+                ask_asset: offer_asset,
+            },
+        )
+        .unwrap_err();
+    assert!(err
+        .to_string()
+        .contains(&ContractError::PoolDepleted {}.to_string()));
+
+    // Providing liquidity again revives the pool
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000_000000),
+        Uint128::new(1000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(user1.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let res: IsPoolDepletedResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::IsPoolDepleted {})
+        .unwrap();
+    assert!(!res.is_depleted);
+    assert_eq!(
+        res.last_withdrawal_ts,
+        Some(app.block_info().time.seconds())
+    );
+}
+
 #[test]
 fn create_pair_with_same_assets() {
     let owner = Addr::unchecked("owner");
@@ -983,7 +1201,7 @@ fn wrong_number_of_assets() {
 }
 
 #[test]
-fn asset_balances_tracking_works_correctly() {
+fn xyk_default_params() {
     let owner = Addr::unchecked("owner");
     let mut app = mock_app(
         owner.clone(),
@@ -1008,7 +1226,7 @@ fn asset_balances_tracking_works_correctly() {
             code_id: pair_code_id,
             maker_fee_bps: 0,
             pair_type: PairType::Xyk {},
-            total_fee_bps: 0,
+            total_fee_bps: 30,
             is_disabled: false,
             is_generator_disabled: false,
             permissioned: false,
@@ -1018,10 +1236,7 @@ fn asset_balances_tracking_works_correctly() {
         owner: owner.to_string(),
         whitelist_code_id: 234u64,
         coin_registry_address: "coin_registry".to_string(),
-        tracker_config: Some(TrackerConfig {
-            code_id: store_tracker_contract(&mut app),
-            token_factory_addr: TOKEN_FACTORY_MODULE.to_string(),
-        }),
+        tracker_config: None,
     };
 
     let factory_instance = app
@@ -1035,50 +1250,26 @@ fn asset_balances_tracking_works_correctly() {
         )
         .unwrap();
 
-    // Instantiate new pair with asset balances tracking starting from instantiation
+    let asset_infos = vec![
+        native_asset_info("uluna".to_string()),
+        native_asset_info("uusd".to_string()),
+    ];
     let msg = FactoryExecuteMsg::CreatePair {
-        asset_infos: vec![
-            AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-        ],
+        asset_infos: asset_infos.clone(),
         pair_type: PairType::Xyk {},
-        init_params: Some(
-            to_json_binary(&XYKPoolParams {
-                track_asset_balances: Some(true),
-            })
-            .unwrap(),
-        ),
+        init_params: Some(FactoryConfig::default_params(&PairType::Xyk {}).unwrap()),
     };
-
     app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
         .unwrap();
 
-    let msg = FactoryQueryMsg::Pair {
-        asset_infos: vec![
-            AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-        ],
-    };
-
     let res: PairInfo = app
         .wrap()
-        .query_wasm_smart(&factory_instance, &msg)
+        .query_wasm_smart(&factory_instance, &FactoryQueryMsg::Pair { asset_infos })
         .unwrap();
-
     let pair_instance = res.contract_addr;
-    let lp_token_address = res.liquidity_token;
 
-    // Provide liquidity
     let (msg, send_funds) = provide_liquidity_msg(
-        Uint128::new(999_000000),
+        Uint128::new(1000_000000),
         Uint128::new(1000_000000),
         None,
         None,
@@ -1087,172 +1278,75 @@ fn asset_balances_tracking_works_correctly() {
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
         .unwrap();
 
-    let owner_lp_balance = app
-        .wrap()
-        .query_balance(owner.to_string(), &lp_token_address)
-        .unwrap();
-    assert_eq!(owner_lp_balance.amount, Uint128::new(999498874));
-
-    // Check that asset balances changed after providing liqudity
-    app.update_block(|b| b.height += 1);
-    let res: Option<Uint128> = app
-        .wrap()
-        .query_wasm_smart(
-            &pair_instance,
-            &QueryMsg::AssetBalanceAt {
-                asset_info: AssetInfo::NativeToken {
-                    denom: "uluna".to_owned(),
-                },
-                block_height: app.block_info().height.into(),
-            },
+    for denom in ["uusd", "uluna"] {
+        let msg = ExecuteMsg::Swap {
+            offer_asset: native_asset_info(denom.to_string()).with_balance(1_000000u128),
+            ask_asset_info: None,
+            belief_price: None,
+            max_spread: None,
+            min_out: None,
+            to: None,
+            callback: None,
+        };
+        app.execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &msg,
+            &[coin(1_000000, denom)],
         )
         .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(1000_000000));
+    }
 
-    let res: Option<Uint128> = app
+    let lp_balance = app
         .wrap()
-        .query_wasm_smart(
-            &pair_instance,
-            &QueryMsg::AssetBalanceAt {
-                asset_info: AssetInfo::NativeToken {
-                    denom: "uusd".to_owned(),
-                },
-                block_height: app.block_info().height.into(),
-            },
-        )
+        .query_balance(&owner, &res.liquidity_token)
         .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(999_000000));
-
-    // Swap
-
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_owned(),
-            },
-            amount: Uint128::new(1_000000),
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &ExecuteMsg::WithdrawLiquidity {
+            assets: vec![],
+            min_assets_to_receive: None,
         },
-        ask_asset_info: None,
-        belief_price: None,
-        max_spread: None,
-        to: None,
-    };
-    let send_funds = vec![Coin {
-        denom: "uusd".to_owned(),
-        amount: Uint128::new(1_000000),
-    }];
-    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
-        .unwrap();
-
-    // Check that asset balances changed after swaping
-    app.update_block(|b| b.height += 1);
-    let res: Option<Uint128> = app
-        .wrap()
-        .query_wasm_smart(
-            &pair_instance,
-            &QueryMsg::AssetBalanceAt {
-                asset_info: AssetInfo::NativeToken {
-                    denom: "uluna".to_owned(),
-                },
-                block_height: app.block_info().height.into(),
-            },
-        )
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(999_000000));
-
-    let res: Option<Uint128> = app
-        .wrap()
-        .query_wasm_smart(
-            &pair_instance,
-            &QueryMsg::AssetBalanceAt {
-                asset_info: AssetInfo::NativeToken {
-                    denom: "uusd".to_owned(),
-                },
-                block_height: app.block_info().height.into(),
-            },
-        )
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(1000_000000));
-
-    app.execute_contract(
-        owner.clone(),
-        pair_instance.clone(),
-        &ExecuteMsg::WithdrawLiquidity {
-            assets: vec![],
-            min_assets_to_receive: None,
-        },
-        &[coin(500_000000u128, lp_token_address)],
-    )
-    .unwrap();
-
-    // Check that asset balances changed after withdrawing
-    app.update_block(|b| b.height += 1);
-    let res: Option<Uint128> = app
-        .wrap()
-        .query_wasm_smart(
-            &pair_instance,
-            &QueryMsg::AssetBalanceAt {
-                asset_info: AssetInfo::NativeToken {
-                    denom: "uluna".to_owned(),
-                },
-                block_height: app.block_info().height.into(),
-            },
-        )
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(499_250063));
-
-    let res: Option<Uint128> = app
-        .wrap()
-        .query_wasm_smart(
-            &pair_instance,
-            &QueryMsg::AssetBalanceAt {
-                asset_info: AssetInfo::NativeToken {
-                    denom: "uusd".to_owned(),
-                },
-                block_height: app.block_info().height.into(),
-            },
-        )
-        .unwrap();
-    assert_eq!(res.unwrap(), Uint128::new(499_749812));
-}
+        &[lp_balance],
+    )
+    .unwrap();
+}
 
 #[test]
-fn update_pair_config() {
-    let owner = Addr::unchecked(OWNER);
-    let mut router = mock_app(
+fn replace_asset() {
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(
         owner.clone(),
         vec![
-            Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::new(100_000_000_000u128),
-            },
-            Coin {
-                denom: "uluna".to_string(),
-                amount: Uint128::new(100_000_000_000u128),
-            },
+            coin(10000_000000u128, "uluna"),
+            coin(10000_000000u128, "uusd"),
         ],
     );
-
-    let token_contract_code_id = store_token_code(&mut router);
-    let pair_contract_code_id = store_pair_code(&mut router);
-
-    let factory_code_id = store_factory_code(&mut router);
+    let token_code_id = store_token_code(&mut app);
+    let pair_code_id = store_pair_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
 
     let init_msg = FactoryInstantiateMsg {
         fee_address: None,
-        pair_configs: vec![],
-        token_code_id: token_contract_code_id,
+        pair_configs: vec![PairConfig {
+            code_id: pair_code_id,
+            maker_fee_bps: 0,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 30,
+            is_disabled: false,
+            is_generator_disabled: false,
+            permissioned: false,
+        }],
+        token_code_id,
         generator_address: Some(String::from("generator")),
         owner: owner.to_string(),
         whitelist_code_id: 234u64,
         coin_registry_address: "coin_registry".to_string(),
-        tracker_config: Some(TrackerConfig {
-            code_id: store_tracker_contract(&mut router),
-            token_factory_addr: TOKEN_FACTORY_MODULE.to_string(),
-        }),
+        tracker_config: None,
     };
 
-    let factory_instance = router
+    let factory_instance = app
         .instantiate_contract(
             factory_code_id,
             owner.clone(),
@@ -1263,88 +1357,195 @@ fn update_pair_config() {
         )
         .unwrap();
 
-    let msg = InstantiateMsg {
-        asset_infos: vec![
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
+    let asset_infos = vec![
+        native_asset_info("uluna".to_string()),
+        native_asset_info("uusd".to_string()),
+    ];
+    app.execute_contract(
+        owner.clone(),
+        factory_instance.clone(),
+        &FactoryExecuteMsg::CreatePair {
+            asset_infos: asset_infos.clone(),
+            pair_type: PairType::Xyk {},
+            init_params: None,
+        },
+        &[],
+    )
+    .unwrap();
+    let pair_instance = app
+        .wrap()
+        .query_wasm_smart::<PairInfo>(
+            &factory_instance,
+            &FactoryQueryMsg::Pair {
+                asset_infos: asset_infos.clone(),
             },
-        ],
-        token_code_id: token_contract_code_id,
-        factory_addr: factory_instance.to_string(),
-        init_params: None,
+        )
+        .unwrap()
+        .contract_addr;
+
+    let (msg, send_funds) = provide_liquidity_msg(
+        Uint128::new(1000_000000),
+        Uint128::new(1000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap();
+
+    let old_asset = native_asset_info("uusd".to_string());
+    let new_asset = native_asset_info("ibc/usd".to_string());
+    let replace_msg = ExecuteMsg::ReplaceAsset {
+        old_asset: old_asset.clone(),
+        new_asset: new_asset.clone(),
     };
 
-    let pair = router
-        .instantiate_contract(
-            pair_contract_code_id,
-            owner.clone(),
-            &msg,
+    // Only the factory owner can replace assets
+    let err = app
+        .execute_contract(
+            Addr::unchecked("random"),
+            pair_instance.clone(),
+            &replace_msg,
             &[],
-            String::from("PAIR"),
-            None,
         )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // The new asset reserve wasn't transferred to the pair yet
+    let err = app
+        .execute_contract(owner.clone(), pair_instance.clone(), &replace_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InsufficientReplacementReserve {
+            asset: "ibc/usd".to_string(),
+            actual: Uint128::zero()
+        }
+    );
+
+    app.init_modules(|router, _, storage| {
+        router.bank.init_balance(
+            storage,
+            &pair_instance,
+            vec![coin(1000_000000u128, "ibc/usd")],
+        )
+    })
+    .unwrap();
+
+    let owner_uusd_before = app.wrap().query_balance(&owner, "uusd").unwrap().amount;
+    app.execute_contract(owner.clone(), pair_instance.clone(), &replace_msg, &[])
         .unwrap();
 
-    let res: ConfigResponse = router
+    // The old asset reserve is sent to the owner
+    let owner_uusd_after = app.wrap().query_balance(&owner, "uusd").unwrap().amount;
+    assert_eq!(
+        owner_uusd_after - owner_uusd_before,
+        Uint128::new(1000_000000)
+    );
+    let pair_uusd = app.wrap().query_balance(&pair_instance, "uusd").unwrap();
+    assert_eq!(pair_uusd.amount, Uint128::zero());
+
+    let res: PairInfo = app
         .wrap()
-        .query_wasm_smart(pair.clone(), &QueryMsg::Config {})
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pair {})
+        .unwrap();
+    assert_eq!(
+        res.asset_infos,
+        vec![native_asset_info("uluna".to_string()), new_asset.clone()]
+    );
+
+    // The factory registered the pair under its new assets
+    let res: PairInfo = app
+        .wrap()
+        .query_wasm_smart(
+            &factory_instance,
+            &FactoryQueryMsg::Pair {
+                asset_infos: vec![native_asset_info("uluna".to_string()), new_asset.clone()],
+            },
+        )
+        .unwrap();
+    assert_eq!(res.contract_addr, pair_instance);
+    app.wrap()
+        .query_wasm_smart::<PairInfo>(&factory_instance, &FactoryQueryMsg::Pair { asset_infos })
+        .unwrap_err();
+
+    // Swaps work with the new asset
+    let user = Addr::unchecked("user");
+    app.send_tokens(owner.clone(), user.clone(), &[coin(1_000000u128, "uluna")])
         .unwrap();
+    app.execute_contract(
+        user.clone(),
+        pair_instance.clone(),
+        &ExecuteMsg::Swap {
+            offer_asset: native_asset_info("uluna".to_string()).with_balance(1_000000u128),
+            ask_asset_info: Some(new_asset),
+            belief_price: None,
+            max_spread: None,
+            min_out: None,
+            to: None,
+            callback: None,
+        },
+        &[coin(1_000000u128, "uluna")],
+    )
+    .unwrap();
+    let balance = app.wrap().query_balance(&user, "ibc/usd").unwrap();
+    assert!(!balance.amount.is_zero());
 
+    let err = app
+        .execute_contract(owner.clone(), pair_instance.clone(), &replace_msg, &[])
+        .unwrap_err();
     assert_eq!(
-        res,
-        ConfigResponse {
-            block_time_last: 0,
-            params: Some(
-                to_json_binary(&XYKPoolConfig {
-                    track_asset_balances: false,
-                    fee_share: None,
-                })
-                .unwrap()
-            ),
-            owner: Addr::unchecked("owner"),
-            factory_addr: Addr::unchecked("contract0"),
-            tracker_addr: None
-        }
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidAsset(old_asset.to_string())
     );
 }
 
 #[test]
-fn enable_disable_fee_sharing() {
-    let owner = Addr::unchecked(OWNER);
-    let mut router = mock_app(
+fn asset_balances_tracking_works_correctly() {
+    let owner = Addr::unchecked("owner");
+    let mut app = mock_app(
         owner.clone(),
         vec![
             Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::new(100_000_000_000u128),
+                denom: "uluna".to_owned(),
+                amount: Uint128::new(10000_000000),
             },
             Coin {
-                denom: "uluna".to_string(),
-                amount: Uint128::new(100_000_000_000u128),
+                denom: "uusd".to_owned(),
+                amount: Uint128::new(10000_000000),
             },
         ],
     );
-
-    let token_contract_code_id = store_token_code(&mut router);
-    let pair_contract_code_id = store_pair_code(&mut router);
-
-    let factory_code_id = store_factory_code(&mut router);
+    let token_code_id = store_token_code(&mut app);
+    let pair_code_id = store_pair_code(&mut app);
+    let factory_code_id = store_factory_code(&mut app);
 
     let init_msg = FactoryInstantiateMsg {
         fee_address: None,
-        pair_configs: vec![],
-        token_code_id: token_contract_code_id,
+        pair_configs: vec![PairConfig {
+            code_id: pair_code_id,
+            maker_fee_bps: 0,
+            pair_type: PairType::Xyk {},
+            total_fee_bps: 0,
+            is_disabled: false,
+            is_generator_disabled: false,
+            permissioned: false,
+        }],
+        token_code_id,
         generator_address: Some(String::from("generator")),
         owner: owner.to_string(),
         whitelist_code_id: 234u64,
         coin_registry_address: "coin_registry".to_string(),
-        tracker_config: None,
+        tracker_config: Some(TrackerConfig {
+            code_id: store_tracker_contract(&mut app),
+            token_factory_addr: TOKEN_FACTORY_MODULE.to_string(),
+        }),
     };
 
-    let factory_instance = router
+    let factory_instance = app
         .instantiate_contract(
             factory_code_id,
             owner.clone(),
@@ -1355,16 +1556,246 @@ fn enable_disable_fee_sharing() {
         )
         .unwrap();
 
-    let msg = InstantiateMsg {
+    // Instantiate new pair with asset balances tracking starting from instantiation
+    let msg = FactoryExecuteMsg::CreatePair {
         asset_infos: vec![
             AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
+                denom: "uluna".to_string(),
             },
             AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
+                denom: "uusd".to_string(),
             },
         ],
-        token_code_id: token_contract_code_id,
+        pair_type: PairType::Xyk {},
+        init_params: Some(
+            to_json_binary(&XYKPoolParams {
+                track_asset_balances: Some(true),
+            })
+            .unwrap(),
+        ),
+    };
+
+    app.execute_contract(owner.clone(), factory_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let msg = FactoryQueryMsg::Pair {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        ],
+    };
+
+    let res: PairInfo = app
+        .wrap()
+        .query_wasm_smart(&factory_instance, &msg)
+        .unwrap();
+
+    let pair_instance = res.contract_addr;
+    let lp_token_address = res.liquidity_token;
+
+    // Provide liquidity
+    let (msg, send_funds) = provide_liquidity_msg(
+        Uint128::new(999_000000),
+        Uint128::new(1000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap();
+
+    let owner_lp_balance = app
+        .wrap()
+        .query_balance(owner.to_string(), &lp_token_address)
+        .unwrap();
+    assert_eq!(owner_lp_balance.amount, Uint128::new(999498874));
+
+    // Check that asset balances changed after providing liqudity
+    app.update_block(|b| b.height += 1);
+    let res: Option<Uint128> = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::AssetBalanceAt {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_owned(),
+                },
+                block_height: app.block_info().height.into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(1000_000000));
+
+    let res: Option<Uint128> = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::AssetBalanceAt {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_owned(),
+                },
+                block_height: app.block_info().height.into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(999_000000));
+
+    // Swap
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_owned(),
+            },
+            amount: Uint128::new(1_000000),
+        },
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        min_out: None,
+        to: None,
+        callback: None,
+    };
+    let send_funds = vec![Coin {
+        denom: "uusd".to_owned(),
+        amount: Uint128::new(1_000000),
+    }];
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &send_funds)
+        .unwrap();
+
+    // Check that asset balances changed after swaping
+    app.update_block(|b| b.height += 1);
+    let res: Option<Uint128> = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::AssetBalanceAt {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_owned(),
+                },
+                block_height: app.block_info().height.into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(999_000000));
+
+    let res: Option<Uint128> = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::AssetBalanceAt {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_owned(),
+                },
+                block_height: app.block_info().height.into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(1000_000000));
+
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &ExecuteMsg::WithdrawLiquidity {
+            assets: vec![],
+            min_assets_to_receive: None,
+        },
+        &[coin(500_000000u128, lp_token_address)],
+    )
+    .unwrap();
+
+    // Check that asset balances changed after withdrawing
+    app.update_block(|b| b.height += 1);
+    let res: Option<Uint128> = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::AssetBalanceAt {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_owned(),
+                },
+                block_height: app.block_info().height.into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(499_250063));
+
+    let res: Option<Uint128> = app
+        .wrap()
+        .query_wasm_smart(
+            &pair_instance,
+            &QueryMsg::AssetBalanceAt {
+                asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_owned(),
+                },
+                block_height: app.block_info().height.into(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.unwrap(), Uint128::new(499_749812));
+}
+
+#[test]
+fn update_pair_config() {
+    let owner = Addr::unchecked(OWNER);
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+        ],
+    );
+
+    let token_contract_code_id = store_token_code(&mut router);
+    let pair_contract_code_id = store_pair_code(&mut router);
+
+    let factory_code_id = store_factory_code(&mut router);
+
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: None,
+        pair_configs: vec![],
+        token_code_id: token_contract_code_id,
+        generator_address: Some(String::from("generator")),
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+        tracker_config: Some(TrackerConfig {
+            code_id: store_tracker_contract(&mut router),
+            token_factory_addr: TOKEN_FACTORY_MODULE.to_string(),
+        }),
+    };
+
+    let factory_instance = router
+        .instantiate_contract(
+            factory_code_id,
+            owner.clone(),
+            &init_msg,
+            &[],
+            "FACTORY",
+            None,
+        )
+        .unwrap();
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+        ],
+        token_code_id: token_contract_code_id,
         factory_addr: factory_instance.to_string(),
         init_params: None,
     };
@@ -1393,47 +1824,167 @@ fn enable_disable_fee_sharing() {
                 to_json_binary(&XYKPoolConfig {
                     track_asset_balances: false,
                     fee_share: None,
+                    arb_protection: None,
+                    flash_loan_fee: Decimal::zero(),
+                    global_max_slippage: None,
+                    max_swap_volume_per_block: None,
+                    volume_limit_decay_blocks: 0,
+                    pause_guardian: None,
                 })
                 .unwrap()
             ),
             owner: Addr::unchecked("owner"),
             factory_addr: Addr::unchecked("contract0"),
-            tracker_addr: None
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     );
+}
 
-    // Attemt to set fee sharing higher than maximum
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&XYKPoolUpdateParams::EnableFeeShare {
-            fee_share_bps: MAX_FEE_SHARE_BPS + 1,
-            fee_share_address: "contract".to_string(),
-        })
-        .unwrap(),
-    };
-    assert_eq!(
-        router
-            .execute_contract(owner.clone(), pair.clone(), &msg, &[])
-            .unwrap_err()
-            .downcast_ref::<ContractError>()
-            .unwrap(),
-        &ContractError::FeeShareOutOfBounds {}
-    );
-
-    // Attemt to set fee sharing to 0
-    let msg = ExecuteMsg::UpdateConfig {
-        params: to_json_binary(&XYKPoolUpdateParams::EnableFeeShare {
-            fee_share_bps: 0,
-            fee_share_address: "contract".to_string(),
-        })
-        .unwrap(),
-    };
-    assert_eq!(
-        router
-            .execute_contract(owner.clone(), pair.clone(), &msg, &[])
+#[test]
+fn enable_disable_fee_sharing() {
+    let owner = Addr::unchecked(OWNER);
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000u128),
+            },
+        ],
+    );
+
+    let token_contract_code_id = store_token_code(&mut router);
+    let pair_contract_code_id = store_pair_code(&mut router);
+
+    let factory_code_id = store_factory_code(&mut router);
+
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: None,
+        pair_configs: vec![],
+        token_code_id: token_contract_code_id,
+        generator_address: Some(String::from("generator")),
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+        tracker_config: None,
+    };
+
+    let factory_instance = router
+        .instantiate_contract(
+            factory_code_id,
+            owner.clone(),
+            &init_msg,
+            &[],
+            "FACTORY",
+            None,
+        )
+        .unwrap();
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+        ],
+        token_code_id: token_contract_code_id,
+        factory_addr: factory_instance.to_string(),
+        init_params: None,
+    };
+
+    let pair = router
+        .instantiate_contract(
+            pair_contract_code_id,
+            owner.clone(),
+            &msg,
+            &[],
+            String::from("PAIR"),
+            None,
+        )
+        .unwrap();
+
+    let res: ConfigResponse = router
+        .wrap()
+        .query_wasm_smart(pair.clone(), &QueryMsg::Config {})
+        .unwrap();
+
+    assert_eq!(
+        res,
+        ConfigResponse {
+            block_time_last: 0,
+            params: Some(
+                to_json_binary(&XYKPoolConfig {
+                    track_asset_balances: false,
+                    fee_share: None,
+                    arb_protection: None,
+                    flash_loan_fee: Decimal::zero(),
+                    global_max_slippage: None,
+                    max_swap_volume_per_block: None,
+                    volume_limit_decay_blocks: 0,
+                    pause_guardian: None,
+                })
+                .unwrap()
+            ),
+            owner: Addr::unchecked("owner"),
+            factory_addr: Addr::unchecked("contract0"),
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
+        }
+    );
+
+    // Attemt to set fee sharing higher than maximum
+    let msg = ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&XYKPoolUpdateParams::EnableFeeShare {
+            fee_share_bps: MAX_FEE_SHARE_BPS + 1,
+            fee_share_address: "contract".to_string(),
+        })
+        .unwrap(),
+    };
+    assert_eq!(
+        router
+            .execute_contract(owner.clone(), pair.clone(), &msg, &[])
+            .unwrap_err()
+            .downcast_ref::<ContractError>()
+            .unwrap(),
+        &ContractError::InvalidFeeRate {
+            rate: Decimal::from_ratio(MAX_FEE_SHARE_BPS + 1, 10000u16),
+            field: "fee_share_bps".to_string(),
+            min: Decimal::from_ratio(1u16, 10000u16),
+            max: Decimal::from_ratio(MAX_FEE_SHARE_BPS, 10000u16),
+        }
+    );
+
+    // Attemt to set fee sharing to 0
+    let msg = ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&XYKPoolUpdateParams::EnableFeeShare {
+            fee_share_bps: 0,
+            fee_share_address: "contract".to_string(),
+        })
+        .unwrap(),
+    };
+    assert_eq!(
+        router
+            .execute_contract(owner.clone(), pair.clone(), &msg, &[])
             .unwrap_err()
             .downcast_ref::<ContractError>()
             .unwrap(),
-        &ContractError::FeeShareOutOfBounds {}
+        &ContractError::InvalidFeeRate {
+            rate: Decimal::from_ratio(0u16, 10000u16),
+            field: "fee_share_bps".to_string(),
+            min: Decimal::from_ratio(1u16, 10000u16),
+            max: Decimal::from_ratio(MAX_FEE_SHARE_BPS, 10000u16),
+        }
     );
 
     let fee_share_bps = 500; // 5%
@@ -1466,12 +2017,21 @@ fn enable_disable_fee_sharing() {
                         bps: fee_share_bps,
                         recipient: Addr::unchecked(fee_share_contract),
                     }),
+                    arb_protection: None,
+                    flash_loan_fee: Decimal::zero(),
+                    global_max_slippage: None,
+                    max_swap_volume_per_block: None,
+                    volume_limit_decay_blocks: 0,
+                    pause_guardian: None,
                 })
                 .unwrap()
             ),
             owner: Addr::unchecked("owner"),
             factory_addr: Addr::unchecked("contract0"),
-            tracker_addr: None
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     );
 
@@ -1496,14 +2056,1524 @@ fn enable_disable_fee_sharing() {
                 to_json_binary(&XYKPoolConfig {
                     track_asset_balances: false,
                     fee_share: None,
+                    arb_protection: None,
+                    flash_loan_fee: Decimal::zero(),
+                    global_max_slippage: None,
+                    max_swap_volume_per_block: None,
+                    volume_limit_decay_blocks: 0,
+                    pause_guardian: None,
                 })
                 .unwrap()
             ),
             owner: Addr::unchecked("owner"),
             factory_addr: Addr::unchecked("contract0"),
-            tracker_addr: None
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
+        }
+    );
+}
+
+#[test]
+fn sweep() {
+    let owner = Addr::unchecked("owner");
+    let recipient = Addr::unchecked("recipient");
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            coin(100_000_000_000u128, "uusd"),
+            coin(100_000_000_000u128, "uluna"),
+            coin(1_000u128, "uairdrop"),
+            coin(1_000u128, "urebate"),
+        ],
+    );
+
+    let pair = instantiate_pair(&mut router, &owner);
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(100_000000),
+        Uint128::new(100_000000),
+        None,
+        None,
+        None,
+    );
+    router
+        .execute_contract(owner.clone(), pair.clone(), &msg, &coins)
+        .unwrap();
+
+    router
+        .send_tokens(
+            owner.clone(),
+            pair.clone(),
+            &[coin(100u128, "uairdrop"), coin(50u128, "urebate")],
+        )
+        .unwrap();
+
+    let sweep_msg = |assets: Option<Vec<AssetInfo>>| ExecuteMsg::Sweep {
+        recipient: recipient.to_string(),
+        assets,
+    };
+
+    // Only the factory owner can sweep tokens
+    let err = router
+        .execute_contract(
+            Addr::unchecked("random"),
+            pair.clone(),
+            &sweep_msg(None),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // Pool assets and the LP token can not be swept
+    let lp_token = format!("factory/{pair}/{LP_SUBDENOM}");
+    for denom in ["uusd", lp_token.as_str()] {
+        let err = router
+            .execute_contract(
+                owner.clone(),
+                pair.clone(),
+                &sweep_msg(Some(vec![native_asset_info(denom.to_string())])),
+                &[],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::SweepPoolAsset {
+                asset: denom.to_string()
+            }
+        );
+    }
+
+    // Sweep only the specified asset
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            pair.clone(),
+            &sweep_msg(Some(vec![native_asset_info("urebate".to_string())])),
+            &[],
+        )
+        .unwrap();
+    assert!(res.has_event(
+        &Event::new("wasm-sweep")
+            .add_attribute("asset", "urebate")
+            .add_attribute("amount", "50")
+            .add_attribute("recipient", recipient.as_str())
+    ));
+    let balance = router.wrap().query_balance(&recipient, "urebate").unwrap();
+    assert_eq!(balance.amount.u128(), 50);
+
+    // Sweep all remaining non-pool assets
+    let res = router
+        .execute_contract(owner.clone(), pair.clone(), &sweep_msg(None), &[])
+        .unwrap();
+    assert!(res.has_event(
+        &Event::new("wasm-sweep")
+            .add_attribute("asset", "uairdrop")
+            .add_attribute("amount", "100")
+            .add_attribute("recipient", recipient.as_str())
+    ));
+    let balances = router.wrap().query_all_balances(&recipient).unwrap();
+    assert_eq!(
+        balances,
+        vec![coin(100u128, "uairdrop"), coin(50u128, "urebate")]
+    );
+
+    // Pool balances are untouched
+    for denom in ["uusd", "uluna"] {
+        let balance = router.wrap().query_balance(&pair, denom).unwrap();
+        assert_eq!(balance.amount.u128(), 100_000000);
+    }
+}
+
+#[test]
+fn liquidity_request() {
+    let owner = Addr::unchecked("owner");
+    let provider = Addr::unchecked("provider");
+    let mut router = mock_app(
+        owner.clone(),
+        vec![
+            coin(100_000_000_000u128, "uusd"),
+            coin(100_000_000_000u128, "uluna"),
+            coin(1_000u128, "ubounty"),
+        ],
+    );
+
+    let pair = instantiate_pair(&mut router, &owner);
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(100_000000),
+        Uint128::new(100_000000),
+        None,
+        None,
+        None,
+    );
+    router
+        .execute_contract(owner.clone(), pair.clone(), &msg, &coins)
+        .unwrap();
+
+    router
+        .send_tokens(
+            owner.clone(),
+            provider.clone(),
+            &[coin(100_000000u128, "uusd"), coin(100_000000u128, "uluna")],
+        )
+        .unwrap();
+
+    let deadline = router.block_info().time.seconds() + 100;
+    let request_msg = ExecuteMsg::RequestLiquidityProvision {
+        amount: Uint128::new(10_000000),
+        deadline,
+    };
+
+    // A bounty is required
+    let err = router
+        .execute_contract(owner.clone(), pair.clone(), &request_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MissingLiquidityRequestBounty {}
+    );
+
+    // Pool assets can not be used as a bounty
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            pair.clone(),
+            &request_msg,
+            &[coin(100u128, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidLiquidityRequestBounty {
+            asset: "uusd".to_string()
+        }
+    );
+
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            pair.clone(),
+            &request_msg,
+            &[coin(500u128, "ubounty")],
+        )
+        .unwrap();
+    assert!(res.has_event(
+        &Event::new("wasm-liquidity_request")
+            .add_attribute("request_id", "0")
+            .add_attribute("requester", owner.as_str())
+            .add_attribute("amount", "10000000")
+            .add_attribute("deadline", deadline.to_string())
+            .add_attribute("bounty", "500ubounty")
+    ));
+
+    // Escrowed bounties are not swept
+    router
+        .execute_contract(
+            owner.clone(),
+            pair.clone(),
+            &ExecuteMsg::Sweep {
+                recipient: owner.to_string(),
+                assets: None,
+            },
+            &[],
+        )
+        .unwrap();
+    let balance = router.wrap().query_balance(&pair, "ubounty").unwrap();
+    assert_eq!(balance.amount.u128(), 500);
+
+    let provide_msg = |amount: u128, request_id: u64| {
+        (
+            ExecuteMsg::ProvideLiquidity {
+                assets: vec![
+                    native_asset_info("uusd".to_string()).with_balance(amount),
+                    native_asset_info("uluna".to_string()).with_balance(amount),
+                ],
+                slippage_tolerance: None,
+                auto_stake: None,
+                receiver: None,
+                min_lp_to_receive: None,
+                request_id: Some(request_id),
+            },
+            [coin(amount, "uluna"), coin(amount, "uusd")],
+        )
+    };
+
+    // The provision has to mint at least the requested amount of LP tokens
+    let (msg, coins) = provide_msg(1_000000, 0);
+    let err = router
+        .execute_contract(provider.clone(), pair.clone(), &msg, &coins)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LiquidityRequestNotMet {
+            requested: Uint128::new(10_000000),
+            minted: Uint128::new(1_000000),
         }
     );
+
+    // Auto staking can not be combined with a liquidity request
+    let (mut msg, coins) = provide_msg(10_000000, 0);
+    if let ExecuteMsg::ProvideLiquidity { auto_stake, .. } = &mut msg {
+        *auto_stake = Some(true);
+    }
+    let err = router
+        .execute_contract(provider.clone(), pair.clone(), &msg, &coins)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LiquidityRequestAutoStake {}
+    );
+
+    let (msg, coins) = provide_msg(10_000000, 0);
+    let res = router
+        .execute_contract(provider.clone(), pair.clone(), &msg, &coins)
+        .unwrap();
+    let unlocks_at = router.block_info().time.seconds() + LIQUIDITY_REQUEST_LOCK_PERIOD;
+    assert!(res.has_event(
+        &Event::new("wasm-liquidity_request_fulfilled")
+            .add_attribute("request_id", "0")
+            .add_attribute("provider", provider.as_str())
+            .add_attribute("lp_amount", "10000000")
+            .add_attribute("unlocks_at", unlocks_at.to_string())
+    ));
+
+    // The minted LP tokens are locked and the bounty stays escrowed
+    let lp_token = router
+        .wrap()
+        .query_wasm_smart::<PairInfo>(&pair, &QueryMsg::Pair {})
+        .unwrap()
+        .liquidity_token;
+    let balance = router.wrap().query_balance(&provider, &lp_token).unwrap();
+    assert_eq!(balance.amount.u128(), 0);
+    let balance = router.wrap().query_balance(&provider, "ubounty").unwrap();
+    assert_eq!(balance.amount.u128(), 0);
+
+    // The request can be fulfilled only once
+    let (msg, coins) = provide_msg(10_000000, 0);
+    let err = router
+        .execute_contract(provider.clone(), pair.clone(), &msg, &coins)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LiquidityRequestFulfilled { request_id: 0 }
+    );
+
+    // A fulfilled request can not be cancelled
+    let err = router
+        .execute_contract(
+            owner.clone(),
+            pair.clone(),
+            &ExecuteMsg::CancelLiquidityRequest { request_id: 0 },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LiquidityRequestFulfilled { request_id: 0 }
+    );
+
+    let claim_msg = ExecuteMsg::ClaimLiquidityRequest { request_id: 0 };
+    let err = router
+        .execute_contract(provider.clone(), pair.clone(), &claim_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LiquidityRequestLocked {
+            request_id: 0,
+            unlocks_at
+        }
+    );
+
+    router.update_block(|b| b.time = b.time.plus_seconds(LIQUIDITY_REQUEST_LOCK_PERIOD));
+    router
+        .execute_contract(owner.clone(), pair.clone(), &claim_msg, &[])
+        .unwrap();
+    let balance = router.wrap().query_balance(&provider, &lp_token).unwrap();
+    assert_eq!(balance.amount.u128(), 10_000000);
+    let balance = router.wrap().query_balance(&provider, "ubounty").unwrap();
+    assert_eq!(balance.amount.u128(), 500);
+
+    let err = router
+        .execute_contract(provider.clone(), pair.clone(), &claim_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LiquidityRequestNotFound { request_id: 0 }
+    );
+
+    let deadline = router.block_info().time.seconds() + 100;
+    let request_msg = ExecuteMsg::RequestLiquidityProvision {
+        amount: Uint128::new(10_000000),
+        deadline,
+    };
+
+    router
+        .execute_contract(
+            owner.clone(),
+            pair.clone(),
+            &request_msg,
+            &[coin(300u128, "ubounty")],
+        )
+        .unwrap();
+
+    // Expired requests can not be fulfilled
+    router.update_block(|b| b.time = b.time.plus_seconds(101));
+    let (msg, coins) = provide_msg(10_000000, 1);
+    let err = router
+        .execute_contract(provider.clone(), pair.clone(), &msg, &coins)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::LiquidityRequestExpired { request_id: 1 }
+    );
+
+    // Only the requester can cancel the request
+    let cancel_msg = ExecuteMsg::CancelLiquidityRequest { request_id: 1 };
+    let err = router
+        .execute_contract(provider.clone(), pair.clone(), &cancel_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    router
+        .execute_contract(owner.clone(), pair.clone(), &cancel_msg, &[])
+        .unwrap();
+    let balance = router.wrap().query_balance(&owner, "ubounty").unwrap();
+    assert_eq!(balance.amount.u128(), 500);
+    let balance = router.wrap().query_balance(&pair, "ubounty").unwrap();
+    assert_eq!(balance.amount.u128(), 0);
+}
+
+#[test]
+fn freeze_config() {
+    let owner = Addr::unchecked(OWNER);
+    let mut router = mock_app(owner.clone(), vec![]);
+
+    let token_contract_code_id = store_token_code(&mut router);
+    let pair_contract_code_id = store_pair_code(&mut router);
+    let factory_code_id = store_factory_code(&mut router);
+
+    let init_msg = FactoryInstantiateMsg {
+        fee_address: None,
+        pair_configs: vec![],
+        token_code_id: token_contract_code_id,
+        generator_address: Some(String::from("generator")),
+        owner: owner.to_string(),
+        whitelist_code_id: 234u64,
+        coin_registry_address: "coin_registry".to_string(),
+        tracker_config: None,
+    };
+
+    let factory_instance = router
+        .instantiate_contract(
+            factory_code_id,
+            owner.clone(),
+            &init_msg,
+            &[],
+            "FACTORY",
+            None,
+        )
+        .unwrap();
+
+    let msg = InstantiateMsg {
+        asset_infos: vec![
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+        ],
+        token_code_id: token_contract_code_id,
+        factory_addr: factory_instance.to_string(),
+        init_params: None,
+    };
+
+    let pair = router
+        .instantiate_contract(
+            pair_contract_code_id,
+            owner.clone(),
+            &msg,
+            &[],
+            String::from("PAIR"),
+            None,
+        )
+        .unwrap();
+
+    // Only the factory owner can freeze the config
+    assert_eq!(
+        router
+            .execute_contract(
+                Addr::unchecked("random"),
+                pair.clone(),
+                &ExecuteMsg::FreezeConfig {},
+                &[]
+            )
+            .unwrap_err()
+            .downcast_ref::<ContractError>()
+            .unwrap(),
+        &ContractError::Unauthorized {}
+    );
+
+    let res = router
+        .execute_contract(
+            owner.clone(),
+            pair.clone(),
+            &ExecuteMsg::FreezeConfig {},
+            &[],
+        )
+        .unwrap();
+    assert!(res.has_event(&Event::new("wasm-config-frozen").add_attribute("pair", pair.as_str())));
+
+    // Freezing is only possible once
+    assert_eq!(
+        router
+            .execute_contract(
+                owner.clone(),
+                pair.clone(),
+                &ExecuteMsg::FreezeConfig {},
+                &[]
+            )
+            .unwrap_err()
+            .downcast_ref::<ContractError>()
+            .unwrap(),
+        &ContractError::ConfigFrozen {}
+    );
+
+    let msg = ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&XYKPoolUpdateParams::EnableFeeShare {
+            fee_share_bps: 500,
+            fee_share_address: "contract".to_string(),
+        })
+        .unwrap(),
+    };
+    assert_eq!(
+        router
+            .execute_contract(owner.clone(), pair.clone(), &msg, &[])
+            .unwrap_err()
+            .downcast_ref::<ContractError>()
+            .unwrap(),
+        &ContractError::ConfigFrozen {}
+    );
+
+    // Pool assets, external liquidity and stray balances can't be changed either
+    let msgs = [
+        ExecuteMsg::ReplaceAsset {
+            old_asset: native_asset_info("uluna".to_string()),
+            new_asset: native_asset_info("uatom".to_string()),
+        },
+        ExecuteMsg::RegisterExternalLiquidity {
+            external_contract: "external".to_string(),
+            amount: [Uint128::new(100), Uint128::new(100)],
+        },
+        ExecuteMsg::Sweep {
+            recipient: owner.to_string(),
+            assets: None,
+        },
+    ];
+    for msg in msgs {
+        assert_eq!(
+            router
+                .execute_contract(owner.clone(), pair.clone(), &msg, &[])
+                .unwrap_err()
+                .downcast_ref::<ContractError>()
+                .unwrap(),
+            &ContractError::ConfigFrozen {}
+        );
+    }
+}
+
+fn flash_loan_receiver_execute(
+    _deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: FlashLoanCallbackExecuteMsg,
+) -> StdResult<Response> {
+    let FlashLoanCallbackExecuteMsg::FlashLoanCallback(callback) = msg;
+
+    // The callback message tells the receiver whether to repay the loan
+    if !from_json::<bool>(&callback.msg)? {
+        return Ok(Response::new());
+    }
+
+    let repayment = callback
+        .assets
+        .iter()
+        .zip(callback.fees.iter())
+        .map(|(asset, fee)| coin((asset.amount + fee.amount).u128(), asset.info.to_string()))
+        .collect();
+
+    Ok(Response::new().add_message(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: repayment,
+    }))
+}
+
+#[test]
+fn flash_loan() {
+    let owner = Addr::unchecked("owner");
+    let user1 = Addr::unchecked("user1");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000000_000000),
+        Uint128::new(1000000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let receiver_code_id = app.store_code(Box::new(ContractWrapper::new_with_empty(
+        flash_loan_receiver_execute,
+        |_, _, _, _: Empty| -> StdResult<Response> { Ok(Response::new()) },
+        |_, _, _: Empty| -> StdResult<Binary> { unimplemented!() },
+    )));
+    let receiver = app
+        .instantiate_contract(
+            receiver_code_id,
+            owner.clone(),
+            &Empty {},
+            &[],
+            "Flash loan receiver",
+            None,
+        )
+        .unwrap();
+    // The receiver pays the fees from its own balance
+    app.send_tokens(owner.clone(), receiver.clone(), &[coin(1_000000, "uusd")])
+        .unwrap();
+
+    // Only the factory owner can set the flash loan fee
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::SetFlashLoanFee {
+                fee: Decimal::permille(3),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::SetFlashLoanFee {
+                fee: Decimal::percent(11),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidFeeRate {
+            rate: Decimal::percent(11),
+            field: "flash_loan_fee".to_string(),
+            min: Decimal::zero(),
+            max: Decimal::from_ratio(MAX_FLASH_LOAN_FEE_BPS, 10000u16),
+        }
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &ExecuteMsg::SetFlashLoanFee {
+            fee: Decimal::permille(3),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Config {})
+        .unwrap();
+    let params: XYKPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(params.flash_loan_fee, Decimal::permille(3));
+
+    let loan_msg = |repay: bool| ExecuteMsg::FlashLoan {
+        assets: vec![native_asset_info("uusd".to_string()).with_balance(100_000000u128)],
+        receiver: receiver.to_string(),
+        msg: to_json_binary(&repay).unwrap(),
+    };
+
+    // Receiver doesn't repay the loan
+    let err = app
+        .execute_contract(user1.clone(), pair_instance.clone(), &loan_msg(false), &[])
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashLoanNotRepaid {
+            asset: "uusd".to_string(),
+            expected: Uint128::new(1000000_000000 + 300_000),
+            actual: Uint128::new(1000000_000000 - 100_000000),
+        }
+        .to_string()
+    );
+
+    let pool: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(pool.assets[0].amount.u128(), 1000000_000000);
+
+    // Loaning more than the pool holds is rejected
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::FlashLoan {
+                assets: vec![
+                    native_asset_info("uusd".to_string()).with_balance(1000001_000000u128)
+                ],
+                receiver: receiver.to_string(),
+                msg: to_json_binary(&true).unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        "Generic error: Not enough uusd in the pool to lend 1000001000000"
+    );
+
+    // Successful round trip. The fee is 0.3% of the loan
+    app.execute_contract(user1.clone(), pair_instance.clone(), &loan_msg(true), &[])
+        .unwrap();
+
+    let pool: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(pool.assets[0].amount.u128(), 1000000_000000 + 300_000);
+    assert_eq!(pool.assets[1].amount.u128(), 1000000_000000);
+
+    let receiver_balance = app.wrap().query_balance(&receiver, "uusd").unwrap();
+    assert_eq!(receiver_balance.amount.u128(), 1_000000 - 300_000);
+}
+
+#[test]
+fn set_slippage_tolerance() {
+    let owner = Addr::unchecked("owner");
+    let user1 = Addr::unchecked("user1");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000_000000),
+        Uint128::new(1000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    // Only the factory owner can set the cap
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::SetSlippageTolerance {
+                max_slippage: Decimal::percent(2),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::SetSlippageTolerance {
+                max_slippage: Decimal::percent(51),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::AllowedSpreadAssertion {}
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &ExecuteMsg::SetSlippageTolerance {
+            max_slippage: Decimal::percent(2),
+        },
+        &[],
+    )
+    .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Config {})
+        .unwrap();
+    let params: XYKPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(params.global_max_slippage, Some(Decimal::percent(2)));
+
+    // A 10% imbalanced provision fits into the user tolerance but not into the cap
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000_000000),
+        Uint128::new(1100_000000),
+        None,
+        Some(Decimal::percent(20)),
+        None,
+    );
+    let err = app
+        .execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::MaxSlippageAssertion {}
+    );
+
+    // Tolerances above the global limit are clamped instead of rejected
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000_000000),
+        Uint128::new(1010_000000),
+        None,
+        Some(Decimal::percent(60)),
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    // The cap also applies to single-sided provisions
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::ProvideLiquiditySingle {
+                offer: native_asset_info("uusd".to_string()).with_balance(500_000000u128),
+                slippage_tolerance: Some(Decimal::percent(50)),
+                receiver: None,
+            },
+            &[coin(500_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ProvideSlippageViolation(..)
+    ));
+}
+
+#[test]
+fn swap_volume_limit() {
+    let owner = Addr::unchecked("owner");
+    let user1 = Addr::unchecked("user1");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000_000000),
+        Uint128::new(1000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let set_limit_msg = ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&XYKPoolUpdateParams::SetSwapVolumeLimit {
+            max_swap_volume_per_block: Some(Uint128::new(10_000000)),
+            volume_limit_decay_blocks: 10,
+        })
+        .unwrap(),
+    };
+
+    // Only the factory owner can set the limit
+    let err = app
+        .execute_contract(user1, pair_instance.clone(), &set_limit_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // The decay period is capped
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::UpdateConfig {
+                params: to_json_binary(&XYKPoolUpdateParams::SetSwapVolumeLimit {
+                    max_swap_volume_per_block: Some(Uint128::new(10_000000)),
+                    volume_limit_decay_blocks: MAX_VOLUME_LIMIT_DECAY_BLOCKS + 1,
+                })
+                .unwrap(),
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::VolumeLimitDecayBlocksTooLarge {
+            max: MAX_VOLUME_LIMIT_DECAY_BLOCKS
+        }
+    );
+
+    app.execute_contract(owner.clone(), pair_instance.clone(), &set_limit_msg, &[])
+        .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Config {})
+        .unwrap();
+    let params: XYKPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(
+        params.max_swap_volume_per_block,
+        Some(Uint128::new(10_000000))
+    );
+    assert_eq!(params.volume_limit_decay_blocks, 10);
+
+    let swap_denom = |app: &mut TestApp, denom: &str, amount: u128| {
+        let msg = ExecuteMsg::Swap {
+            offer_asset: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: denom.to_owned(),
+                },
+                amount: Uint128::new(amount),
+            },
+            ask_asset_info: None,
+            belief_price: None,
+            max_spread: Some(Decimal::percent(50)),
+            min_out: None,
+            to: None,
+            callback: None,
+        };
+        app.execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &msg,
+            &[coin(amount, denom)],
+        )
+    };
+    let swap = |app: &mut TestApp, amount: u128| swap_denom(app, "uusd", amount);
+
+    // A single swap above the limit
+    let err = swap(&mut app, 10_000001).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockVolumeLimitExceeded {
+            volume: Uint128::new(10_000001),
+            limit: Uint128::new(10_000000),
+        }
+    );
+
+    // Two swaps which exceed the limit together
+    swap(&mut app, 6_000000).unwrap();
+    let err = swap(&mut app, 6_000000).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockVolumeLimitExceeded {
+            volume: Uint128::new(12_000000),
+            limit: Uint128::new(10_000000),
+        }
+    );
+
+    // Volumes are tracked separately for every offer asset
+    swap_denom(&mut app, "uluna", 10_000000).unwrap();
+    let err = swap_denom(&mut app, "uluna", 1).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockVolumeLimitExceeded {
+            volume: Uint128::new(10_000001),
+            limit: Uint128::new(10_000000),
+        }
+    );
+
+    // Half of the previous volume is released after 5 blocks
+    app.update_block(|b| b.height += 5);
+    swap(&mut app, 7_000001).unwrap_err();
+    swap(&mut app, 7_000000).unwrap();
+
+    // The whole volume is released after the decay period
+    app.update_block(|b| b.height += 10);
+    swap(&mut app, 10_000000).unwrap();
+
+    // The internal swap of a single-sided provision counts towards the limit
+    let provide_single_msg = ExecuteMsg::ProvideLiquiditySingle {
+        offer: native_asset_info("uusd".to_string()).with_balance(2_000000u128),
+        slippage_tolerance: None,
+        receiver: None,
+    };
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &provide_single_msg,
+            &[coin(2_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockVolumeLimitExceeded { .. }
+    ));
+
+    app.update_block(|b| b.height += 10);
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &provide_single_msg,
+        &[coin(2_000000, "uusd")],
+    )
+    .unwrap();
+}
+
+#[test]
+fn pause_and_unpause() {
+    let owner = Addr::unchecked("owner");
+    let guardian = Addr::unchecked("guardian");
+    let user1 = Addr::unchecked("user1");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+        ],
+    );
+
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    let (provide_msg, provide_coins) = provide_liquidity_msg(
+        Uint128::new(1000_000000),
+        Uint128::new(1000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &provide_msg,
+        &provide_coins,
+    )
+    .unwrap();
+
+    // Only the factory owner can set the pause guardian
+    let set_guardian_msg = ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&XYKPoolUpdateParams::SetPauseGuardian {
+            pause_guardian: Some(guardian.to_string()),
+        })
+        .unwrap(),
+    };
+    let err = app
+        .execute_contract(
+            guardian.clone(),
+            pair_instance.clone(),
+            &set_guardian_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &set_guardian_msg, &[])
+        .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Config {})
+        .unwrap();
+    let params: XYKPoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(params.pause_guardian, Some(guardian.clone()));
+    assert!(!config.is_paused);
+
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_owned(),
+            },
+            amount: Uint128::new(1_000000),
+        },
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: Some(Decimal::percent(50)),
+        min_out: None,
+        to: None,
+        callback: None,
+    };
+
+    // Random users can't pause the pair
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::Pause {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(
+        guardian.clone(),
+        pair_instance.clone(),
+        &ExecuteMsg::Pause {},
+        &[],
+    )
+    .unwrap();
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Config {})
+        .unwrap();
+    assert!(config.is_paused);
+
+    // Queries still work while paused
+    let _: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &swap_msg,
+            &[coin(1_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ContractPaused {}
+    );
+
+    let err = app
+        .execute_contract(
+            owner.clone(),
+            pair_instance.clone(),
+            &provide_msg,
+            &provide_coins,
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ContractPaused {}
+    );
+
+    // The guardian can't unpause the pair
+    let err = app
+        .execute_contract(
+            guardian.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::Unpause {},
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &ExecuteMsg::Unpause {},
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &swap_msg,
+        &[coin(1_000000, "uusd")],
+    )
+    .unwrap();
+}
+
+#[test]
+fn provide_liquidity_single() {
+    let owner = Addr::unchecked("owner");
+    let user1 = Addr::unchecked("user1");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+            Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::new(100_000_000_000000u128),
+            },
+        ],
+    );
+    app.send_tokens(
+        owner.clone(),
+        user1.clone(),
+        &[coin(1_000_000_000000, "uusd")],
+    )
+    .unwrap();
+
+    let pair_instance = instantiate_pair(&mut app, &owner);
+    let lp_denom = format!("factory/{pair_instance}/{LP_SUBDENOM}");
+
+    let offer = |amount: u128| ExecuteMsg::ProvideLiquiditySingle {
+        offer: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::new(amount),
+        },
+        slippage_tolerance: None,
+        receiver: None,
+    };
+
+    // Only native offers are supported
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            pair_instance.clone(),
+            &ExecuteMsg::ProvideLiquiditySingle {
+                offer: Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: Addr::unchecked("cw20_token"),
+                    },
+                    amount: Uint128::new(1000_000000),
+                },
+                slippage_tolerance: None,
+                receiver: None,
+            },
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::NonSupported {}
+    );
+
+    // Single-sided provision requires existing liquidity
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            pair_instance.clone(),
+            &offer(1000_000000),
+            &[coin(1000_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::SingleSidedProvideToEmptyPool {}
+    );
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1000000_000000),
+        Uint128::new(1000000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    // A large deposit moves the price too much compared to a balanced provision
+    let err = app
+        .execute_contract(
+            user1.clone(),
+            pair_instance.clone(),
+            &offer(440_000_000000),
+            &[coin(440_000_000000, "uusd")],
+        )
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::ProvideSlippageViolation(..)
+    ));
+
+    app.execute_contract(
+        user1.clone(),
+        pair_instance.clone(),
+        &offer(1000_000000),
+        &[coin(1000_000000, "uusd")],
+    )
+    .unwrap();
+
+    // About half of the deposit is swapped, the share is slightly below a balanced provision
+    let lp_amount = app.wrap().query_balance(&user1, &lp_denom).unwrap().amount;
+    assert!(lp_amount > Uint128::new(499_870000) && lp_amount < Uint128::new(500_000000));
+
+    let res: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(res.assets[0].amount, Uint128::new(1001000_000000));
+    assert!(res.assets[1].amount < Uint128::new(1000000_000000));
+    assert_eq!(res.total_share, Uint128::new(1000000_000000) + lp_amount);
+}
+
+#[test]
+fn register_external_liquidity() {
+    let owner = Addr::unchecked("owner");
+    let user1 = Addr::unchecked("user1");
+    let external = Addr::unchecked("orderbook");
+
+    let mut app = mock_app(owner.clone(), vec![]);
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    let msg = ExecuteMsg::RegisterExternalLiquidity {
+        external_contract: external.to_string(),
+        amount: [Uint128::new(100), Uint128::new(200)],
+    };
+
+    let err = app
+        .execute_contract(user1.clone(), pair_instance.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
+        .unwrap();
+
+    let res: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(
+        res.external_liquidity,
+        vec![(
+            external.clone(),
+            [
+                native_asset_info("uusd".to_string()).with_balance(100u128),
+                native_asset_info("uluna".to_string()).with_balance(200u128),
+            ]
+        )]
+    );
+    // External liquidity is reported separately from the pool balances
+    assert!(res.assets.iter().all(|asset| asset.amount.is_zero()));
+
+    // Zero amounts remove the record
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &ExecuteMsg::RegisterExternalLiquidity {
+            external_contract: external.to_string(),
+            amount: [Uint128::zero(), Uint128::zero()],
+        },
+        &[],
+    )
+    .unwrap();
+
+    let res: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert!(res.external_liquidity.is_empty());
+}
+
+#[test]
+fn accumulated_protocol_fees() {
+    let owner = Addr::unchecked("owner");
+    let maker = Addr::unchecked("maker");
+
+    let mut app = mock_app(
+        owner.clone(),
+        vec![
+            coin(100_000_000_000000, "uusd"),
+            coin(100_000_000_000000, "uluna"),
+        ],
+    );
+    let pair_instance = instantiate_pair(&mut app, &owner);
+
+    let config: ConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Config {})
+        .unwrap();
+    let factory_config: FactoryConfigResponse = app
+        .wrap()
+        .query_wasm_smart(&config.factory_addr, &FactoryQueryMsg::Config {})
+        .unwrap();
+    app.execute_contract(
+        owner.clone(),
+        config.factory_addr.clone(),
+        &FactoryExecuteMsg::UpdatePairConfig {
+            config: PairConfig {
+                maker_fee_bps: 5000,
+                total_fee_bps: 30,
+                ..factory_config.pair_configs[0].clone()
+            },
+        },
+        &[],
+    )
+    .unwrap();
+
+    let (msg, coins) = provide_liquidity_msg(
+        Uint128::new(1_000_000000),
+        Uint128::new(1_000_000000),
+        None,
+        None,
+        None,
+    );
+    app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &coins)
+        .unwrap();
+
+    let swap_msg = ExecuteMsg::Swap {
+        offer_asset: native_asset_info("uusd".to_string()).with_balance(1_000000u128),
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        min_out: None,
+        to: None,
+        callback: None,
+    };
+
+    // Without a fee address the protocol fee stays in the pool and is not recorded
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &swap_msg,
+        &[coin(1_000000, "uusd")],
+    )
+    .unwrap();
+
+    let res: AccumulatedFeesResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::AccumulatedFees {})
+        .unwrap();
+    assert!(res.fees.iter().all(|asset| asset.amount.is_zero()));
+
+    app.execute_contract(
+        owner.clone(),
+        config.factory_addr.clone(),
+        &FactoryExecuteMsg::UpdateConfig {
+            token_code_id: None,
+            fee_address: Some(maker.to_string()),
+            generator_address: None,
+            whitelist_code_id: None,
+            coin_registry_address: None,
+        },
+        &[],
+    )
+    .unwrap();
+
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &swap_msg,
+        &[coin(1_000000, "uusd")],
+    )
+    .unwrap();
+
+    // The protocol fee is sent to the Maker and recorded in the ledger
+    let maker_fee = app.wrap().query_balance(&maker, "uluna").unwrap().amount;
+    assert!(!maker_fee.is_zero());
+
+    let res: AccumulatedFeesResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::AccumulatedFees {})
+        .unwrap();
+    assert_eq!(
+        res.fees,
+        vec![
+            native_asset_info("uusd".to_string()).with_balance(0u128),
+            native_asset_info("uluna".to_string()).with_balance(maker_fee),
+        ]
+    );
+
+    // Protocol fees don't leave the pool reserves
+    let pair_balance = app
+        .wrap()
+        .query_balance(&pair_instance, "uluna")
+        .unwrap()
+        .amount;
+    let res: PoolResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::Pool {})
+        .unwrap();
+    assert_eq!(res.assets[1].amount, pair_balance);
+
+    // The ledger keeps adding up the fees of further swaps
+    app.execute_contract(
+        owner.clone(),
+        pair_instance.clone(),
+        &swap_msg,
+        &[coin(1_000000, "uusd")],
+    )
+    .unwrap();
+
+    let res: AccumulatedFeesResponse = app
+        .wrap()
+        .query_wasm_smart(&pair_instance, &QueryMsg::AccumulatedFees {})
+        .unwrap();
+    let total_maker_fee = app.wrap().query_balance(&maker, "uluna").unwrap().amount;
+    assert_eq!(res.fees[1].amount, total_maker_fee);
 }
 
 #[test]
@@ -1667,6 +3737,7 @@ fn provide_liquidity_with_autostaking_to_generator() {
         auto_stake: Some(true),
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let coins = [
@@ -2063,6 +4134,7 @@ fn test_fee_share(
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -2087,6 +4159,7 @@ fn test_fee_share(
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: Some(user.to_string()),
         })
         .unwrap(),
@@ -1,4 +1,4 @@
-use cosmwasm_std::{Addr, StdError};
+use cosmwasm_std::{coin, Addr, StdError};
 use cw_multi_test::{App, Executor};
 
 use astroport::asset::{AssetInfo, AssetInfoExt, PairInfo};
@@ -172,6 +172,80 @@ fn test_outpost() {
     );
 }
 
+#[test]
+fn test_native_swap_funds_mismatch() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![
+        TestCoin::native("ibc/old_astro"),
+        TestCoin::native("tf_astro"),
+    ];
+    let mut helper = Helper::new(&owner, test_coins.clone()).unwrap();
+    helper.setup_converter_and_migrate(&test_coins[0], &test_coins[1]);
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000000u128);
+    helper.give_me_money(
+        &[helper.assets[&test_coins[0]].with_balance(3_000000u128)],
+        &user,
+    );
+
+    let swap_msg = pair::ExecuteMsg::Swap {
+        offer_asset: offer_asset.clone(),
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        to: None,
+    };
+
+    for sent_amount in [500000u128, 2_000000u128] {
+        let err = helper
+            .app
+            .execute_contract(
+                user.clone(),
+                helper.pair_addr.clone(),
+                &swap_msg,
+                &[coin(sent_amount, "ibc/old_astro")],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::Std(StdError::generic_err(
+                "Native token balance mismatch between the argument and the transferred"
+            ))
+        );
+    }
+
+    let err = helper
+        .app
+        .execute_contract(user.clone(), helper.pair_addr.clone(), &swap_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Std(StdError::generic_err("No funds sent"))
+    );
+
+    helper
+        .app
+        .execute_contract(
+            user.clone(),
+            helper.pair_addr.clone(),
+            &swap_msg,
+            &[coin(1_000000u128, "ibc/old_astro")],
+        )
+        .unwrap();
+    assert_eq!(
+        helper
+            .app
+            .wrap()
+            .query_balance(&user, "tf_astro")
+            .unwrap()
+            .amount
+            .u128(),
+        1_000000u128
+    );
+}
+
 #[test]
 fn test_queries() {
     let owner = Addr::unchecked("owner");
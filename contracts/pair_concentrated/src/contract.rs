@@ -3,14 +3,15 @@ use std::vec;
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, coin, ensure, ensure_eq, from_json, to_json_binary, wasm_execute, Addr, Binary, Coin,
-    CosmosMsg, Decimal, Decimal256, DepsMut, Empty, Env, MessageInfo, Reply, Response, StdError,
-    StdResult, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, coin, ensure, ensure_eq, from_json, to_json_binary, wasm_execute, Addr, BankMsg, Binary,
+    Coin, CosmosMsg, Decimal, Decimal256, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+    StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, Uint128, Uint256, WasmMsg,
 };
 use cw2::{get_contract_version, set_contract_version};
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{AllowanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
 use cw_utils::{
-    one_coin, parse_reply_instantiate_data, MsgInstantiateContractResponse, PaymentError,
+    nonpayable, one_coin, parse_reply_instantiate_data, MsgInstantiateContractResponse,
+    PaymentError,
 };
 use itertools::Itertools;
 
@@ -21,13 +22,15 @@ use astroport::asset::{
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner, LP_SUBDENOM};
 use astroport::cosmwasm_ext::{DecimalToInteger, IntegerToDecimal};
 use astroport::factory::PairType;
-use astroport::observation::{PrecommitObservation, OBSERVATIONS_SIZE};
+use astroport::observation::{try_dec256_into_dec, PrecommitObservation, OBSERVATIONS_SIZE};
+use astroport::oracle_interface::observation_history_msg;
 use astroport::pair::{
-    Cw20HookMsg, ExecuteMsg, FeeShareConfig, InstantiateMsg, ReplyIds, MAX_FEE_SHARE_BPS,
-    MIN_TRADE_SIZE,
+    assert_max_spread, assert_min_out, Cw20HookMsg, ExecuteMsg, FeeShareConfig,
+    FlashLoanCallbackMsg, InstantiateMsg, MigrateMsg, ReplyIds, MAX_FEE_SHARE_BPS,
+    MAX_FLASH_LOAN_FEE_BPS, MAX_VOLUME_LIMIT_DECAY_BLOCKS, MIN_TRADE_SIZE,
 };
 use astroport::pair_concentrated::{
-    ConcentratedPoolParams, ConcentratedPoolUpdateParams, UpdatePoolParams,
+    ConcentratedPoolParams, ConcentratedPoolUpdateParams, SwapRecord, UpdatePoolParams,
 };
 use astroport::querier::{
     query_factory_config, query_fee_info, query_native_supply, query_tracker_config,
@@ -37,19 +40,28 @@ use astroport::token_factory::{
 };
 use astroport::tokenfactory_tracker;
 use astroport_circular_buffer::BufferManager;
+use astroport_pair_common::state::CODE_VERSION;
+use astroport_pair_common::utils::{
+    accumulate_block_volume, assert_code_version, transfer_lp_and_stake,
+};
+use astroport_pcl_common::consts::DEFAULT_SLIPPAGE;
 use astroport_pcl_common::state::{
-    AmpGamma, Config, PoolParams, PoolState, Precisions, PriceState,
+    validate_volatility_fee, AmpGamma, Config, PoolParams, PoolState, Precisions, PriceState,
 };
 use astroport_pcl_common::utils::{
-    accumulate_prices, assert_max_spread, before_swap_check, calc_last_prices, check_asset_infos,
-    check_cw20_in_pool, compute_swap, get_share_in_assets, mint_liquidity_token_message,
+    accumulate_prices, before_swap_check, calc_last_prices, check_asset_infos, check_cw20_in_pool,
+    compute_swap_with_prices, get_share_in_assets, mint_liquidity_token_message,
 };
 use astroport_pcl_common::{calc_d, get_xcp};
 
 use crate::error::ContractError;
-use crate::state::{BALANCES, CONFIG, OBSERVATIONS, OWNERSHIP_PROPOSAL};
+use crate::state::{
+    FlashLoanState, ACCUMULATED_FEES, BALANCES, CONFIG, CUMULATIVE_VOLUMES, FLASH_LOAN,
+    OBSERVATIONS, ORACLE, OWNERSHIP_PROPOSAL,
+};
 use crate::utils::{
-    accumulate_swap_sizes, calculate_shares, get_assets_with_precision, query_pools,
+    accumulate_swap_sizes, balanced_provide_assets, calculate_shares, get_assets_with_precision,
+    pool_info, query_pools, record_swap, volatility_window_prices,
 };
 
 /// Contract name that is used for migration.
@@ -83,6 +95,7 @@ pub fn instantiate(
     }
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
 
     let factory_addr = deps.api.addr_validate(&msg.factory_addr)?;
 
@@ -143,6 +156,11 @@ pub fn instantiate(
         track_asset_balances: params.track_asset_balances.unwrap_or_default(),
         fee_share: None,
         tracker_addr: None,
+        volatility_fee: None,
+        max_swap_volume_per_block: None,
+        volume_limit_decay_blocks: 0,
+        hide_swap_traders: false,
+        flash_loan_fee: Decimal::zero(),
     };
 
     if config.track_asset_balances {
@@ -250,6 +268,68 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                 .add_message(set_hook_msg)
                 .add_attribute("tracker_contract", contract_address))
         }
+        ReplyIds::FlashLoan => {
+            // The receiver has to return the borrowed assets plus fees within its callback
+            let flash_loan = FLASH_LOAN.load(deps.storage)?;
+            FLASH_LOAN.remove(deps.storage);
+
+            let mut config = CONFIG.load(deps.storage)?;
+            let pools = config
+                .pair_info
+                .query_pools(&deps.querier, &env.contract.address)?;
+
+            for (pool, expected) in pools.iter().zip(flash_loan.expected_balances.iter()) {
+                if pool.amount < expected.amount {
+                    return Err(ContractError::FlashLoanNotRepaid {
+                        asset: pool.info.to_string(),
+                        expected: expected.amount,
+                        actual: pool.amount,
+                    });
+                }
+            }
+
+            // Rounding of the fees must not let the repaid pool end up with a lower invariant
+            let xs = query_internal_xs(deps.as_ref(), &env, &config)?;
+            let d = calc_d(&xs, &config.pool_state.get_amp_gamma(&env))?;
+            if d < flash_loan.d {
+                return Err(ContractError::FlashLoanInvariantDecreased {
+                    before: flash_loan.d,
+                    after: d,
+                });
+            }
+
+            // The fees stay in the pool, so the fee profit has to be recalculated
+            let total_share =
+                query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?
+                    .to_decimal256(LP_TOKEN_PRECISION)?;
+            let last_price = config.pool_state.price_state.last_price;
+            config.pool_state.update_price(
+                &config.pool_params,
+                &env,
+                total_share,
+                &xs,
+                last_price,
+            )?;
+            CONFIG.save(deps.storage, &config)?;
+
+            if config.track_asset_balances {
+                for pool in &pools {
+                    BALANCES.save(deps.storage, &pool.info, &pool.amount, env.block.height)?;
+                }
+            }
+
+            Ok(Response::new().add_attributes(vec![
+                attr("action", "flash_loan_repaid"),
+                attr(
+                    "assets",
+                    pools
+                        .iter()
+                        .map(|asset| asset.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            ]))
+        }
     }
 }
 
@@ -268,12 +348,35 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
 ///             receiver,
 ///         }** Provides liquidity in the pair with the specified input parameters.
 ///
+/// * **ExecuteMsg::ProvideLiquidityBalanced {
+///             offer,
+///             slippage_tolerance,
+///             receiver,
+///         }** Provides the offered asset together with the counterpart matching the pool ratio.
+///
 /// * **ExecuteMsg::Swap {
 ///             offer_asset,
 ///             belief_price,
 ///             max_spread,
+///             min_out,
 ///             to,
 ///         }** Performs a swap operation with the specified parameters.
+///
+/// * **ExecuteMsg::TransferLpAndStake {
+///             recipient,
+///             amount,
+///             generator,
+///         }** Stakes the attached LP tokens in the Incentives contract on behalf of the recipient.
+///
+/// * **ExecuteMsg::UpgradeOracle {
+///             new_oracle,
+///             carry_over_observations,
+///         }** Switches to a new oracle contract and optionally sends it the observation history.
+///
+/// * **ExecuteMsg::FlashLoan { assets, receiver, msg }** Lends pool assets to the receiver within
+/// a single transaction.
+///
+/// * **ExecuteMsg::SetFlashLoanFee { fee }** Sets the fee charged on flash loans.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -281,16 +384,30 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Reject messages if the stored code version differs from the compiled one
+    assert_code_version::<ContractError>(deps.storage, CONTRACT_VERSION)?;
+
+    // The pool reserves are incomplete until the ongoing flash loan is repaid
+    if FLASH_LOAN.exists(deps.storage) {
+        return Err(ContractError::FlashLoanInProgress {});
+    }
+
     let config = CONFIG.load(deps.storage)?;
 
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        // Liquidity requests are only supported by the constant product pair
+        ExecuteMsg::ProvideLiquidity {
+            request_id: Some(_),
+            ..
+        } => Err(ContractError::NonSupported {}),
         ExecuteMsg::ProvideLiquidity {
             assets,
             slippage_tolerance,
             auto_stake,
             receiver,
             min_lp_to_receive,
+            ..
         } => provide_liquidity(
             deps,
             env,
@@ -300,14 +417,26 @@ pub fn execute(
             auto_stake,
             receiver,
             min_lp_to_receive,
+            vec![],
         ),
+        ExecuteMsg::ProvideLiquidityBalanced {
+            offer,
+            slippage_tolerance,
+            receiver,
+        } => provide_liquidity_balanced(deps, env, info, offer, slippage_tolerance, receiver),
         ExecuteMsg::Swap {
             offer_asset,
             belief_price,
             max_spread,
+            min_out,
             to,
+            callback,
             ..
         } => {
+            // Swap callbacks are only supported by the constant product pair
+            if callback.is_some() {
+                return Err(ContractError::NonSupported {});
+            }
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -327,6 +456,7 @@ pub fn execute(
                 offer_asset,
                 belief_price,
                 max_spread,
+                min_out,
                 to_addr,
             )
         }
@@ -334,7 +464,7 @@ pub fn execute(
         ExecuteMsg::ProposeNewOwner { owner, expires_in } => {
             let factory_config = query_factory_config(&deps.querier, config.factory_addr)?;
 
-            propose_new_owner(
+            Ok(propose_new_owner(
                 deps,
                 info,
                 env,
@@ -342,32 +472,86 @@ pub fn execute(
                 expires_in,
                 config.owner.unwrap_or(factory_config.owner),
                 OWNERSHIP_PROPOSAL,
-            )
-            .map_err(Into::into)
+            )?)
         }
         ExecuteMsg::DropOwnershipProposal {} => {
             let factory_config = query_factory_config(&deps.querier, config.factory_addr)?;
 
-            drop_ownership_proposal(
+            Ok(drop_ownership_proposal(
                 deps,
                 info,
                 config.owner.unwrap_or(factory_config.owner),
                 OWNERSHIP_PROPOSAL,
-            )
-            .map_err(Into::into)
+            )?)
         }
-        ExecuteMsg::ClaimOwnership {} => {
-            claim_ownership(deps, info, env, OWNERSHIP_PROPOSAL, |deps, new_owner| {
+        ExecuteMsg::ClaimOwnership {} => Ok(claim_ownership(
+            deps,
+            info,
+            env,
+            OWNERSHIP_PROPOSAL,
+            |deps, new_owner| {
                 CONFIG.update::<_, StdError>(deps.storage, |mut config| {
                     config.owner = Some(new_owner);
                     Ok(config)
                 })?;
 
                 Ok(())
-            })
-            .map_err(Into::into)
-        }
+            },
+        )?),
         ExecuteMsg::WithdrawLiquidity { assets, .. } => withdraw_liquidity(deps, env, info, assets),
+        ExecuteMsg::TransferLpAndStake {
+            recipient,
+            amount,
+            generator,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            transfer_lp_and_stake(
+                deps.api,
+                info,
+                &config.pair_info.liquidity_token,
+                recipient,
+                amount,
+                generator,
+            )
+        }
+        ExecuteMsg::UpgradeOracle {
+            new_oracle,
+            carry_over_observations,
+        } => upgrade_oracle(deps, info, new_oracle, carry_over_observations),
+        ExecuteMsg::FlashLoan {
+            assets,
+            receiver,
+            msg,
+        } => flash_loan(deps, env, info, assets, receiver, msg),
+        ExecuteMsg::SetFlashLoanFee { fee } => {
+            let mut config = config;
+            let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+            if info.sender != config.owner.unwrap_or(factory_config.owner) {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            validate_flash_loan_fee(fee)?;
+            config.flash_loan_fee = fee;
+            CONFIG.save(deps.storage, &config)?;
+
+            Ok(Response::new().add_attributes([
+                attr("action", "set_flash_loan_fee"),
+                attr("flash_loan_fee", fee.to_string()),
+            ]))
+        }
+        ExecuteMsg::DonateToPool { .. }
+        | ExecuteMsg::ReplaceAsset { .. }
+        | ExecuteMsg::FreezeConfig {}
+        | ExecuteMsg::Sweep { .. }
+        | ExecuteMsg::RequestLiquidityProvision { .. }
+        | ExecuteMsg::CancelLiquidityRequest { .. }
+        | ExecuteMsg::ClaimLiquidityRequest { .. }
+        | ExecuteMsg::SetSlippageTolerance { .. }
+        | ExecuteMsg::RegisterExternalLiquidity { .. }
+        | ExecuteMsg::Pause {}
+        | ExecuteMsg::Unpause {} => Err(ContractError::NonSupported {}),
+        // TODO: support single-sided provision by searching the swap amount via the simulation path
+        ExecuteMsg::ProvideLiquiditySingle { .. } => Err(ContractError::NonSupported {}),
     }
 }
 
@@ -384,6 +568,7 @@ fn receive_cw20(
         Cw20HookMsg::Swap {
             belief_price,
             max_spread,
+            min_out,
             to,
             ..
         } => {
@@ -400,9 +585,11 @@ fn receive_cw20(
                 token_asset(info.sender, cw20_msg.amount),
                 belief_price,
                 max_spread,
+                min_out,
                 to_addr,
             )
         }
+        Cw20HookMsg::StakeAndProvide { .. } => Err(ContractError::NonSupported {}),
     }
 }
 
@@ -419,6 +606,9 @@ fn receive_cw20(
 /// * **receiver** is an optional parameter which defines the receiver of the LP tokens.
 /// If no custom receiver is specified, the pair will mint LP tokens for the function caller.
 ///
+/// * **refund** native coins which are attached to the message on top of the deposits.
+/// They are excluded from the pool reserves and sent back to the caller.
+///
 /// NOTE - the address that wants to provide liquidity should approve the pair contract to pull its relevant tokens.
 #[allow(clippy::too_many_arguments)]
 pub fn provide_liquidity(
@@ -430,6 +620,7 @@ pub fn provide_liquidity(
     auto_stake: Option<bool>,
     receiver: Option<String>,
     min_lp_to_receive: Option<Uint128>,
+    refund: Vec<Coin>,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
 
@@ -439,6 +630,18 @@ pub fn provide_liquidity(
     let precisions = Precisions::new(deps.storage)?;
 
     let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    for refund_coin in &refund {
+        if let Some(pool) = pools
+            .iter_mut()
+            .find(|pool| pool.info == AssetInfo::native(&refund_coin.denom))
+        {
+            pool.amount = pool.amount.checked_sub(
+                refund_coin
+                    .amount
+                    .to_decimal256(precisions.get_precision(&pool.info)?)?,
+            )?;
+        }
+    }
 
     let old_real_price = config.pool_state.price_state.last_price;
 
@@ -544,9 +747,172 @@ pub fn provide_liquidity(
         attr("slippage", slippage.to_string()),
     ];
 
+    if !refund.is_empty() {
+        messages.push(
+            BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: refund,
+            }
+            .into(),
+        );
+    }
+
     Ok(Response::new().add_messages(messages).add_attributes(attrs))
 }
 
+/// Provides `offer` together with the amount of the other pool asset which matches
+/// the current pool ratio.
+///
+/// * **offer** the asset to provide.
+///
+/// * **slippage_tolerance** is an optional parameter which is used to specify how much
+/// the pool price can move until the provide liquidity transaction goes through.
+///
+/// * **receiver** is an optional parameter which defines the receiver of the LP tokens.
+/// If no custom receiver is specified, the pair will mint LP tokens for the function caller.
+///
+/// NOTE - a CW20 counterpart is pulled using the caller's allowance while a native counterpart
+/// must be attached to the message. The attached surplus is refunded.
+fn provide_liquidity_balanced(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    offer: Asset,
+    slippage_tolerance: Option<Decimal>,
+    receiver: Option<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let attached = |denom: &str| {
+        info.funds
+            .iter()
+            .find(|coin| coin.denom == denom)
+            .map(|coin| coin.amount)
+            .unwrap_or_default()
+    };
+
+    // Attached native coins are already part of the pool balances
+    let (mut pools, _) = pool_info(deps.querier, &config)?;
+    for pool in pools.iter_mut() {
+        if let AssetInfo::NativeToken { denom } = &pool.info {
+            pool.amount = pool.amount.checked_sub(attached(denom))?;
+        }
+    }
+
+    let [offer, counterpart] = balanced_provide_assets(&pools, &offer)?;
+
+    let available = match &counterpart.info {
+        AssetInfo::Token { contract_addr } => {
+            deps.querier
+                .query_wasm_smart::<AllowanceResponse>(
+                    contract_addr,
+                    &Cw20QueryMsg::Allowance {
+                        owner: info.sender.to_string(),
+                        spender: env.contract.address.to_string(),
+                    },
+                )?
+                .allowance
+        }
+        AssetInfo::NativeToken { denom } => attached(denom),
+    };
+    ensure!(
+        counterpart.amount <= available,
+        ContractError::InsufficientCounterpart {
+            asset: counterpart.info.to_string(),
+            required: counterpart.amount,
+            available,
+        }
+    );
+
+    // The offered coins must match exactly while the counterpart may come with a surplus
+    info.funds.assert_coins_properly_sent(
+        &[offer.clone(), counterpart.info.with_balance(available)],
+        &config.pair_info.asset_infos,
+    )?;
+
+    let mut funds = vec![];
+    let mut refund = vec![];
+    for asset in [&offer, &counterpart] {
+        if let AssetInfo::NativeToken { denom } = &asset.info {
+            if !asset.amount.is_zero() {
+                funds.push(coin(asset.amount.u128(), denom));
+            }
+            let surplus = attached(denom) - asset.amount;
+            if !surplus.is_zero() {
+                refund.push(coin(surplus.u128(), denom));
+            }
+        }
+    }
+
+    provide_liquidity(
+        deps,
+        env,
+        MessageInfo {
+            sender: info.sender,
+            funds,
+        },
+        vec![offer, counterpart],
+        slippage_tolerance,
+        None,
+        receiver,
+        None,
+        refund,
+    )
+}
+
+/// Switches the pair to a new oracle contract. Only the contract owner can execute this.
+/// If **carry_over_observations** is set, all stored observations are sent to the new oracle
+/// sorted from the oldest to the newest one so TWAP history is not lost.
+///
+/// * **new_oracle** new oracle contract address.
+///
+/// * **carry_over_observations** whether to send the observation history to the new oracle.
+fn upgrade_oracle(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_oracle: String,
+    carry_over_observations: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    let owner = config.owner.as_ref().unwrap_or(&factory_config.owner);
+    if info.sender != *owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let new_oracle = deps.api.addr_validate(&new_oracle)?;
+    ORACLE.save(deps.storage, &new_oracle)?;
+
+    let mut response = Response::new();
+    let mut observations_count = 0;
+
+    if carry_over_observations {
+        let buffer = BufferManager::new(deps.storage, OBSERVATIONS)?;
+        let head = buffer.head();
+
+        // The oldest observation is stored right at the head if the buffer has already wrapped around
+        let mut observations = if buffer.exists(deps.storage, head) {
+            buffer.read(deps.storage, head..buffer.capacity(), true)?
+        } else {
+            vec![]
+        };
+        observations.extend(buffer.read(deps.storage, 0..head, true)?);
+        observations_count = observations.len();
+
+        response = response.add_submessage(SubMsg::new(observation_history_msg(
+            &new_oracle,
+            observations,
+        )?));
+    }
+
+    Ok(response.add_attributes([
+        attr("action", "upgrade_oracle"),
+        attr("new_oracle", new_oracle),
+        attr("observations_count", observations_count.to_string()),
+    ]))
+}
+
 /// Withdraw liquidity from the pool.
 ///
 /// * **sender** address that will receive assets back from the pair contract
@@ -603,9 +969,10 @@ fn withdraw_liquidity(
         .map(|asset| {
             let prec = precisions.get_precision(&asset.info).unwrap();
 
+            // Refunds are rounded down so the pool never pays out more than the burnt LP share
             Ok(Asset {
+                amount: asset.to_uint_floor(prec)?,
                 info: asset.info,
-                amount: asset.amount.to_uint(prec)?,
             })
         })
         .collect::<StdResult<Vec<_>>>()?;
@@ -657,7 +1024,10 @@ fn withdraw_liquidity(
 ///
 /// * **max_spread** sets the maximum spread of the swap operation.
 ///
+/// * **min_out** sets the minimum amount of ask assets to receive.
+///
 /// * **to** sets the recipient of the swap operation.
+#[allow(clippy::too_many_arguments)]
 fn swap(
     deps: DepsMut,
     env: Env,
@@ -665,6 +1035,7 @@ fn swap(
     offer_asset: Asset,
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
+    min_out: Option<Uint128>,
     to: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let precisions = Precisions::new(deps.storage)?;
@@ -704,7 +1075,8 @@ fn swap(
         share_fee_share = Decimal256::from_ratio(fee_share.bps, 10000u16);
     }
 
-    let swap_result = compute_swap(
+    let prices = volatility_window_prices(deps.storage, &config)?;
+    let swap_result = compute_swap_with_prices(
         &xs,
         offer_asset_dec.amount,
         ask_ind,
@@ -712,19 +1084,35 @@ fn swap(
         &env,
         maker_fee_share,
         share_fee_share,
+        &prices,
     )?;
     xs[offer_ind] += offer_asset_dec.amount;
     xs[ask_ind] -= swap_result.dy + swap_result.maker_fee + swap_result.share_fee;
 
     let return_amount = swap_result.dy.to_uint(ask_asset_prec)?;
     let spread_amount = swap_result.spread_fee.to_uint(ask_asset_prec)?;
+    // PCL pools fall back to a wider default slippage than the other pair types
+    let max_spread = match max_spread {
+        Some(max_spread) => max_spread,
+        None => try_dec256_into_dec(DEFAULT_SLIPPAGE)?,
+    };
     assert_max_spread(
         belief_price,
-        max_spread,
+        Some(max_spread),
         offer_asset.amount,
         return_amount,
         spread_amount,
     )?;
+    assert_min_out(min_out, return_amount)?;
+
+    accumulate_block_volume::<ContractError>(
+        deps.storage,
+        env.block.height,
+        config.max_swap_volume_per_block,
+        config.volume_limit_decay_blocks,
+        &offer_asset.info,
+        offer_asset.amount,
+    )?;
 
     let total_share = query_native_supply(&deps.querier, &config.pair_info.liquidity_token)?
         .to_decimal256(LP_TOKEN_PRECISION)?;
@@ -766,6 +1154,11 @@ fn swap(
     if let Some(fee_address) = fee_info.fee_address {
         maker_fee = swap_result.maker_fee.to_uint(ask_asset_prec)?;
         if !maker_fee.is_zero() {
+            ACCUMULATED_FEES.update(
+                deps.storage,
+                &pools[ask_ind].info,
+                |fees| -> StdResult<_> { Ok(fees.unwrap_or_default().checked_add(maker_fee)?) },
+            )?;
             let fee = pools[ask_ind].info.with_balance(maker_fee);
             messages.push(fee.into_msg(fee_address)?);
         }
@@ -773,6 +1166,14 @@ fn swap(
 
     accumulate_prices(&env, &mut config, old_real_price);
 
+    // Accumulate swapped volumes for the assets in the pool
+    let mut volumes = CUMULATIVE_VOLUMES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    volumes[offer_ind] += Uint256::from(offer_asset.amount);
+    volumes[ask_ind] += Uint256::from(return_amount);
+    CUMULATIVE_VOLUMES.save(deps.storage, &volumes)?;
+
     // Store observation from precommit data
     accumulate_swap_sizes(deps.storage, &env)?;
 
@@ -790,6 +1191,21 @@ fn swap(
 
     CONFIG.save(deps.storage, &config)?;
 
+    let commission_amount = swap_result.total_fee.to_uint(ask_asset_prec)?;
+    record_swap(
+        deps.storage,
+        &SwapRecord {
+            ts: env.block.time.seconds(),
+            offer: offer_asset.clone(),
+            returned: pools[ask_ind].info.with_balance(return_amount),
+            spread: spread_amount,
+            fee: commission_amount,
+            trader: (!config.hide_swap_traders).then(|| sender.clone()),
+            height: env.block.height,
+            virtual_price: config.pool_state.price_state.xcp_profit_real,
+        },
+    )?;
+
     if config.track_asset_balances {
         BALANCES.save(
             deps.storage,
@@ -817,15 +1233,146 @@ fn swap(
         attr("offer_amount", offer_asset.amount),
         attr("return_amount", return_amount),
         attr("spread_amount", spread_amount),
-        attr(
-            "commission_amount",
-            swap_result.total_fee.to_uint(ask_asset_prec)?,
-        ),
+        attr("commission_amount", commission_amount),
         attr("maker_fee_amount", maker_fee),
         attr("fee_share_amount", fee_share_amount),
     ]))
 }
 
+/// Lends pool assets to `receiver` and calls it with [`FlashLoanCallbackMsg`].
+/// The reply to the callback checks that the pool holds the lent assets plus fees again and
+/// that the pool invariant did not decrease, otherwise the whole transaction is reverted.
+///
+/// * **assets** assets to lend. They must be pool assets.
+///
+/// * **receiver** contract receiving the assets and the callback.
+///
+/// * **msg** message passed to the receiver in the callback.
+fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<Asset>,
+    receiver: String,
+    msg: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+
+    let receiver = deps.api.addr_validate(&receiver)?;
+    let config = CONFIG.load(deps.storage)?;
+
+    if assets.is_empty() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let mut expected_balances = config
+        .pair_info
+        .query_pools(&deps.querier, &env.contract.address)?;
+    let mut fees = vec![];
+
+    for asset in &assets {
+        if asset.amount.is_zero() {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+
+        let pool = expected_balances
+            .iter_mut()
+            .find(|pool| pool.info.equal(&asset.info))
+            .ok_or_else(|| ContractError::InvalidAsset(asset.info.to_string()))?;
+
+        if asset.amount > pool.amount {
+            return Err(StdError::generic_err(format!(
+                "Not enough {} in the pool to lend {}",
+                asset.info, asset.amount
+            ))
+            .into());
+        }
+
+        // Fees are rounded up in favor of the pool
+        let fee = asset
+            .info
+            .with_balance(asset.amount.mul_ceil(config.flash_loan_fee));
+        pool.amount += fee.amount;
+        fees.push(fee);
+    }
+
+    let xs = query_internal_xs(deps.as_ref(), &env, &config)?;
+    let d = calc_d(&xs, &config.pool_state.get_amp_gamma(&env))?;
+
+    FLASH_LOAN.save(
+        deps.storage,
+        &FlashLoanState {
+            expected_balances,
+            d,
+        },
+    )?;
+
+    let mut messages = assets
+        .iter()
+        .map(|asset| asset.clone().into_msg(&receiver).map(SubMsg::new))
+        .collect::<StdResult<Vec<_>>>()?;
+    messages.push(SubMsg::reply_on_success(
+        FlashLoanCallbackMsg {
+            assets: assets.clone(),
+            fees: fees.clone(),
+            msg,
+        }
+        .into_cosmos_msg(&receiver)?,
+        ReplyIds::FlashLoan as u64,
+    ));
+
+    Ok(Response::new().add_submessages(messages).add_attributes([
+        attr("action", "flash_loan"),
+        attr("sender", info.sender),
+        attr("receiver", receiver),
+        attr(
+            "assets",
+            assets
+                .iter()
+                .map(|asset| asset.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+        attr(
+            "fees",
+            fees.iter()
+                .map(|asset| asset.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        ),
+    ]))
+}
+
+/// Returns the pool balances in the internal representation used by [`calc_d`].
+fn query_internal_xs(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+) -> Result<Vec<Decimal256>, ContractError> {
+    let precisions = Precisions::new(deps.storage)?;
+    let mut xs = query_pools(deps.querier, &env.contract.address, config, &precisions)?
+        .into_iter()
+        .map(|pool| pool.amount)
+        .collect_vec();
+    xs[1] *= config.pool_state.price_state.price_scale;
+
+    Ok(xs)
+}
+
+fn validate_flash_loan_fee(fee: Decimal) -> Result<(), ContractError> {
+    let max_fee = Decimal::from_ratio(MAX_FLASH_LOAN_FEE_BPS, 10000u16);
+    if fee > max_fee {
+        return Err(ContractError::InvalidFeeRate {
+            rate: fee,
+            field: "flash_loan_fee".to_string(),
+            min: Decimal::zero(),
+            max: max_fee,
+        });
+    }
+
+    Ok(())
+}
+
 /// Updates the pool configuration with the specified parameters in the `params` variable.
 ///
 /// * **params** new parameter values in [`Binary`] form.
@@ -871,7 +1418,12 @@ fn update_config(
 
             // Ensure the fee share isn't 0 and doesn't exceed the maximum allowed value
             if fee_share_bps == 0 || fee_share_bps > MAX_FEE_SHARE_BPS {
-                return Err(ContractError::FeeShareOutOfBounds {});
+                return Err(ContractError::InvalidFeeRate {
+                    rate: Decimal::from_ratio(fee_share_bps, 10000u16),
+                    field: "fee_share_bps".to_string(),
+                    min: Decimal::from_ratio(1u16, 10000u16),
+                    max: Decimal::from_ratio(MAX_FEE_SHARE_BPS, 10000u16),
+                });
             }
 
             // Set sharing config
@@ -894,6 +1446,70 @@ fn update_config(
                 .attributes
                 .push(attr("action", "disable_fee_share"));
         }
+        ConcentratedPoolUpdateParams::EnableVolatilityFee(volatility_fee) => {
+            validate_volatility_fee(&volatility_fee)?;
+
+            response.attributes.extend(vec![
+                attr("action", "enable_volatility_fee"),
+                attr("min_fee_bps", volatility_fee.min_fee_bps.to_string()),
+                attr("max_fee_bps", volatility_fee.max_fee_bps.to_string()),
+                attr(
+                    "volatility_window",
+                    volatility_fee.volatility_window.to_string(),
+                ),
+            ]);
+            config.volatility_fee = Some(volatility_fee);
+        }
+        ConcentratedPoolUpdateParams::DisableVolatilityFee => {
+            config.volatility_fee = None;
+            response
+                .attributes
+                .push(attr("action", "disable_volatility_fee"));
+        }
+        ConcentratedPoolUpdateParams::SetSwapVolumeLimit {
+            max_swap_volume_per_block,
+            volume_limit_decay_blocks,
+        } => {
+            ensure!(
+                volume_limit_decay_blocks <= MAX_VOLUME_LIMIT_DECAY_BLOCKS,
+                ContractError::VolumeLimitDecayBlocksTooLarge {
+                    max: MAX_VOLUME_LIMIT_DECAY_BLOCKS
+                }
+            );
+
+            config.max_swap_volume_per_block = max_swap_volume_per_block;
+            config.volume_limit_decay_blocks = volume_limit_decay_blocks;
+
+            response.attributes.extend(vec![
+                attr("action", "set_swap_volume_limit"),
+                attr(
+                    "max_swap_volume_per_block",
+                    max_swap_volume_per_block
+                        .map(|limit| limit.to_string())
+                        .unwrap_or_else(|| "none".to_string()),
+                ),
+                attr(
+                    "volume_limit_decay_blocks",
+                    volume_limit_decay_blocks.to_string(),
+                ),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::SetFlashLoanFee { fee } => {
+            validate_flash_loan_fee(fee)?;
+            config.flash_loan_fee = fee;
+
+            response.attributes.extend(vec![
+                attr("action", "set_flash_loan_fee"),
+                attr("flash_loan_fee", fee.to_string()),
+            ]);
+        }
+        ConcentratedPoolUpdateParams::SetHideSwapTraders { hide_swap_traders } => {
+            config.hide_swap_traders = hide_swap_traders;
+            response.attributes.extend(vec![
+                attr("action", "set_hide_swap_traders"),
+                attr("hide_swap_traders", hide_swap_traders.to_string()),
+            ]);
+        }
     };
     CONFIG.save(deps.storage, &config)?;
 
@@ -901,18 +1517,39 @@ fn update_config(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
     let contract_version = get_contract_version(deps.storage)?;
 
+    if let Some(from_version) = msg.from_version {
+        ensure_eq!(
+            from_version,
+            contract_version.version,
+            ContractError::IncompatibleMigrationSource {
+                expected: from_version,
+                actual: contract_version.version,
+            }
+        );
+    }
+
+    ensure_eq!(
+        msg.to_version,
+        CONTRACT_VERSION,
+        ContractError::IncompatibleMigrationTarget {
+            expected: msg.to_version,
+            actual: CONTRACT_VERSION.to_string(),
+        }
+    );
+
     match contract_version.contract.as_ref() {
         "astroport-pair-concentrated" => match contract_version.version.as_ref() {
-            "4.0.0" | "4.0.1" => {}
+            "4.0.0" | "4.0.1" | "4.0.2" => {}
             _ => return Err(ContractError::MigrationError {}),
         },
         _ => return Err(ContractError::MigrationError {}),
     }
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
 
     Ok(Response::new()
         .add_attribute("previous_contract_name", &contract_version.contract)
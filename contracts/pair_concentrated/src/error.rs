@@ -1,10 +1,14 @@
-use cosmwasm_std::{ConversionOverflowError, OverflowError, StdError, Uint128};
+use cosmwasm_std::{
+    ConversionOverflowError, Decimal, Decimal256, OverflowError, StdError, Uint128,
+};
 use thiserror::Error;
 
 use cw_utils::{ParseReplyError, PaymentError};
 
-use astroport::{asset::MINIMUM_LIQUIDITY_AMOUNT, pair::MAX_FEE_SHARE_BPS};
+use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
+use astroport::pair::{MinOutNotMet, SpreadAssertionError};
 use astroport_circular_buffer::error::BufferError;
+use astroport_pair_common::error::{BlockVolumeLimitExceeded, LpAmountMismatch, VersionMismatch};
 use astroport_pcl_common::error::PclError;
 
 /// This enum describes pair contract errors
@@ -34,9 +38,15 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Contract version mismatch: expected {expected}, got {got}")]
+    VersionMismatch { expected: String, got: String },
+
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
+    #[error("Operation non supported")]
+    NonSupported {},
+
     #[error("You need to provide init params")]
     InitParamsNotFound {},
 
@@ -61,12 +71,109 @@ pub enum ContractError {
     #[error("Contract can't be migrated!")]
     MigrationError {},
 
-    #[error(
-        "Fee share is 0 or exceeds maximum allowed value of {} bps",
-        MAX_FEE_SHARE_BPS
-    )]
-    FeeShareOutOfBounds {},
+    #[error("Can't migrate from version {actual}, expected {expected}")]
+    IncompatibleMigrationSource { expected: String, actual: String },
+
+    #[error("Can't migrate to version {expected}, the new code has version {actual}")]
+    IncompatibleMigrationTarget { expected: String, actual: String },
+
+    #[error("{field} of {rate} is out of bounds: must be between {min} and {max}")]
+    InvalidFeeRate {
+        rate: Decimal,
+        field: String,
+        min: Decimal,
+        max: Decimal,
+    },
 
     #[error("Slippage is more than expected: received {0}, expected {1} LP tokens")]
     ProvideSlippageViolation(Uint128, Uint128),
+
+    #[error("Swap returns {actual} which is less than the minimum output {expected}")]
+    MinOutNotMet { expected: Uint128, actual: Uint128 },
+
+    #[error("Balanced provision requires {required} of {asset} but only {available} is available")]
+    InsufficientCounterpart {
+        asset: String,
+        required: Uint128,
+        available: Uint128,
+    },
+
+    #[error("Sent {sent} LP tokens but expected {expected}")]
+    LpAmountMismatch { sent: Uint128, expected: Uint128 },
+
+    #[error("No observation found at or before {timestamp}")]
+    ObservationNotFound { timestamp: u64 },
+
+    #[error("Swap volume {volume} exceeds the per block limit of {limit}")]
+    BlockVolumeLimitExceeded { volume: Uint128, limit: Uint128 },
+
+    #[error("Volume limit decay blocks must not exceed {max}")]
+    VolumeLimitDecayBlocksTooLarge { max: u64 },
+
+    #[error("Operation is not allowed while a flash loan is in progress")]
+    FlashLoanInProgress {},
+
+    #[error("Flash loan is not repaid: expected {expected} {asset}, got {actual}")]
+    FlashLoanNotRepaid {
+        asset: String,
+        expected: Uint128,
+        actual: Uint128,
+    },
+
+    #[error("Flash loan decreased the pool invariant from {before} to {after}")]
+    FlashLoanInvariantDecreased {
+        before: Decimal256,
+        after: Decimal256,
+    },
+}
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) | ContractError::PclError(PclError::Std(err)) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
+
+impl From<LpAmountMismatch> for ContractError {
+    fn from(err: LpAmountMismatch) -> Self {
+        ContractError::LpAmountMismatch {
+            sent: err.sent,
+            expected: err.expected,
+        }
+    }
+}
+
+impl From<SpreadAssertionError> for ContractError {
+    fn from(err: SpreadAssertionError) -> Self {
+        PclError::from(err).into()
+    }
+}
+
+impl From<VersionMismatch> for ContractError {
+    fn from(err: VersionMismatch) -> Self {
+        ContractError::VersionMismatch {
+            expected: err.expected,
+            got: err.got,
+        }
+    }
+}
+
+impl From<MinOutNotMet> for ContractError {
+    fn from(err: MinOutNotMet) -> Self {
+        ContractError::MinOutNotMet {
+            expected: err.expected,
+            actual: err.actual,
+        }
+    }
+}
+
+impl From<BlockVolumeLimitExceeded> for ContractError {
+    fn from(err: BlockVolumeLimitExceeded) -> Self {
+        ContractError::BlockVolumeLimitExceeded {
+            volume: err.volume,
+            limit: err.limit,
+        }
+    }
 }
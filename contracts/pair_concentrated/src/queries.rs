@@ -1,30 +1,50 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    to_json_binary, Binary, Decimal, Decimal256, Deps, Env, StdError, StdResult, Uint128, Uint64,
+    to_json_binary, Binary, ConversionOverflowError, Decimal, Decimal256, Deps, Env, Int128,
+    StdError, StdResult, Uint128, Uint64,
 };
-use itertools::Itertools;
-
-use astroport::asset::{Asset, AssetInfo};
-use astroport::cosmwasm_ext::{DecimalToInteger, IntegerToDecimal};
-use astroport::observation::query_observation;
+use itertools::{Itertools, MinMaxResult};
+
+use astroport::asset::{Asset, AssetInfo, AssetInfoExt, Decimal256Ext};
+use astroport::cosmwasm_ext::{ConvertInto, DecimalToInteger, IntegerToDecimal};
+use astroport::gas_estimates::CONCENTRATED_SWAP;
+use astroport::math::{estimate_fee_income, SECONDS_IN_DAY};
+use astroport::observation::{
+    compute_median_price, query_geo_twap, query_observation, query_observation_at,
+    query_observation_range, query_observation_window, query_volume_window, try_dec256_into_dec,
+    ObservationResponse, OracleObservation,
+};
+use astroport::oracle_interface::OracleInterfaceQueryMsg;
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse,
+    calculate_price_impact, find_min_swap_amount, AccumulatedFeesResponse, ConcentrationParams,
+    ConfigResponse, CumulativePricesResponse, MinSwapAmountResponse, PoolResponse,
+    ReverseSimulationResponse, SimulationResponse,
+};
+use astroport::pair_concentrated::{
+    ConcentratedPoolConfig, LiquidityBandResponse, OptimalLiquidityResponse, OracleResponse,
+    PairStatsResponse, PoolStatisticsResponse, PriceDeviationResponse, QueryMsg, SwapRecord,
+    MAX_ORACLE_VOLATILITY, MAX_RECENT_SWAPS, MAX_SMA_DEVIATION, MIN_ORACLE_OBSERVATIONS,
 };
-use astroport::pair_concentrated::{ConcentratedPoolConfig, QueryMsg};
 use astroport::querier::{query_factory_config, query_fee_info, query_native_supply};
-use astroport_pcl_common::state::Precisions;
+use astroport_circular_buffer::BufferManager;
+use astroport_pcl_common::consts::TWO;
+use astroport_pcl_common::state::{Config, Precisions};
 use astroport_pcl_common::utils::{
-    accumulate_prices, before_swap_check, calc_last_prices, compute_offer_amount, compute_swap,
-    get_share_in_assets,
+    accumulate_prices, before_swap_check, calc_last_prices, compute_offer_amount_with_prices,
+    compute_swap_fee, compute_swap_with_prices, get_share_in_assets,
 };
-use astroport_pcl_common::{calc_d, get_xcp};
+use astroport_pcl_common::{calc_d, calc_y, get_xcp};
 
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
-use crate::state::{BALANCES, CONFIG, OBSERVATIONS};
-use crate::utils::{calculate_shares, get_assets_with_precision, pool_info, query_pools};
+use crate::state::{
+    ACCUMULATED_FEES, BALANCES, CONFIG, CUMULATIVE_VOLUMES, OBSERVATIONS, ORACLE, RECENT_SWAPS,
+};
+use crate::utils::{
+    balanced_provide_assets, calculate_shares, get_assets_with_precision, pool_info, query_pools,
+    volatility_window_prices,
+};
 
 /// Exposes all the queries available in the contract.
 ///
@@ -49,14 +69,44 @@ use crate::utils::{calculate_shares, get_assets_with_precision, pool_info, query
 ///
 /// * **QueryMsg::AssetBalanceAt { asset_info, block_height }** Returns the balance of the specified
 /// asset that was in the pool just preceding the moment of the specified block height creation.
+///
+/// * **QueryMsg::RecentSwaps { limit }** Returns the latest swaps in a vector of [`SwapRecord`] objects.
+///
+/// * **QueryMsg::ImpermanentGain { entry_lp_amount, entry_prices }** Returns the signed difference
+/// between the current value of an LP position and the value of holding its entry assets.
+///
+/// * **QueryMsg::ExternalOracle {}** Returns the external oracle contract address if it was set.
+///
+/// * **QueryMsg::Oracle {}** Returns the oracle configuration and quality metrics in an [`OracleResponse`] object.
+///
+/// * **QueryMsg::PoolStatistics { from_ts, to_ts }** Returns swap activity metrics aggregated over
+/// the recent swaps log in a [`PoolStatisticsResponse`] object.
+///
+/// * **QueryMsg::PairStats { from_height, to_height }** Returns price, volume and LP token value
+/// statistics over a block height range in a [`PairStatsResponse`] object.
+///
+/// * **QueryMsg::PriceDeviationFromSma { window_secs }** Returns the deviation of the latest observed
+/// price from its moving average in a [`PriceDeviationResponse`] object.
+///
+/// * **QueryMsg::LiquidityBand { coverage_pct }** Returns the narrowest price range holding the given
+/// share of the pool liquidity in a [`LiquidityBandResponse`] object.
+///
+/// * **QueryMsg::CurrentFee {}** Returns the fee rate which is currently charged on swaps.
+///
+/// * **QueryMsg::OptimalLiquidityAmounts { asset }** Returns a balanced provision of the given asset
+/// in an [`OptimalLiquidityResponse`] object.
+///
+/// * **QueryMsg::MinSwapAmount {}** Returns the minimum viable swap amount of each pool asset
+/// using a [`MinSwapAmountResponse`] object.
+///
+/// * **QueryMsg::AccumulatedFees {}** Returns the protocol fees sent to the Maker using an
+/// [`AccumulatedFeesResponse`] object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Pair {} => to_json_binary(&CONFIG.load(deps.storage)?.pair_info),
         QueryMsg::Pool {} => to_json_binary(&query_pool(deps)?),
-        QueryMsg::Share { amount } => to_json_binary(
-            &query_share(deps, amount).map_err(|err| StdError::generic_err(err.to_string()))?,
-        ),
+        QueryMsg::Share { amount } => to_json_binary(&query_share(deps, amount)?),
         QueryMsg::Simulation { offer_asset, .. } => to_json_binary(
             &query_simulation(deps, env, offer_asset)
                 .map_err(|err| StdError::generic_err(format!("{err}")))?,
@@ -70,8 +120,40 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 .map_err(|err| StdError::generic_err(format!("{err}")))?,
         ),
         QueryMsg::Observe { seconds_ago } => {
-            to_json_binary(&query_observation(deps, env, OBSERVATIONS, seconds_ago)?)
+            to_json_binary(&query_observation_with_fallback(deps, env, seconds_ago)?)
         }
+        QueryMsg::MedianPrice { window } => {
+            to_json_binary(&compute_median_price(deps.storage, OBSERVATIONS, window)?)
+        }
+        QueryMsg::VolumeWindow { window_seconds } => to_json_binary(&query_volume_window(
+            deps,
+            env,
+            OBSERVATIONS,
+            window_seconds,
+        )?),
+        QueryMsg::ObservationWindow { window_seconds } => to_json_binary(
+            &query_observation_window(deps, env, OBSERVATIONS, window_seconds)?,
+        ),
+        QueryMsg::ObservationRange {
+            start_ts,
+            end_ts,
+            start_after,
+        } => to_json_binary(&query_observation_range(
+            deps.storage,
+            OBSERVATIONS,
+            start_ts,
+            end_ts,
+            start_after,
+        )?),
+        QueryMsg::ObservationAt { timestamp } => to_json_binary(
+            &query_observation_at(deps, OBSERVATIONS, timestamp)?
+                .map(ObservationResponse::from)
+                .ok_or_else(|| {
+                    StdError::generic_err(
+                        ContractError::ObservationNotFound { timestamp }.to_string(),
+                    )
+                })?,
+        ),
         QueryMsg::Config {} => to_json_binary(&query_config(deps, env)?),
         QueryMsg::LpPrice {} => to_json_binary(&query_lp_price(deps, env)?),
         QueryMsg::ComputeD {} => to_json_binary(&query_compute_d(deps, env)?),
@@ -88,12 +170,63 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             assets,
             slippage_tolerance,
         )?),
-        QueryMsg::SimulateWithdraw { lp_amount } => to_json_binary(
-            &query_share(deps, lp_amount).map_err(|err| StdError::generic_err(err.to_string()))?,
+        QueryMsg::SimulateWithdraw { lp_amount } => to_json_binary(&query_share(deps, lp_amount)?),
+        QueryMsg::RecentSwaps { limit } => to_json_binary(&query_recent_swaps(deps, limit)?),
+        QueryMsg::ImpermanentGain {
+            entry_lp_amount,
+            entry_prices,
+        } => to_json_binary(&query_impermanent_gain(
+            deps,
+            env,
+            entry_lp_amount,
+            entry_prices,
+        )?),
+        QueryMsg::ExternalOracle {} => to_json_binary(&ORACLE.may_load(deps.storage)?),
+        QueryMsg::Oracle {} => to_json_binary(&query_oracle(deps, env)?),
+        QueryMsg::PoolStatistics { from_ts, to_ts } => {
+            to_json_binary(&query_pool_statistics(deps, from_ts, to_ts)?)
+        }
+        QueryMsg::PairStats {
+            from_height,
+            to_height,
+        } => to_json_binary(&query_pair_stats(deps, from_height, to_height)?),
+        QueryMsg::PriceDeviationFromSma { window_secs } => {
+            to_json_binary(&query_price_deviation_from_sma(deps, env, window_secs)?)
+        }
+        QueryMsg::LiquidityBand { coverage_pct } => {
+            to_json_binary(&query_liquidity_band(deps, env, coverage_pct)?)
+        }
+        QueryMsg::CurrentFee {} => to_json_binary(&query_current_fee(deps, env)?),
+        QueryMsg::OptimalLiquidityAmounts { asset } => {
+            to_json_binary(&query_optimal_liquidity_amounts(deps, env, asset)?)
+        }
+        QueryMsg::MinSwapAmount {} => to_json_binary(
+            &query_min_swap_amount(deps, env)
+                .map_err(|err| StdError::generic_err(format!("{err}")))?,
         ),
+        QueryMsg::AccumulatedFees {} => to_json_binary(&query_accumulated_fees(deps)?),
     }
 }
 
+/// Returns the protocol fees the pool sent to the Maker in an [`AccumulatedFeesResponse`] object.
+pub fn query_accumulated_fees(deps: Deps) -> StdResult<AccumulatedFeesResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let fees = config
+        .pair_info
+        .asset_infos
+        .into_iter()
+        .map(|asset_info| {
+            let amount = ACCUMULATED_FEES
+                .may_load(deps.storage, &asset_info)?
+                .unwrap_or_default();
+            Ok(asset_info.with_balance(amount))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(AccumulatedFeesResponse { fees })
+}
+
 /// Returns the amounts of assets in the pair contract as well as the amount of LP
 /// tokens currently minted in an object of type [`PoolResponse`].
 fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
@@ -103,6 +236,7 @@ fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
     let resp = PoolResponse {
         assets,
         total_share,
+        external_liquidity: vec![],
     };
 
     Ok(resp)
@@ -131,9 +265,10 @@ fn query_share(deps: Deps, amount: Uint128) -> Result<Vec<Asset>, ContractError>
         .map(|asset| {
             let prec = precisions.get_precision(&asset.info).unwrap();
 
+            // Refunds are rounded down so the pool never pays out more than the burnt LP share
             Ok(Asset {
+                amount: asset.to_uint_floor(prec)?,
                 info: asset.info,
-                amount: asset.amount.to_uint(prec)?,
             })
         })
         .collect::<StdResult<Vec<_>>>()?;
@@ -181,7 +316,8 @@ pub fn query_simulation(
         share_fee_share = Decimal256::from_ratio(fee_share.bps, 10000u16);
     }
 
-    let swap_result = compute_swap(
+    let prices = volatility_window_prices(deps.storage, &config)?;
+    let swap_result = compute_swap_with_prices(
         &xs,
         offer_asset_dec.amount,
         ask_ind,
@@ -189,15 +325,81 @@ pub fn query_simulation(
         &env,
         maker_fee_share,
         share_fee_share,
+        &prices,
     )?;
 
+    let spot_price = calc_spot_price(&xs, offer_ind, &config, &env);
+    let price_impact = calculate_price_impact(spot_price, offer_asset_dec.amount, swap_result.dy)?;
+
     Ok(SimulationResponse {
         return_amount: swap_result.dy.to_uint(ask_asset_prec)?,
         spread_amount: swap_result.spread_fee.to_uint(ask_asset_prec)?,
         commission_amount: swap_result.total_fee.to_uint(ask_asset_prec)?,
+        price_impact,
+        gas_estimate: CONCENTRATED_SWAP.for_offer(&offer_asset.info),
     })
 }
 
+/// Returns the minimum amount of each pool asset which can be swapped for a non-zero return
+/// in a [`MinSwapAmountResponse`] object. The amounts are found by simulating swaps.
+pub fn query_min_swap_amount(deps: Deps, env: Env) -> Result<MinSwapAmountResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+
+    let pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
+    let xs = pools.iter().map(|asset| asset.amount).collect_vec();
+
+    // Get fee info from the factory
+    let fee_info = query_fee_info(
+        &deps.querier,
+        &config.factory_addr,
+        config.pair_info.pair_type.clone(),
+    )?;
+    let mut maker_fee_share = Decimal256::zero();
+    if fee_info.fee_address.is_some() {
+        maker_fee_share = fee_info.maker_fee_rate.into();
+    }
+    // If this pool is configured to share fees
+    let mut share_fee_share = Decimal256::zero();
+    if let Some(fee_share) = config.fee_share.clone() {
+        share_fee_share = Decimal256::from_ratio(fee_share.bps, 10000u16);
+    }
+
+    let prices = volatility_window_prices(deps.storage, &config)?;
+
+    let min_amounts = pools
+        .iter()
+        .enumerate()
+        .map(|(offer_ind, offer_pool)| {
+            let ask_ind = 1 - offer_ind;
+            let offer_prec = precisions.get_precision(&offer_pool.info)?;
+            let ask_prec = precisions.get_precision(&pools[ask_ind].info)?;
+
+            let amount = find_min_swap_amount(offer_pool.amount.to_uint(offer_prec)?, |amount| {
+                let offer_amount = Decimal256::with_precision(amount, offer_prec)?;
+                // Amounts for which the invariant can't be solved are not viable
+                Ok(compute_swap_with_prices(
+                    &xs,
+                    offer_amount,
+                    ask_ind,
+                    &config,
+                    &env,
+                    maker_fee_share,
+                    share_fee_share,
+                    &prices,
+                )
+                .ok()
+                .and_then(|swap_result| swap_result.dy.to_uint(ask_prec).ok())
+                .is_some_and(|return_amount: Uint128| !return_amount.is_zero()))
+            })?;
+
+            Ok(offer_pool.info.with_balance(amount))
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    Ok(MinSwapAmountResponse { min_amounts })
+}
+
 /// Returns information about a reverse swap simulation.
 pub fn query_reverse_simulation(
     deps: Deps,
@@ -219,16 +421,39 @@ pub fn query_reverse_simulation(
     let offer_asset_prec = precisions.get_precision(&pools[offer_ind].info)?;
 
     let xs = pools.iter().map(|asset| asset.amount).collect_vec();
-    let (offer_amount, spread_amount, commission_amount) =
-        compute_offer_amount(&xs, ask_asset_dec.amount, ask_ind, &config, &env)?;
+    let prices = volatility_window_prices(deps.storage, &config)?;
+    let (offer_amount, spread_amount, commission_amount) = compute_offer_amount_with_prices(
+        &xs,
+        ask_asset_dec.amount,
+        ask_ind,
+        &config,
+        &env,
+        &prices,
+    )?;
+
+    let spot_price = calc_spot_price(&xs, offer_ind, &config, &env);
+    let price_impact = calculate_price_impact(spot_price, offer_amount, ask_asset_dec.amount)?;
 
     Ok(ReverseSimulationResponse {
         offer_amount: offer_amount.to_uint(offer_asset_prec)?,
         spread_amount: spread_amount.to_uint(offer_asset_prec)?,
         commission_amount: commission_amount.to_uint(offer_asset_prec)?,
+        price_impact,
     })
 }
 
+/// Returns the current price of the ask asset denominated in ask assets per one offer asset.
+/// Returns zero if the price can't be calculated, e.g. for an empty pool.
+fn calc_spot_price(xs: &[Decimal256], offer_ind: usize, config: &Config, env: &Env) -> Decimal256 {
+    // The last price is denominated in the first pool asset per one second pool asset
+    let last_price = calc_last_prices(xs, config, env).unwrap_or_default();
+    if offer_ind == 0 {
+        last_price.inv().unwrap_or_default()
+    } else {
+        last_price
+    }
+}
+
 /// Returns information about cumulative prices for the assets in the pool.
 fn query_cumulative_prices(
     deps: Deps,
@@ -245,10 +470,17 @@ fn query_cumulative_prices(
 
     let (assets, total_share) = pool_info(deps.querier, &config)?;
 
+    let [volume0_cumulative_last, volume1_cumulative_last] = CUMULATIVE_VOLUMES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
     Ok(CumulativePricesResponse {
         assets,
         total_share,
         cumulative_prices: config.cumulative_prices,
+        volume0_cumulative_last,
+        volume1_cumulative_last,
+        geo_twap: query_geo_twap(deps.storage, OBSERVATIONS)?,
     })
 }
 
@@ -259,8 +491,7 @@ pub fn query_lp_price(deps: Deps, env: Env) -> StdResult<Decimal256> {
         .to_decimal256(LP_TOKEN_PRECISION)?;
     if !total_lp.is_zero() {
         let precisions = Precisions::new(deps.storage)?;
-        let mut ixs = query_pools(deps.querier, &env.contract.address, &config, &precisions)
-            .map_err(|err| StdError::generic_err(err.to_string()))?
+        let mut ixs = query_pools(deps.querier, &env.contract.address, &config, &precisions)?
             .into_iter()
             .map(|asset| asset.amount)
             .collect_vec();
@@ -288,6 +519,23 @@ pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
 
     let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
 
+    // Observations store the traded volumes. The estimate is skipped if they don't cover the last day
+    // or the pool is empty
+    let estimated_annual_fee_rate =
+        query_volume_window(deps, env.clone(), OBSERVATIONS, SECONDS_IN_DAY)
+            .ok()
+            .and_then(|volume| {
+                let pool = query_pool(deps).ok()?;
+                let fee_rate = query_current_fee(deps, env).ok()?;
+                estimate_fee_income(
+                    &pool,
+                    &config.pair_info.asset_infos[1],
+                    fee_rate,
+                    volume.quote_volume,
+                )
+                .ok()
+            });
+
     Ok(ConfigResponse {
         block_time_last: config.block_time_last,
         params: Some(to_json_binary(&ConcentratedPoolConfig {
@@ -302,10 +550,24 @@ pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
             ma_half_time: config.pool_params.ma_half_time,
             track_asset_balances: config.track_asset_balances,
             fee_share: config.fee_share,
+            volatility_fee: config.volatility_fee,
+            max_swap_volume_per_block: config.max_swap_volume_per_block,
+            volume_limit_decay_blocks: config.volume_limit_decay_blocks,
+            hide_swap_traders: config.hide_swap_traders,
+            flash_loan_fee: config.flash_loan_fee,
         })?),
         owner: config.owner.unwrap_or(factory_config.owner),
         factory_addr: config.factory_addr,
         tracker_addr: config.tracker_addr,
+        liquidity_concentration: Some(ConcentrationParams {
+            amp: amp_gamma.amp,
+            gamma: amp_gamma.gamma,
+            fee_gamma: config.pool_params.fee_gamma,
+            mid_fee: config.pool_params.mid_fee,
+            out_fee: config.pool_params.out_fee,
+        }),
+        is_paused: false,
+        estimated_annual_fee_rate,
     })
 }
 
@@ -314,8 +576,7 @@ pub fn query_compute_d(deps: Deps, env: Env) -> StdResult<Decimal256> {
     let config = CONFIG.load(deps.storage)?;
     let precisions = Precisions::new(deps.storage)?;
 
-    let mut xs = query_pools(deps.querier, &env.contract.address, &config, &precisions)
-        .map_err(|e| StdError::generic_err(e.to_string()))?
+    let mut xs = query_pools(deps.querier, &env.contract.address, &config, &precisions)?
         .into_iter()
         .map(|a| a.amount)
         .collect_vec();
@@ -354,12 +615,10 @@ pub fn query_simulate_provide(
 
     let precisions = Precisions::new(deps.storage)?;
 
-    let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)
-        .map_err(|e| StdError::generic_err(e.to_string()))?;
+    let mut pools = query_pools(deps.querier, &env.contract.address, &config, &precisions)?;
 
     let deposits =
-        get_assets_with_precision(deps, &config, &mut assets, pools.clone(), &precisions)
-            .map_err(|e| StdError::generic_err(e.to_string()))?;
+        get_assets_with_precision(deps, &config, &mut assets, pools.clone(), &precisions)?;
 
     let (share_uint128, _) = calculate_shares(
         &env,
@@ -368,12 +627,438 @@ pub fn query_simulate_provide(
         total_share,
         deposits.clone(),
         slippage_tolerance,
-    )
-    .map_err(|e| StdError::generic_err(e.to_string()))?;
+    )?;
 
     Ok(share_uint128)
 }
 
+/// Returns up to `limit` latest swaps starting from the most recent one.
+pub fn query_recent_swaps(deps: Deps, limit: u32) -> StdResult<Vec<SwapRecord>> {
+    let limit = limit.min(MAX_RECENT_SWAPS) as usize;
+
+    RECENT_SWAPS.iter(deps.storage)?.rev().take(limit).collect()
+}
+
+/// Returns the difference between the current value of an LP position and the value of holding
+/// the assets deposited at entry, in units of the first pool asset.
+///
+/// Positions are valued through the LP virtual price: an LP token backs `2 * sqrt(price_scale)`
+/// units of the first asset per unit of virtual price, split equally in value between both assets.
+///
+/// * **entry_lp_amount** amount of LP tokens in the position.
+///
+/// * **entry_prices** LP token virtual price and price scale at the moment of entry.
+pub fn query_impermanent_gain(
+    deps: Deps,
+    env: Env,
+    entry_lp_amount: Uint128,
+    entry_prices: [Decimal; 2],
+) -> StdResult<Int128> {
+    let [entry_lp_price, entry_price_scale] = entry_prices.map(Decimal256::from);
+    if entry_price_scale.is_zero() {
+        return Err(StdError::generic_err("Entry price scale can't be zero"));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let precision =
+        Precisions::new(deps.storage)?.get_precision(&config.pair_info.asset_infos[0])?;
+
+    let lp_amount = entry_lp_amount.to_decimal256(LP_TOKEN_PRECISION)?;
+    let lp_price = query_lp_price(deps, env)?;
+    let price_scale = config.pool_state.price_state.price_scale;
+
+    let lp_value = lp_amount * lp_price * TWO * price_scale.sqrt();
+    // Entry assets were worth the same value on each side at the entry price scale
+    let entry_sqrt_price = entry_price_scale.sqrt();
+    let hodl_value =
+        lp_amount * entry_lp_price * (entry_sqrt_price + price_scale / entry_sqrt_price);
+
+    let to_signed = |value: Decimal256| -> StdResult<i128> {
+        let value: Uint128 = value.to_uint(precision)?;
+        i128::try_from(value.u128())
+            .map_err(|_| ConversionOverflowError::new("Uint128", "i128", value.to_string()).into())
+    };
+
+    if lp_value >= hodl_value {
+        Ok(Int128::new(to_signed(lp_value - hodl_value)?))
+    } else {
+        Ok(Int128::new(-to_signed(hodl_value - lp_value)?))
+    }
+}
+
+/// Returns the price observed `seconds_ago` seconds before the current block.
+/// Observations which are not available in the pair buffer (e.g. the history carried over by
+/// [`ExecuteMsg::UpgradeOracle`](astroport::pair::ExecuteMsg::UpgradeOracle)) are queried
+/// from the external oracle if one is set.
+pub fn query_observation_with_fallback(
+    deps: Deps,
+    env: Env,
+    seconds_ago: u64,
+) -> StdResult<OracleObservation> {
+    query_observation(deps, env, OBSERVATIONS, seconds_ago).or_else(|err| {
+        match ORACLE.may_load(deps.storage)? {
+            Some(oracle) => deps
+                .querier
+                .query_wasm_smart(oracle, &OracleInterfaceQueryMsg::Observe { seconds_ago }),
+            None => Err(err),
+        }
+    })
+}
+
+/// Returns the oracle configuration and quality metrics based on the stored observations.
+pub fn query_oracle(deps: Deps, env: Env) -> StdResult<OracleResponse> {
+    let oracle_type = if ORACLE.may_load(deps.storage)?.is_some() {
+        "external"
+    } else {
+        "internal"
+    };
+
+    let buffer = BufferManager::new(deps.storage, OBSERVATIONS)?;
+    let observations = buffer.read_all(deps.storage)?;
+
+    let last_observation_age = buffer
+        .read_last(deps.storage)?
+        .map(|obs| env.block.time.seconds().saturating_sub(obs.ts))
+        .unwrap_or_default();
+
+    let price_volatility = match observations.iter().map(|obs| obs.price).minmax() {
+        MinMaxResult::MinMax(min, max) if !min.is_zero() => (max - min) / min,
+        _ => Decimal::zero(),
+    };
+
+    let observations_count = observations.len() as u32;
+
+    Ok(OracleResponse {
+        oracle_type: oracle_type.to_string(),
+        observations_count,
+        last_observation_age,
+        price_volatility,
+        is_manipulation_resistant: observations_count >= MIN_ORACLE_OBSERVATIONS
+            && price_volatility < MAX_ORACLE_VOLATILITY,
+    })
+}
+
+/// Compares the latest observed price with the simple moving average of the prices observed
+/// within the last `window_secs` seconds.
+pub fn query_price_deviation_from_sma(
+    deps: Deps,
+    env: Env,
+    window_secs: u64,
+) -> StdResult<PriceDeviationResponse> {
+    let buffer = BufferManager::new(deps.storage, OBSERVATIONS)?;
+    let spot_price = buffer
+        .read_last(deps.storage)?
+        .ok_or_else(|| StdError::generic_err("No observations found"))?
+        .price;
+
+    let from_ts = env.block.time.seconds().saturating_sub(window_secs);
+    let window_prices = buffer
+        .read_all(deps.storage)?
+        .into_iter()
+        .filter(|obs| obs.ts >= from_ts)
+        .map(|obs| obs.price)
+        .collect_vec();
+    if window_prices.is_empty() {
+        return Err(StdError::generic_err(format!(
+            "No observations within the last {window_secs} seconds"
+        )));
+    }
+
+    let sma_price = window_prices
+        .iter()
+        .fold(Decimal::zero(), |acc, price| acc + price)
+        / Decimal::from_ratio(window_prices.len() as u128, 1u8);
+    let deviation_pct = if sma_price.is_zero() {
+        Decimal::zero()
+    } else {
+        spot_price.abs_diff(sma_price) / sma_price
+    };
+
+    Ok(PriceDeviationResponse {
+        spot_price,
+        sma_price,
+        deviation_pct,
+        is_anomalous: deviation_pct > MAX_SMA_DEVIATION,
+    })
+}
+
+/// Returns the narrowest price range which contains `coverage_pct` of the pool liquidity.
+///
+/// * **coverage_pct** share of the pool liquidity inside the band. Must be between 0 and 1 exclusively.
+pub fn query_liquidity_band(
+    deps: Deps,
+    env: Env,
+    coverage_pct: Decimal,
+) -> StdResult<LiquidityBandResponse> {
+    if coverage_pct.is_zero() || coverage_pct >= Decimal::one() {
+        return Err(StdError::generic_err(
+            "Coverage must be greater than 0 and less than 1",
+        ));
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+
+    let xs = query_pools(deps.querier, &env.contract.address, &config, &precisions)?
+        .into_iter()
+        .map(|a| a.amount)
+        .collect_vec();
+
+    if xs[0].is_zero() || xs[1].is_zero() {
+        return Err(StdError::generic_err("Pools are empty"));
+    }
+
+    let (price_lower, price_upper, liquidity_in_band) =
+        compute_liquidity_band(&xs, coverage_pct.into(), &config, &env)?;
+
+    Ok(LiquidityBandResponse {
+        price_lower: try_dec256_into_dec(price_lower)?,
+        price_upper: try_dec256_into_dec(price_upper)?,
+        liquidity_in_band,
+    })
+}
+
+/// Number of ternary search iterations used to split the liquidity band between the pool sides
+const LIQUIDITY_BAND_ITERATIONS: u8 = 20;
+
+/// Calculates the liquidity band by taking `coverage` of the pool value out of the curve.
+/// The value is measured in the first pool asset at the price scale. Taking value out of the first
+/// side moves the price towards the lower bound, taking it out of the second side moves it towards
+/// the upper bound. The split between both sides is searched for the smallest bounds ratio.
+/// Returns the lower and upper price of the second pool asset together with the band value.
+pub fn compute_liquidity_band(
+    xs: &[Decimal256],
+    coverage: Decimal256,
+    config: &Config,
+    env: &Env,
+) -> StdResult<(Decimal256, Decimal256, Decimal256)> {
+    let price_scale = config.pool_state.price_state.price_scale;
+    let ixs = [xs[0], xs[1] * price_scale];
+    let amp_gamma = config.pool_state.get_amp_gamma(env);
+    let d = calc_d(&ixs, &amp_gamma)?;
+    let band_value = (ixs[0] + ixs[1]) * coverage;
+
+    // Price after `amount` of internal value is taken out of the pool side `ind`
+    let price_after = |ind: usize, amount: Decimal256| -> StdResult<Decimal256> {
+        let mut new_ixs = ixs;
+        new_ixs[ind] = new_ixs[ind].checked_sub(amount)?;
+        new_ixs[1 ^ ind] = calc_y(&new_ixs, d, &amp_gamma, 1 ^ ind)?;
+        calc_last_prices(&[new_ixs[0], new_ixs[1] / price_scale], config, env)
+    };
+    let bounds = |split: Decimal256| -> StdResult<(Decimal256, Decimal256)> {
+        Ok((
+            price_after(0, band_value * split)?,
+            price_after(1, band_value * (Decimal256::one() - split))?,
+        ))
+    };
+    // Splits which drain one of the sides are ranked last
+    let width = |split: Decimal256| -> Decimal256 {
+        bounds(split)
+            .ok()
+            .and_then(|(lower, upper)| upper.checked_div(lower).ok())
+            .unwrap_or(Decimal256::MAX)
+    };
+
+    // The first side can give away at most its whole value and the second side the rest of the band
+    let mut lo = Decimal256::one()
+        .checked_sub(ixs[1] / band_value)
+        .unwrap_or_default();
+    let mut hi = (ixs[0] / band_value).min(Decimal256::one());
+    for _ in 0..LIQUIDITY_BAND_ITERATIONS {
+        let third = (hi - lo) / Decimal256::from_ratio(3u8, 1u8);
+        let (left, right) = (lo + third, hi - third);
+        if width(left) <= width(right) {
+            hi = right;
+        } else {
+            lo = left;
+        }
+    }
+
+    let (price_lower, price_upper) = bounds((lo + hi) / TWO)?;
+
+    Ok((price_lower, price_upper, band_value))
+}
+
+/// Aggregates swap activity metrics over the recent swaps log within the inclusive
+/// `from_ts`..=`to_ts` time range. Only the latest [`MAX_RECENT_SWAPS`] swaps are taken into account.
+pub fn query_pool_statistics(
+    deps: Deps,
+    from_ts: u64,
+    to_ts: u64,
+) -> StdResult<PoolStatisticsResponse> {
+    if from_ts > to_ts {
+        return Err(StdError::generic_err(
+            "from_ts must be less than or equal to to_ts",
+        ));
+    }
+
+    let base_info = CONFIG.load(deps.storage)?.pair_info.asset_infos[0].clone();
+
+    let mut stats = PoolStatisticsResponse {
+        trade_count: 0,
+        total_volume_base: Uint128::zero(),
+        total_volume_quote: Uint128::zero(),
+        average_fee: Decimal::zero(),
+        max_single_trade_base: Uint128::zero(),
+        min_single_trade_base: Uint128::zero(),
+    };
+    let mut total_fee_rate = Decimal::zero();
+
+    for swap in RECENT_SWAPS.iter(deps.storage)? {
+        let swap = swap?;
+        if swap.ts < from_ts || swap.ts > to_ts {
+            continue;
+        }
+
+        let (base_amount, quote_amount) = if swap.offer.info == base_info {
+            (swap.offer.amount, swap.returned.amount)
+        } else {
+            (swap.returned.amount, swap.offer.amount)
+        };
+
+        stats.max_single_trade_base = stats.max_single_trade_base.max(base_amount);
+        stats.min_single_trade_base = if stats.trade_count == 0 {
+            base_amount
+        } else {
+            stats.min_single_trade_base.min(base_amount)
+        };
+        stats.trade_count += 1;
+        stats.total_volume_base += base_amount;
+        stats.total_volume_quote += quote_amount;
+        // The fee is charged in ask asset units on top of the returned amount
+        total_fee_rate += Decimal::checked_from_ratio(swap.fee, swap.returned.amount + swap.fee)
+            .unwrap_or_default();
+    }
+
+    if stats.trade_count > 0 {
+        stats.average_fee = total_fee_rate / Uint128::from(stats.trade_count);
+    }
+
+    Ok(stats)
+}
+
+/// Returns price, volume, fee and LP token value statistics for the swaps executed within the
+/// `from_height`..=`to_height` block range in a [`PairStatsResponse`] object.
+/// Only the latest [`MAX_RECENT_SWAPS`] swaps are taken into account.
+pub fn query_pair_stats(
+    deps: Deps,
+    from_height: u64,
+    to_height: u64,
+) -> StdResult<PairStatsResponse> {
+    if from_height > to_height {
+        return Err(StdError::generic_err(
+            "from_height must be less than or equal to to_height",
+        ));
+    }
+
+    let base_info = CONFIG.load(deps.storage)?.pair_info.asset_infos[0].clone();
+
+    let mut stats: Option<PairStatsResponse> = None;
+    let mut first_virtual_price = Decimal256::zero();
+    let mut last_virtual_price = Decimal256::zero();
+
+    for swap in RECENT_SWAPS.iter(deps.storage)? {
+        let swap = swap?;
+        if swap.height < from_height || swap.height > to_height {
+            continue;
+        }
+
+        let (base_amount, quote_amount, fee_in_base) = if swap.offer.info == base_info {
+            // The fee is charged in quote units, convert it using the execution price
+            let fee = swap
+                .fee
+                .checked_multiply_ratio(swap.offer.amount, swap.returned.amount)
+                .unwrap_or_default();
+            (swap.offer.amount, swap.returned.amount, fee)
+        } else {
+            (swap.returned.amount, swap.offer.amount, swap.fee)
+        };
+        let price = Decimal::checked_from_ratio(quote_amount, base_amount).unwrap_or_default();
+
+        match stats.as_mut() {
+            None => {
+                first_virtual_price = swap.virtual_price;
+                stats = Some(PairStatsResponse {
+                    open_price: price,
+                    close_price: price,
+                    high_price: price,
+                    low_price: price,
+                    total_volume: base_amount,
+                    fee_income: fee_in_base,
+                    lp_token_appreciation: Decimal::zero(),
+                });
+            }
+            Some(stats) => {
+                stats.close_price = price;
+                stats.high_price = stats.high_price.max(price);
+                stats.low_price = stats.low_price.min(price);
+                stats.total_volume += base_amount;
+                stats.fee_income += fee_in_base;
+            }
+        }
+        last_virtual_price = swap.virtual_price;
+    }
+
+    let mut stats = stats.ok_or_else(|| {
+        StdError::generic_err(format!(
+            "No swaps within block heights {from_height}..={to_height}"
+        ))
+    })?;
+
+    // Swaps recorded before the virtual price was tracked have zero virtual price
+    if !first_virtual_price.is_zero() && last_virtual_price > first_virtual_price {
+        stats.lp_token_appreciation =
+            (last_virtual_price / first_virtual_price - Decimal256::one()).conv()?;
+    }
+
+    Ok(stats)
+}
+
+/// Returns the fee rate charged on swaps at the current pool state. If the volatility fee is
+/// disabled, the rate depends on the pool balance and is returned for the current balances.
+pub fn query_current_fee(deps: Deps, env: Env) -> StdResult<Decimal> {
+    let config = CONFIG.load(deps.storage)?;
+    let precisions = Precisions::new(deps.storage)?;
+
+    let mut ixs = query_pools(deps.querier, &env.contract.address, &config, &precisions)?
+        .into_iter()
+        .map(|a| a.amount)
+        .collect_vec();
+
+    if ixs[0].is_zero() && ixs[1].is_zero() {
+        return Err(StdError::generic_err("Pools are empty"));
+    }
+    ixs[1] *= config.pool_state.price_state.price_scale;
+
+    let prices = volatility_window_prices(deps.storage, &config)?;
+
+    try_dec256_into_dec(compute_swap_fee(&config, &ixs, &prices)?)
+}
+
+/// Returns the amount of the other pool asset required to provide `asset` at the current pool
+/// ratio. The LP tokens are estimated the same way as in [`query_simulate_provide`],
+/// i.e. they account for the current LP virtual price.
+///
+/// * **asset** the asset to provide.
+pub fn query_optimal_liquidity_amounts(
+    deps: Deps,
+    env: Env,
+    asset: Asset,
+) -> StdResult<OptimalLiquidityResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let (pools, _) = pool_info(deps.querier, &config)?;
+
+    let [asset_a, asset_b] = balanced_provide_assets(&pools, &asset)?;
+    let lp_tokens_estimated =
+        query_simulate_provide(deps, env, vec![asset_a.clone(), asset_b.clone()], None)?;
+
+    Ok(OptimalLiquidityResponse {
+        asset_a,
+        asset_b,
+        lp_tokens_estimated,
+    })
+}
+
 #[cfg(test)]
 mod testing {
 
@@ -402,6 +1087,9 @@ mod testing {
             .map(|i| Observation {
                 ts: env.block.time.seconds() + i * 1000,
                 price_sma: Decimal::from_ratio(i, i * i),
+                price_geo_mean: Default::default(),
+                base_volume: Default::default(),
+                quote_volume: Default::default(),
                 price: Default::default(),
             })
             .collect_vec();
@@ -451,6 +1139,9 @@ mod testing {
                 ts: env.block.time.seconds() + i * 1000,
                 price: Default::default(),
                 price_sma: Decimal::from_ratio(i, i * i),
+                price_geo_mean: Default::default(),
+                base_volume: Default::default(),
+                quote_volume: Default::default(),
             })
             .collect_vec();
         buffer.push_many(&array);
@@ -493,6 +1184,9 @@ mod testing {
                 ts: ts + i as u64 * 1000,
                 price: Default::default(),
                 price_sma: Decimal::from_ratio(i * i, i),
+                price_geo_mean: Default::default(),
+                base_volume: Default::default(),
+                quote_volume: Default::default(),
             })
             .collect_vec();
 
@@ -1,9 +1,11 @@
-use cosmwasm_std::Uint128;
-use cw_storage_plus::{Item, SnapshotMap};
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, Decimal256, Uint128, Uint256};
+use cw_storage_plus::{Deque, Item, Map, SnapshotMap};
 
-use astroport::asset::AssetInfo;
+use astroport::asset::{Asset, AssetInfo};
 use astroport::common::OwnershipProposal;
 use astroport::observation::Observation;
+use astroport::pair_concentrated::SwapRecord;
 use astroport_circular_buffer::CircularBuffer;
 use astroport_pcl_common::state::Config;
 
@@ -17,6 +19,30 @@ pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_pro
 pub const OBSERVATIONS: CircularBuffer<Observation> =
     CircularBuffer::new("observations_state", "observations_buffer");
 
+/// Stores the oracle contract set with [`astroport::pair::ExecuteMsg::UpgradeOracle`]
+pub const ORACLE: Item<Addr> = Item::new("oracle");
+
+/// Stores the latest swaps. Bounded by [`astroport::pair_concentrated::MAX_RECENT_SWAPS`]
+pub const RECENT_SWAPS: Deque<SwapRecord> = Deque::new("recent_swaps");
+
+/// This structure describes an ongoing flash loan.
+#[cw_serde]
+pub struct FlashLoanState {
+    /// The pool balances expected once the loan is repaid (pre-loan balances plus fees)
+    pub expected_balances: Vec<Asset>,
+    /// The pool invariant D before the loan
+    pub d: Decimal256,
+}
+
+/// Stores the ongoing flash loan. The pool reserves are incomplete while it exists
+pub const FLASH_LOAN: Item<FlashLoanState> = Item::new("flash_loan");
+
+/// Protocol fees sent to the Maker by asset
+pub const ACCUMULATED_FEES: Map<&AssetInfo, Uint128> = Map::new("accumulated_fees");
+
+/// Total swapped volume of each pool asset
+pub const CUMULATIVE_VOLUMES: Item<[Uint256; 2]> = Item::new("cumulative_volumes");
+
 /// Stores asset balances to query them later at any block height
 pub const BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
     "balances",
@@ -7,10 +7,16 @@ use cosmwasm_std::{
     Addr, Decimal, Decimal256, Deps, Env, QuerierWrapper, StdError, StdResult, Storage, Uint128,
 };
 
-use astroport::asset::{Asset, Decimal256Ext, DecimalAsset, MINIMUM_LIQUIDITY_AMOUNT};
-use astroport::observation::{safe_sma_buffer_not_full, safe_sma_calculation};
+use astroport::asset::{
+    Asset, AssetInfoExt, Decimal256Ext, DecimalAsset, MINIMUM_LIQUIDITY_AMOUNT,
+};
+use astroport::observation::{
+    safe_geo_sma_buffer_not_full, safe_geo_sma_calculation, safe_sma_buffer_not_full,
+    safe_sma_calculation,
+};
 use astroport::observation::{Observation, PrecommitObservation};
 use astroport::pair::MIN_TRADE_SIZE;
+use astroport::pair_concentrated::{SwapRecord, MAX_RECENT_SWAPS};
 use astroport::querier::query_native_supply;
 use astroport_circular_buffer::error::BufferResult;
 use astroport_circular_buffer::BufferManager;
@@ -19,7 +25,7 @@ use itertools::Itertools;
 
 use crate::contract::LP_TOKEN_PRECISION;
 use crate::error::ContractError;
-use crate::state::OBSERVATIONS;
+use crate::state::{OBSERVATIONS, RECENT_SWAPS};
 
 /// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
 pub(crate) fn pool_info(
@@ -46,11 +52,7 @@ pub(crate) fn query_pools(
         .pair_info
         .query_pools(&querier, addr)?
         .into_iter()
-        .map(|asset| {
-            asset
-                .to_decimal_asset(precisions.get_precision(&asset.info)?)
-                .map_err(Into::into)
-        })
+        .map(|asset| Ok(asset.to_decimal_asset(precisions.get_precision(&asset.info)?)?))
         .collect()
 }
 
@@ -62,6 +64,11 @@ pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResu
         precommit_ts,
     }) = PrecommitObservation::may_load(storage)?
     {
+        // Dust swaps can round the quote amount down to zero. Such observations have no price
+        if quote_amount.is_zero() {
+            return Ok(());
+        }
+
         let mut buffer = BufferManager::new(storage, OBSERVATIONS)?;
         let observed_price = Decimal::from_ratio(base_amount, quote_amount);
 
@@ -69,6 +76,8 @@ pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResu
         if let Some(last_obs) = buffer.read_last(storage)? {
             // Skip saving observation if it has been already saved
             if last_obs.ts < precommit_ts {
+                let (base_volume, quote_volume) =
+                    last_obs.accumulate_volume(base_amount, quote_amount)?;
                 // Since this is circular buffer the next index contains the oldest value
                 let count = buffer.capacity();
                 if let Some(oldest_obs) = buffer.read_single(storage, buffer.head() + 1)? {
@@ -78,20 +87,34 @@ pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResu
                         count,
                         observed_price,
                     )?;
+                    let price_geo_mean = safe_geo_sma_calculation(
+                        last_obs.geo_mean(),
+                        oldest_obs.price,
+                        count,
+                        observed_price,
+                    )?;
                     new_observation = Observation {
                         ts: precommit_ts,
                         price: observed_price,
                         price_sma,
+                        price_geo_mean,
+                        base_volume,
+                        quote_volume,
                     };
                 } else {
                     // Buffer is not full yet
                     let count = buffer.head();
                     let price_sma =
                         safe_sma_buffer_not_full(last_obs.price_sma, count, observed_price)?;
+                    let price_geo_mean =
+                        safe_geo_sma_buffer_not_full(last_obs.geo_mean(), count, observed_price)?;
                     new_observation = Observation {
                         ts: precommit_ts,
                         price: observed_price,
                         price_sma,
+                        price_geo_mean,
+                        base_volume,
+                        quote_volume,
                     };
                 }
 
@@ -104,6 +127,9 @@ pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResu
                     ts: precommit_ts,
                     price: observed_price,
                     price_sma: observed_price,
+                    price_geo_mean: observed_price,
+                    base_volume: base_amount,
+                    quote_volume: quote_amount,
                 };
 
                 buffer.instant_push(storage, &new_observation)?
@@ -114,6 +140,63 @@ pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResu
     Ok(())
 }
 
+/// Returns the latest observed prices used to estimate the volatility for the swap fee.
+/// The vector is empty if the volatility fee is disabled.
+pub(crate) fn volatility_window_prices(
+    storage: &dyn Storage,
+    config: &Config,
+) -> StdResult<Vec<Decimal>> {
+    let volatility_window = match &config.volatility_fee {
+        Some(volatility_fee) => volatility_fee.volatility_window as u32,
+        None => return Ok(vec![]),
+    };
+
+    let buffer = BufferManager::new(storage, OBSERVATIONS)?;
+    let capacity = buffer.capacity();
+    let prices = (1..=volatility_window.min(capacity))
+        .map(|i| buffer.read_single(storage, (buffer.head() + capacity - i) % capacity))
+        .map_while(|res| res.transpose())
+        .map(|res| res.map(|obs| obs.price))
+        .collect::<BufferResult<Vec<_>>>()?;
+
+    Ok(prices)
+}
+
+/// Appends a swap to the recent swaps log. The oldest record is dropped once the log is full.
+pub(crate) fn record_swap(storage: &mut dyn Storage, record: &SwapRecord) -> StdResult<()> {
+    RECENT_SWAPS.push_back(storage, record)?;
+    if RECENT_SWAPS.len(storage)? > MAX_RECENT_SWAPS {
+        RECENT_SWAPS.pop_front(storage)?;
+    }
+
+    Ok(())
+}
+
+/// Returns `asset` together with the amount of the other pool asset which matches
+/// the current pool ratio, in this order.
+///
+/// * **pools** pool reserves excluding the deposits of the current provision.
+pub(crate) fn balanced_provide_assets(
+    pools: &[Asset],
+    asset: &Asset,
+) -> Result<[Asset; 2], ContractError> {
+    let (offer_ind, ask_ind) = pools
+        .iter()
+        .position(|pool| pool.info.equal(&asset.info))
+        .map(|ind| (ind, 1 ^ ind))
+        .ok_or_else(|| ContractError::InvalidAsset(asset.info.to_string()))?;
+
+    if pools[offer_ind].amount.is_zero() || pools[ask_ind].amount.is_zero() {
+        return Err(StdError::generic_err("Pools are empty").into());
+    }
+
+    let counterpart = pools[ask_ind]
+        .amount
+        .multiply_ratio(asset.amount, pools[offer_ind].amount);
+
+    Ok([asset.clone(), pools[ask_ind].info.with_balance(counterpart)])
+}
+
 pub(crate) fn get_assets_with_precision(
     deps: Deps,
     config: &Config,
@@ -257,8 +340,15 @@ mod tests {
     use std::fmt::Display;
     use std::str::FromStr;
 
-    use cosmwasm_std::testing::{mock_env, MockStorage};
+    use astroport::observation::{
+        compute_median_price, query_observation_range, query_observation_window,
+        query_volume_window, VolumeWindowResponse, MAX_OBSERVATION_RANGE_LIMIT,
+    };
+    use astroport::pair_concentrated::VolatilityFeeConfig;
+    use astroport_pcl_common::utils::volatility_fee_rate;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, MockStorage};
     use cosmwasm_std::{BlockInfo, Timestamp};
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -293,4 +383,358 @@ mod tests {
         assert_eq!(dec_to_f64(obs.price_sma), 2.0);
         assert_eq!(dec_to_f64(obs.price), 2.0);
     }
+
+    #[test]
+    fn test_zero_quote_observation_skipped() {
+        let mut store = MockStorage::new();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1);
+
+        BufferManager::init(&mut store, OBSERVATIONS, 10).unwrap();
+
+        PrecommitObservation::save(&mut store, &env, 1000u128.into(), 500u128.into()).unwrap();
+        env.block.time = env.block.time.plus_seconds(1);
+        accumulate_swap_sizes(&mut store, &env).unwrap();
+
+        // Precommit a dust swap which produced no quote amount
+        PrecommitObservation::save(&mut store, &env, 1000u128.into(), Uint128::zero()).unwrap();
+        env.block.time = env.block.time.plus_seconds(1);
+        accumulate_swap_sizes(&mut store, &env).unwrap();
+
+        let buffer = BufferManager::new(&store, OBSERVATIONS).unwrap();
+        assert_eq!(buffer.head(), 1);
+        let obs = buffer.read_last(&store).unwrap().unwrap();
+        assert_eq!(obs.ts, 1);
+        assert_eq!(dec_to_f64(obs.price), 2.0);
+        assert_eq!(dec_to_f64(obs.price_sma), 2.0);
+
+        // The next valid observation is accumulated as usual
+        PrecommitObservation::save(&mut store, &env, 1500u128.into(), 500u128.into()).unwrap();
+        env.block.time = env.block.time.plus_seconds(1);
+        accumulate_swap_sizes(&mut store, &env).unwrap();
+
+        let buffer = BufferManager::new(&store, OBSERVATIONS).unwrap();
+        assert_eq!(buffer.head(), 2);
+        let obs = buffer.read_last(&store).unwrap().unwrap();
+        assert_eq!(obs.ts, 3);
+        assert_eq!(dec_to_f64(obs.price), 3.0);
+        assert_eq!(dec_to_f64(obs.price_sma), 2.5);
+    }
+
+    #[test]
+    fn test_median_price_ignores_spike() {
+        let mut store = MockStorage::new();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1);
+
+        BufferManager::init(&mut store, OBSERVATIONS, 10).unwrap();
+
+        // Five regular swaps at price 2 followed by a single spike at price 100
+        for base_amount in [1000u128, 1000, 1000, 1000, 1000, 50000] {
+            PrecommitObservation::save(&mut store, &env, base_amount.into(), 500u128.into())
+                .unwrap();
+            env.block.time = env.block.time.plus_seconds(1);
+            accumulate_swap_sizes(&mut store, &env).unwrap();
+        }
+
+        let buffer = BufferManager::new(&store, OBSERVATIONS).unwrap();
+        let obs = buffer.read_last(&store).unwrap().unwrap();
+        assert_eq!(dec_to_f64(obs.price), 100.0);
+        assert!(dec_to_f64(obs.price_sma) > 18.0);
+
+        let median = compute_median_price(&store, OBSERVATIONS, 6).unwrap();
+        assert_eq!(dec_to_f64(median), 2.0);
+
+        // Window is capped by the number of stored observations
+        let median = compute_median_price(&store, OBSERVATIONS, 100).unwrap();
+        assert_eq!(dec_to_f64(median), 2.0);
+
+        // Even window averages the two middle prices
+        let median = compute_median_price(&store, OBSERVATIONS, 2).unwrap();
+        assert_eq!(dec_to_f64(median), 51.0);
+
+        let err = compute_median_price(&store, OBSERVATIONS, 0).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Median window must be greater than zero")
+        );
+    }
+
+    #[test]
+    fn test_volume_window() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1);
+
+        BufferManager::init(&mut deps.storage, OBSERVATIONS, 3).unwrap();
+
+        let res = query_volume_window(deps.as_ref(), env.clone(), OBSERVATIONS, 10).unwrap();
+        assert_eq!(res, VolumeWindowResponse::default());
+
+        // Observations at ts 1..=4 while the buffer keeps only the latest three
+        for base_amount in [1000u128, 2000, 3000, 4000] {
+            PrecommitObservation::save(&mut deps.storage, &env, base_amount.into(), 500u128.into())
+                .unwrap();
+            env.block.time = env.block.time.plus_seconds(1);
+            accumulate_swap_sizes(&mut deps.storage, &env).unwrap();
+        }
+
+        let buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+        let obs = buffer.read_last(&deps.storage).unwrap().unwrap();
+        assert_eq!(obs.base_volume.u128(), 10000);
+        assert_eq!(obs.quote_volume.u128(), 2000);
+
+        let res = query_volume_window(deps.as_ref(), env.clone(), OBSERVATIONS, 2).unwrap();
+        assert_eq!(res.base_volume.u128(), 4000);
+        assert_eq!(res.quote_volume.u128(), 500);
+
+        let res = query_volume_window(deps.as_ref(), env.clone(), OBSERVATIONS, 3).unwrap();
+        assert_eq!(res.base_volume.u128(), 7000);
+        assert_eq!(res.quote_volume.u128(), 1000);
+
+        // The observation at ts 1 has already been overwritten
+        let err = query_volume_window(deps.as_ref(), env, OBSERVATIONS, 100).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Volume window exceeds stored observations")
+        );
+    }
+
+    #[test]
+    fn test_observation_window() {
+        let mut deps = mock_dependencies();
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(300);
+
+        let obs = |ts: u64, price: u64| Observation {
+            ts,
+            price: Decimal::from_ratio(price, 1u8),
+            price_sma: Default::default(),
+            price_geo_mean: Default::default(),
+            base_volume: Default::default(),
+            quote_volume: Default::default(),
+        };
+        let observations = [obs(100, 1), obs(200, 3)];
+
+        BufferManager::init(&mut deps.storage, OBSERVATIONS, 10).unwrap();
+        let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+        buffer.push_many(&observations);
+        buffer.commit(&mut deps.storage).unwrap();
+
+        // The latest price holds for the whole window
+        let twap = query_observation_window(deps.as_ref(), env.clone(), OBSERVATIONS, 50).unwrap();
+        assert_eq!(twap, Decimal::from_ratio(3u8, 1u8));
+
+        // Exactly covers both periods: (1 + 3) / 2 * 100 + 3 * 100 over 200 seconds
+        let twap = query_observation_window(deps.as_ref(), env.clone(), OBSERVATIONS, 200).unwrap();
+        assert_eq!(twap, Decimal::from_ratio(5u8, 2u8));
+
+        // The window starts at ts 150 where the interpolated price is 2
+        let twap = query_observation_window(deps.as_ref(), env.clone(), OBSERVATIONS, 150).unwrap();
+        assert_eq!(twap, Decimal::from_ratio(425u16, 150u8));
+
+        let err =
+            query_observation_window(deps.as_ref(), env.clone(), OBSERVATIONS, 250).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Observation window exceeds stored observations")
+        );
+
+        let err = query_observation_window(deps.as_ref(), env, OBSERVATIONS, 0).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Observation window must be greater than zero")
+        );
+    }
+
+    #[test]
+    fn test_observation_range() {
+        let mut store = MockStorage::new();
+
+        let observations = (1..=250u64)
+            .map(|ts| Observation {
+                ts,
+                price: Decimal::one(),
+                price_sma: Default::default(),
+                price_geo_mean: Default::default(),
+                base_volume: Default::default(),
+                quote_volume: Default::default(),
+            })
+            .collect_vec();
+
+        BufferManager::init(&mut store, OBSERVATIONS, 300).unwrap();
+        let mut buffer = BufferManager::new(&store, OBSERVATIONS).unwrap();
+        buffer.push_many(&observations);
+        buffer.commit(&mut store).unwrap();
+
+        let timestamps = |start_ts: u64, end_ts: u64, start_after: Option<u64>| {
+            query_observation_range(&store, OBSERVATIONS, start_ts, end_ts, start_after)
+                .unwrap()
+                .into_iter()
+                .map(|obs| obs.ts)
+                .collect_vec()
+        };
+
+        assert_eq!(timestamps(10, 15, None), vec![10, 11, 12, 13, 14, 15]);
+        assert_eq!(timestamps(251, 300, None), Vec::<u64>::new());
+        assert_eq!(timestamps(15, 10, None), Vec::<u64>::new());
+
+        // Large ranges are read in pages
+        let page = timestamps(1, 250, None);
+        assert_eq!(page.len(), MAX_OBSERVATION_RANGE_LIMIT);
+        assert_eq!(page.last(), Some(&200));
+        assert_eq!(timestamps(1, 250, Some(200)), (201..=250).collect_vec());
+    }
+
+    #[test]
+    fn test_volatility_fee_rate() {
+        let volatility_fee = VolatilityFeeConfig {
+            min_fee_bps: 10,
+            max_fee_bps: 50,
+            volatility_window: 10,
+        };
+        let bps = |value: u16| Decimal256::from_ratio(value, 10000u16);
+
+        // Not enough prices to estimate the volatility
+        let fee = volatility_fee_rate(&volatility_fee, &[Decimal::percent(150)]).unwrap();
+        assert_eq!(fee, bps(10));
+
+        let fee = volatility_fee_rate(&volatility_fee, &[Decimal::one(); 10]).unwrap();
+        assert_eq!(fee, bps(10));
+
+        // Prices deviate by 2.5% from the mean which is half of the saturation level
+        let prices = [Decimal::percent(975), Decimal::percent(1025)];
+        let fee = volatility_fee_rate(&volatility_fee, &prices).unwrap();
+        assert_eq!(fee, bps(30));
+
+        let prices = [Decimal::one(), Decimal::percent(200)];
+        let fee = volatility_fee_rate(&volatility_fee, &prices).unwrap();
+        assert_eq!(fee, bps(50));
+    }
+
+    proptest! {
+        #[test]
+        fn volatility_fee_within_bounds(
+            min_fee_bps in 1..=100u16,
+            fee_range_bps in 0..=100u16,
+            prices in prop::collection::vec(1..=u64::MAX as u128, 0..=u8::MAX as usize),
+        ) {
+            let max_fee_bps = (min_fee_bps + fee_range_bps).min(100);
+            let volatility_fee = VolatilityFeeConfig {
+                min_fee_bps,
+                max_fee_bps,
+                volatility_window: u8::MAX,
+            };
+            let prices = prices
+                .into_iter()
+                .map(|price| Decimal::from_ratio(price, 1_000000u128))
+                .collect_vec();
+
+            let fee = volatility_fee_rate(&volatility_fee, &prices).unwrap();
+            prop_assert!(fee >= Decimal256::from_ratio(min_fee_bps, 10000u16));
+            prop_assert!(fee <= Decimal256::from_ratio(max_fee_bps, 10000u16));
+        }
+
+        #[test]
+        fn accumulate_swap_sizes_fuzz(
+            capacity in 1..=20u32,
+            swaps in prop::collection::vec(
+                (1..=u64::MAX as u128, 1..=u64::MAX as u128, 0..3u64),
+                1..100,
+            ),
+        ) {
+            let mut store = MockStorage::new();
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(1);
+
+            BufferManager::init(&mut store, OBSERVATIONS, capacity).unwrap();
+
+            let mut min_price = Decimal::MAX;
+            let mut max_price = Decimal::zero();
+            let mut last_ts = None;
+            let mut observations_count = 0u128;
+
+            for (base_amount, quote_amount, blocks_skipped) in swaps {
+                accumulate_swap_sizes(&mut store, &env).unwrap();
+
+                let buffer = BufferManager::new(&store, OBSERVATIONS).unwrap();
+                if let Some(obs) = buffer.read_last(&store).unwrap() {
+                    if last_ts != Some(obs.ts) {
+                        min_price = min_price.min(obs.price);
+                        max_price = max_price.max(obs.price);
+                        last_ts = Some(obs.ts);
+                        observations_count += 1;
+                    }
+
+                    // The SMA is always an average of observed prices regardless of the buffer
+                    // capacity. Every update rounds it down by less than one atomic unit
+                    // and the rounding errors accumulate over the observations.
+                    let rounding_tolerance = Decimal::raw(observations_count);
+                    prop_assert!(obs.price_sma + rounding_tolerance >= min_price);
+                    prop_assert!(obs.price_sma <= max_price);
+                }
+
+                PrecommitObservation::save(
+                    &mut store,
+                    &env,
+                    base_amount.into(),
+                    quote_amount.into(),
+                )
+                .unwrap();
+
+                env.block.height += blocks_skipped;
+                env.block.time = env.block.time.plus_seconds(blocks_skipped);
+            }
+
+            // Read observations from the oldest to the newest one
+            let buffer = BufferManager::new(&store, OBSERVATIONS).unwrap();
+            let head = buffer.head();
+            let mut observations = if buffer.exists(&store, head) {
+                buffer.read(&store, head..buffer.capacity(), true).unwrap()
+            } else {
+                vec![]
+            };
+            observations.extend(buffer.read(&store, 0..head, true).unwrap());
+
+            prop_assert!(observations
+                .iter()
+                .tuple_windows()
+                .all(|(a, b)| a.ts < b.ts));
+        }
+
+        #[test]
+        fn geo_mean_bounded_by_arithmetic_mean(
+            capacity in 3..=20u32,
+            swaps in prop::collection::vec((1..=1_000_000u128, 1..=1_000_000u128), 1..100),
+        ) {
+            let mut store = MockStorage::new();
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(1);
+
+            BufferManager::init(&mut store, OBSERVATIONS, capacity).unwrap();
+
+            let mut min_price = f64::MAX;
+            for (base_amount, quote_amount) in swaps {
+                PrecommitObservation::save(
+                    &mut store,
+                    &env,
+                    base_amount.into(),
+                    quote_amount.into(),
+                )
+                .unwrap();
+                env.block.height += 1;
+                env.block.time = env.block.time.plus_seconds(1);
+                accumulate_swap_sizes(&mut store, &env).unwrap();
+
+                let buffer = BufferManager::new(&store, OBSERVATIONS).unwrap();
+                let obs = buffer.read_last(&store).unwrap().unwrap();
+                min_price = min_price.min(dec_to_f64(obs.price));
+
+                // AM-GM inequality with a tolerance for rounding errors
+                let geo_mean = dec_to_f64(obs.price_geo_mean);
+                prop_assert!(geo_mean <= dec_to_f64(obs.price_sma) * (1.0 + 1e-9));
+                prop_assert!(geo_mean >= min_price * (1.0 - 1e-9));
+            }
+        }
+    }
 }
@@ -332,6 +332,7 @@ impl Helper {
             auto_stake: Some(true),
             receiver: None,
             min_lp_to_receive: None,
+            request_id: None,
         };
 
         self.app
@@ -353,6 +354,7 @@ impl Helper {
             auto_stake: None,
             receiver: None,
             min_lp_to_receive: None,
+            request_id: None,
         };
 
         self.app
@@ -377,6 +379,7 @@ impl Helper {
             auto_stake,
             receiver,
             min_lp_to_receive,
+            request_id: None,
         };
 
         self.app
@@ -425,6 +428,7 @@ impl Helper {
                         ask_asset_info: None,
                         belief_price,
                         max_spread,
+                        min_out: None,
                         to: None,
                     })
                     .unwrap(),
@@ -446,7 +450,9 @@ impl Helper {
                     ask_asset_info: None,
                     belief_price,
                     max_spread,
+                    min_out: None,
                     to: None,
+                    callback: None,
                 };
 
                 self.app
@@ -2,28 +2,44 @@
 
 use std::str::FromStr;
 
-use cosmwasm_std::{Addr, Coin, Decimal, Decimal256, StdError, Uint128};
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{
+    coin, from_json, to_json_binary, Addr, BankMsg, Binary, Coin, Decimal, Decimal256, Deps,
+    DepsMut, Empty, Env, Int128, MessageInfo, Response, StdError, StdResult, Uint128,
+};
+use cw2::{get_contract_version, set_contract_version};
 use itertools::{max, Itertools};
 
 use astroport::asset::{
     native_asset_info, Asset, AssetInfo, AssetInfoExt, MINIMUM_LIQUIDITY_AMOUNT,
 };
 use astroport::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
+use astroport::factory::{Config as FactoryConfig, PairType};
 use astroport::observation::OracleObservation;
-use astroport::pair::{ExecuteMsg, PoolResponse, MAX_FEE_SHARE_BPS};
+use astroport::oracle_interface::{OracleInterfaceExecuteMsg, OracleInterfaceQueryMsg};
+use astroport::pair::{
+    AccumulatedFeesResponse, ConfigResponse, ExecuteMsg, FlashLoanCallbackExecuteMsg, MigrateMsg,
+    MinSwapAmountResponse, PoolResponse, MAX_FEE_SHARE_BPS, MAX_FLASH_LOAN_FEE_BPS,
+    MAX_VOLUME_LIMIT_DECAY_BLOCKS,
+};
 use astroport::pair_concentrated::{
-    ConcentratedPoolParams, ConcentratedPoolUpdateParams, PromoteParams, QueryMsg, UpdatePoolParams,
+    ConcentratedPoolParams, ConcentratedPoolUpdateParams, LiquidityBandResponse,
+    OptimalLiquidityResponse, OracleResponse, PairStatsResponse, PoolStatisticsResponse,
+    PriceDeviationResponse, PromoteParams, QueryMsg, SwapRecord, UpdatePoolParams,
+    VolatilityFeeConfig, MAX_ORACLE_VOLATILITY, MAX_RECENT_SWAPS, MAX_SMA_DEVIATION,
+    MIN_ORACLE_OBSERVATIONS,
 };
 use astroport::tokenfactory_tracker::{
     ConfigResponse as TrackerConfigResponse, QueryMsg as TrackerQueryMsg,
 };
+use astroport_pair_concentrated::contract::migrate;
 use astroport_pair_concentrated::error::ContractError;
 use astroport_pcl_common::consts::{AMP_MAX, AMP_MIN, MA_HALF_TIME_LIMITS};
 use astroport_pcl_common::error::PclError;
 
 use astroport_test::coins::TestCoin;
 use astroport_test::convert::{dec_to_f64, f64_to_dec};
-use astroport_test::cw_multi_test::{Executor, TOKEN_FACTORY_MODULE};
+use astroport_test::cw_multi_test::{ContractWrapper, Executor, TOKEN_FACTORY_MODULE};
 
 use crate::helper::{common_pcl_params, AppExtension, Helper};
 
@@ -85,6 +101,45 @@ fn check_observe_queries() {
     );
 }
 
+#[test]
+fn check_estimated_annual_fee_rate() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let query_fee_rate = |helper: &Helper| {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart::<ConfigResponse>(&helper.pair_addr, &QueryMsg::Config {})
+            .unwrap()
+            .estimated_annual_fee_rate
+    };
+
+    // The estimate is not available for an empty pool
+    assert_eq!(query_fee_rate(&helper), None);
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+    assert_eq!(query_fee_rate(&helper), Some(Decimal::zero()));
+
+    let user = Addr::unchecked("user");
+    for i in 0..3 {
+        let offer_asset = helper.assets[&test_coins[i % 2]].with_balance(100_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+        helper.app.next_block(1000);
+    }
+
+    let fee_rate = query_fee_rate(&helper).unwrap();
+    assert!(!fee_rate.is_zero());
+}
+
 #[test]
 fn check_wrong_initialization() {
     let owner = Addr::unchecked("owner");
@@ -678,7 +733,9 @@ fn check_swaps_simple() {
                 ask_asset_info: None,
                 belief_price: None,
                 max_spread: None,
+                min_out: None,
                 to: None,
+                callback: None,
             },
             &[],
         )
@@ -882,6 +939,16 @@ fn check_amp_gamma_change() {
     assert_eq!(dec_to_f64(amp_gamma.gamma), 0.000095);
     assert_eq!(amp_gamma.future_time, future_time);
 
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
+        .unwrap();
+    let concentration = config.liquidity_concentration.unwrap();
+    assert_eq!(dec_to_f64(concentration.amp), 42f64);
+    assert_eq!(dec_to_f64(concentration.gamma), 0.000095);
+    assert_eq!(dec_to_f64(concentration.mid_fee), 0.002);
+
     helper.app.next_block(50_000);
 
     let amp_gamma = helper.query_amp_gamma().unwrap();
@@ -1546,7 +1613,12 @@ fn check_correct_fee_share() {
     let err = helper.update_config(&owner, &action).unwrap_err();
     assert_eq!(
         err.downcast::<ContractError>().unwrap(),
-        ContractError::FeeShareOutOfBounds {}
+        ContractError::InvalidFeeRate {
+            rate: Decimal::from_ratio(MAX_FEE_SHARE_BPS + 1, 10000u16),
+            field: "fee_share_bps".to_string(),
+            min: Decimal::from_ratio(1u16, 10000u16),
+            max: Decimal::from_ratio(MAX_FEE_SHARE_BPS, 10000u16),
+        }
     );
 
     // Attempt setting fee share with max+1 fee share
@@ -1557,7 +1629,12 @@ fn check_correct_fee_share() {
     let err = helper.update_config(&owner, &action).unwrap_err();
     assert_eq!(
         err.downcast::<ContractError>().unwrap(),
-        ContractError::FeeShareOutOfBounds {}
+        ContractError::InvalidFeeRate {
+            rate: Decimal::from_ratio(0u16, 10000u16),
+            field: "fee_share_bps".to_string(),
+            min: Decimal::from_ratio(1u16, 10000u16),
+            max: Decimal::from_ratio(MAX_FEE_SHARE_BPS, 10000u16),
+        }
     );
 
     helper.app.next_block(1000);
@@ -1913,6 +1990,7 @@ fn test_provide_liquidity_without_funds() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let err = helper
@@ -2057,3 +2135,1324 @@ fn test_tracker_contract() {
 
     assert_eq!(alice_share[0].amount, alice_hist_bal);
 }
+
+#[test]
+fn check_recent_swaps() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    for i in 0..(MAX_RECENT_SWAPS + 5) {
+        let offer_asset = helper.assets[&test_coins[(i % 2) as usize]].with_balance(1_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+        helper.app.next_block(10);
+    }
+
+    let swaps: Vec<SwapRecord> = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::RecentSwaps { limit: 2 })
+        .unwrap();
+    assert_eq!(swaps.len(), 2);
+    // The most recent swap goes first
+    assert!(swaps[0].ts > swaps[1].ts);
+    assert_eq!(swaps[0].offer.info, helper.assets[&test_coins[0]]);
+    assert_eq!(swaps[0].returned.info, helper.assets[&test_coins[1]]);
+    assert_eq!(swaps[0].trader, Some(user.clone()));
+    assert!(!swaps[0].fee.is_zero());
+
+    // The log is bounded
+    let swaps: Vec<SwapRecord> = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::RecentSwaps { limit: 100 })
+        .unwrap();
+    assert_eq!(swaps.len(), MAX_RECENT_SWAPS as usize);
+
+    // Traders are not recorded once hidden
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetHideSwapTraders {
+                hide_swap_traders: true,
+            },
+        )
+        .unwrap();
+    assert!(helper.query_config().unwrap().hide_swap_traders);
+
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let swaps: Vec<SwapRecord> = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::RecentSwaps { limit: 1 })
+        .unwrap();
+    assert_eq!(swaps[0].trader, None);
+}
+
+#[test]
+fn upgrade_oracle_with_observations() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    for i in 0..3 {
+        let offer_asset = helper.assets[&test_coins[i % 2]].with_balance(1_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+        helper.app.next_block(1000);
+    }
+
+    let oracle_code_id = helper
+        .app
+        .store_code(Box::new(ContractWrapper::new_with_empty(
+            |_: DepsMut,
+             _: Env,
+             _: MessageInfo,
+             msg: OracleInterfaceExecuteMsg|
+             -> StdResult<Response> {
+                let OracleInterfaceExecuteMsg::ReceiveObservationHistory { observations } = msg;
+                assert!(observations
+                    .iter()
+                    .tuple_windows()
+                    .all(|(a, b)| a.ts < b.ts));
+
+                Ok(Response::new().add_attribute("received", observations.len().to_string()))
+            },
+            |_: DepsMut, _: Env, _: MessageInfo, _: Empty| -> StdResult<Response> {
+                Ok(Response::new())
+            },
+            |_: Deps, env: Env, msg: OracleInterfaceQueryMsg| -> StdResult<Binary> {
+                let OracleInterfaceQueryMsg::Observe { seconds_ago } = msg;
+                to_json_binary(&OracleObservation {
+                    timestamp: env.block.time.seconds() - seconds_ago,
+                    price: Decimal::percent(50),
+                })
+            },
+        )));
+    let new_oracle = helper
+        .app
+        .instantiate_contract(
+            oracle_code_id,
+            owner.clone(),
+            &Empty {},
+            &[],
+            "oracle",
+            None,
+        )
+        .unwrap();
+
+    let query_external_oracle = |helper: &Helper| -> Option<Addr> {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(&helper.pair_addr, &QueryMsg::ExternalOracle {})
+            .unwrap()
+    };
+    assert_eq!(query_external_oracle(&helper), None);
+
+    let msg = ExecuteMsg::UpgradeOracle {
+        new_oracle: new_oracle.to_string(),
+        carry_over_observations: true,
+    };
+
+    let err = helper
+        .app
+        .execute_contract(user.clone(), helper.pair_addr.clone(), &msg, &[])
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    let resp = helper
+        .app
+        .execute_contract(owner.clone(), helper.pair_addr.clone(), &msg, &[])
+        .unwrap();
+    let received = resp
+        .events
+        .iter()
+        .flat_map(|event| &event.attributes)
+        .find(|attr| attr.key == "received")
+        .unwrap();
+    // The last swap is still in precommit state
+    assert_eq!(received.value, "2");
+    assert_eq!(query_external_oracle(&helper), Some(new_oracle.clone()));
+
+    // Observations older than the pair buffer are served by the external oracle
+    let observe = |helper: &Helper, seconds_ago: u64| -> OracleObservation {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(&helper.pair_addr, &QueryMsg::Observe { seconds_ago })
+            .unwrap()
+    };
+    assert_ne!(observe(&helper, 0).price, Decimal::percent(50));
+    assert_eq!(observe(&helper, 100_000).price, Decimal::percent(50));
+
+    // Observations are not sent when they are not carried over
+    let resp = helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::UpgradeOracle {
+                new_oracle: new_oracle.to_string(),
+                carry_over_observations: false,
+            },
+            &[],
+        )
+        .unwrap();
+    assert!(!resp
+        .events
+        .iter()
+        .flat_map(|event| &event.attributes)
+        .any(|attr| attr.key == "received"));
+}
+
+#[test]
+fn check_migrate_versions() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins, common_pcl_params()).unwrap();
+
+    let new_code_id = helper.app.store_code(Box::new(
+        ContractWrapper::new_with_empty(
+            astroport_pair_concentrated::contract::execute,
+            astroport_pair_concentrated::contract::instantiate,
+            astroport_pair_concentrated::queries::query,
+        )
+        .with_reply_empty(astroport_pair_concentrated::contract::reply)
+        .with_migrate_empty(astroport_pair_concentrated::contract::migrate),
+    ));
+
+    let err = helper
+        .app
+        .migrate_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &MigrateMsg {
+                from_version: Some("1.0.0".to_string()),
+                to_version: env!("CARGO_PKG_VERSION").to_string(),
+            },
+            new_code_id,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IncompatibleMigrationSource {
+            expected: "1.0.0".to_string(),
+            actual: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        err.downcast().unwrap()
+    );
+
+    let err = helper
+        .app
+        .migrate_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &MigrateMsg {
+                from_version: None,
+                to_version: "1.0.0".to_string(),
+            },
+            new_code_id,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::IncompatibleMigrationTarget {
+            expected: "1.0.0".to_string(),
+            actual: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn check_migrate_from_previous_version() {
+    let mut deps = mock_dependencies();
+    set_contract_version(
+        deps.as_mut().storage,
+        "astroport-pair-concentrated",
+        "4.0.2",
+    )
+    .unwrap();
+
+    // The target version is required while the source version is optional
+    from_json::<MigrateMsg>(b"{}").unwrap_err();
+    let msg: MigrateMsg = from_json(br#"{"to_version": "4.1.0"}"#).unwrap();
+    assert_eq!(
+        msg,
+        MigrateMsg {
+            from_version: None,
+            to_version: "4.1.0".to_string(),
+        }
+    );
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            from_version: Some("4.0.2".to_string()),
+            to_version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+    )
+    .unwrap();
+
+    let version = get_contract_version(deps.as_ref().storage).unwrap();
+    assert_eq!(version.contract, "astroport-pair-concentrated");
+    assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+}
+
+#[test]
+fn check_impermanent_gain() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let lp_amount = Uint128::from(helper.native_balance(&helper.lp_token, &owner));
+    let price_scale = helper
+        .query_config()
+        .unwrap()
+        .pool_state
+        .price_state
+        .price_scale;
+    let entry_prices = [
+        Decimal::try_from(helper.query_lp_price().unwrap()).unwrap(),
+        Decimal::try_from(price_scale).unwrap(),
+    ];
+    let query_gain = |helper: &Helper, entry_prices: [Decimal; 2]| {
+        helper.app.wrap().query_wasm_smart::<Int128>(
+            &helper.pair_addr,
+            &QueryMsg::ImpermanentGain {
+                entry_lp_amount: lp_amount,
+                entry_prices,
+            },
+        )
+    };
+
+    // Nothing happened since entry
+    assert_eq!(query_gain(&helper, entry_prices).unwrap(), Int128::zero());
+
+    let err = query_gain(&helper, [entry_prices[0], Decimal::zero()]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: Entry price scale can't be zero"
+    );
+
+    // Balanced swaps accrue fees while the price barely moves
+    let user = Addr::unchecked("user");
+    for i in 0..10 {
+        let offer_asset = helper.assets[&test_coins[i % 2]].with_balance(1_000_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+        helper.app.next_block(10);
+    }
+
+    let gain = query_gain(&helper, entry_prices).unwrap();
+    assert!(gain > Int128::zero(), "expected positive gain, got {gain}");
+}
+
+#[test]
+fn check_oracle_query() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let query_oracle = |helper: &Helper| -> OracleResponse {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(&helper.pair_addr, &QueryMsg::Oracle {})
+            .unwrap()
+    };
+
+    let oracle = query_oracle(&helper);
+    assert_eq!(oracle.oracle_type, "internal");
+    assert_eq!(oracle.observations_count, 0);
+    assert!(!oracle.is_manipulation_resistant);
+
+    let user = Addr::unchecked("user");
+    for i in 0..(MIN_ORACLE_OBSERVATIONS + 1) {
+        let offer_asset = helper.assets[&test_coins[(i % 2) as usize]].with_balance(1_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+        helper.app.next_block(10);
+    }
+
+    let oracle = query_oracle(&helper);
+    assert_eq!(oracle.observations_count, MIN_ORACLE_OBSERVATIONS);
+    assert!(oracle.last_observation_age > 0);
+    assert!(oracle.price_volatility < MAX_ORACLE_VOLATILITY);
+    assert!(oracle.is_manipulation_resistant);
+}
+
+#[test]
+fn check_concentrated_default_params() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let params: ConcentratedPoolParams = from_json(
+        FactoryConfig::default_params(&PairType::Custom("concentrated".to_string())).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(params, common_pcl_params());
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    for coin in &test_coins {
+        let offer_asset = helper.assets[coin].with_balance(1_000_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+    }
+
+    let lp_amount = helper.native_balance(&helper.lp_token, &owner);
+    helper
+        .withdraw_liquidity(&owner, lp_amount, vec![])
+        .unwrap();
+}
+
+#[test]
+fn check_price_deviation_from_sma() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let query_deviation = |helper: &Helper, window_secs: u64| {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart::<PriceDeviationResponse>(
+                &helper.pair_addr,
+                &QueryMsg::PriceDeviationFromSma { window_secs },
+            )
+    };
+
+    let err = query_deviation(&helper, 1000).unwrap_err();
+    assert!(err.to_string().contains("No observations found"));
+
+    let user = Addr::unchecked("user");
+    for i in 0..11 {
+        let offer_asset = helper.assets[&test_coins[i % 2]].with_balance(1_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+        helper.app.next_block(10);
+    }
+
+    let deviation = query_deviation(&helper, 1000).unwrap();
+    assert!(deviation.deviation_pct < MAX_SMA_DEVIATION);
+    assert!(!deviation.is_anomalous);
+
+    let err = query_deviation(&helper, 5).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("No observations within the last 5 seconds"));
+
+    // A large swap moves the observed price far away from its moving average
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(50_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper
+        .swap(&user, &offer_asset, Some(Decimal::percent(50)))
+        .unwrap();
+    helper.app.next_block(10);
+
+    // The observation is committed on the next swap in a later block
+    let offer_asset = helper.assets[&test_coins[1]].with_balance(1_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let deviation = query_deviation(&helper, 1000).unwrap();
+    assert!(deviation.spot_price > deviation.sma_price);
+    assert!(deviation.deviation_pct > MAX_SMA_DEVIATION);
+    assert!(deviation.is_anomalous);
+}
+
+#[test]
+fn check_pool_statistics() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    for i in 0..4 {
+        let offer_asset =
+            helper.assets[&test_coins[i % 2]].with_balance((i as u128 + 1) * 1_000000);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+        helper.app.next_block(10);
+    }
+
+    let swaps: Vec<SwapRecord> = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::RecentSwaps { limit: 10 })
+        .unwrap();
+    let query_stats = |helper: &Helper, from_ts: u64, to_ts: u64| {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart::<PoolStatisticsResponse>(
+                &helper.pair_addr,
+                &QueryMsg::PoolStatistics { from_ts, to_ts },
+            )
+    };
+
+    let stats = query_stats(&helper, 0, u64::MAX).unwrap();
+    assert_eq!(stats.trade_count, 4);
+    let base_amounts = swaps
+        .iter()
+        .map(|swap| {
+            if swap.offer.info == helper.assets[&test_coins[0]] {
+                swap.offer.amount
+            } else {
+                swap.returned.amount
+            }
+        })
+        .collect_vec();
+    assert_eq!(
+        stats.total_volume_base,
+        base_amounts.iter().copied().sum::<Uint128>()
+    );
+    assert_eq!(
+        stats.max_single_trade_base,
+        *base_amounts.iter().max().unwrap()
+    );
+    assert_eq!(
+        stats.min_single_trade_base,
+        *base_amounts.iter().min().unwrap()
+    );
+    assert!(!stats.average_fee.is_zero());
+
+    // Skip the oldest swap
+    let stats = query_stats(&helper, swaps[2].ts, swaps[0].ts).unwrap();
+    assert_eq!(stats.trade_count, 3);
+
+    let stats = query_stats(&helper, swaps[0].ts + 1, u64::MAX).unwrap();
+    assert_eq!(stats.trade_count, 0);
+    assert_eq!(stats.total_volume_base, Uint128::zero());
+
+    let err = query_stats(&helper, 10, 1).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: from_ts must be less than or equal to to_ts"
+    );
+}
+
+#[test]
+fn check_pair_stats() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    for i in 0..4 {
+        let offer_asset =
+            helper.assets[&test_coins[i % 2]].with_balance((i as u128 + 1) * 1_000000);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+        helper.app.next_block(10);
+    }
+
+    // Swaps are returned starting from the most recent one
+    let mut swaps: Vec<SwapRecord> = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::RecentSwaps { limit: 10 })
+        .unwrap();
+    swaps.reverse();
+
+    let query_stats = |helper: &Helper, from_height: u64, to_height: u64| {
+        helper.app.wrap().query_wasm_smart::<PairStatsResponse>(
+            &helper.pair_addr,
+            &QueryMsg::PairStats {
+                from_height,
+                to_height,
+            },
+        )
+    };
+
+    let prices = swaps
+        .iter()
+        .map(|swap| {
+            if swap.offer.info == helper.assets[&test_coins[0]] {
+                Decimal::from_ratio(swap.returned.amount, swap.offer.amount)
+            } else {
+                Decimal::from_ratio(swap.offer.amount, swap.returned.amount)
+            }
+        })
+        .collect_vec();
+
+    let stats = query_stats(&helper, 0, u64::MAX).unwrap();
+    assert_eq!(stats.open_price, prices[0]);
+    assert_eq!(stats.close_price, prices[3]);
+    assert_eq!(stats.high_price, *prices.iter().max().unwrap());
+    assert_eq!(stats.low_price, *prices.iter().min().unwrap());
+    // Uluna volume: offered 1 and 3 LUNA, received roughly 2 and 4 LUNA
+    assert!(stats.total_volume > Uint128::new(9_900000));
+    assert!(stats.total_volume < Uint128::new(10_000000));
+    assert!(!stats.fee_income.is_zero());
+    assert!(!stats.lp_token_appreciation.is_zero());
+
+    // Only the two middle swaps
+    let stats = query_stats(&helper, swaps[1].height, swaps[2].height).unwrap();
+    assert_eq!(stats.open_price, prices[1]);
+    assert_eq!(stats.close_price, prices[2]);
+
+    let err = query_stats(&helper, swaps[3].height + 1, u64::MAX).unwrap_err();
+    assert!(err.to_string().contains("No swaps within block heights"));
+
+    let err = query_stats(&helper, 10, 1).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: Querier contract error: Generic error: from_height must be less than or equal to to_height"
+    );
+}
+
+#[test]
+fn check_liquidity_band() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let query_band = |helper: &Helper, coverage_pct: Decimal| {
+        helper.app.wrap().query_wasm_smart::<LiquidityBandResponse>(
+            &helper.pair_addr,
+            &QueryMsg::LiquidityBand { coverage_pct },
+        )
+    };
+
+    let err = query_band(&helper, Decimal::percent(50)).unwrap_err();
+    assert!(err.to_string().contains("Pools are empty"));
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    for coverage_pct in [Decimal::zero(), Decimal::one()] {
+        let err = query_band(&helper, coverage_pct).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: Querier contract error: Generic error: Coverage must be greater than 0 and less than 1"
+        );
+    }
+
+    let wide = query_band(&helper, Decimal::percent(50)).unwrap();
+    assert!(wide.price_lower < Decimal::one());
+    assert!(wide.price_upper > Decimal::one());
+    assert_eq!(
+        wide.liquidity_in_band,
+        Decimal256::from_ratio(100_000u64, 1u8)
+    );
+    // The balanced pool is symmetric around the price scale
+    assert!((dec_to_f64(wide.price_lower) * dec_to_f64(wide.price_upper) - 1.0).abs() < 1e-2);
+
+    let narrow = query_band(&helper, Decimal::percent(10)).unwrap();
+    assert!(narrow.price_lower > wide.price_lower);
+    assert!(narrow.price_upper < wide.price_upper);
+    assert!(narrow.price_lower < Decimal::one() && narrow.price_upper > Decimal::one());
+}
+
+#[test]
+fn check_volatility_fee() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let query_fee = |helper: &Helper| -> Decimal {
+        helper
+            .app
+            .wrap()
+            .query_wasm_smart(&helper.pair_addr, &QueryMsg::CurrentFee {})
+            .unwrap()
+    };
+
+    // The balanced pool charges mid_fee
+    assert_eq!(query_fee(&helper), common_pcl_params().mid_fee);
+
+    let invalid_configs = [
+        (
+            VolatilityFeeConfig {
+                min_fee_bps: 0,
+                max_fee_bps: 50,
+                volatility_window: 10,
+            },
+            ("min_fee_bps", "1", "100"),
+        ),
+        (
+            VolatilityFeeConfig {
+                min_fee_bps: 10,
+                max_fee_bps: 5,
+                volatility_window: 10,
+            },
+            ("max_fee_bps", "10", "100"),
+        ),
+        (
+            VolatilityFeeConfig {
+                min_fee_bps: 10,
+                max_fee_bps: 50,
+                volatility_window: 1,
+            },
+            ("volatility_window", "2", "255"),
+        ),
+    ];
+    for (volatility_fee, (name, min, max)) in invalid_configs {
+        let err = helper
+            .update_config(
+                &owner,
+                &ConcentratedPoolUpdateParams::EnableVolatilityFee(volatility_fee),
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.downcast::<ContractError>().unwrap(),
+            ContractError::PclError(PclError::IncorrectPoolParam(
+                name.to_string(),
+                min.to_string(),
+                max.to_string()
+            ))
+        );
+    }
+
+    let volatility_fee = VolatilityFeeConfig {
+        min_fee_bps: 10,
+        max_fee_bps: 50,
+        volatility_window: 10,
+    };
+    helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::EnableVolatilityFee(volatility_fee.clone()),
+        )
+        .unwrap();
+    assert_eq!(
+        helper.query_config().unwrap().volatility_fee,
+        Some(volatility_fee)
+    );
+
+    // There are no observations yet
+    assert_eq!(query_fee(&helper), Decimal::permille(1));
+
+    let user = Addr::unchecked("user");
+    for i in 0..10 {
+        let offer_asset = helper.assets[&test_coins[i % 2]].with_balance(1_000_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+        helper.app.next_block(1000);
+    }
+
+    let fee = query_fee(&helper);
+    assert!(fee > Decimal::permille(1) && fee <= Decimal::permille(5));
+
+    // Simulation charges the current fee
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000000u128);
+    let sim = helper.simulate_swap(&offer_asset, None).unwrap();
+    let fee_rate = Decimal::from_ratio(
+        sim.commission_amount,
+        sim.return_amount + sim.commission_amount,
+    );
+    assert!((dec_to_f64(fee_rate) - dec_to_f64(fee)).abs() < 1e-5);
+
+    helper
+        .update_config(&owner, &ConcentratedPoolUpdateParams::DisableVolatilityFee)
+        .unwrap();
+    assert_eq!(helper.query_config().unwrap().volatility_fee, None);
+    assert!(query_fee(&helper) >= common_pcl_params().mid_fee);
+}
+
+#[test]
+fn check_swap_volume_limit() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let set_limit = ConcentratedPoolUpdateParams::SetSwapVolumeLimit {
+        max_swap_volume_per_block: Some(Uint128::new(100_000000)),
+        volume_limit_decay_blocks: 10,
+    };
+    let err = helper
+        .update_config(&Addr::unchecked("random"), &set_limit)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    // The decay period is capped
+    let err = helper
+        .update_config(
+            &owner,
+            &ConcentratedPoolUpdateParams::SetSwapVolumeLimit {
+                max_swap_volume_per_block: Some(Uint128::new(100_000000)),
+                volume_limit_decay_blocks: MAX_VOLUME_LIMIT_DECAY_BLOCKS + 1,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::VolumeLimitDecayBlocksTooLarge {
+            max: MAX_VOLUME_LIMIT_DECAY_BLOCKS
+        }
+    );
+
+    helper.update_config(&owner, &set_limit).unwrap();
+
+    let config = helper.query_config().unwrap();
+    assert_eq!(
+        config.max_swap_volume_per_block,
+        Some(Uint128::new(100_000000))
+    );
+    assert_eq!(config.volume_limit_decay_blocks, 10);
+
+    let user = Addr::unchecked("user");
+    let swap_coin = |helper: &mut Helper, coin: &TestCoin, amount: u128| {
+        let offer_asset = helper.assets[coin].with_balance(amount);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, Some(Decimal::percent(50)))
+    };
+    let swap = |helper: &mut Helper, amount: u128| swap_coin(helper, &test_coins[1], amount);
+
+    // A single swap above the limit
+    let err = swap(&mut helper, 100_000001).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockVolumeLimitExceeded {
+            volume: Uint128::new(100_000001),
+            limit: Uint128::new(100_000000),
+        }
+    );
+
+    // Two swaps which exceed the limit together
+    swap(&mut helper, 60_000000).unwrap();
+    let err = swap(&mut helper, 60_000000).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockVolumeLimitExceeded {
+            volume: Uint128::new(120_000000),
+            limit: Uint128::new(100_000000),
+        }
+    );
+
+    // Volumes are tracked separately for every offer asset
+    swap_coin(&mut helper, &test_coins[0], 100_000000).unwrap();
+    let err = swap_coin(&mut helper, &test_coins[0], 1).unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::BlockVolumeLimitExceeded {
+            volume: Uint128::new(100_000001),
+            limit: Uint128::new(100_000000),
+        }
+    );
+
+    // Half of the previous volume is released after 5 blocks
+    for _ in 0..5 {
+        helper.app.next_block(5);
+    }
+    swap(&mut helper, 70_000001).unwrap_err();
+    swap(&mut helper, 70_000000).unwrap();
+
+    // The whole volume is released after the decay period
+    for _ in 0..10 {
+        helper.app.next_block(5);
+    }
+    swap(&mut helper, 100_000000).unwrap();
+}
+
+#[test]
+fn check_swap_min_out() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+
+    let return_amount = helper
+        .simulate_swap(&offer_asset, None)
+        .unwrap()
+        .return_amount;
+    let swap_msg = |min_out: Uint128| ExecuteMsg::Swap {
+        offer_asset: offer_asset.clone(),
+        ask_asset_info: None,
+        belief_price: None,
+        max_spread: None,
+        min_out: Some(min_out),
+        to: None,
+        callback: None,
+    };
+    let funds = [offer_asset.as_coin().unwrap()];
+
+    let err = helper
+        .app
+        .execute_contract(
+            user.clone(),
+            helper.pair_addr.clone(),
+            &swap_msg(return_amount + Uint128::one()),
+            &funds,
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::MinOutNotMet {
+            expected: return_amount + Uint128::one(),
+            actual: return_amount,
+        },
+        err.downcast().unwrap()
+    );
+
+    helper
+        .app
+        .execute_contract(
+            user.clone(),
+            helper.pair_addr.clone(),
+            &swap_msg(return_amount),
+            &funds,
+        )
+        .unwrap();
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &user),
+        return_amount.u128()
+    );
+}
+
+#[test]
+fn check_min_swap_amount() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20precise("FOO", 8)];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_00000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let resp: MinSwapAmountResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::MinSwapAmount {})
+        .unwrap();
+    assert_eq!(resp.min_amounts.len(), 2);
+
+    for min_amount in resp.min_amounts {
+        let sim_resp = helper.simulate_swap(&min_amount, None).unwrap();
+        assert!(!sim_resp.return_amount.is_zero());
+
+        if min_amount.amount > Uint128::one() {
+            let below_min = min_amount
+                .info
+                .with_balance(min_amount.amount - Uint128::one());
+            let sim_resp = helper.simulate_swap(&below_min, None).unwrap();
+            assert!(sim_resp.return_amount.is_zero());
+        }
+    }
+}
+
+#[test]
+fn check_provide_liquidity_balanced() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusdc")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    // Move the pool ratio away from 1:1
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(5_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &owner);
+    helper.swap(&owner, &offer_asset, None).unwrap();
+
+    let offer = helper.assets[&test_coins[0]].with_balance(1_000_000000u128);
+    let optimal: OptimalLiquidityResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &helper.pair_addr,
+            &QueryMsg::OptimalLiquidityAmounts {
+                asset: offer.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(optimal.asset_a, offer);
+    let pool = helper.query_pool().unwrap();
+    assert_eq!(
+        optimal.asset_b,
+        helper.assets[&test_coins[1]].with_balance(
+            pool.assets[1]
+                .amount
+                .multiply_ratio(offer.amount, pool.assets[0].amount)
+        )
+    );
+
+    let user = Addr::unchecked("user");
+    let surplus = 1_000000u128;
+    helper.give_me_money(
+        &[
+            offer.clone(),
+            optimal
+                .asset_b
+                .info
+                .with_balance(optimal.asset_b.amount.u128() + surplus),
+        ],
+        &user,
+    );
+
+    let provide_msg = ExecuteMsg::ProvideLiquidityBalanced {
+        offer: offer.clone(),
+        slippage_tolerance: None,
+        receiver: None,
+    };
+
+    // The counterpart is not covered by the attached coins
+    let err = helper
+        .app
+        .execute_contract(
+            user.clone(),
+            helper.pair_addr.clone(),
+            &provide_msg,
+            &[
+                Coin::new(offer.amount.u128(), "uluna"),
+                Coin::new(optimal.asset_b.amount.u128() - 1, "uusdc"),
+            ],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InsufficientCounterpart {
+            asset: "uusdc".to_string(),
+            required: optimal.asset_b.amount,
+            available: optimal.asset_b.amount - Uint128::one(),
+        },
+        err.downcast().unwrap()
+    );
+
+    helper
+        .app
+        .execute_contract(
+            user.clone(),
+            helper.pair_addr.clone(),
+            &provide_msg,
+            &[
+                Coin::new(offer.amount.u128(), "uluna"),
+                Coin::new(optimal.asset_b.amount.u128() + surplus, "uusdc"),
+            ],
+        )
+        .unwrap();
+    let user_lp = helper.native_balance(&helper.lp_token, &user);
+    assert_eq!(user_lp, optimal.lp_tokens_estimated.u128());
+    // The surplus is refunded
+    assert_eq!(helper.coin_balance(&test_coins[0], &user), 0);
+    assert_eq!(helper.coin_balance(&test_coins[1], &user), surplus);
+
+    // A manual balanced provision of the same amounts mints the same LP tokens
+    let manual_user = Addr::unchecked("manual_user");
+    let manual_assets = vec![optimal.asset_a.clone(), optimal.asset_b.clone()];
+    helper.give_me_money(&manual_assets, &manual_user);
+    helper
+        .provide_liquidity(&manual_user, &manual_assets)
+        .unwrap();
+    let manual_lp = helper.native_balance(&helper.lp_token, &manual_user);
+    assert!(
+        user_lp.abs_diff(manual_lp) <= 1_000,
+        "{user_lp} != {manual_lp}"
+    );
+}
+
+fn flash_loan_receiver_execute(
+    _deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: FlashLoanCallbackExecuteMsg,
+) -> StdResult<Response> {
+    let FlashLoanCallbackExecuteMsg::FlashLoanCallback(callback) = msg;
+
+    // The callback message tells the receiver whether to repay the loan
+    if !from_json::<bool>(&callback.msg)? {
+        return Ok(Response::new());
+    }
+
+    let repayment = callback
+        .assets
+        .iter()
+        .zip(callback.fees.iter())
+        .map(|(asset, fee)| coin((asset.amount + fee.amount).u128(), asset.info.to_string()))
+        .collect();
+
+    Ok(Response::new().add_message(BankMsg::Send {
+        to_address: info.sender.to_string(),
+        amount: repayment,
+    }))
+}
+
+#[test]
+fn check_accumulated_fees() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(1_000_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    // The Maker fee is still sent to the Maker and recorded in the ledger
+    let maker_fee = helper.native_balance("uusd", &helper.fake_maker);
+    assert!(maker_fee > 0);
+
+    let res: AccumulatedFeesResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::AccumulatedFees {})
+        .unwrap();
+    assert_eq!(
+        res.fees,
+        vec![
+            helper.assets[&test_coins[0]].with_balance(0u128),
+            helper.assets[&test_coins[1]].with_balance(maker_fee),
+        ]
+    );
+
+    // The ledger keeps adding up the fees of further swaps
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper.swap(&user, &offer_asset, None).unwrap();
+
+    let total_maker_fee = helper.native_balance("uusd", &helper.fake_maker);
+    let res: AccumulatedFeesResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::AccumulatedFees {})
+        .unwrap();
+    assert_eq!(res.fees[1].amount.u128(), total_maker_fee);
+}
+
+#[test]
+fn check_flash_loan() {
+    let owner = Addr::unchecked("owner");
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), common_pcl_params()).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets).unwrap();
+
+    let receiver_code_id = helper
+        .app
+        .store_code(Box::new(ContractWrapper::new_with_empty(
+            flash_loan_receiver_execute,
+            |_, _, _, _: Empty| -> StdResult<Response> { Ok(Response::new()) },
+            |_, _, _: Empty| -> StdResult<Binary> { unimplemented!() },
+        )));
+    let receiver = helper
+        .app
+        .instantiate_contract(
+            receiver_code_id,
+            owner.clone(),
+            &Empty {},
+            &[],
+            "Flash loan receiver",
+            None,
+        )
+        .unwrap();
+    // The receiver pays the fees from its own balance
+    helper.give_me_money(
+        &[helper.assets[&test_coins[1]].with_balance(1_000000u128)],
+        &receiver,
+    );
+
+    let set_fee = |fee| ConcentratedPoolUpdateParams::SetFlashLoanFee { fee };
+    let err = helper
+        .update_config(&Addr::unchecked("random"), &set_fee(Decimal::permille(3)))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::Unauthorized {}
+    );
+
+    let err = helper
+        .update_config(&owner, &set_fee(Decimal::percent(11)))
+        .unwrap_err();
+    assert_eq!(
+        err.downcast::<ContractError>().unwrap(),
+        ContractError::InvalidFeeRate {
+            rate: Decimal::percent(11),
+            field: "flash_loan_fee".to_string(),
+            min: Decimal::zero(),
+            max: Decimal::from_ratio(MAX_FLASH_LOAN_FEE_BPS, 10000u16),
+        }
+    );
+
+    helper
+        .update_config(&owner, &set_fee(Decimal::permille(3)))
+        .unwrap();
+    assert_eq!(
+        helper.query_config().unwrap().flash_loan_fee,
+        Decimal::permille(3)
+    );
+
+    let loan_msg = |repay: bool| ExecuteMsg::FlashLoan {
+        assets: vec![native_asset_info("uusd".to_string()).with_balance(100_000000u128)],
+        receiver: receiver.to_string(),
+        msg: to_json_binary(&repay).unwrap(),
+    };
+
+    // Receiver doesn't repay the loan
+    let err = helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &loan_msg(false),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        err.root_cause().to_string(),
+        ContractError::FlashLoanNotRepaid {
+            asset: "uusd".to_string(),
+            expected: Uint128::new(100_000_000000 + 300_000),
+            actual: Uint128::new(100_000_000000 - 100_000000),
+        }
+        .to_string()
+    );
+
+    let d_before = helper.query_d().unwrap();
+    let xcp_profit_real_before = helper
+        .query_config()
+        .unwrap()
+        .pool_state
+        .price_state
+        .xcp_profit_real;
+
+    // Successful round trip. The fee is 0.3% of the loan and stays with the LPs
+    helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &loan_msg(true),
+            &[],
+        )
+        .unwrap();
+
+    let pool = helper.query_pool().unwrap();
+    assert_eq!(pool.assets[1].amount.u128(), 100_000_000000 + 300_000);
+    assert_eq!(
+        helper.coin_balance(&test_coins[1], &receiver),
+        1_000000 - 300_000
+    );
+
+    assert!(helper.query_d().unwrap() > d_before);
+    let xcp_profit_real = helper
+        .query_config()
+        .unwrap()
+        .pool_state
+        .price_state
+        .xcp_profit_real;
+    assert!(xcp_profit_real > xcp_profit_real_before);
+}
@@ -1,52 +1,61 @@
 use std::collections::HashMap;
-use std::str::FromStr;
 use std::vec;
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, coin, ensure_eq, from_json, to_json_binary, Addr, Binary, Coin, CosmosMsg, Decimal,
-    Decimal256, Deps, DepsMut, Empty, Env, Fraction, MessageInfo, QuerierWrapper, Reply, Response,
-    StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, coin, ensure, ensure_eq, from_json, to_json_binary, Addr, Binary, Coin, CosmosMsg,
+    Decimal, Decimal256, Deps, DepsMut, Env, Fraction, MessageInfo, QuerierWrapper, Reply,
+    Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, Uint128, Uint256, WasmMsg,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use cw_utils::{one_coin, PaymentError};
 use itertools::Itertools;
 
 use astroport::asset::{
-    addr_opt_validate, check_swap_parameters, Asset, AssetInfo, CoinsExt, Decimal256Ext,
-    DecimalAsset, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
+    addr_opt_validate, check_swap_parameters, Asset, AssetInfo, AssetInfoExt, CoinsExt,
+    Decimal256Ext, DecimalAsset, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
 use astroport::common::{claim_ownership, drop_ownership_proposal, propose_new_owner, LP_SUBDENOM};
 use astroport::cosmwasm_ext::IntegerToDecimal;
 use astroport::factory::PairType;
-use astroport::observation::{query_observation, PrecommitObservation, OBSERVATIONS_SIZE};
+use astroport::gas_estimates::STABLE_SWAP;
+use astroport::observation::{
+    compute_median_price, query_geo_twap, query_observation, query_observation_at,
+    query_observation_range, query_observation_window, query_volume_window, ObservationResponse,
+    PrecommitObservation, OBSERVATIONS_SIZE,
+};
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, FeeShareConfig, InstantiateMsg, StablePoolParams,
-    StablePoolUpdateParams, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, MAX_FEE_SHARE_BPS,
-    MIN_TRADE_SIZE,
+    assert_max_spread, assert_min_out, calculate_price_impact, Cw20HookMsg, ExecuteMsg,
+    PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse, StablePoolConfig,
 };
 use astroport::pair::{
-    Cw20HookMsg, ExecuteMsg, PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse,
-    StablePoolConfig,
+    ConfigResponse, CumulativePricesResponse, FeeShareConfig, InstantiateMsg, MigrateMsg,
+    MinSwapAmountResponse, StablePoolParams, StablePoolUpdateParams, MAX_FEE_SHARE_BPS,
+    MAX_VOLUME_LIMIT_DECAY_BLOCKS, MIN_TRADE_SIZE,
 };
 use astroport::querier::{query_factory_config, query_fee_info, query_native_supply};
 use astroport::token_factory::{tf_burn_msg, tf_create_denom_msg, MsgCreateDenomResponse};
 use astroport::DecimalCheckedOps;
 use astroport_circular_buffer::BufferManager;
+use astroport_pair_common::state::CODE_VERSION;
+use astroport_pair_common::utils::{
+    accumulate_block_volume, assert_code_version, replace_asset_messages, transfer_lp_and_stake,
+};
 
 use crate::error::ContractError;
 use crate::math::{
     calc_y, compute_d, AMP_PRECISION, MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME,
 };
 use crate::state::{
-    get_precision, store_precisions, Config, CONFIG, OBSERVATIONS, OWNERSHIP_PROPOSAL,
+    get_precision, store_precisions, Config, CONFIG, CUMULATIVE_VOLUMES, OBSERVATIONS,
+    OWNERSHIP_PROPOSAL, PRECISIONS,
 };
 use crate::utils::{
     accumulate_prices, accumulate_swap_sizes, adjust_precision, calculate_shares,
-    check_asset_infos, check_cw20_in_pool, compute_current_amp, compute_swap,
-    determine_base_quote_amount, get_assets_collection, get_share_in_assets,
+    check_asset_infos, check_cw20_in_pool, compute_current_amp, compute_min_swap_amount,
+    compute_swap, determine_base_quote_amount, get_assets_collection, get_share_in_assets,
     mint_liquidity_token_message, select_pools, SwapResult,
 };
 
@@ -84,6 +93,7 @@ pub fn instantiate(
     }
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
 
     let factory_addr = deps.api.addr_validate(&msg.factory_addr)?;
     let greatest_precision = store_precisions(deps.branch(), &msg.asset_infos, &factory_addr)?;
@@ -116,6 +126,8 @@ pub fn instantiate(
         cumulative_prices,
         fee_share: None,
         tracker_addr: None,
+        max_swap_volume_per_block: None,
+        volume_limit_decay_blocks: 0,
     };
 
     CONFIG.save(deps.storage, &config)?;
@@ -180,12 +192,24 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             offer_asset,
 ///             belief_price,
 ///             max_spread,
+///             min_out,
 ///             to,
 ///         }** Performs an swap using the specified parameters.
 /// * **ExecuteMsg::WithdrawLiquidity {
 ///            assets,
 ///           min_assets_to_receive,
 ///       }** Withdraws liquidity from the pool.
+///
+/// * **ExecuteMsg::TransferLpAndStake {
+///             recipient,
+///             amount,
+///             generator,
+///         }** Stakes the attached LP tokens in the Incentives contract on behalf of the recipient.
+///
+/// * **ExecuteMsg::ReplaceAsset {
+///             old_asset,
+///             new_asset,
+///         }** Replaces a pool asset with a new one and sends the old asset reserve to the caller.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
@@ -193,9 +217,17 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Reject messages if the stored code version differs from the compiled one
+    assert_code_version::<ContractError>(deps.storage, CONTRACT_VERSION)?;
+
     match msg {
         ExecuteMsg::UpdateConfig { params } => update_config(deps, env, info, params),
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        // Liquidity requests are only supported by the constant product pair
+        ExecuteMsg::ProvideLiquidity {
+            request_id: Some(_),
+            ..
+        } => Err(ContractError::NonSupported {}),
         ExecuteMsg::ProvideLiquidity {
             assets,
             auto_stake,
@@ -216,9 +248,15 @@ pub fn execute(
             ask_asset_info,
             belief_price,
             max_spread,
+            min_out,
             to,
+            callback,
             ..
         } => {
+            // Swap callbacks are only supported by the constant product pair
+            if callback.is_some() {
+                return Err(ContractError::NonSupported {});
+            }
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -235,6 +273,7 @@ pub fn execute(
                 ask_asset_info,
                 belief_price,
                 max_spread,
+                min_out,
                 to_addr,
             )
         }
@@ -242,7 +281,7 @@ pub fn execute(
             let cfg = CONFIG.load(deps.storage)?;
             let factory_config = query_factory_config(&deps.querier, cfg.factory_addr.clone())?;
 
-            propose_new_owner(
+            Ok(propose_new_owner(
                 deps,
                 info,
                 env,
@@ -250,36 +289,71 @@ pub fn execute(
                 expires_in,
                 cfg.owner.unwrap_or(factory_config.owner),
                 OWNERSHIP_PROPOSAL,
-            )
-            .map_err(|e| e.into())
+            )?)
         }
         ExecuteMsg::DropOwnershipProposal {} => {
             let cfg = CONFIG.load(deps.storage)?;
             let factory_config = query_factory_config(&deps.querier, cfg.factory_addr.clone())?;
 
-            drop_ownership_proposal(
+            Ok(drop_ownership_proposal(
                 deps,
                 info,
                 cfg.owner.unwrap_or(factory_config.owner),
                 OWNERSHIP_PROPOSAL,
-            )
-            .map_err(|e| e.into())
+            )?)
         }
-        ExecuteMsg::ClaimOwnership {} => {
-            claim_ownership(deps, info, env, OWNERSHIP_PROPOSAL, |deps, new_owner| {
+        ExecuteMsg::ClaimOwnership {} => Ok(claim_ownership(
+            deps,
+            info,
+            env,
+            OWNERSHIP_PROPOSAL,
+            |deps, new_owner| {
                 CONFIG.update::<_, StdError>(deps.storage, |mut config| {
                     config.owner = Some(new_owner);
                     Ok(config)
                 })?;
 
                 Ok(())
-            })
-            .map_err(|e| e.into())
-        }
+            },
+        )?),
         ExecuteMsg::WithdrawLiquidity {
             assets,
             min_assets_to_receive,
         } => withdraw_liquidity(deps, env, info, assets, min_assets_to_receive),
+        ExecuteMsg::TransferLpAndStake {
+            recipient,
+            amount,
+            generator,
+        } => {
+            let config = CONFIG.load(deps.storage)?;
+            transfer_lp_and_stake(
+                deps.api,
+                info,
+                &config.pair_info.liquidity_token,
+                recipient,
+                amount,
+                generator,
+            )
+        }
+        ExecuteMsg::ReplaceAsset {
+            old_asset,
+            new_asset,
+        } => replace_asset(deps, env, info, old_asset, new_asset),
+        ExecuteMsg::DonateToPool { .. }
+        | ExecuteMsg::UpgradeOracle { .. }
+        | ExecuteMsg::FreezeConfig {}
+        | ExecuteMsg::Sweep { .. }
+        | ExecuteMsg::RequestLiquidityProvision { .. }
+        | ExecuteMsg::CancelLiquidityRequest { .. }
+        | ExecuteMsg::ClaimLiquidityRequest { .. }
+        | ExecuteMsg::FlashLoan { .. }
+        | ExecuteMsg::SetFlashLoanFee { .. }
+        | ExecuteMsg::SetSlippageTolerance { .. }
+        | ExecuteMsg::RegisterExternalLiquidity { .. }
+        | ExecuteMsg::Pause {}
+        | ExecuteMsg::Unpause {}
+        | ExecuteMsg::ProvideLiquiditySingle { .. }
+        | ExecuteMsg::ProvideLiquidityBalanced { .. } => Err(ContractError::NonSupported {}),
     }
 }
 
@@ -297,6 +371,7 @@ pub fn receive_cw20(
             ask_asset_info,
             belief_price,
             max_spread,
+            min_out,
             to,
         } => {
             let config = CONFIG.load(deps.storage)?;
@@ -318,9 +393,11 @@ pub fn receive_cw20(
                 ask_asset_info,
                 belief_price,
                 max_spread,
+                min_out,
                 to_addr,
             )
         }
+        Cw20HookMsg::StakeAndProvide { .. } => Err(ContractError::NonSupported {}),
     }
 }
 
@@ -449,6 +526,101 @@ pub fn provide_liquidity(
     ]))
 }
 
+/// Replaces a pool asset with a new one. Only the contract owner can execute this.
+/// The pair must already hold at least the same reserve of the new asset as it holds of the old one,
+/// taking precisions into account. The old asset reserve is sent to the caller, stored observations
+/// are dropped and the factory is asked to register the pair under its new assets.
+///
+/// * **old_asset** the pool asset to replace.
+///
+/// * **new_asset** the asset that takes its place.
+pub fn replace_asset(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    old_asset: AssetInfo,
+    new_asset: AssetInfo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let factory_config = query_factory_config(&deps.querier, &config.factory_addr)?;
+
+    if info.sender != config.owner.clone().unwrap_or(factory_config.owner) {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    new_asset.check(deps.api)?;
+
+    if !config.pair_info.asset_infos.contains(&old_asset) {
+        return Err(ContractError::InvalidAsset(old_asset.to_string()));
+    }
+    if config.pair_info.asset_infos.contains(&new_asset) {
+        return Err(ContractError::DoublingAssets {});
+    }
+
+    let old_precision = get_precision(deps.storage, &old_asset)?;
+    let new_precision = new_asset.decimals(&deps.querier, &config.factory_addr)?;
+
+    let old_reserve = old_asset.query_pool(&deps.querier, &env.contract.address)?;
+    let new_reserve = new_asset.query_pool(&deps.querier, &env.contract.address)?;
+
+    if Decimal256::with_precision(new_reserve, new_precision)?
+        < Decimal256::with_precision(old_reserve, old_precision)?
+    {
+        return Err(ContractError::InsufficientReplacementReserve {
+            asset: new_asset.to_string(),
+            actual: new_reserve,
+        });
+    }
+
+    PRECISIONS.remove(deps.storage, old_asset.to_string());
+    PRECISIONS.save(deps.storage, new_asset.to_string(), &new_precision)?;
+
+    let old_asset_infos = config.pair_info.asset_infos.clone();
+    for asset_info in config.pair_info.asset_infos.iter_mut() {
+        if *asset_info == old_asset {
+            *asset_info = new_asset.clone();
+        }
+    }
+    for (from, to, _) in config.cumulative_prices.iter_mut() {
+        if *from == old_asset {
+            *from = new_asset.clone();
+        }
+        if *to == old_asset {
+            *to = new_asset.clone();
+        }
+    }
+    config.greatest_precision = config
+        .pair_info
+        .asset_infos
+        .iter()
+        .map(|asset_info| get_precision(deps.storage, asset_info))
+        .collect::<StdResult<Vec<_>>>()?
+        .into_iter()
+        .max()
+        .unwrap_or_default();
+
+    CONFIG.save(deps.storage, &config)?;
+
+    // Observations are denominated in the old asset so they can't be used anymore
+    BufferManager::new(deps.storage, OBSERVATIONS)?.reset(deps.storage)?;
+    PrecommitObservation::remove(deps.storage);
+
+    let messages = replace_asset_messages(
+        &config.factory_addr,
+        old_asset_infos,
+        old_asset.clone(),
+        old_reserve,
+        &info.sender,
+    )?;
+
+    Ok(Response::new().add_messages(messages).add_attributes([
+        attr("action", "replace_asset"),
+        attr("old_asset", old_asset.to_string()),
+        attr("new_asset", new_asset.to_string()),
+        attr("reserve", new_reserve),
+    ]))
+}
+
 /// Withdraw liquidity from the pool.
 pub fn withdraw_liquidity(
     deps: DepsMut,
@@ -518,6 +690,8 @@ pub fn withdraw_liquidity(
 ///
 /// * **max_spread** sets the maximum spread of the swap operation.
 ///
+/// * **min_out** sets the minimum amount of ask assets to receive.
+///
 /// * **to** sets the recipient of the swap operation.
 ///
 /// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
@@ -530,6 +704,7 @@ pub fn swap(
     ask_asset_info: Option<AssetInfo>,
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
+    min_out: Option<Uint128>,
     to: Option<Addr>,
 ) -> Result<Response, ContractError> {
     let mut config = CONFIG.load(deps.storage)?;
@@ -601,6 +776,15 @@ pub fn swap(
         return_amount + commission_amount,
         spread_amount,
     )?;
+    assert_min_out(min_out, return_amount)?;
+    accumulate_block_volume::<ContractError>(
+        deps.storage,
+        env.block.height,
+        config.max_swap_volume_per_block,
+        config.volume_limit_decay_blocks,
+        &offer_asset.info,
+        offer_asset.amount,
+    )?;
 
     let receiver = to.unwrap_or_else(|| sender.clone());
 
@@ -652,6 +836,19 @@ pub fn swap(
         }
     }
 
+    // Accumulate swapped volumes for the assets in the pool
+    let mut volumes = CUMULATIVE_VOLUMES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let (offer_ind, ask_ind) = if offer_pool.info.equal(&pools[0].info) {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    volumes[offer_ind] += Uint256::from(offer_asset.amount);
+    volumes[ask_ind] += Uint256::from(return_amount);
+    CUMULATIVE_VOLUMES.save(deps.storage, &volumes)?;
+
     if accumulate_prices(deps.storage, &env, &mut config, &pools)? {
         CONFIG.save(deps.storage, &config)?;
     }
@@ -739,6 +936,9 @@ pub fn calculate_maker_fee(
 /// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amount of assets that could be withdrawn from the pool
 /// using a specific amount of LP tokens. The result is returned in a vector that contains objects of type [`Asset`].
 /// * **QueryMsg::SimulateProvide { msg }** Simulates the liquidity provision in the pair contract.
+///
+/// * **QueryMsg::MinSwapAmount {}** Returns the minimum viable swap amount of each pool asset
+/// using a [`MinSwapAmountResponse`] object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -762,6 +962,38 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::Observe { seconds_ago } => {
             to_json_binary(&query_observation(deps, env, OBSERVATIONS, seconds_ago)?)
         }
+        QueryMsg::MedianPrice { window } => {
+            to_json_binary(&compute_median_price(deps.storage, OBSERVATIONS, window)?)
+        }
+        QueryMsg::VolumeWindow { window_seconds } => to_json_binary(&query_volume_window(
+            deps,
+            env,
+            OBSERVATIONS,
+            window_seconds,
+        )?),
+        QueryMsg::ObservationWindow { window_seconds } => to_json_binary(
+            &query_observation_window(deps, env, OBSERVATIONS, window_seconds)?,
+        ),
+        QueryMsg::ObservationRange {
+            start_ts,
+            end_ts,
+            start_after,
+        } => to_json_binary(&query_observation_range(
+            deps.storage,
+            OBSERVATIONS,
+            start_ts,
+            end_ts,
+            start_after,
+        )?),
+        QueryMsg::ObservationAt { timestamp } => to_json_binary(
+            &query_observation_at(deps, OBSERVATIONS, timestamp)?
+                .map(ObservationResponse::from)
+                .ok_or_else(|| {
+                    StdError::generic_err(
+                        ContractError::ObservationNotFound { timestamp }.to_string(),
+                    )
+                })?,
+        ),
         QueryMsg::Config {} => to_json_binary(&query_config(deps, env)?),
         QueryMsg::SimulateWithdraw { lp_amount } => to_json_binary(&query_share(deps, lp_amount)?),
         QueryMsg::SimulateProvide { assets, .. } => to_json_binary(
@@ -769,6 +1001,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
                 .map_err(|e| StdError::generic_err(e.to_string()))?,
         ),
         QueryMsg::QueryComputeD {} => to_json_binary(&query_compute_d(deps, env)?),
+        QueryMsg::MinSwapAmount {} => to_json_binary(&query_min_swap_amount(deps, env)?),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -782,6 +1015,7 @@ pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
     let resp = PoolResponse {
         assets,
         total_share,
+        external_liquidity: vec![],
     };
 
     Ok(resp)
@@ -837,6 +1071,12 @@ pub fn query_simulation(
             return_amount: Uint128::zero(),
             spread_amount: Uint128::zero(),
             commission_amount: Uint128::zero(),
+            price_impact: if offer_asset.amount.is_zero() {
+                Decimal::zero()
+            } else {
+                Decimal::one()
+            },
+            gas_estimate: STABLE_SWAP.for_offer(&offer_asset.info),
         });
     }
 
@@ -864,13 +1104,55 @@ pub fn query_simulation(
     let commission_amount = fee_info.total_fee_rate.checked_mul_uint128(return_amount)?;
     let return_amount = return_amount.saturating_sub(commission_amount);
 
+    // The spot price is considered 1:1 in stable swap
+    let ask_precision = get_precision(deps.storage, &ask_pool.info)?;
+    let price_impact = calculate_price_impact(
+        Decimal256::one(),
+        Decimal256::with_precision(offer_asset.amount, offer_precision)?,
+        Decimal256::with_precision(return_amount, ask_precision)?,
+    )?;
+
     Ok(SimulationResponse {
         return_amount,
         spread_amount,
         commission_amount,
+        price_impact,
+        gas_estimate: STABLE_SWAP.for_offer(&offer_asset.info),
     })
 }
 
+/// Returns the minimum amount of each pool asset which can be swapped for a non-zero return
+/// in a [`MinSwapAmountResponse`] object. If the pool holds more than two assets,
+/// the amount is viable for every other pool asset.
+pub fn query_min_swap_amount(deps: Deps, env: Env) -> StdResult<MinSwapAmountResponse> {
+    let config = CONFIG.load(deps.storage)?;
+    let pools = config.pair_info.query_pools_decimal(
+        &deps.querier,
+        &config.pair_info.contract_addr,
+        &config.factory_addr,
+    )?;
+
+    let min_amounts = pools
+        .iter()
+        .map(|offer_pool| {
+            let mut amount = Uint128::zero();
+            for ask_pool in pools.iter().filter(|pool| pool.info != offer_pool.info) {
+                amount = amount.max(compute_min_swap_amount(
+                    deps.storage,
+                    &env,
+                    &config,
+                    offer_pool,
+                    ask_pool,
+                    &pools,
+                )?);
+            }
+            Ok(offer_pool.info.with_balance(amount))
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(MinSwapAmountResponse { min_amounts })
+}
+
 /// Returns information about a reverse swap simulation in a [`ReverseSimulationResponse`] object.
 ///
 /// * **ask_asset** is the asset to swap to as well as the desired amount of ask
@@ -914,6 +1196,11 @@ pub fn query_reverse_simulation(
             offer_amount: Uint128::zero(),
             spread_amount: Uint128::zero(),
             commission_amount: Uint128::zero(),
+            price_impact: if ask_asset.amount.is_zero() {
+                Decimal::zero()
+            } else {
+                Decimal::one()
+            },
         });
     }
 
@@ -944,6 +1231,13 @@ pub fn query_reverse_simulation(
     )?;
     let offer_amount = adjust_precision(offer_amount, config.greatest_precision, offer_precision)?;
 
+    // The spot price is considered 1:1 in stable swap
+    let price_impact = calculate_price_impact(
+        Decimal256::one(),
+        Decimal256::with_precision(offer_amount, offer_precision)?,
+        Decimal256::with_precision(ask_asset.amount, ask_precision)?,
+    )?;
+
     Ok(ReverseSimulationResponse {
         offer_amount,
         spread_amount: offer_amount
@@ -951,6 +1245,7 @@ pub fn query_reverse_simulation(
         commission_amount: fee_info
             .total_fee_rate
             .checked_mul_uint128(before_commission.to_uint128_with_precision(ask_precision)?)?,
+        price_impact,
     })
 }
 
@@ -970,10 +1265,17 @@ pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePric
     accumulate_prices(deps.storage, &env, &mut config, &decimal_assets)
         .map_err(|err| StdError::generic_err(format!("{err}")))?;
 
+    let [volume0_cumulative_last, volume1_cumulative_last] = CUMULATIVE_VOLUMES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
     Ok(CumulativePricesResponse {
         assets,
         total_share,
         cumulative_prices: config.cumulative_prices,
+        volume0_cumulative_last,
+        volume1_cumulative_last,
+        geo_twap: query_geo_twap(deps.storage, OBSERVATIONS)?,
     })
 }
 
@@ -986,66 +1288,60 @@ pub fn query_config(deps: Deps, env: Env) -> StdResult<ConfigResponse> {
         params: Some(to_json_binary(&StablePoolConfig {
             amp: Decimal::from_ratio(compute_current_amp(&config, &env)?, AMP_PRECISION),
             fee_share: config.fee_share,
+            max_swap_volume_per_block: config.max_swap_volume_per_block,
+            volume_limit_decay_blocks: config.volume_limit_decay_blocks,
         })?),
         owner: config.owner.unwrap_or(factory_config.owner),
         factory_addr: config.factory_addr,
         tracker_addr: config.tracker_addr,
+        liquidity_concentration: None,
+        is_paused: false,
+        estimated_annual_fee_rate: None,
     })
 }
 
-/// If `belief_price` and `max_spread` are both specified, we compute a new spread,
-/// otherwise we just use the swap spread to check `max_spread`.
-///
-/// * **belief_price** belief price used in the swap.
-///
-/// * **max_spread** max spread allowed so that the swap can be executed successfully.
-///
-/// * **offer_amount** amount of assets to swap.
-///
-/// * **return_amount** amount of assets to receive from the swap.
-///
-/// * **spread_amount** spread used in the swap.
-pub fn assert_max_spread(
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
-    offer_amount: Uint128,
-    return_amount: Uint128,
-    spread_amount: Uint128,
-) -> Result<(), ContractError> {
-    let default_spread = Decimal::from_str(DEFAULT_SLIPPAGE)?;
-    let max_allowed_spread = Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?;
-
-    let max_spread = max_spread.unwrap_or(default_spread);
-    if max_spread.gt(&max_allowed_spread) {
-        return Err(ContractError::AllowedSpreadAssertion {});
+/// Manages the contract migration.
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_version = get_contract_version(deps.storage)?;
+
+    if let Some(from_version) = msg.from_version {
+        ensure_eq!(
+            from_version,
+            contract_version.version,
+            ContractError::IncompatibleMigrationSource {
+                expected: from_version,
+                actual: contract_version.version,
+            }
+        );
     }
 
-    if let Some(belief_price) = belief_price {
-        let expected_return = offer_amount
-            * belief_price.inv().ok_or_else(|| {
-                ContractError::Std(StdError::generic_err(
-                    "Invalid belief_price. Check the input values.",
-                ))
-            })?;
-
-        let spread_amount = expected_return.saturating_sub(return_amount);
-
-        if return_amount < expected_return
-            && Decimal::from_ratio(spread_amount, expected_return) > max_spread
-        {
-            return Err(ContractError::MaxSpreadAssertion {});
+    ensure_eq!(
+        msg.to_version,
+        CONTRACT_VERSION,
+        ContractError::IncompatibleMigrationTarget {
+            expected: msg.to_version,
+            actual: CONTRACT_VERSION.to_string(),
         }
-    } else if Decimal::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
-        return Err(ContractError::MaxSpreadAssertion {});
+    );
+
+    // Versions prior to 4.0.0 use CW20 LP tokens and can not be migrated
+    match contract_version.contract.as_ref() {
+        "astroport-pair-stable" => match contract_version.version.as_ref() {
+            "4.0.0" | "4.0.1" => {}
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        _ => return Err(ContractError::MigrationError {}),
     }
 
-    Ok(())
-}
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
 
-/// Manages the contract migration.
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
-    unimplemented!("No safe path available for migration from cw20 to tokenfactory LP tokens")
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", &contract_version.contract)
+        .add_attribute("previous_contract_version", &contract_version.version)
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION))
 }
 
 /// Returns the total amount of assets in the pool as well as the total amount of LP tokens currently minted.
@@ -1098,7 +1394,12 @@ pub fn update_config(
 
             // Ensure the fee share isn't 0 and doesn't exceed the maximum allowed value
             if fee_share_bps == 0 || fee_share_bps > MAX_FEE_SHARE_BPS {
-                return Err(ContractError::FeeShareOutOfBounds {});
+                return Err(ContractError::InvalidFeeRate {
+                    rate: Decimal::from_ratio(fee_share_bps, 10000u16),
+                    field: "fee_share_bps".to_string(),
+                    min: Decimal::from_ratio(1u16, 10000u16),
+                    max: Decimal::from_ratio(MAX_FEE_SHARE_BPS, 10000u16),
+                });
             }
 
             // Set sharing config
@@ -1126,6 +1427,35 @@ pub fn update_config(
                 .attributes
                 .push(attr("action", "disable_fee_share"));
         }
+        StablePoolUpdateParams::SetSwapVolumeLimit {
+            max_swap_volume_per_block,
+            volume_limit_decay_blocks,
+        } => {
+            ensure!(
+                volume_limit_decay_blocks <= MAX_VOLUME_LIMIT_DECAY_BLOCKS,
+                ContractError::VolumeLimitDecayBlocksTooLarge {
+                    max: MAX_VOLUME_LIMIT_DECAY_BLOCKS
+                }
+            );
+
+            config.max_swap_volume_per_block = max_swap_volume_per_block;
+            config.volume_limit_decay_blocks = volume_limit_decay_blocks;
+            CONFIG.save(deps.storage, &config)?;
+
+            response.attributes.extend([
+                attr("action", "set_swap_volume_limit"),
+                attr(
+                    "max_swap_volume_per_block",
+                    max_swap_volume_per_block
+                        .map(|limit| limit.to_string())
+                        .unwrap_or_else(|| "none".to_string()),
+                ),
+                attr(
+                    "volume_limit_decay_blocks",
+                    volume_limit_decay_blocks.to_string(),
+                ),
+            ]);
+        }
     }
 
     Ok(response)
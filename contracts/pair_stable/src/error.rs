@@ -1,11 +1,13 @@
 use cosmwasm_std::{
-    CheckedMultiplyRatioError, ConversionOverflowError, OverflowError, StdError, Uint128,
+    CheckedMultiplyRatioError, ConversionOverflowError, Decimal, OverflowError, StdError, Uint128,
 };
 use cw_utils::PaymentError;
 use thiserror::Error;
 
-use astroport::{asset::MINIMUM_LIQUIDITY_AMOUNT, pair::MAX_FEE_SHARE_BPS};
+use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
+use astroport::pair::{MinOutNotMet, SpreadAssertionError};
 use astroport_circular_buffer::error::BufferError;
+use astroport_pair_common::error::{BlockVolumeLimitExceeded, LpAmountMismatch, VersionMismatch};
 
 use crate::math::{MAX_AMP, MAX_AMP_CHANGE, MIN_AMP_CHANGING_TIME};
 
@@ -27,9 +29,15 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Contract version mismatch: expected {expected}, got {got}")]
+    VersionMismatch { expected: String, got: String },
+
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
+    #[error("Operation non supported")]
+    NonSupported {},
+
     #[error("Doubling assets in asset infos")]
     DoublingAssets {},
 
@@ -45,6 +53,15 @@ pub enum ContractError {
     #[error("Operation exceeds max spread limit")]
     MaxSpreadAssertion {},
 
+    #[error("Swap returns {actual} which is less than the minimum output {expected}")]
+    MinOutNotMet { expected: Uint128, actual: Uint128 },
+
+    #[error("Swap volume {volume} exceeds the per block limit of {limit}")]
+    BlockVolumeLimitExceeded { volume: Uint128, limit: Uint128 },
+
+    #[error("Volume limit decay blocks must not exceed {max}")]
+    VolumeLimitDecayBlocksTooLarge { max: u64 },
+
     #[error("Native token balance mismatch between the argument and the transferred")]
     AssetMismatch {},
 
@@ -90,17 +107,25 @@ pub enum ContractError {
     #[error("Contract can't be migrated!")]
     MigrationError {},
 
+    #[error("Can't migrate from version {actual}, expected {expected}")]
+    IncompatibleMigrationSource { expected: String, actual: String },
+
+    #[error("Can't migrate to version {expected}, the new code has version {actual}")]
+    IncompatibleMigrationTarget { expected: String, actual: String },
+
     #[error("Initial liquidity must be more than {}", MINIMUM_LIQUIDITY_AMOUNT)]
     MinimumLiquidityAmountError {},
 
     #[error("Failed to parse or process reply message")]
     FailedToParseReply {},
 
-    #[error(
-        "Fee share is 0 or exceeds maximum allowed value of {} bps",
-        MAX_FEE_SHARE_BPS
-    )]
-    FeeShareOutOfBounds {},
+    #[error("{field} of {rate} is out of bounds: must be between {min} and {max}")]
+    InvalidFeeRate {
+        rate: Decimal,
+        field: String,
+        min: Decimal,
+        max: Decimal,
+    },
 
     #[error("Slippage is more than expected: received {0}, expected {1} LP tokens")]
     ProvideSlippageViolation(Uint128, Uint128),
@@ -114,6 +139,15 @@ pub enum ContractError {
 
     #[error("Wrong asset length: expected {expected}, actual {actual}")]
     WrongAssetLength { expected: usize, actual: usize },
+
+    #[error("Sent {sent} LP tokens but expected {expected}")]
+    LpAmountMismatch { sent: Uint128, expected: Uint128 },
+
+    #[error("Insufficient {asset} reserve for replacement: {actual}")]
+    InsufficientReplacementReserve { asset: String, actual: Uint128 },
+
+    #[error("No observation found at or before {timestamp}")]
+    ObservationNotFound { timestamp: u64 },
 }
 
 impl From<OverflowError> for ContractError {
@@ -127,3 +161,49 @@ impl From<ConversionOverflowError> for ContractError {
         StdError::from(o).into()
     }
 }
+
+impl From<LpAmountMismatch> for ContractError {
+    fn from(err: LpAmountMismatch) -> Self {
+        ContractError::LpAmountMismatch {
+            sent: err.sent,
+            expected: err.expected,
+        }
+    }
+}
+
+impl From<SpreadAssertionError> for ContractError {
+    fn from(err: SpreadAssertionError) -> Self {
+        match err {
+            SpreadAssertionError::Std(err) => ContractError::Std(err),
+            SpreadAssertionError::AllowedSpread => ContractError::AllowedSpreadAssertion {},
+            SpreadAssertionError::MaxSpread => ContractError::MaxSpreadAssertion {},
+        }
+    }
+}
+
+impl From<VersionMismatch> for ContractError {
+    fn from(err: VersionMismatch) -> Self {
+        ContractError::VersionMismatch {
+            expected: err.expected,
+            got: err.got,
+        }
+    }
+}
+
+impl From<MinOutNotMet> for ContractError {
+    fn from(err: MinOutNotMet) -> Self {
+        ContractError::MinOutNotMet {
+            expected: err.expected,
+            actual: err.actual,
+        }
+    }
+}
+
+impl From<BlockVolumeLimitExceeded> for ContractError {
+    fn from(err: BlockVolumeLimitExceeded) -> Self {
+        ContractError::BlockVolumeLimitExceeded {
+            volume: err.volume,
+            limit: err.limit,
+        }
+    }
+}
@@ -99,6 +99,8 @@ impl WasmMockQuerier {
                                 generator_address: None,
                                 whitelist_code_id: 0,
                                 coin_registry_address: Addr::unchecked("coin_registry"),
+                                pending_admin: None,
+                                pending_admin_accepts_at: None,
                             })
                             .into(),
                         ),
@@ -1,5 +1,5 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, DepsMut, StdResult, Storage, Uint128};
+use cosmwasm_std::{Addr, DepsMut, StdResult, Storage, Uint128, Uint256};
 use cw_storage_plus::{Item, Map};
 
 use astroport::asset::{AssetInfo, PairInfo};
@@ -35,6 +35,12 @@ pub struct Config {
     pub fee_share: Option<FeeShareConfig>,
     /// The tracker contract address
     pub tracker_addr: Option<Addr>,
+    /// The maximum swap volume of each asset (in that asset's units) accepted within a block
+    #[serde(default)]
+    pub max_swap_volume_per_block: Option<Uint128>,
+    /// The number of blocks over which previously swapped volume is linearly released
+    #[serde(default)]
+    pub volume_limit_decay_blocks: u64,
 }
 
 /// Circular buffer to store trade size observations
@@ -46,6 +52,9 @@ pub const CONFIG: Item<Config> = Item::new("config");
 /// Stores map of AssetInfo (as String) -> precision
 pub const PRECISIONS: Map<String, u8> = Map::new("precisions");
 
+/// Total swapped volume of each pool asset
+pub const CUMULATIVE_VOLUMES: Item<[Uint256; 2]> = Item::new("cumulative_volumes");
+
 /// Stores the latest contract ownership transfer proposal
 pub const OWNERSHIP_PROPOSAL: Item<OwnershipProposal> = Item::new("ownership_proposal");
 
@@ -1,8 +1,8 @@
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coin, from_json, to_json_binary, Addr, BankMsg, Binary, BlockInfo, Coin, CosmosMsg,
-    Decimal, DepsMut, Env, Reply, ReplyOn, Response, SubMsg, SubMsgResponse, SubMsgResult,
-    Timestamp, Uint128, WasmMsg,
+    attr, coin, from_json, to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal, Deps,
+    DepsMut, Reply, ReplyOn, Response, SubMsg, SubMsgResponse, SubMsgResult, Timestamp, Uint128,
+    WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use itertools::Itertools;
@@ -14,18 +14,20 @@ use astroport::asset::{native_asset, native_asset_info, Asset, AssetInfo};
 use astroport::common::LP_SUBDENOM;
 use astroport::observation::query_observation;
 use astroport::observation::Observation;
+use astroport::observation::ObservationResponse;
 use astroport::observation::OracleObservation;
 use astroport::pair::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
-    SimulationResponse, StablePoolParams,
+    assert_max_spread, ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse,
+    QueryMsg, SimulationResponse, StablePoolParams,
 };
+use astroport::test_utils::{make_cw20_asset, make_native_asset, mock_env_with_block_time};
 use astroport::token_factory::{MsgBurn, MsgCreateDenom, MsgCreateDenomResponse, MsgMint};
 use astroport_circular_buffer::BufferManager;
 use astroport_test::convert::f64_to_dec;
 
 use crate::contract::{
-    assert_max_spread, execute, instantiate, query, query_pool, query_reverse_simulation,
-    query_share, query_simulation, reply,
+    execute, instantiate, query, query_pool, query_reverse_simulation, query_share,
+    query_simulation, reply,
 };
 use crate::error::ContractError;
 use crate::mock_querier::mock_dependencies;
@@ -200,6 +202,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env();
@@ -211,6 +214,14 @@ fn provide_liquidity() {
         }],
     );
 
+    // Liquidity requests are not supported by the stableswap pair
+    let mut request_msg = msg.clone();
+    if let ExecuteMsg::ProvideLiquidity { request_id, .. } = &mut request_msg {
+        *request_id = Some(0);
+    }
+    let err = execute(deps.as_mut(), env.clone(), info.clone(), request_msg).unwrap_err();
+    assert_eq!(err, ContractError::NonSupported {});
+
     let res = execute(deps.as_mut(), env.clone().clone(), info, msg).unwrap();
     let transfer_from_msg = res.messages.get(0).expect("no message");
     let mint_min_liquidity_msg = res.messages.get(1).expect("no message");
@@ -326,6 +337,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -405,6 +417,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env();
@@ -482,6 +495,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -523,6 +537,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -731,7 +746,9 @@ fn try_native_to_token() {
         ask_asset_info: None,
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
+        min_out: None,
         to: None,
+        callback: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -888,7 +905,9 @@ fn try_token_to_native() {
         ask_asset_info: None,
         belief_price: None,
         max_spread: None,
+        min_out: None,
         to: None,
+        callback: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -903,6 +922,7 @@ fn try_token_to_native() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: None,
         })
         .unwrap(),
@@ -1001,6 +1021,7 @@ fn try_token_to_native() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: None,
         })
         .unwrap(),
@@ -1107,18 +1128,8 @@ fn test_query_pool() {
     assert_eq!(
         res.assets,
         [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: asset_0_amount
-            },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: Addr::unchecked("asset0000"),
-                },
-                amount: asset_1_amount
-            }
+            make_native_asset("uusd", asset_0_amount),
+            make_cw20_asset("asset0000", asset_1_amount)
         ]
     );
     assert_eq!(res.total_share, total_share_amount);
@@ -1200,6 +1211,9 @@ fn observations_full_buffer() {
             ts: env.block.time.seconds() + i * 1000,
             price: Default::default(),
             price_sma: Decimal::from_ratio(i, i * i),
+            price_geo_mean: Default::default(),
+            base_volume: Default::default(),
+            quote_volume: Default::default(),
         })
         .collect_vec();
     buffer.push_many(&array);
@@ -1230,6 +1244,61 @@ fn observations_full_buffer() {
     );
 }
 
+#[test]
+fn observation_at() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env_with_block_time(100_000);
+    BufferManager::init(&mut deps.storage, OBSERVATIONS, 20).unwrap();
+
+    let query_at = |deps: Deps, timestamp: u64| {
+        query(deps, env.clone(), QueryMsg::ObservationAt { timestamp })
+            .and_then(|res| from_json::<ObservationResponse>(&res))
+    };
+
+    let err = query_at(deps.as_ref(), 100_000).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: No observation found at or before 100000"
+    );
+
+    let mut buffer = BufferManager::new(&deps.storage, OBSERVATIONS).unwrap();
+    let array = (1..=30)
+        .map(|i| Observation {
+            ts: 100_000 + i * 1000,
+            price: Decimal::from_ratio(i, 1u8),
+            price_sma: Decimal::from_ratio(1u8, i),
+            price_geo_mean: Default::default(),
+            base_volume: Default::default(),
+            quote_volume: Default::default(),
+        })
+        .collect_vec();
+    buffer.push_many(&array);
+    buffer.commit(&mut deps.storage).unwrap();
+
+    let expected = |i: u64| ObservationResponse {
+        timestamp: 100_000 + i * 1000,
+        price: Decimal::from_ratio(i, 1u8),
+        price_sma: Decimal::from_ratio(1u8, i),
+    };
+
+    // Exact hits
+    assert_eq!(query_at(deps.as_ref(), 111_000).unwrap(), expected(11));
+    assert_eq!(query_at(deps.as_ref(), 125_000).unwrap(), expected(25));
+    assert_eq!(query_at(deps.as_ref(), 130_000).unwrap(), expected(30));
+
+    // The nearest preceding observation is returned
+    assert_eq!(query_at(deps.as_ref(), 125_999).unwrap(), expected(25));
+    assert_eq!(query_at(deps.as_ref(), 111_001).unwrap(), expected(11));
+    assert_eq!(query_at(deps.as_ref(), 200_000).unwrap(), expected(30));
+
+    // The buffer holds only the last 20 observations
+    let err = query_at(deps.as_ref(), 110_999).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Generic error: No observation found at or before 110999"
+    );
+}
+
 #[test]
 fn observations_incomplete_buffer() {
     let mut deps = mock_dependencies(&[]);
@@ -1248,6 +1317,9 @@ fn observations_incomplete_buffer() {
             ts: env.block.time.seconds() + i * 1000,
             price: Default::default(),
             price_sma: Decimal::from_ratio(i, i * i),
+            price_geo_mean: Default::default(),
+            base_volume: Default::default(),
+            quote_volume: Default::default(),
         })
         .collect_vec();
     buffer.push_many(&array);
@@ -1290,6 +1362,9 @@ fn observations_checking_triple_capacity_step_by_step() {
             ts: ts + i as u64 * 1000,
             price: Default::default(),
             price_sma: Decimal::from_ratio(i * i, i),
+            price_geo_mean: Default::default(),
+            base_volume: Default::default(),
+            quote_volume: Default::default(),
         })
         .collect_vec();
 
@@ -1326,16 +1401,6 @@ fn observations_checking_triple_capacity_step_by_step() {
     }
 }
 
-fn mock_env_with_block_time(time: u64) -> Env {
-    let mut env = mock_env();
-    env.block = BlockInfo {
-        height: 1,
-        time: Timestamp::from_seconds(time),
-        chain_id: "columbus".to_string(),
-    };
-    env
-}
-
 proptest! {
     #[test]
     fn constant_product_swap_no_fee(
@@ -12,9 +12,10 @@ use itertools::Itertools;
 
 use astroport::asset::{Asset, AssetInfo, Decimal256Ext, DecimalAsset, MINIMUM_LIQUIDITY_AMOUNT};
 use astroport::observation::{
-    safe_sma_buffer_not_full, safe_sma_calculation, Observation, PrecommitObservation,
+    safe_geo_sma_buffer_not_full, safe_geo_sma_calculation, safe_sma_buffer_not_full,
+    safe_sma_calculation, Observation, PrecommitObservation,
 };
-use astroport::pair::TWAP_PRECISION;
+use astroport::pair::{find_min_swap_amount, TWAP_PRECISION};
 use astroport::querier::query_factory_config;
 use astroport_circular_buffer::error::BufferResult;
 use astroport_circular_buffer::BufferManager;
@@ -34,8 +35,9 @@ pub(crate) fn check_asset_infos(
 
     asset_infos
         .iter()
-        .try_for_each(|asset_info| asset_info.check(api))
-        .map_err(Into::into)
+        .try_for_each(|asset_info| asset_info.validate(api))?;
+
+    Ok(())
 }
 
 /// Helper function to check that the assets in a given array are valid.
@@ -48,14 +50,16 @@ pub(crate) fn check_assets(api: &dyn Api, assets: &[Asset]) -> Result<(), Contra
 ///
 /// * **cw20_sender** is cw20 token address which is being checked.
 pub(crate) fn check_cw20_in_pool(config: &Config, cw20_sender: &Addr) -> Result<(), ContractError> {
-    for asset_info in &config.pair_info.asset_infos {
-        match asset_info {
-            AssetInfo::Token { contract_addr } if contract_addr == cw20_sender => return Ok(()),
-            _ => {}
-        }
+    if config
+        .pair_info
+        .asset_infos
+        .iter()
+        .any(|asset_info| asset_info.token_contract() == Some(cw20_sender))
+    {
+        Ok(())
+    } else {
+        Err(ContractError::Unauthorized {})
     }
-
-    Err(ContractError::Unauthorized {})
 }
 
 /// Select offer and ask pools based on given offer and ask infos.
@@ -269,7 +273,8 @@ pub(crate) fn compute_swap(
         token_precision,
     )?;
 
-    let return_amount = ask_pool.amount.to_uint128_with_precision(token_precision)? - new_ask_pool;
+    // The ask pool is rounded down so the return amount never exceeds the actual pool balance
+    let return_amount = ask_pool.to_uint_floor(token_precision)? - new_ask_pool;
     let offer_asset_amount = offer_asset
         .amount
         .to_uint128_with_precision(token_precision)?;
@@ -283,6 +288,47 @@ pub(crate) fn compute_swap(
     })
 }
 
+/// Returns the minimum amount of offered assets which can be swapped for a non-zero return.
+/// The commission is rounded down, so only the return before the commission has to be non-zero.
+///
+/// * **offer_pool** pool of offered asset.
+///
+/// * **ask_pool** asked asset.
+///
+/// * **pools** array with assets available in the pool.
+pub(crate) fn compute_min_swap_amount(
+    storage: &dyn Storage,
+    env: &Env,
+    config: &Config,
+    offer_pool: &DecimalAsset,
+    ask_pool: &DecimalAsset,
+    pools: &[DecimalAsset],
+) -> StdResult<Uint128> {
+    let offer_precision = get_precision(storage, &offer_pool.info)?;
+    let max_amount = offer_pool
+        .amount
+        .to_uint128_with_precision(offer_precision)?;
+
+    find_min_swap_amount(max_amount, |amount| {
+        let offer_asset = DecimalAsset {
+            info: offer_pool.info.clone(),
+            amount: Decimal256::with_precision(amount, offer_precision)?,
+        };
+        // Amounts for which the invariant can't be solved are not viable
+        Ok(compute_swap(
+            storage,
+            env,
+            config,
+            &offer_asset,
+            offer_pool,
+            ask_pool,
+            pools,
+        )
+        .map(|result| !result.return_amount.is_zero())
+        .unwrap_or(false))
+    })
+}
+
 /// Accumulate token prices for the assets in the pool.
 ///
 /// * **pools** array with assets available in the pool.
@@ -339,6 +385,11 @@ pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResu
         precommit_ts,
     }) = PrecommitObservation::may_load(storage)?
     {
+        // Dust swaps can round the quote amount down to zero. Such observations have no price
+        if quote_amount.is_zero() {
+            return Ok(());
+        }
+
         let mut buffer = BufferManager::new(storage, OBSERVATIONS)?;
         let observed_price = Decimal::from_ratio(base_amount, quote_amount);
 
@@ -346,6 +397,8 @@ pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResu
         if let Some(last_obs) = buffer.read_last(storage)? {
             // Skip saving observation if it has been already saved
             if last_obs.ts < precommit_ts {
+                let (base_volume, quote_volume) =
+                    last_obs.accumulate_volume(base_amount, quote_amount)?;
                 // Since this is circular buffer the next index contains the oldest value
                 let count = buffer.capacity();
                 if let Some(oldest_obs) = buffer.read_single(storage, buffer.head() + 1)? {
@@ -355,20 +408,34 @@ pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResu
                         count,
                         observed_price,
                     )?;
+                    let price_geo_mean = safe_geo_sma_calculation(
+                        last_obs.geo_mean(),
+                        oldest_obs.price,
+                        count,
+                        observed_price,
+                    )?;
                     new_observation = Observation {
                         ts: precommit_ts,
                         price: observed_price,
                         price_sma,
+                        price_geo_mean,
+                        base_volume,
+                        quote_volume,
                     };
                 } else {
                     // Buffer is not full yet
                     let count = buffer.head();
                     let price_sma =
                         safe_sma_buffer_not_full(last_obs.price_sma, count, observed_price)?;
+                    let price_geo_mean =
+                        safe_geo_sma_buffer_not_full(last_obs.geo_mean(), count, observed_price)?;
                     new_observation = Observation {
                         ts: precommit_ts,
                         price: observed_price,
                         price_sma,
+                        price_geo_mean,
+                        base_volume,
+                        quote_volume,
                     };
                 }
 
@@ -381,6 +448,9 @@ pub fn accumulate_swap_sizes(storage: &mut dyn Storage, env: &Env) -> BufferResu
                     ts: precommit_ts,
                     price: observed_price,
                     price_sma: observed_price,
+                    price_geo_mean: observed_price,
+                    base_volume: base_amount,
+                    quote_volume: quote_amount,
                 };
 
                 buffer.instant_push(storage, &new_observation)?
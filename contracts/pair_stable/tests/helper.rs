@@ -219,6 +219,7 @@ impl Helper {
             auto_stake: None,
             receiver: None,
             min_lp_to_receive,
+            request_id: None,
         };
 
         self.app
@@ -258,6 +259,7 @@ impl Helper {
                         ask_asset_info,
                         belief_price: None,
                         max_spread: None,
+                        min_out: None,
                         to: None,
                     })
                     .unwrap(),
@@ -279,7 +281,9 @@ impl Helper {
                     ask_asset_info,
                     belief_price: None,
                     max_spread: None,
+                    min_out: None,
                     to: None,
+                    callback: None,
                 };
 
                 self.app
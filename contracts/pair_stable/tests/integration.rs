@@ -548,6 +548,7 @@ fn provide_liquidity_msg(
         auto_stake: None,
         receiver,
         min_lp_to_receive,
+        request_id: None,
     };
 
     let coins = [
@@ -741,6 +742,7 @@ fn provide_lp_for_single_token() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: None,
         })
         .unwrap(),
@@ -774,6 +776,7 @@ fn provide_lp_for_single_token() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let err = app
@@ -803,6 +806,7 @@ fn provide_lp_for_single_token() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -828,6 +832,7 @@ fn provide_lp_for_single_token() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -861,6 +866,7 @@ fn provide_lp_for_single_token() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -873,6 +879,7 @@ fn provide_lp_for_single_token() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: None,
         })
         .unwrap(),
@@ -889,6 +896,7 @@ fn provide_lp_for_single_token() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: None,
         })
         .unwrap(),
@@ -1094,6 +1102,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -1113,6 +1122,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: Some(user.to_string()),
         })
         .unwrap(),
@@ -1637,7 +1647,12 @@ fn enable_disable_fee_sharing() {
             .unwrap_err()
             .downcast_ref::<ContractError>()
             .unwrap(),
-        &ContractError::FeeShareOutOfBounds {}
+        &ContractError::InvalidFeeRate {
+            rate: Decimal::from_ratio(MAX_FEE_SHARE_BPS + 1, 10000u16),
+            field: "fee_share_bps".to_string(),
+            min: Decimal::from_ratio(1u16, 10000u16),
+            max: Decimal::from_ratio(MAX_FEE_SHARE_BPS, 10000u16),
+        }
     );
 
     // Attemt to set fee sharing to 0
@@ -1655,7 +1670,12 @@ fn enable_disable_fee_sharing() {
             .unwrap_err()
             .downcast_ref::<ContractError>()
             .unwrap(),
-        &ContractError::FeeShareOutOfBounds {}
+        &ContractError::InvalidFeeRate {
+            rate: Decimal::from_ratio(0u16, 10000u16),
+            field: "fee_share_bps".to_string(),
+            min: Decimal::from_ratio(1u16, 10000u16),
+            max: Decimal::from_ratio(MAX_FEE_SHARE_BPS, 10000u16),
+        }
     );
 
     let fee_share_bps = 500; // 5%
@@ -1763,7 +1783,9 @@ fn check_observe_queries() {
         ask_asset_info: None,
         belief_price: None,
         max_spread: None,
+        min_out: None,
         to: None,
+        callback: None,
     };
     let send_funds = vec![Coin {
         denom: "uusd".to_owned(),
@@ -1958,6 +1980,7 @@ fn provide_liquidity_with_autostaking_to_generator() {
         auto_stake: Some(true),
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let coins = [
@@ -2446,6 +2469,7 @@ fn test_fee_share(
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     app.execute_contract(owner.clone(), pair_instance.clone(), &msg, &[])
@@ -2479,6 +2503,7 @@ fn test_fee_share(
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: Some(user.to_string()),
         })
         .unwrap(),
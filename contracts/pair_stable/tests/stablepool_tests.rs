@@ -1,15 +1,21 @@
 #![cfg(not(tarpaulin_include))]
 
-use cosmwasm_std::{Addr, Decimal, StdError};
+use cosmwasm_std::{coin, from_json, to_json_binary, Addr, Binary, Decimal, StdError, Uint128};
 use itertools::Itertools;
 use std::str::FromStr;
 
-use astroport::asset::AssetInfoExt;
+use astroport::asset::{native_asset_info, AssetInfoExt, PairInfo};
 use astroport::cosmwasm_ext::AbsDiff;
+use astroport::factory::{Config as FactoryConfig, PairType, QueryMsg as FactoryQueryMsg};
 use astroport::observation::OracleObservation;
+use astroport::pair::{
+    ConfigResponse, ExecuteMsg, MinSwapAmountResponse, QueryMsg, StablePoolConfig,
+    StablePoolParams, StablePoolUpdateParams, MAX_VOLUME_LIMIT_DECAY_BLOCKS,
+};
 use astroport_pair_stable::error::ContractError;
 use astroport_test::coins::TestCoin;
 use astroport_test::convert::f64_to_dec;
+use astroport_test::cw_multi_test::Executor;
 use helper::AppExtension;
 
 use crate::helper::Helper;
@@ -284,6 +290,44 @@ fn check_swaps() {
     assert_eq!(99_949011, helper.coin_balance(&test_coins[1], &user));
 }
 
+#[test]
+fn check_swap_callback_not_supported() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+
+    let err = helper
+        .app
+        .execute_contract(
+            user.clone(),
+            helper.pair_addr.clone(),
+            &ExecuteMsg::Swap {
+                offer_asset: offer_asset.clone(),
+                ask_asset_info: None,
+                belief_price: None,
+                max_spread: None,
+                min_out: None,
+                to: None,
+                callback: Some(Binary::from(b"callback")),
+            },
+            &[coin(100_000000u128, "uluna")],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::NonSupported {}, err.downcast().unwrap());
+}
+
 #[test]
 fn check_wrong_initializations() {
     let owner = Addr::unchecked("owner");
@@ -602,3 +646,286 @@ fn check_pool_prices() {
     );
     helper.app.next_block(10);
 }
+
+#[test]
+fn check_replace_asset() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let old_asset = helper.assets[&test_coins[1]].clone();
+    let new_asset = native_asset_info("ibc/usd".to_string());
+    let replace_msg = ExecuteMsg::ReplaceAsset {
+        old_asset: old_asset.clone(),
+        new_asset: new_asset.clone(),
+    };
+
+    let err = helper
+        .app
+        .execute_contract(
+            Addr::unchecked("random"),
+            helper.pair_addr.clone(),
+            &replace_msg,
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    // The new asset reserve wasn't transferred to the pair yet
+    let err = helper
+        .app
+        .execute_contract(owner.clone(), helper.pair_addr.clone(), &replace_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InsufficientReplacementReserve {
+            asset: "ibc/usd".to_string(),
+            actual: Uint128::zero()
+        },
+        err.downcast().unwrap()
+    );
+
+    let pair_addr = helper.pair_addr.clone();
+    helper
+        .app
+        .init_modules(|router, _, storage| {
+            router.bank.init_balance(
+                storage,
+                &pair_addr,
+                vec![coin(100_000_000000u128, "ibc/usd")],
+            )
+        })
+        .unwrap();
+
+    let owner_uusd_before = helper.native_balance("uusd", &owner);
+    helper
+        .app
+        .execute_contract(owner.clone(), helper.pair_addr.clone(), &replace_msg, &[])
+        .unwrap();
+
+    // The old asset reserve is sent to the owner
+    assert_eq!(
+        helper.native_balance("uusd", &owner) - owner_uusd_before,
+        100_000_000000u128
+    );
+    assert_eq!(helper.native_balance("uusd", &helper.pair_addr), 0);
+
+    // The factory registered the pair under its new assets
+    let factory_pair: PairInfo = helper
+        .app
+        .wrap()
+        .query_wasm_smart(
+            &helper.factory,
+            &FactoryQueryMsg::Pair {
+                asset_infos: vec![helper.assets[&test_coins[0]].clone(), new_asset.clone()],
+            },
+        )
+        .unwrap();
+    assert_eq!(factory_pair.contract_addr, helper.pair_addr);
+    helper
+        .app
+        .wrap()
+        .query_wasm_smart::<PairInfo>(
+            &helper.factory,
+            &FactoryQueryMsg::Pair {
+                asset_infos: vec![helper.assets[&test_coins[0]].clone(), old_asset.clone()],
+            },
+        )
+        .unwrap_err();
+
+    let pair_info: PairInfo = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Pair {})
+        .unwrap();
+    assert!(pair_info.asset_infos.contains(&new_asset));
+    assert!(!pair_info.asset_infos.contains(&old_asset));
+
+    // Swaps work with the new asset
+    let user = Addr::unchecked("user");
+    let offer_asset = helper.assets[&test_coins[0]].with_balance(100_000000u128);
+    helper.give_me_money(&[offer_asset.clone()], &user);
+    helper
+        .swap(&user, &offer_asset, Some(new_asset.clone()))
+        .unwrap();
+    assert!(helper.native_balance("ibc/usd", &user) > 0);
+
+    let err = helper
+        .app
+        .execute_contract(owner.clone(), helper.pair_addr.clone(), &replace_msg, &[])
+        .unwrap_err();
+    assert_eq!(
+        ContractError::InvalidAsset(old_asset.to_string()),
+        err.downcast().unwrap()
+    );
+}
+
+#[test]
+fn check_min_swap_amount() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20precise("FOO", 5)];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_00000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let resp: MinSwapAmountResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::MinSwapAmount {})
+        .unwrap();
+    assert_eq!(resp.min_amounts.len(), 2);
+
+    // One unit of FOO is worth ten units of uluna, so it always returns something
+    assert_eq!(
+        resp.min_amounts[1],
+        helper.assets[&test_coins[1]].with_balance(1u128)
+    );
+
+    for min_amount in resp.min_amounts {
+        let sim_resp = helper.simulate_swap(&min_amount, None).unwrap();
+        assert!(!sim_resp.return_amount.is_zero());
+
+        if min_amount.amount > Uint128::one() {
+            let below_min = min_amount
+                .info
+                .with_balance(min_amount.amount - Uint128::one());
+            let sim_resp = helper.simulate_swap(&below_min, None).unwrap();
+            assert!(sim_resp.return_amount.is_zero());
+        }
+    }
+}
+
+#[test]
+fn check_stable_default_params() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::cw20("USDC")];
+
+    let params: StablePoolParams =
+        from_json(FactoryConfig::default_params(&PairType::Stable {}).unwrap()).unwrap();
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), params.amp, Some(5)).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let user = Addr::unchecked("user");
+    for coin in &test_coins {
+        let offer_asset = helper.assets[coin].with_balance(1_000_000000u128);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None).unwrap();
+    }
+
+    let lp_amount = helper.native_balance(&helper.lp_token, &owner);
+    helper
+        .withdraw_liquidity(&owner, lp_amount, vec![], None)
+        .unwrap();
+}
+
+#[test]
+fn check_swap_volume_limit() {
+    let owner = Addr::unchecked("owner");
+
+    let test_coins = vec![TestCoin::native("uluna"), TestCoin::native("uusd")];
+
+    let mut helper = Helper::new(&owner, test_coins.clone(), 100u64, None).unwrap();
+
+    let assets = vec![
+        helper.assets[&test_coins[0]].with_balance(100_000_000000u128),
+        helper.assets[&test_coins[1]].with_balance(100_000_000000u128),
+    ];
+    helper.provide_liquidity(&owner, &assets, None).unwrap();
+
+    let set_limit = |volume_limit_decay_blocks: u64| ExecuteMsg::UpdateConfig {
+        params: to_json_binary(&StablePoolUpdateParams::SetSwapVolumeLimit {
+            max_swap_volume_per_block: Some(Uint128::new(100_000000)),
+            volume_limit_decay_blocks,
+        })
+        .unwrap(),
+    };
+
+    let err = helper
+        .app
+        .execute_contract(
+            Addr::unchecked("random"),
+            helper.pair_addr.clone(),
+            &set_limit(10),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(ContractError::Unauthorized {}, err.downcast().unwrap());
+
+    // The decay period is capped
+    let err = helper
+        .app
+        .execute_contract(
+            owner.clone(),
+            helper.pair_addr.clone(),
+            &set_limit(MAX_VOLUME_LIMIT_DECAY_BLOCKS + 1),
+            &[],
+        )
+        .unwrap_err();
+    assert_eq!(
+        ContractError::VolumeLimitDecayBlocksTooLarge {
+            max: MAX_VOLUME_LIMIT_DECAY_BLOCKS
+        },
+        err.downcast().unwrap()
+    );
+
+    helper
+        .app
+        .execute_contract(owner.clone(), helper.pair_addr.clone(), &set_limit(10), &[])
+        .unwrap();
+
+    let config: ConfigResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::Config {})
+        .unwrap();
+    let params: StablePoolConfig = from_json(config.params.unwrap()).unwrap();
+    assert_eq!(
+        params.max_swap_volume_per_block,
+        Some(Uint128::new(100_000000))
+    );
+    assert_eq!(params.volume_limit_decay_blocks, 10);
+
+    let user = Addr::unchecked("user");
+    let swap = |helper: &mut Helper, coin: &TestCoin, amount: u128| {
+        let offer_asset = helper.assets[coin].with_balance(amount);
+        helper.give_me_money(&[offer_asset.clone()], &user);
+        helper.swap(&user, &offer_asset, None)
+    };
+
+    swap(&mut helper, &test_coins[0], 60_000000).unwrap();
+    let err = swap(&mut helper, &test_coins[0], 60_000000).unwrap_err();
+    assert_eq!(
+        ContractError::BlockVolumeLimitExceeded {
+            volume: Uint128::new(120_000000),
+            limit: Uint128::new(100_000000),
+        },
+        err.downcast().unwrap()
+    );
+
+    // Volumes are tracked separately for every offer asset
+    swap(&mut helper, &test_coins[1], 100_000000).unwrap();
+
+    // The whole volume is released after the decay period
+    helper.app.update_block(|block| block.height += 10);
+    swap(&mut helper, &test_coins[0], 100_000000).unwrap();
+}
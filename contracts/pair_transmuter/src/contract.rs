@@ -1,20 +1,22 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, coin, ensure, ensure_eq, BankMsg, Coin, DepsMut, Empty, Env, MessageInfo, Reply,
-    Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, Uint128,
+    attr, coin, ensure, ensure_eq, BankMsg, Coin, DepsMut, Env, MessageInfo, Reply, Response,
+    StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult, Uint128,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 use cw_utils::{one_coin, PaymentError};
 use itertools::Itertools;
 
 use astroport::asset::{addr_opt_validate, Asset, AssetInfo, CoinsExt, PairInfo};
 use astroport::common::LP_SUBDENOM;
 use astroport::factory::PairType;
-use astroport::pair::{ExecuteMsg, InstantiateMsg};
+use astroport::pair::{assert_min_out, ExecuteMsg, InstantiateMsg, MigrateMsg};
 use astroport::token_factory::{
     tf_burn_msg, tf_create_denom_msg, tf_mint_msg, MsgCreateDenomResponse,
 };
+use astroport_pair_common::state::CODE_VERSION;
+use astroport_pair_common::utils::assert_code_version;
 
 use crate::error::ContractError;
 use crate::state::{Config, CONFIG};
@@ -43,6 +45,7 @@ pub fn instantiate(
     check_asset_infos(deps.api, &msg.asset_infos)?;
 
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
 
     let factory_addr = deps.api.addr_validate(&msg.factory_addr)?;
 
@@ -103,7 +106,15 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Reject messages if the stored code version differs from the compiled one
+    assert_code_version::<ContractError>(deps.storage, CONTRACT_VERSION)?;
+
     match msg {
+        // Liquidity requests are only supported by the constant product pair
+        ExecuteMsg::ProvideLiquidity {
+            request_id: Some(_),
+            ..
+        } => Err(ContractError::NotSupported {}),
         ExecuteMsg::ProvideLiquidity {
             assets,
             auto_stake,
@@ -121,8 +132,17 @@ pub fn execute(
             offer_asset,
             to,
             ask_asset_info,
+            min_out,
+            callback,
             ..
-        } => swap(deps, info, offer_asset, ask_asset_info, to),
+        } => {
+            // Swap callbacks are only supported by the constant product pair
+            if callback.is_some() {
+                return Err(ContractError::NotSupported {});
+            }
+
+            swap(deps, info, offer_asset, ask_asset_info, min_out, to)
+        }
         ExecuteMsg::WithdrawLiquidity { assets, .. } => withdraw_liquidity(deps, env, info, assets),
         _ => Err(ContractError::NotSupported {}),
     }
@@ -269,18 +289,23 @@ pub fn provide_liquidity(
 /// * **ask_asset_info** is the asset to be received after the swap operation.
 /// Must be set if the pool contains more than 2 assets.
 ///
+/// * **min_out** is the minimum amount of ask assets to receive.
+///
 /// * **to** sets the recipient of the swap operation.
 pub fn swap(
     deps: DepsMut,
     info: MessageInfo,
     offer_asset: Asset,
     ask_asset_info: Option<AssetInfo>,
+    min_out: Option<Uint128>,
     to: Option<String>,
 ) -> Result<Response, ContractError> {
     offer_asset.assert_sent_native_token_balance(&info)?;
 
     let return_asset = assert_and_swap(deps.as_ref(), &offer_asset, ask_asset_info)?;
 
+    assert_min_out(min_out, return_asset.amount)?;
+
     let receiver = addr_opt_validate(deps.api, &to)?.unwrap_or_else(|| info.sender.clone());
 
     let attrs = [
@@ -301,6 +326,44 @@ pub fn swap(
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: Empty) -> Result<Response, ContractError> {
-    unimplemented!("No safe path available for migration from cw20 to tokenfactory LP tokens")
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_version = get_contract_version(deps.storage)?;
+
+    if let Some(from_version) = msg.from_version {
+        ensure_eq!(
+            from_version,
+            contract_version.version,
+            ContractError::IncompatibleMigrationSource {
+                expected: from_version,
+                actual: contract_version.version,
+            }
+        );
+    }
+
+    ensure_eq!(
+        msg.to_version,
+        CONTRACT_VERSION,
+        ContractError::IncompatibleMigrationTarget {
+            expected: msg.to_version,
+            actual: CONTRACT_VERSION.to_string(),
+        }
+    );
+
+    // Versions prior to 1.1.0 use CW20 LP tokens and can not be migrated
+    match contract_version.contract.as_ref() {
+        "astroport-pair-transmuter" => match contract_version.version.as_ref() {
+            "1.1.0" | "1.1.1" | "1.1.2" => {}
+            _ => return Err(ContractError::MigrationError {}),
+        },
+        _ => return Err(ContractError::MigrationError {}),
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
+
+    Ok(Response::new()
+        .add_attribute("previous_contract_name", &contract_version.contract)
+        .add_attribute("previous_contract_version", &contract_version.version)
+        .add_attribute("new_contract_name", CONTRACT_NAME)
+        .add_attribute("new_contract_version", CONTRACT_VERSION))
 }
@@ -2,6 +2,9 @@ use cosmwasm_std::{CheckedFromRatioError, StdError, Uint128};
 use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
+use astroport::pair::MinOutNotMet;
+use astroport_pair_common::error::VersionMismatch;
+
 /// This enum describes pair contract errors
 #[derive(Error, Debug, PartialEq)]
 pub enum ContractError {
@@ -20,6 +23,18 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Contract version mismatch: expected {expected}, got {got}")]
+    VersionMismatch { expected: String, got: String },
+
+    #[error("Contract can't be migrated!")]
+    MigrationError {},
+
+    #[error("Can't migrate from version {actual}, expected {expected}")]
+    IncompatibleMigrationSource { expected: String, actual: String },
+
+    #[error("Can't migrate to version {expected}, the new code has version {actual}")]
+    IncompatibleMigrationTarget { expected: String, actual: String },
+
     #[error("Endpoint is not supported")]
     NotSupported {},
 
@@ -35,6 +50,9 @@ pub enum ContractError {
     #[error("Event of zero transfer")]
     InvalidZeroAmount {},
 
+    #[error("Swap returns {actual} which is less than the minimum output {expected}")]
+    MinOutNotMet { expected: Uint128, actual: Uint128 },
+
     #[error("Insufficient LP tokens. Required: {required}, available: {available}")]
     InsufficientLpTokens {
         required: Uint128,
@@ -57,3 +75,21 @@ pub enum ContractError {
     #[error("ask_asset_info must be set for pools with >2 assets")]
     AskAssetMustBeSet {},
 }
+
+impl From<VersionMismatch> for ContractError {
+    fn from(err: VersionMismatch) -> Self {
+        ContractError::VersionMismatch {
+            expected: err.expected,
+            got: err.got,
+        }
+    }
+}
+
+impl From<MinOutNotMet> for ContractError {
+    fn from(err: MinOutNotMet) -> Self {
+        ContractError::MinOutNotMet {
+            expected: err.expected,
+            actual: err.actual,
+        }
+    }
+}
@@ -1,14 +1,16 @@
-use cosmwasm_std::{entry_point, to_json_binary, Binary, Deps, Env, StdResult, Uint128};
+use cosmwasm_std::{entry_point, to_json_binary, Binary, Decimal, Deps, Env, StdResult, Uint128};
 
 use astroport::asset::{Asset, AssetInfo, AssetInfoExt};
+use astroport::gas_estimates::TRANSMUTER_SWAP;
 use astroport::pair::{
-    ConfigResponse, PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse,
+    ConfigResponse, MinSwapAmountResponse, PoolResponse, QueryMsg, ReverseSimulationResponse,
+    SimulationResponse,
 };
 use astroport::querier::query_factory_config;
 
 use crate::error::ContractError;
 use crate::state::{Config, CONFIG};
-use crate::utils::{assert_and_swap, get_share_in_assets, pool_info};
+use crate::utils::{assert_and_swap, compute_min_swap_amount, get_share_in_assets, pool_info};
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
@@ -23,10 +25,13 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
         } => {
             let return_asset = assert_and_swap(deps, &offer_asset, ask_asset_info)?;
 
+            // Transmuter swaps are always 1:1 thus there is no price impact
             Ok(to_json_binary(&SimulationResponse {
                 return_amount: return_asset.amount,
                 spread_amount: Uint128::zero(),
                 commission_amount: Uint128::zero(),
+                price_impact: Decimal::zero(),
+                gas_estimate: TRANSMUTER_SWAP.for_offer(&offer_asset.info),
             })?)
         }
         QueryMsg::ReverseSimulation {
@@ -39,8 +44,10 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
                 offer_amount,
                 spread_amount: Uint128::zero(),
                 commission_amount: Uint128::zero(),
+                price_impact: Decimal::zero(),
             })?)
         }
+        QueryMsg::MinSwapAmount {} => Ok(to_json_binary(&query_min_swap_amount(deps)?)?),
         _ => Err(ContractError::NotSupported {}),
     }
 }
@@ -54,6 +61,7 @@ pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
     let resp = PoolResponse {
         assets,
         total_share,
+        external_liquidity: vec![],
     };
 
     Ok(resp)
@@ -70,6 +78,9 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         owner: factory_config.owner,
         factory_addr: config.factory_addr,
         tracker_addr: None,
+        liquidity_concentration: None,
+        is_paused: false,
+        estimated_annual_fee_rate: None,
     })
 }
 
@@ -84,6 +95,38 @@ pub fn query_share(deps: Deps, amount: Uint128) -> Result<Vec<Asset>, ContractEr
     get_share_in_assets(&pools, amount, total_share)
 }
 
+/// Returns the minimum amount of each pool asset which converts to at least one unit of every
+/// other pool asset in a [`MinSwapAmountResponse`] object.
+pub fn query_min_swap_amount(deps: Deps) -> Result<MinSwapAmountResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let coeff = |asset_info: &AssetInfo| {
+        config
+            .norm_coeff
+            .get(&asset_info.to_string())
+            .copied()
+            .ok_or_else(|| ContractError::InvalidAsset(asset_info.to_string()))
+    };
+
+    let min_amounts = config
+        .pair_info
+        .asset_infos
+        .iter()
+        .map(|offer_info| {
+            let offer_coeff = coeff(offer_info)?;
+            let mut amount = Uint128::one();
+            for ask_info in config.pair_info.asset_infos.iter() {
+                if ask_info != offer_info {
+                    amount = amount.max(compute_min_swap_amount(offer_coeff, coeff(ask_info)?)?);
+                }
+            }
+            Ok(offer_info.with_balance(amount))
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+
+    Ok(MinSwapAmountResponse { min_amounts })
+}
+
 /// Returns the amount of offer_asset required to swap for a specific amount of ask_asset.
 /// offer_asset_info must be set if the pair contract contains more than 2 assets.
 pub fn reverse_swap(
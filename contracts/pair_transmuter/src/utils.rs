@@ -17,7 +17,7 @@ pub fn check_asset_infos(api: &dyn Api, asset_infos: &[AssetInfo]) -> Result<(),
         if !asset_info.is_native_token() {
             Err(ContractError::Cw20TokenNotSupported {})
         } else {
-            Ok(asset_info.check(api)?)
+            Ok(asset_info.validate(api)?)
         }
     })
 }
@@ -95,3 +95,16 @@ pub fn assert_and_swap(
         })
     }
 }
+
+/// Returns the minimum amount of offer assets which converts to at least one unit of ask assets.
+/// Transmuter swaps are 1:1 in normalized units, so the minimum depends only on the normalization
+/// coefficients and not on the pool reserves.
+pub fn compute_min_swap_amount(offer_coeff: Uint128, ask_coeff: Uint128) -> StdResult<Uint128> {
+    // offer_amount * offer_coeff / ask_coeff >= 1
+    let min_amount = ask_coeff.checked_div(offer_coeff)?;
+    if ask_coeff.checked_rem(offer_coeff)?.is_zero() {
+        Ok(min_amount)
+    } else {
+        Ok(min_amount + Uint128::one())
+    }
+}
@@ -229,6 +229,7 @@ impl Helper {
             auto_stake: None,
             receiver: None,
             min_lp_to_receive: None,
+            request_id: None,
         };
 
         self.app
@@ -268,6 +269,7 @@ impl Helper {
                         ask_asset_info,
                         belief_price: None,
                         max_spread: None,
+                        min_out: None,
                         to,
                     })
                     .unwrap(),
@@ -289,7 +291,9 @@ impl Helper {
                     ask_asset_info,
                     belief_price: None,
                     max_spread: None,
+                    min_out: None,
                     to,
+                    callback: None,
                 };
 
                 self.app
@@ -1,9 +1,10 @@
 use cosmwasm_std::{Addr, StdError};
 
 use astroport::asset::{Asset, AssetInfo, AssetInfoExt};
+use astroport::gas_estimates::TRANSMUTER_SWAP;
 use astroport::pair::{
-    ConfigResponse, CumulativePricesResponse, ExecuteMsg, QueryMsg, ReverseSimulationResponse,
-    SimulationResponse,
+    ConfigResponse, CumulativePricesResponse, ExecuteMsg, MinSwapAmountResponse, QueryMsg,
+    ReverseSimulationResponse, SimulationResponse,
 };
 use astroport_pair_transmuter::error::ContractError;
 use astroport_test::coins::TestCoin;
@@ -105,6 +106,7 @@ fn test_provide_and_withdraw() {
                 auto_stake: Some(true),
                 receiver: None,
                 min_lp_to_receive: None,
+                request_id: None,
             },
             &[
                 helper.assets[&test_coins[0]]
@@ -135,6 +137,7 @@ fn test_provide_and_withdraw() {
                 auto_stake: Some(false),
                 receiver: None,
                 min_lp_to_receive: None,
+                request_id: None,
             },
             &[
                 helper.assets[&test_coins[0]]
@@ -479,6 +482,7 @@ fn test_provide_liquidity_without_funds() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let err = helper
@@ -536,7 +540,10 @@ fn test_queries() {
             params: None,
             owner: owner.clone(),
             factory_addr: helper.factory.clone(),
-            tracker_addr: None
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     );
 
@@ -561,6 +568,8 @@ fn test_queries() {
             return_amount: 1_000000u128.into(),
             spread_amount: Default::default(),
             commission_amount: Default::default(),
+            price_impact: Default::default(),
+            gas_estimate: TRANSMUTER_SWAP.native,
         }
     );
 
@@ -607,6 +616,7 @@ fn test_queries() {
             offer_amount: 1_000000u128.into(),
             spread_amount: Default::default(),
             commission_amount: Default::default(),
+            price_impact: Default::default(),
         }
     );
 
@@ -659,7 +669,9 @@ fn test_drain_pool() {
                 ask_asset_info: None,
                 belief_price: None,
                 max_spread: None,
+                min_out: None,
                 to: None,
+                callback: None,
             },
             &[],
         )
@@ -813,6 +825,20 @@ fn test_different_decimals() {
             available: 100_000_000000u128.into(),
         }
     );
+
+    // One unit of usdt.eth converts to 0.01 units of usdt
+    let resp: MinSwapAmountResponse = helper
+        .app
+        .wrap()
+        .query_wasm_smart(&helper.pair_addr, &QueryMsg::MinSwapAmount {})
+        .unwrap();
+    assert_eq!(
+        resp.min_amounts,
+        vec![
+            helper.assets[&test_coins[0]].with_balance(1u128),
+            helper.assets[&test_coins[1]].with_balance(100u128),
+        ]
+    );
 }
 
 #[test]
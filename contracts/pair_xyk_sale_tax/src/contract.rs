@@ -7,9 +7,9 @@ use cosmwasm_schema::cw_serde;
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     attr, coin, coins, ensure_eq, from_json, to_json_binary, wasm_execute, Addr, BankMsg, Binary,
-    Coin, CosmosMsg, CustomMsg, CustomQuery, Decimal, Decimal256, Deps, DepsMut, Env, Fraction,
-    MessageInfo, QuerierWrapper, Reply, Response, StdError, StdResult, SubMsg, SubMsgResponse,
-    SubMsgResult, Uint128, Uint256, Uint64, WasmMsg,
+    Coin, CosmosMsg, CustomMsg, CustomQuery, Decimal, Decimal256, Deps, DepsMut, Env, MessageInfo,
+    QuerierWrapper, Reply, Response, StdError, StdResult, SubMsg, SubMsgResponse, SubMsgResult,
+    Uint128, Uint256, Uint64, WasmMsg,
 };
 use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
@@ -18,17 +18,19 @@ use cw_utils::{
 };
 
 use astroport::asset::{
-    addr_opt_validate, check_swap_parameters, Asset, AssetInfo, CoinsExt, PairInfo,
-    MINIMUM_LIQUIDITY_AMOUNT,
+    addr_opt_validate, check_swap_parameters, compute_pool_shares, Asset, AssetInfo, AssetInfoExt,
+    CoinsExt, PairInfo, MINIMUM_LIQUIDITY_AMOUNT,
 };
 use astroport::common::LP_SUBDENOM;
 use astroport::factory::PairType;
+use astroport::gas_estimates::XYK_SALE_TAX_SWAP;
 use astroport::incentives::ExecuteMsg as IncentiveExecuteMsg;
-use astroport::pair::{ConfigResponse, ReplyIds, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE};
 use astroport::pair::{
-    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
+    assert_max_spread, assert_min_out, calculate_price_impact, find_min_swap_amount,
+    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MinSwapAmountResponse,
+    PoolResponse, QueryMsg, ReverseSimulationResponse, SimulationResponse, TWAP_PRECISION,
 };
+use astroport::pair::{ConfigResponse, ReplyIds, DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE};
 use astroport::pair_xyk_sale_tax::{
     MigrateMsg, SaleTaxConfigUpdates, SaleTaxInitParams, TaxConfigChecked,
 };
@@ -38,11 +40,14 @@ use astroport::querier::{
 use astroport::token_factory::{
     tf_before_send_hook_msg, tf_burn_msg, tf_create_denom_msg, tf_mint_msg, MsgCreateDenomResponse,
 };
-use astroport::{tokenfactory_tracker, U256};
+use astroport::tokenfactory_tracker;
+use astroport_pair::contract::compute_min_swap_amount as xyk_compute_min_swap_amount;
 use astroport_pair::state::{Config as XykConfig, CONFIG as XYK_CONFIG};
+use astroport_pair_common::state::CODE_VERSION;
+use astroport_pair_common::utils::assert_code_version;
 
 use crate::error::ContractError;
-use crate::state::{Config, BALANCES, CONFIG};
+use crate::state::{Config, BALANCES, CONFIG, CUMULATIVE_VOLUMES};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = env!("CARGO_PKG_NAME");
@@ -60,13 +65,14 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
 
     // Validate asset infos
     if msg.asset_infos.len() != 2 {
         return Err(StdError::generic_err("asset_infos must contain exactly two elements").into());
     }
-    msg.asset_infos[0].check(deps.api)?;
-    msg.asset_infos[1].check(deps.api)?;
+    msg.asset_infos[0].validate(deps.api)?;
+    msg.asset_infos[1].validate(deps.api)?;
     if msg.asset_infos[0] == msg.asset_infos[1] {
         return Err(ContractError::DoublingAssets {});
     }
@@ -191,6 +197,7 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
                 .add_message(set_hook_msg)
                 .add_attribute("tracker_contract", contract_address))
         }
+        ReplyIds::FlashLoan => Err(ContractError::NonSupported {}),
     }
 }
 
@@ -213,6 +220,7 @@ pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractEr
 ///             offer_asset,
 ///             belief_price,
 ///             max_spread,
+///             min_out,
 ///             to,
 ///         }** Performs a swap operation with the specified parameters.
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -222,8 +230,16 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Reject messages if the stored code version differs from the compiled one
+    assert_code_version::<ContractError>(deps.storage, CONTRACT_VERSION)?;
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        // Liquidity requests are only supported by the constant product pair
+        ExecuteMsg::ProvideLiquidity {
+            request_id: Some(_),
+            ..
+        } => Err(ContractError::NonSupported {}),
         ExecuteMsg::ProvideLiquidity {
             assets,
             slippage_tolerance,
@@ -243,9 +259,15 @@ pub fn execute(
             offer_asset,
             belief_price,
             max_spread,
+            min_out,
             to,
+            callback,
             ..
         } => {
+            // Swap callbacks are only supported by the constant product pair
+            if callback.is_some() {
+                return Err(ContractError::NonSupported {});
+            }
             offer_asset.info.check(deps.api)?;
             if !offer_asset.is_native_token() {
                 return Err(ContractError::Cw20DirectSwap {});
@@ -261,6 +283,7 @@ pub fn execute(
                 offer_asset,
                 belief_price,
                 max_spread,
+                min_out,
                 to_addr,
             )
         }
@@ -283,20 +306,17 @@ pub fn receive_cw20(
         Cw20HookMsg::Swap {
             belief_price,
             max_spread,
+            min_out,
             to,
             ..
         } => {
             // Only asset contract can execute this message
-            let mut authorized = false;
             let config = CONFIG.load(deps.storage)?;
-
-            for pool in config.pair_info.asset_infos {
-                if let AssetInfo::Token { contract_addr, .. } = &pool {
-                    if contract_addr == info.sender {
-                        authorized = true;
-                    }
-                }
-            }
+            let authorized = config
+                .pair_info
+                .asset_infos
+                .iter()
+                .any(|asset_info| asset_info.token_contract() == Some(&info.sender));
 
             if !authorized {
                 return Err(ContractError::Unauthorized {});
@@ -316,9 +336,11 @@ pub fn receive_cw20(
                 },
                 belief_price,
                 max_spread,
+                min_out,
                 to_addr,
             )
         }
+        Cw20HookMsg::StakeAndProvide { .. } => Err(ContractError::NonSupported {}),
     }
 }
 
@@ -588,6 +610,8 @@ pub fn get_share_in_assets(pools: &[Asset], amount: Uint128, total_share: Uint12
 ///
 /// * **max_spread** sets the maximum spread of the swap operation.
 ///
+/// * **min_out** sets the minimum amount of ask assets to receive.
+///
 /// * **to** sets the recipient of the swap operation.
 ///
 /// NOTE - the address that wants to swap should approve the pair contract to pull the offer token.
@@ -600,6 +624,7 @@ pub fn swap(
     offer_asset: Asset,
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
+    min_out: Option<Uint128>,
     to: Option<Addr>,
 ) -> Result<Response, ContractError> {
     offer_asset.assert_sent_native_token_balance(&info)?;
@@ -663,6 +688,7 @@ pub fn swap(
         return_amount + commission_amount,
         spread_amount,
     )?;
+    assert_min_out(min_out, return_amount)?;
 
     let return_asset = Asset {
         info: ask_pool.info.clone(),
@@ -714,6 +740,19 @@ pub fn swap(
         )?;
     }
 
+    // Accumulate swapped volumes for the assets in the pool
+    let mut volumes = CUMULATIVE_VOLUMES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    let (offer_ind, ask_ind) = if offer_pool.info.equal(&pools[0].info) {
+        (0, 1)
+    } else {
+        (1, 0)
+    };
+    volumes[offer_ind] += Uint256::from(offer_amount);
+    volumes[ask_ind] += Uint256::from(return_amount);
+    CUMULATIVE_VOLUMES.save(deps.storage, &volumes)?;
+
     // Accumulate prices for the assets in the pool
     if let Some((price0_cumulative_new, price1_cumulative_new, block_time)) =
         accumulate_prices(env, &config, pools[0].amount, pools[1].amount)?
@@ -871,6 +910,9 @@ pub fn calculate_maker_fee(
 /// * **QueryMsg::SimulateProvide { assets, slippage_tolerance }** Returns the amount of LP tokens that will be minted
 ///
 /// * **QueryMsg::SimulateWithdraw { lp_amount }** Returns the amount of assets that could be withdrawn from the pool using a specific amount of LP tokens.
+///
+/// * **QueryMsg::MinSwapAmount {}** Returns the minimum viable swap amount of each pool asset
+/// using a [`MinSwapAmountResponse`] object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
@@ -894,6 +936,7 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             slippage_tolerance,
         } => to_json_binary(&query_simulate_provide(deps, assets, slippage_tolerance)?),
         QueryMsg::SimulateWithdraw { lp_amount } => to_json_binary(&query_share(deps, lp_amount)?),
+        QueryMsg::MinSwapAmount {} => to_json_binary(&query_min_swap_amount(deps)?),
         _ => Err(StdError::generic_err("Query is not supported")),
     }
 }
@@ -907,6 +950,7 @@ pub fn query_pool(deps: Deps) -> StdResult<PoolResponse> {
     let resp = PoolResponse {
         assets,
         total_share,
+        external_liquidity: vec![],
     };
 
     Ok(resp)
@@ -924,6 +968,30 @@ pub fn query_share(deps: Deps, amount: Uint128) -> StdResult<Vec<Asset>> {
     Ok(refund_assets)
 }
 
+/// Returns the minimum amount of each pool asset which can be swapped for a non-zero return
+/// in a [`MinSwapAmountResponse`] object.
+pub fn query_min_swap_amount(deps: Deps) -> StdResult<MinSwapAmountResponse> {
+    let config = CONFIG.load(deps.storage)?;
+
+    let pools = config
+        .pair_info
+        .query_pools(&deps.querier, &config.pair_info.contract_addr)?;
+
+    let min_amount = |offer_ind: usize| -> StdResult<Asset> {
+        let offer_pool = &pools[offer_ind];
+        let amount = compute_min_swap_amount(
+            offer_pool.amount,
+            pools[1 - offer_ind].amount,
+            config.tax_configs.get(&offer_pool.info.to_string()),
+        )?;
+        Ok(offer_pool.info.with_balance(amount))
+    };
+
+    Ok(MinSwapAmountResponse {
+        min_amounts: vec![min_amount(0)?, min_amount(1)?],
+    })
+}
+
 /// Returns information about a swap simulation in a [`SimulationResponse`] object.
 ///
 /// * **offer_asset** is the asset to swap as well as an amount of the said asset.
@@ -970,10 +1038,18 @@ pub fn query_simulation(deps: Deps, offer_asset: Asset) -> StdResult<SimulationR
         tax_config,
     )?;
 
+    let price_impact = calculate_price_impact(
+        Decimal256::checked_from_ratio(ask_pool.amount, offer_pool.amount).unwrap_or_default(),
+        Decimal256::from_ratio(offer_asset.amount, 1u8),
+        Decimal256::from_ratio(return_amount, 1u8),
+    )?;
+
     Ok(SimulationResponse {
         return_amount,
         spread_amount,
         commission_amount,
+        price_impact,
+        gas_estimate: XYK_SALE_TAX_SWAP.for_offer(&offer_asset.info),
     })
 }
 
@@ -1022,10 +1098,17 @@ pub fn query_reverse_simulation(
         tax_config,
     )?;
 
+    let price_impact = calculate_price_impact(
+        Decimal256::checked_from_ratio(ask_pool.amount, offer_pool.amount).unwrap_or_default(),
+        Decimal256::from_ratio(offer_amount, 1u8),
+        Decimal256::from_ratio(ask_asset.amount, 1u8),
+    )?;
+
     Ok(ReverseSimulationResponse {
         offer_amount,
         spread_amount,
         commission_amount,
+        price_impact,
     })
 }
 
@@ -1057,10 +1140,17 @@ pub fn query_cumulative_prices(deps: Deps, env: Env) -> StdResult<CumulativePric
         ),
     ];
 
+    let [volume0_cumulative_last, volume1_cumulative_last] = CUMULATIVE_VOLUMES
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+
     let resp = CumulativePricesResponse {
         assets,
         total_share,
         cumulative_prices,
+        volume0_cumulative_last,
+        volume1_cumulative_last,
+        geo_twap: None,
     };
 
     Ok(resp)
@@ -1082,6 +1172,9 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         owner: factory_config.owner,
         factory_addr: config.factory_addr,
         tracker_addr: config.tracker_addr,
+        liquidity_concentration: None,
+        is_paused: false,
+        estimated_annual_fee_rate: None,
     })
 }
 
@@ -1192,6 +1285,31 @@ pub fn compute_swap(
     })
 }
 
+/// Returns the minimum amount of offer assets which can be swapped for a non-zero return.
+/// The sale tax is deducted from the offer amount before the swap, so the XYK minimum is grossed up
+/// until the taxed offer amount still reaches it.
+///
+/// * **offer_pool** total amount of offer assets in the pool.
+///
+/// * **ask_pool** total amount of ask assets in the pool.
+///
+/// * **tax_config** tax configuration of the offer asset.
+pub fn compute_min_swap_amount(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    tax_config: Option<&TaxConfigChecked>,
+) -> StdResult<Uint128> {
+    let min_amount = xyk_compute_min_swap_amount(offer_pool, ask_pool)?;
+
+    match tax_config {
+        // The tax rate is capped at 50% so twice the untaxed minimum is always enough
+        Some(tax_config) => find_min_swap_amount(min_amount.checked_mul(2u8.into())?, |amount| {
+            Ok(amount - tax_config.tax_rate * amount >= min_amount)
+        }),
+        None => Ok(min_amount),
+    }
+}
+
 /// Returns an amount of offer assets for a specified amount of ask assets.
 ///
 /// * **offer_pool** total amount of offer assets in the pool.
@@ -1262,13 +1380,10 @@ pub fn calculate_shares(
 ) -> Result<Uint128, ContractError> {
     let share = if total_share.is_zero() {
         // Initial share = collateral amount
-        let share = Uint128::new(
-            (U256::from(deposits[0].u128()) * U256::from(deposits[1].u128()))
-                .integer_sqrt()
-                .as_u128(),
-        )
-        .checked_sub(MINIMUM_LIQUIDITY_AMOUNT)
-        .map_err(|_| ContractError::MinimumLiquidityAmountError {})?;
+        let share =
+            compute_pool_shares(*deposits, [pools[0].amount, pools[1].amount], total_share)?
+                .checked_sub(MINIMUM_LIQUIDITY_AMOUNT)
+                .map_err(|_| ContractError::MinimumLiquidityAmountError {})?;
 
         // share cannot become zero after minimum liquidity subtraction
         if share.is_zero() {
@@ -1285,10 +1400,7 @@ pub fn calculate_shares(
         // == deposit_0 * total_share / pool_0
         // 2. sqrt(deposit_1 * exchange_rate_1_to_0 * deposit_1) * (total_share / sqrt(pool_1 * pool_1))
         // == deposit_1 * total_share / pool_1
-        std::cmp::min(
-            deposits[0].multiply_ratio(total_share, pools[0].amount),
-            deposits[1].multiply_ratio(total_share, pools[1].amount),
-        )
+        compute_pool_shares(*deposits, [pools[0].amount, pools[1].amount], total_share)?
     };
     Ok(share)
 }
@@ -1329,52 +1441,6 @@ pub fn get_deposits_from_assets(
     Ok(deposits)
 }
 
-/// If `belief_price` and `max_spread` are both specified, we compute a new spread,
-/// otherwise we just use the swap spread to check `max_spread`.
-///
-/// * **belief_price** belief price used in the swap.
-///
-/// * **max_spread** max spread allowed so that the swap can be executed successfully.
-///
-/// * **offer_amount** amount of assets to swap.
-///
-/// * **return_amount** amount of assets to receive from the swap.
-///
-/// * **spread_amount** spread used in the swap.
-pub fn assert_max_spread(
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
-    offer_amount: Uint128,
-    return_amount: Uint128,
-    spread_amount: Uint128,
-) -> Result<(), ContractError> {
-    let default_spread = Decimal::from_str(DEFAULT_SLIPPAGE)?;
-    let max_allowed_spread = Decimal::from_str(MAX_ALLOWED_SLIPPAGE)?;
-
-    let max_spread = max_spread.unwrap_or(default_spread);
-    if max_spread.gt(&max_allowed_spread) {
-        return Err(ContractError::AllowedSpreadAssertion {});
-    }
-
-    if let Some(belief_price) = belief_price {
-        let expected_return = offer_amount
-            * belief_price
-                .inv()
-                .ok_or_else(|| StdError::generic_err("Belief price must not be zero!"))?;
-        let spread_amount = expected_return.saturating_sub(return_amount);
-
-        if return_amount < expected_return
-            && Decimal::from_ratio(spread_amount, expected_return) > max_spread
-        {
-            return Err(ContractError::MaxSpreadAssertion {});
-        }
-    } else if Decimal::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
-        return Err(ContractError::MaxSpreadAssertion {});
-    }
-
-    Ok(())
-}
-
 /// This is an internal function that enforces slippage tolerance for swaps.
 ///
 /// * **slippage_tolerance** slippage tolerance to enforce.
@@ -1455,6 +1521,7 @@ pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, Co
 
     // Set new cw2 data
     cw2::set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    CODE_VERSION.save(deps.storage, &CONTRACT_VERSION.to_string())?;
 
     Ok(Response::default().add_attributes([
         ("previous_contract_name", contract_version.contract.as_str()),
@@ -1,5 +1,7 @@
 use astroport::asset::MINIMUM_LIQUIDITY_AMOUNT;
-use cosmwasm_std::{OverflowError, StdError};
+use astroport::pair::{MinOutNotMet, SpreadAssertionError};
+use astroport_pair_common::error::VersionMismatch;
+use cosmwasm_std::{OverflowError, StdError, Uint128};
 use cw_utils::{ParseReplyError, PaymentError};
 use thiserror::Error;
 
@@ -18,6 +20,9 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Contract version mismatch: expected {expected}, got {got}")]
+    VersionMismatch { expected: String, got: String },
+
     #[error("CW20 tokens can be swapped via Cw20::Send message only")]
     Cw20DirectSwap {},
 
@@ -30,6 +35,9 @@ pub enum ContractError {
     #[error("Operation exceeds max spread limit")]
     MaxSpreadAssertion {},
 
+    #[error("Swap returns {actual} which is less than the minimum output {expected}")]
+    MinOutNotMet { expected: Uint128, actual: Uint128 },
+
     #[error("Provided spread amount exceeds allowed limit")]
     AllowedSpreadAssertion {},
 
@@ -63,3 +71,31 @@ impl From<OverflowError> for ContractError {
         StdError::from(o).into()
     }
 }
+
+impl From<SpreadAssertionError> for ContractError {
+    fn from(err: SpreadAssertionError) -> Self {
+        match err {
+            SpreadAssertionError::Std(err) => ContractError::Std(err),
+            SpreadAssertionError::AllowedSpread => ContractError::AllowedSpreadAssertion {},
+            SpreadAssertionError::MaxSpread => ContractError::MaxSpreadAssertion {},
+        }
+    }
+}
+
+impl From<VersionMismatch> for ContractError {
+    fn from(err: VersionMismatch) -> Self {
+        ContractError::VersionMismatch {
+            expected: err.expected,
+            got: err.got,
+        }
+    }
+}
+
+impl From<MinOutNotMet> for ContractError {
+    fn from(err: MinOutNotMet) -> Self {
+        ContractError::MinOutNotMet {
+            expected: err.expected,
+            actual: err.actual,
+        }
+    }
+}
@@ -3,7 +3,7 @@ use astroport::{
     pair_xyk_sale_tax::TaxConfigs,
 };
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{Addr, Uint128, Uint256};
 use cw_storage_plus::{Item, SnapshotMap};
 
 /// This structure stores the main config parameters for a constant product pair contract.
@@ -32,6 +32,9 @@ pub struct Config {
 /// Stores the config struct at the given key
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Total swapped volume of each pool asset
+pub const CUMULATIVE_VOLUMES: Item<[Uint256; 2]> = Item::new("cumulative_volumes");
+
 /// Stores asset balances to query them later at any block height
 pub const BALANCES: SnapshotMap<&AssetInfo, Uint128> = SnapshotMap::new(
     "balances",
@@ -1,8 +1,7 @@
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coin, to_json_binary, Addr, BankMsg, Binary, BlockInfo, Coin, CosmosMsg, Decimal,
-    DepsMut, Env, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgResponse, SubMsgResult,
-    Timestamp, Uint128, WasmMsg,
+    attr, coin, to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal, DepsMut, Reply,
+    ReplyOn, Response, StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 use proptest::prelude::*;
@@ -11,15 +10,17 @@ use prost::Message;
 use astroport::asset::{Asset, AssetInfo, PairInfo};
 use astroport::common::LP_SUBDENOM;
 use astroport::factory::PairType;
+use astroport::pair::assert_max_spread;
 use astroport::pair::{
     Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
     SimulationResponse, TWAP_PRECISION,
 };
 use astroport::pair_xyk_sale_tax::{SaleTaxInitParams, TaxConfigChecked, TaxConfigsChecked};
+use astroport::test_utils::{make_cw20_asset, make_native_asset, mock_env_with_block_time};
 use astroport::token_factory::{MsgBurn, MsgCreateDenom, MsgCreateDenomResponse, MsgMint};
 
 use crate::contract::{
-    accumulate_prices, assert_max_spread, compute_swap, execute, instantiate, query_pool,
+    accumulate_prices, compute_min_swap_amount, compute_swap, execute, instantiate, query_pool,
     query_reverse_simulation, query_share, query_simulation, reply,
 };
 use crate::contract::{compute_offer_amount, SwapResult};
@@ -180,6 +181,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env();
@@ -303,6 +305,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -382,6 +385,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env();
@@ -446,6 +450,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -488,6 +493,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -530,6 +536,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -571,6 +578,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let env = mock_env_with_block_time(env.block.time.seconds() + 1000);
@@ -602,6 +610,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
     let info = mock_info(
         "addr0001",
@@ -632,6 +641,7 @@ fn provide_liquidity() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
     let info = mock_info(
         "addr0001",
@@ -828,7 +838,9 @@ fn try_native_to_token() {
         ask_asset_info: None,
         belief_price: None,
         max_spread: Some(Decimal::percent(50)),
+        min_out: None,
         to: None,
+        callback: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info(
@@ -1057,7 +1069,9 @@ fn try_token_to_native() {
         ask_asset_info: None,
         belief_price: None,
         max_spread: None,
+        min_out: None,
         to: None,
+        callback: None,
     };
     let env = mock_env_with_block_time(1000);
     let info = mock_info("addr0000", &[]);
@@ -1072,6 +1086,7 @@ fn try_token_to_native() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: Some(Decimal::percent(50)),
+            min_out: None,
             to: None,
         })
         .unwrap(),
@@ -1195,6 +1210,7 @@ fn try_token_to_native() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: None,
         })
         .unwrap(),
@@ -1304,18 +1320,8 @@ fn test_query_pool() {
     assert_eq!(
         res.assets,
         [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: asset_0_amount
-            },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: Addr::unchecked("asset0000"),
-                },
-                amount: asset_1_amount
-            }
+            make_native_asset("uusd", asset_0_amount),
+            make_cw20_asset("asset0000", asset_1_amount)
         ]
     );
     assert_eq!(res.total_share, total_share_amount);
@@ -1495,14 +1501,38 @@ fn test_accumulate_prices() {
     }
 }
 
-fn mock_env_with_block_time(time: u64) -> Env {
-    let mut env = mock_env();
-    env.block = BlockInfo {
-        height: 1,
-        time: Timestamp::from_seconds(time),
-        chain_id: "columbus".to_string(),
-    };
-    env
+#[test]
+fn compute_min_swap_amount_with_tax() {
+    let offer_pool = Uint128::from(5_000_000_000_000_u128);
+    let ask_pool = Uint128::from(1_000_000_000_u128);
+    let offer_asset = |amount: u128| Asset::new(AssetInfo::native("uusd"), amount);
+    let tax_config = TaxConfigChecked::default();
+
+    let min_amount = compute_min_swap_amount(offer_pool, ask_pool, None).unwrap();
+    assert_eq!(min_amount, Uint128::new(5001));
+
+    // 5% of the offer amount is taxed before the swap
+    let min_amount = compute_min_swap_amount(offer_pool, ask_pool, Some(&tax_config)).unwrap();
+    assert_eq!(min_amount, Uint128::new(5264));
+
+    let swap_result = compute_swap(
+        offer_pool,
+        ask_pool,
+        &offer_asset(min_amount.u128()),
+        Decimal::permille(3),
+        Some(&tax_config),
+    )
+    .unwrap();
+    assert_eq!(swap_result.return_amount, Uint128::one());
+    let swap_result = compute_swap(
+        offer_pool,
+        ask_pool,
+        &offer_asset(min_amount.u128() - 1),
+        Decimal::permille(3),
+        Some(&tax_config),
+    )
+    .unwrap();
+    assert_eq!(swap_result.return_amount, Uint128::zero());
 }
 
 #[test]
@@ -442,7 +442,10 @@ fn test_provide_and_withdraw_liquidity() {
             params: Some(to_json_binary(&SaleTaxInitParams::default()).unwrap()),
             owner,
             factory_addr: config.factory_addr,
-            tracker_addr: config.tracker_addr
+            tracker_addr: config.tracker_addr,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     )
 }
@@ -472,6 +475,7 @@ fn provide_liquidity_msg(
         auto_stake: None,
         receiver,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let coins = [
@@ -757,6 +761,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: Some(user.to_string()),
         })
         .unwrap(),
@@ -788,6 +793,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     app.execute_contract(
@@ -806,6 +812,7 @@ fn test_compatibility_of_tokens_with_different_precision() {
             ask_asset_info: None,
             belief_price: None,
             max_spread: None,
+            min_out: None,
             to: Some(user.to_string()),
         })
         .unwrap(),
@@ -1281,7 +1288,9 @@ fn asset_balances_tracking_works_correctly() {
         ask_asset_info: None,
         belief_price: None,
         max_spread: None,
+        min_out: None,
         to: None,
+        callback: None,
     };
     let send_funds = vec![Coin {
         denom: "uusd".to_owned(),
@@ -1447,7 +1456,10 @@ fn update_pair_config() {
             params: Some(to_json_binary(&SaleTaxInitParams::default()).unwrap()),
             owner: Addr::unchecked("owner"),
             factory_addr: Addr::unchecked("contract0"),
-            tracker_addr: None
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     );
 }
@@ -1535,7 +1547,10 @@ fn update_tax_configs() {
             params: Some(to_json_binary(&SaleTaxInitParams::default()).unwrap()),
             owner: Addr::unchecked("owner"),
             factory_addr: Addr::unchecked("contract0"),
-            tracker_addr: None
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     );
 
@@ -1577,7 +1592,10 @@ fn update_tax_configs() {
             ),
             owner: Addr::unchecked("owner"),
             factory_addr: Addr::unchecked("contract0"),
-            tracker_addr: None
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     );
 
@@ -1628,7 +1646,10 @@ fn update_tax_configs() {
             ),
             owner: Addr::unchecked("owner"),
             factory_addr: Addr::unchecked("contract0"),
-            tracker_addr: None
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     );
 }
@@ -1796,6 +1817,7 @@ fn provide_liquidity_with_autostaking_to_generator() {
         auto_stake: Some(true),
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let coins = [
@@ -1947,6 +1969,7 @@ fn test_tracker_contract() {
         auto_stake: None,
         receiver: None,
         min_lp_to_receive: None,
+        request_id: None,
     };
 
     let coins = [
@@ -2199,7 +2222,10 @@ fn test_migrate_from_standard_xyk(old_version: &str) {
             params: Some(to_json_binary(&SaleTaxInitParams::default()).unwrap()),
             owner,
             factory_addr: config.factory_addr,
-            tracker_addr: None
+            tracker_addr: None,
+            liquidity_concentration: None,
+            is_paused: false,
+            estimated_annual_fee_rate: None,
         }
     )
 }
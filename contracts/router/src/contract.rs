@@ -6,10 +6,13 @@ use cw2::{get_contract_version, set_contract_version};
 use cw20::Cw20ReceiveMsg;
 
 use astroport::asset::{addr_opt_validate, Asset, AssetInfo};
-use astroport::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
+use astroport::pair::{
+    PoolResponse, QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse,
+};
 use astroport::querier::query_pair_info;
 use astroport::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, HopSimulation, InstantiateMsg, MigrateMsg,
+    MultiHopSimulationResponse, QueryMsg, ReverseMultiHopSimulationResponse,
     SimulateSwapOperationsResponse, SwapOperation, SwapResponseData, MAX_SWAP_OPERATIONS,
 };
 
@@ -233,6 +236,14 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
 ///             offer_amount,
 ///             operations,
 ///         }** Simulates one or multiple swap operations and returns the end result in a [`SimulateSwapOperationsResponse`] object.
+/// * **QueryMsg::SimulateMultiHopSwap {
+///             offer_asset,
+///             operations,
+///         }** Simulates swap operations and returns the result of every hop in a [`MultiHopSimulationResponse`] object.
+/// * **QueryMsg::SimulateReverseMultiHopSwap {
+///             ask_asset,
+///             operations,
+///         }** Reverse simulates swap operations and returns the result of every hop in a [`ReverseMultiHopSimulationResponse`] object.
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
@@ -245,6 +256,20 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
             offer_amount,
             operations,
         )?)?),
+        QueryMsg::SimulateMultiHopSwap {
+            offer_asset,
+            operations,
+        } => Ok(to_json_binary(&simulate_multi_hop_swap(
+            deps,
+            offer_asset,
+            operations,
+        )?)?),
+        QueryMsg::SimulateReverseMultiHopSwap {
+            ask_asset,
+            operations,
+        } => Ok(to_json_binary(&simulate_reverse_multi_hop_swap(
+            deps, ask_asset, operations,
+        )?)?),
     }
 }
 
@@ -335,6 +360,173 @@ fn simulate_swap_operations(
     })
 }
 
+/// Returns the end result of a simulation for swap operations along with the result of every hop
+/// using a [`MultiHopSimulationResponse`] object.
+///
+/// * **offer_asset** the asset offered to the first swap operation.
+///
+/// * **operations** is a vector that contains objects of type [`SwapOperation`].
+/// These are all the swap operations for which we perform a simulation.
+fn simulate_multi_hop_swap(
+    deps: Deps,
+    offer_asset: Asset,
+    operations: Vec<SwapOperation>,
+) -> Result<MultiHopSimulationResponse, ContractError> {
+    assert_operations(deps.api, &operations)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut offer_asset = offer_asset;
+    let mut spread_amount = Uint128::zero();
+    let mut hops = Vec::with_capacity(operations.len());
+
+    for operation in operations {
+        let SwapOperation::AstroSwap {
+            offer_asset_info,
+            ask_asset_info,
+        } = operation
+        else {
+            return Err(ContractError::NativeSwapNotSupported {});
+        };
+
+        let pair = query_hop_pair(
+            deps,
+            &config.astroport_factory,
+            &offer_asset_info,
+            &ask_asset_info,
+        )?;
+
+        let res: SimulationResponse = deps.querier.query_wasm_smart(
+            &pair,
+            &PairQueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: offer_asset_info,
+                    amount: offer_asset.amount,
+                },
+                ask_asset_info: Some(ask_asset_info.clone()),
+            },
+        )?;
+
+        let return_asset = Asset {
+            info: ask_asset_info,
+            amount: res.return_amount,
+        };
+        spread_amount = spread_amount.checked_add(res.spread_amount)?;
+        hops.push(HopSimulation {
+            pair,
+            offer_asset,
+            return_asset: return_asset.clone(),
+            spread_amount: res.spread_amount,
+            commission_amount: res.commission_amount,
+        });
+
+        offer_asset = return_asset;
+    }
+
+    Ok(MultiHopSimulationResponse {
+        return_amount: offer_asset.amount,
+        spread_amount,
+        hops,
+    })
+}
+
+/// Returns the amount to offer for receiving `ask_asset` from swap operations along with the
+/// result of every hop using a [`ReverseMultiHopSimulationResponse`] object.
+/// The operations are simulated backwards starting from the last one.
+///
+/// * **ask_asset** the asset to receive from the last swap operation.
+///
+/// * **operations** is a vector that contains objects of type [`SwapOperation`].
+/// These are all the swap operations for which we perform a simulation.
+fn simulate_reverse_multi_hop_swap(
+    deps: Deps,
+    ask_asset: Asset,
+    operations: Vec<SwapOperation>,
+) -> Result<ReverseMultiHopSimulationResponse, ContractError> {
+    assert_operations(deps.api, &operations)?;
+
+    let config = CONFIG.load(deps.storage)?;
+    let mut ask_asset = ask_asset;
+    let mut spread_amount = Uint128::zero();
+    let mut hops = Vec::with_capacity(operations.len());
+
+    for operation in operations.into_iter().rev() {
+        let SwapOperation::AstroSwap {
+            offer_asset_info,
+            ask_asset_info,
+        } = operation
+        else {
+            return Err(ContractError::NativeSwapNotSupported {});
+        };
+
+        let pair = query_hop_pair(
+            deps,
+            &config.astroport_factory,
+            &offer_asset_info,
+            &ask_asset_info,
+        )?;
+
+        let res: ReverseSimulationResponse = deps.querier.query_wasm_smart(
+            &pair,
+            &PairQueryMsg::ReverseSimulation {
+                offer_asset_info: Some(offer_asset_info.clone()),
+                ask_asset: Asset {
+                    info: ask_asset_info,
+                    amount: ask_asset.amount,
+                },
+            },
+        )?;
+
+        let offer_asset = Asset {
+            info: offer_asset_info,
+            amount: res.offer_amount,
+        };
+        spread_amount = spread_amount.checked_add(res.spread_amount)?;
+        hops.push(HopSimulation {
+            pair,
+            offer_asset: offer_asset.clone(),
+            return_asset: ask_asset,
+            spread_amount: res.spread_amount,
+            commission_amount: res.commission_amount,
+        });
+
+        ask_asset = offer_asset;
+    }
+
+    hops.reverse();
+
+    Ok(ReverseMultiHopSimulationResponse {
+        offer_amount: ask_asset.amount,
+        spread_amount,
+        hops,
+    })
+}
+
+/// Returns the address of the pair performing a swap operation.
+/// Pairs without liquidity are rejected as their simulations can't be threaded to the next hop.
+fn query_hop_pair(
+    deps: Deps,
+    astroport_factory: &Addr,
+    offer_asset_info: &AssetInfo,
+    ask_asset_info: &AssetInfo,
+) -> Result<Addr, ContractError> {
+    let pair_info = query_pair_info(
+        &deps.querier,
+        astroport_factory,
+        &[offer_asset_info.clone(), ask_asset_info.clone()],
+    )?;
+
+    let pool: PoolResponse = deps
+        .querier
+        .query_wasm_smart(&pair_info.contract_addr, &PairQueryMsg::Pool {})?;
+    if pool.assets.iter().any(|asset| asset.amount.is_zero()) {
+        return Err(ContractError::NoLiquidityInPath {
+            pair: pair_info.contract_addr.to_string(),
+        });
+    }
+
+    Ok(pair_info.contract_addr)
+}
+
 /// Validates swap operations.
 ///
 /// * **operations** is a vector that contains objects of type [`SwapOperation`]. These are all the swap operations we check.
@@ -41,6 +41,9 @@ pub enum ContractError {
     #[error("Native swap operations are not supported!")]
     NativeSwapNotSupported {},
 
+    #[error("Pair {pair} in the swap path has no liquidity")]
+    NoLiquidityInPath { pair: String },
+
     #[error("Contract can't be migrated!")]
     MigrationError {},
 }
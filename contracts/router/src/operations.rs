@@ -110,7 +110,9 @@ pub fn asset_into_swap_msg(
                 ask_asset_info: Some(ask_asset_info),
                 belief_price,
                 max_spread,
+                min_out: None,
                 to,
+                callback: None,
             })?,
         })),
         AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -123,6 +125,7 @@ pub fn asset_into_swap_msg(
                     ask_asset_info: Some(ask_asset_info),
                     belief_price,
                     max_spread,
+                    min_out: None,
                     to,
                 })?,
             })?,
@@ -1,8 +1,8 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_json, to_json_binary, Addr, Binary, Coin, ContractResult, Empty, OwnedDeps, Querier,
-    QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+    from_json, to_json_binary, Addr, Binary, Coin, ContractResult, Decimal, Empty, OwnedDeps,
+    Querier, QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
 use std::collections::HashMap;
 
@@ -138,7 +138,7 @@ impl WasmMockQuerier {
                 match self.astroport_factory_querier.pairs.get(&key) {
                     Some(v) => SystemResult::Ok(ContractResult::from(to_json_binary(&PairInfo {
                         contract_addr: Addr::unchecked(v),
-                        liquidity_token: Addr::unchecked("liquidity"),
+                        liquidity_token: "liquidity".to_string(),
                         asset_infos: vec![
                             AssetInfo::NativeToken {
                                 denom: "uusd".to_string(),
@@ -160,6 +160,8 @@ impl WasmMockQuerier {
                     return_amount: offer_asset.amount,
                     commission_amount: Uint128::zero(),
                     spread_amount: Uint128::zero(),
+                    price_impact: Decimal::zero(),
+                    gas_estimate: 0,
                 })))
             }
         }
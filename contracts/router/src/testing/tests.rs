@@ -350,6 +350,7 @@ fn execute_swap_operation() {
                         ask_asset_info: Some(native_asset_info("uusd".to_string())),
                         belief_price: None,
                         max_spread: None,
+                        min_out: None,
                         to: Some(String::from("addr0000")),
                     })
                     .unwrap()
@@ -3,7 +3,7 @@
 use cosmwasm_std::{coins, from_json, to_json_binary, Addr, Empty, StdError};
 use cw20::Cw20ExecuteMsg;
 
-use astroport::asset::{native_asset_info, token_asset_info};
+use astroport::asset::{native_asset_info, token_asset_info, AssetInfoExt};
 use astroport::factory::PairType;
 use astroport::router::{ExecuteMsg, InstantiateMsg, SwapOperation, SwapResponseData};
 use astroport_router::error::ContractError;
@@ -893,3 +893,184 @@ fn test_swap_route() {
     let profit = balance_res.balance.saturating_sub(donated_atom);
     println!("Attacker2's profit: {:?}", profit);
 }
+
+#[test]
+fn simulate_multi_hop_swap() {
+    use astroport::router::{
+        MultiHopSimulationResponse, QueryMsg, ReverseMultiHopSimulationResponse,
+        SimulateSwapOperationsResponse,
+    };
+
+    let mut app = mock_app();
+    let owner = Addr::unchecked("owner");
+    let mut helper = FactoryHelper::init(&mut app, &owner);
+
+    let astro = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "astro", None);
+    let inj = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "inj", None);
+    let osmo = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "osmo", None);
+    let atom = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "atom", None);
+    let ntrn = instantiate_token(&mut app, helper.cw20_token_code_id, &owner, "ntrn", None);
+
+    let mut pairs = vec![];
+    for (a, b, liq) in [
+        (&astro, &inj, 100_000_000000),
+        (&inj, &osmo, 50_000_000000),
+        (&osmo, &atom, 10_000_000000),
+    ] {
+        let pair = helper
+            .create_pair(
+                &mut app,
+                &owner,
+                PairType::Xyk {},
+                [token_asset_info(a.clone()), token_asset_info(b.clone())],
+                None,
+            )
+            .unwrap();
+        mint(&mut app, &owner, a, liq, &pair).unwrap();
+        mint(&mut app, &owner, b, liq, &pair).unwrap();
+        pairs.push(pair);
+    }
+
+    // The atom/ntrn pair has no liquidity
+    let empty_pair = helper
+        .create_pair(
+            &mut app,
+            &owner,
+            PairType::Xyk {},
+            [
+                token_asset_info(atom.clone()),
+                token_asset_info(ntrn.clone()),
+            ],
+            None,
+        )
+        .unwrap();
+
+    let router_code = app.store_code(router_contract());
+    let router = app
+        .instantiate_contract(
+            router_code,
+            owner.clone(),
+            &InstantiateMsg {
+                astroport_factory: helper.factory.to_string(),
+            },
+            &[],
+            "router",
+            None,
+        )
+        .unwrap();
+
+    let operations = [(&astro, &inj), (&inj, &osmo), (&osmo, &atom)]
+        .into_iter()
+        .map(|(offer, ask)| SwapOperation::AstroSwap {
+            offer_asset_info: token_asset_info(offer.clone()),
+            ask_asset_info: token_asset_info(ask.clone()),
+        })
+        .collect::<Vec<_>>();
+    let offer_asset = token_asset_info(astro.clone()).with_balance(1_000_000000u128);
+
+    let res: MultiHopSimulationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateMultiHopSwap {
+                offer_asset: offer_asset.clone(),
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+
+    // The result matches the end amount of the existing simulation
+    let simulate_res: SimulateSwapOperationsResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateSwapOperations {
+                offer_amount: offer_asset.amount,
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(res.return_amount, simulate_res.amount);
+
+    // Every hop offers the return of the previous one
+    assert_eq!(res.hops.len(), 3);
+    assert_eq!(res.hops[0].offer_asset, offer_asset);
+    for (hop, pair) in res.hops.iter().zip(&pairs) {
+        assert_eq!(&hop.pair, pair);
+    }
+    for window in res.hops.windows(2) {
+        assert_eq!(window[0].return_asset, window[1].offer_asset);
+    }
+    assert_eq!(
+        res.hops[2].return_asset,
+        token_asset_info(atom.clone()).with_balance(res.return_amount)
+    );
+    assert_eq!(
+        res.spread_amount,
+        res.hops.iter().map(|hop| hop.spread_amount).sum()
+    );
+
+    // Reverse simulation of the received amount requires the same offer up to rounding
+    let reverse_res: ReverseMultiHopSimulationResponse = app
+        .wrap()
+        .query_wasm_smart(
+            &router,
+            &QueryMsg::SimulateReverseMultiHopSwap {
+                ask_asset: token_asset_info(atom.clone()).with_balance(res.return_amount),
+                operations: operations.clone(),
+            },
+        )
+        .unwrap();
+    assert_eq!(reverse_res.hops.len(), 3);
+    assert_eq!(reverse_res.hops[0].pair, pairs[0]);
+    assert_eq!(
+        reverse_res.hops[0].offer_asset.amount,
+        reverse_res.offer_amount
+    );
+    for window in reverse_res.hops.windows(2) {
+        assert_eq!(window[0].return_asset, window[1].offer_asset);
+    }
+    assert!(
+        reverse_res.offer_amount.abs_diff(offer_asset.amount).u128() <= 10,
+        "{} != {}",
+        reverse_res.offer_amount,
+        offer_asset.amount
+    );
+
+    // A hop through a pair without liquidity fails in both directions
+    let mut operations = operations;
+    operations.push(SwapOperation::AstroSwap {
+        offer_asset_info: token_asset_info(atom.clone()),
+        ask_asset_info: token_asset_info(ntrn.clone()),
+    });
+    let expected_err = StdError::generic_err(format!(
+        "Querier contract error: {}",
+        ContractError::NoLiquidityInPath {
+            pair: empty_pair.to_string()
+        }
+    ));
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<MultiHopSimulationResponse>(
+            &router,
+            &QueryMsg::SimulateMultiHopSwap {
+                offer_asset,
+                operations: operations.clone(),
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, expected_err);
+
+    let err = app
+        .wrap()
+        .query_wasm_smart::<ReverseMultiHopSimulationResponse>(
+            &router,
+            &QueryMsg::SimulateReverseMultiHopSwap {
+                ask_asset: token_asset_info(ntrn.clone()).with_balance(1_000000u128),
+                operations,
+            },
+        )
+        .unwrap_err();
+    assert_eq!(err, expected_err);
+}
@@ -1078,6 +1078,7 @@ impl Helper {
             auto_stake: Some(auto_stake),
             receiver: None,
             min_lp_to_receive: None,
+            request_id: None,
         };
 
         self.app
@@ -18,6 +18,7 @@ use crate::pair::QueryMsg as PairQueryMsg;
 use crate::querier::{
     query_balance, query_token_balance, query_token_precision, query_token_symbol,
 };
+use crate::U256;
 
 /// UST token denomination
 pub const UUSD_DENOM: &str = "uusd";
@@ -47,10 +48,15 @@ pub struct DecimalAsset {
 impl DecimalAsset {
     pub fn into_asset(self, precision: impl Into<u32> + Sized) -> StdResult<Asset> {
         Ok(Asset {
+            amount: self.to_uint_floor(precision)?,
             info: self.info,
-            amount: self.amount.to_uint(precision)?,
         })
     }
+
+    /// Converts the amount to an integer with the given precision rounding down.
+    pub fn to_uint_floor(&self, precision: impl Into<u32>) -> StdResult<Uint128> {
+        Ok(self.amount.to_uint(precision)?)
+    }
 }
 
 impl fmt::Display for Asset {
@@ -467,6 +473,12 @@ impl AssetInfo {
         native_asset_info(denom.into())
     }
 
+    /// Returns an [`AssetInfo`] object representing the Token Factory denom `factory/{creator}/{subdenom}`.
+    /// Token Factory tokens are native, thus they are handled the same way as any other native asset.
+    pub fn token_factory<A: Into<String>, B: Into<String>>(creator: A, subdenom: B) -> Self {
+        native_asset_info(format!("factory/{}/{}", creator.into(), subdenom.into()))
+    }
+
     /// Returns an [`AssetInfo`] object representing the address of a CW20 token contract.
     pub fn cw20(contract_addr: Addr) -> Self {
         token_asset_info(contract_addr)
@@ -488,6 +500,14 @@ impl AssetInfo {
         }
     }
 
+    /// Returns the CW20 contract address if the caller is a CW20 token. Otherwise returns None.
+    pub fn token_contract(&self) -> Option<&Addr> {
+        match self {
+            AssetInfo::Token { contract_addr } => Some(contract_addr),
+            AssetInfo::NativeToken { .. } => None,
+        }
+    }
+
     /// Checks whether the native coin is IBCed token or not.
     pub fn is_ibc(&self) -> bool {
         match self {
@@ -496,6 +516,16 @@ impl AssetInfo {
         }
     }
 
+    /// Checks whether the native coin is a Token Factory token (`factory/{creator}/{subdenom}`) or not.
+    pub fn is_token_factory(&self) -> bool {
+        match self {
+            AssetInfo::NativeToken { denom } => {
+                denom.starts_with("factory/") && denom.split('/').count() >= 3
+            }
+            AssetInfo::Token { .. } => false,
+        }
+    }
+
     /// Returns the balance of token in a pool.
     ///
     /// * **pool_addr** is the address of the contract whose token balance we check.
@@ -550,18 +580,27 @@ impl AssetInfo {
         }
     }
 
-    /// Checks that the tokens' denom or contract addr is valid.
+    /// Checks that the tokens' denom or contract addr is valid. Same as [`AssetInfo::validate`].
     pub fn check(&self, api: &dyn Api) -> StdResult<()> {
+        self.validate(api)
+    }
+
+    /// Checks that the tokens' denom or contract addr is valid.
+    pub fn validate(&self, api: &dyn Api) -> StdResult<()> {
+        self.to_checked(api).map(|_| ())
+    }
+
+    /// Returns a copy of the asset info with a validated contract address or denom.
+    pub fn to_checked(&self, api: &dyn Api) -> StdResult<AssetInfo> {
         match self {
-            AssetInfo::Token { contract_addr } => {
-                api.addr_validate(contract_addr.as_str())?;
-            }
+            AssetInfo::Token { contract_addr } => Ok(AssetInfo::Token {
+                contract_addr: api.addr_validate(contract_addr.as_str())?,
+            }),
             AssetInfo::NativeToken { denom } => {
                 validate_native_denom(denom)?;
+                Ok(self.clone())
             }
         }
-
-        Ok(())
     }
 }
 
@@ -610,6 +649,30 @@ pub struct PairInfo {
 }
 
 impl PairInfo {
+    /// Returns true if the pair uses the constant product invariant `x * y = k`.
+    /// Liquidity is spread over the whole price range and the price moves with every trade.
+    pub fn is_xyk_pair(&self) -> bool {
+        matches!(self.pair_type, PairType::Xyk {})
+    }
+
+    /// Returns true if the pair uses the StableSwap invariant. It behaves like a constant sum curve
+    /// around the peg, weighted by the amplification coefficient, and falls back to constant product
+    /// when the pool gets imbalanced.
+    pub fn is_stable_pair(&self) -> bool {
+        matches!(self.pair_type, PairType::Stable {})
+    }
+
+    /// Returns true if the pair uses the CryptoSwap invariant. Liquidity is concentrated around
+    /// the internal price scale, which is repegged towards the oracle price as the pool earns fees.
+    pub fn is_concentrated_pair(&self) -> bool {
+        match &self.pair_type {
+            PairType::Custom(pair_type) => {
+                pair_type == "concentrated" || pair_type == "concentrated_inj_orderbook"
+            }
+            _ => false,
+        }
+    }
+
     /// Returns the balance for each asset in the pool.
     ///
     /// * **contract_addr** is pair's pool address.
@@ -779,6 +842,41 @@ pub fn check_swap_parameters(pools: Vec<Uint128>, swap_amount: Uint128) -> StdRe
     Ok(())
 }
 
+/// Returns the amount of LP tokens minted for a deposit into a constant product pool.
+/// If no LP tokens were minted yet, the share is `sqrt(deposit_0 * deposit_1)`.
+/// Otherwise it is `min(deposit_0 / reserve_0, deposit_1 / reserve_1) * total_supply`.
+///
+/// * **deposit** deposited amounts of both pool assets.
+///
+/// * **reserves** amounts of both pool assets before the deposit.
+///
+/// * **total_supply** total amount of LP tokens currently minted.
+pub fn compute_pool_shares(
+    deposit: [Uint128; 2],
+    reserves: [Uint128; 2],
+    total_supply: Uint128,
+) -> StdResult<Uint128> {
+    if total_supply.is_zero() {
+        let share = (U256::from(deposit[0].u128()) * U256::from(deposit[1].u128())).integer_sqrt();
+        return Ok(Uint128::new(share.as_u128()));
+    }
+
+    if reserves.iter().any(|reserve| reserve.is_zero()) {
+        return Err(StdError::generic_err("One of the pools is empty"));
+    }
+
+    let share = deposit
+        .iter()
+        .zip(reserves.iter())
+        .map(|(deposit, reserve)| {
+            Uint256::from(*deposit) * Uint256::from(total_supply) / Uint256::from(*reserve)
+        })
+        .min()
+        .unwrap_or_default();
+
+    Ok(share.try_into()?)
+}
+
 /// Trait extension for AssetInfo to produce [`Asset`] objects from [`AssetInfo`].
 pub trait AssetInfoExt {
     fn with_balance(&self, balance: impl Into<Uint128>) -> Asset;
@@ -876,8 +974,9 @@ impl Decimal256Ext for Decimal256 {
 
 #[cfg(test)]
 mod tests {
-    use cosmwasm_std::testing::mock_info;
-    use cosmwasm_std::{coin, coins};
+    use cosmwasm_std::testing::{mock_dependencies_with_balances, mock_info, MockApi};
+    use cosmwasm_std::{coin, coins, from_json, to_json_string, Empty};
+    use std::str::FromStr;
     use test_case::test_case;
 
     use super::*;
@@ -1080,6 +1179,140 @@ mod tests {
         validate_native_denom("factory/wasm1jdppe6fnj2q7hjsepty5crxtrryzhuqsjrj95y/uusd").unwrap();
     }
 
+    #[test]
+    fn test_token_factory_asset_info() {
+        let info = AssetInfo::token_factory("creator", "uusd");
+        assert_eq!(info, AssetInfo::native("factory/creator/uusd"));
+        assert!(info.is_native_token());
+        assert!(info.is_token_factory());
+        assert!(!AssetInfo::native("uusd").is_token_factory());
+        assert!(!AssetInfo::native("factory/uusd").is_token_factory());
+        assert!(!AssetInfo::native("ibc/EBD5A24C554198EBAF").is_token_factory());
+        assert!(!AssetInfo::cw20_unchecked("factory").is_token_factory());
+
+        // Token Factory denoms keep the native token JSON shape
+        let json = r#"{"native_token":{"denom":"factory/creator/uusd"}}"#;
+        assert_eq!(to_json_string(&info).unwrap(), json);
+        assert_eq!(from_json::<AssetInfo>(json).unwrap(), info);
+
+        let deps = mock_dependencies_with_balances(&[(
+            "pool",
+            coins(1000, "factory/creator/uusd").as_slice(),
+        )]);
+        let querier = QuerierWrapper::<Empty>::new(&deps.querier);
+        assert_eq!(
+            info.query_pool(&querier, "pool").unwrap(),
+            Uint128::new(1000)
+        );
+
+        let asset = info.with_balance(1_500000u128);
+        assert_eq!(
+            asset.to_decimal_asset(6u8).unwrap().amount,
+            Decimal256::from_str("1.5").unwrap()
+        );
+        assert_eq!(
+            asset.as_coin().unwrap(),
+            coin(1_500000, "factory/creator/uusd")
+        );
+    }
+
+    #[test]
+    fn test_asset_info_validate() {
+        let api = MockApi::default();
+
+        let info = AssetInfo::cw20_unchecked("contract0000");
+        assert_eq!(info.to_checked(&api).unwrap(), info);
+        AssetInfo::native("uusd").validate(&api).unwrap();
+
+        AssetInfo::cw20_unchecked("a").validate(&api).unwrap_err();
+        AssetInfo::native("").validate(&api).unwrap_err();
+        AssetInfo::native("1usd").validate(&api).unwrap_err();
+    }
+
+    #[test]
+    fn test_decimal_asset_rounding() {
+        let asset = native_asset_info("uusd".to_string())
+            .with_dec_balance(Decimal256::from_str("1.0000005").unwrap());
+        assert_eq!(asset.to_uint_floor(6u8).unwrap(), Uint128::new(1_000000));
+
+        // Exact amounts are not rounded
+        assert_eq!(asset.to_uint_floor(7u8).unwrap(), Uint128::new(10_000005));
+
+        // The amount doesn't fit into Uint128
+        let asset = native_asset_info("uusd".to_string())
+            .with_dec_balance(Decimal256::from_ratio(Uint128::MAX, 1u8) + Decimal256::one());
+        asset.to_uint_floor(6u8).unwrap_err();
+    }
+
+    #[test]
+    fn test_compute_pool_shares() {
+        // Initial deposit
+        let share = compute_pool_shares(
+            [Uint128::new(400), Uint128::new(100)],
+            [Uint128::zero(), Uint128::zero()],
+            Uint128::zero(),
+        )
+        .unwrap();
+        assert_eq!(share, Uint128::new(200));
+
+        // The smaller share of the deposit wins
+        let share = compute_pool_shares(
+            [Uint128::new(100), Uint128::new(300)],
+            [Uint128::new(1_000), Uint128::new(2_000)],
+            Uint128::new(500),
+        )
+        .unwrap();
+        assert_eq!(share, Uint128::new(50));
+
+        // Intermediate values don't overflow
+        let share = compute_pool_shares(
+            [Uint128::MAX, Uint128::MAX],
+            [Uint128::MAX, Uint128::MAX],
+            Uint128::MAX,
+        )
+        .unwrap();
+        assert_eq!(share, Uint128::MAX);
+
+        let err = compute_pool_shares(
+            [Uint128::new(100), Uint128::new(100)],
+            [Uint128::zero(), Uint128::new(100)],
+            Uint128::new(100),
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("One of the pools is empty"));
+
+        // The result doesn't fit into Uint128
+        compute_pool_shares(
+            [Uint128::MAX, Uint128::MAX],
+            [Uint128::one(), Uint128::one()],
+            Uint128::new(2),
+        )
+        .unwrap_err();
+    }
+
+    #[test]
+    fn test_pair_info_pair_type() {
+        let pair_info = |pair_type| PairInfo {
+            asset_infos: vec![],
+            contract_addr: Addr::unchecked("pair"),
+            liquidity_token: "lp".to_string(),
+            pair_type,
+        };
+
+        assert!(pair_info(PairType::Xyk {}).is_xyk_pair());
+        assert!(pair_info(PairType::Stable {}).is_stable_pair());
+        assert!(pair_info(PairType::Custom("concentrated".to_string())).is_concentrated_pair());
+        assert!(
+            pair_info(PairType::Custom("concentrated_inj_orderbook".to_string()))
+                .is_concentrated_pair()
+        );
+
+        let transmuter = pair_info(PairType::Custom("transmuter".to_string()));
+        assert!(!transmuter.is_xyk_pair());
+        assert!(!transmuter.is_stable_pair());
+        assert!(!transmuter.is_concentrated_pair());
+    }
+
     #[test]
     fn test_native_asset_info() {
         let info = AssetInfo::native("uusd");
@@ -1248,4 +1481,13 @@ mod tests {
             asset_cw20.try_into().unwrap()
         )
     }
+
+    #[test]
+    fn test_token_contract() {
+        assert_eq!(
+            mock_cw20().info.token_contract(),
+            Some(&Addr::unchecked("mock_token"))
+        );
+        assert_eq!(mock_native().info.token_contract(), None);
+    }
 }
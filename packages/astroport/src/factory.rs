@@ -1,11 +1,15 @@
 use crate::asset::{AssetInfo, PairInfo};
+use crate::pair::{StablePoolParams, XYKPoolParams};
+use crate::pair_concentrated::ConcentratedPoolParams;
 
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Addr, Binary};
+use cosmwasm_std::{to_json_binary, Addr, Binary, Decimal, StdError, StdResult, Uint128};
 use std::fmt::{Display, Formatter, Result};
 
 const MAX_TOTAL_FEE_BPS: u16 = 10_000;
 const MAX_MAKER_FEE_BPS: u16 = 10_000;
+/// The minimum delay between proposing and accepting a new factory admin, 1 day
+pub const MIN_ADMIN_TRANSFER_DELAY: u64 = 86400;
 
 /// This structure holds the main contract parameters.
 #[cw_serde]
@@ -24,6 +28,44 @@ pub struct Config {
     pub coin_registry_address: Addr,
 }
 
+impl Config {
+    /// Returns default `init_params` for a new pair of the given type:
+    /// * **xyk** - "high-fee volatile" preset, meant to be paired with a 30 bps total fee;
+    /// * **stable** - "low-fee stable" preset for pegged assets, meant to be paired with a 5 bps total fee;
+    /// * **concentrated** - "concentrated default" preset for volatile assets with a dynamic fee.
+    ///
+    /// Other custom pair types have no presets.
+    pub fn default_params(pair_type: &PairType) -> StdResult<Binary> {
+        match pair_type {
+            PairType::Xyk {} => to_json_binary(&XYKPoolParams {
+                track_asset_balances: None,
+            }),
+            PairType::Stable {} => to_json_binary(&StablePoolParams {
+                amp: 100,
+                owner: None,
+            }),
+            PairType::Custom(custom) if custom == "concentrated" => {
+                to_json_binary(&ConcentratedPoolParams {
+                    amp: Decimal::from_ratio(40u8, 1u8),
+                    gamma: Decimal::from_ratio(145u8, 1_000_000u32),
+                    mid_fee: Decimal::from_ratio(26u8, 10_000u16),
+                    out_fee: Decimal::from_ratio(45u8, 10_000u16),
+                    fee_gamma: Decimal::from_ratio(23u8, 100_000u32),
+                    repeg_profit_threshold: Decimal::from_ratio(2u8, 1_000_000u32),
+                    min_price_scale_delta: Decimal::from_ratio(146u8, 1_000_000u32),
+                    price_scale: Decimal::one(),
+                    ma_half_time: 600,
+                    track_asset_balances: None,
+                    fee_share: None,
+                })
+            }
+            PairType::Custom(_) => Err(StdError::generic_err(format!(
+                "No default params for pair type {pair_type}"
+            ))),
+        }
+    }
+}
+
 /// This enum describes available pair types.
 /// ## Available pool types
 /// ```
@@ -125,6 +167,12 @@ pub enum ExecuteMsg {
         /// The address of the contract that contains the coins and their accuracy
         coin_registry_address: Option<String>,
     },
+    /// Sets the address governance fees are sent to (the Maker).
+    /// The address must be able to receive funds.
+    SetFeeRecipient {
+        /// The new fee recipient
+        recipient: String,
+    },
     UpdateTrackerConfig {
         /// Tracking contract code id
         tracker_code_id: u64,
@@ -150,18 +198,38 @@ pub enum ExecuteMsg {
         /// The assets for which we deregister a pool
         asset_infos: Vec<AssetInfo>,
     },
+    /// Registers a pair under its current assets after one of them was replaced.
+    /// Only the pair registered under `old_asset_infos` can execute this
+    UpdatePairKey {
+        /// The assets the pair was registered with
+        old_asset_infos: Vec<AssetInfo>,
+    },
     /// ProposeNewOwner creates a proposal to change contract ownership.
-    /// The validity period for the proposal is set in the `expires_in` variable.
+    /// It starts a time-locked admin transfer with the [`MIN_ADMIN_TRANSFER_DELAY`] delay,
+    /// see [`ExecuteMsg::ProposeAdmin`]
     ProposeNewOwner {
         /// Newly proposed contract owner
         owner: String,
-        /// The date after which this proposal expires
+        /// Ignored. Time-locked admin transfers don't expire, kept for compatibility
         expires_in: u64,
     },
     /// DropOwnershipProposal removes the existing offer to change contract ownership.
+    /// Same as [`ExecuteMsg::DropAdminProposal`]
     DropOwnershipProposal {},
-    /// Used to claim contract ownership.
+    /// Used to claim contract ownership. Same as [`ExecuteMsg::AcceptAdmin`]
     ClaimOwnership {},
+    /// Starts a time-locked admin transfer. `new_admin` can accept it once `delay_seconds` have passed.
+    /// A new proposal replaces the pending one
+    ProposeAdmin {
+        /// The proposed admin
+        new_admin: String,
+        /// The delay before the transfer can be accepted. Must be at least [`MIN_ADMIN_TRANSFER_DELAY`]
+        delay_seconds: u64,
+    },
+    /// Accepts the pending admin transfer. Only the proposed admin can execute this after the delay
+    AcceptAdmin {},
+    /// Removes the pending admin transfer. Only the owner can execute this
+    DropAdminProposal {},
 }
 
 /// This structure describes the available query messages for the factory contract.
@@ -196,6 +264,31 @@ pub enum QueryMsg {
     BlacklistedPairTypes {},
     #[returns(TrackerConfig)]
     TrackerConfig {},
+    /// Returns the pair code IDs which are allowed to create pairs along with their pair types and fees
+    #[returns(WhitelistResponse)]
+    Whitelist {},
+    /// Returns whether a pair code ID is allowed to create pairs
+    #[returns(bool)]
+    IsWhitelisted {
+        /// The pair contract code ID
+        code_id: u64,
+    },
+    /// Returns the shortest route of at most three pairs between two assets
+    #[returns(SwapPathResponse)]
+    SwapPath {
+        /// The asset to swap from
+        from: AssetInfo,
+        /// The asset to swap to
+        to: AssetInfo,
+    },
+    /// Returns aggregated statistics over the registered pairs
+    #[returns(GlobalStatsResponse)]
+    GlobalStats {
+        /// The pair item to start reading from
+        start_after: Option<Vec<AssetInfo>>,
+        /// The maximum number of pairs to query, 30 by default and at most 100
+        pair_limit: Option<u32>,
+    },
 }
 
 #[cw_serde]
@@ -220,6 +313,12 @@ pub struct ConfigResponse {
     pub whitelist_code_id: u64,
     /// The address of the contract that contains the coins and their accuracy
     pub coin_registry_address: Addr,
+    /// The admin proposed with [`ExecuteMsg::ProposeAdmin`]
+    #[serde(default)]
+    pub pending_admin: Option<Addr>,
+    /// The timestamp (in seconds) from which the pending admin can accept the transfer
+    #[serde(default)]
+    pub pending_admin_accepts_at: Option<u64>,
 }
 
 /// A custom struct for each query response that returns an array of objects of type [`PairInfo`].
@@ -229,6 +328,55 @@ pub struct PairsResponse {
     pub pairs: Vec<PairInfo>,
 }
 
+/// This structure describes a single pair code ID whitelisted in the factory.
+#[cw_serde]
+pub struct WhitelistEntry {
+    /// ID of contract which is allowed to create pairs of this type
+    pub code_id: u64,
+    /// The pair type (provided in a [`PairType`])
+    pub pair_type: PairType,
+    /// Whether new pairs of this type can be created
+    pub allowed: bool,
+    /// The total fees (in bps) charged by a pair of this type
+    pub max_total_fee_bps: u16,
+}
+
+/// A custom struct for each query response that returns an object of type [`WhitelistResponse`].
+#[cw_serde]
+pub struct WhitelistResponse {
+    /// Whitelisted pair code IDs
+    pub entries: Vec<WhitelistEntry>,
+}
+
+/// A custom struct for each query response that returns an object of type [`SwapPathResponse`].
+#[cw_serde]
+pub struct SwapPathResponse {
+    /// Pairs to swap through, in swap order
+    pub hops: Vec<PairInfo>,
+    /// Assets received between the hops
+    pub intermediate_assets: Vec<AssetInfo>,
+    /// Compounded fee rate charged along the route, based on the total fees configured for
+    /// the pair types. Price impact is not included
+    pub total_fee_rate: Decimal,
+}
+
+/// A custom struct for each query response that returns an object of type [`GlobalStatsResponse`].
+/// `pairs_by_type` only covers the queried page of pairs while `total_pairs` counts all of them.
+#[cw_serde]
+pub struct GlobalStatsResponse {
+    /// The number of registered pairs
+    pub total_pairs: u32,
+    /// Total value locked in USD. None if no price oracle is available
+    pub total_tvl_usd: Option<Uint128>,
+    /// Trading volume over the last 24 hours in USD. None if no price oracle is available
+    pub total_24h_volume_usd: Option<Uint128>,
+    /// The number of queried pairs per pair type
+    pub pairs_by_type: Vec<(PairType, u32)>,
+    /// The assets of the last queried pair if more pairs are registered after it.
+    /// Pass them as `start_after` to query the next page
+    pub next_start_after: Option<Vec<AssetInfo>>,
+}
+
 /// A custom struct for each query response that returns an object of type [`FeeInfoResponse`].
 #[cw_serde]
 pub struct FeeInfoResponse {
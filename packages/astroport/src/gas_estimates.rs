@@ -0,0 +1,54 @@
+use crate::asset::AssetInfo;
+
+/// Empirically measured gas costs of a swap offering either a native or a CW20 token.
+/// The values are rough upper bounds reported by swap simulations as a hint for setting gas limits.
+/// They are not binding: the actual cost depends on the chain, the pool state and the enabled
+/// pair features (fee sharing, balance tracking, etc.).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapGasEstimate {
+    /// The gas consumed by a swap offering a native token
+    pub native: u64,
+    /// The gas consumed by a swap offering a CW20 token. It includes the CW20 `send` call
+    pub cw20: u64,
+}
+
+impl SwapGasEstimate {
+    /// Returns the estimate for the given offer asset type.
+    pub fn for_offer(&self, offer_asset_info: &AssetInfo) -> u64 {
+        if offer_asset_info.is_native_token() {
+            self.native
+        } else {
+            self.cw20
+        }
+    }
+}
+
+/// Constant product pair
+pub const XYK_SWAP: SwapGasEstimate = SwapGasEstimate {
+    native: 190_000,
+    cw20: 260_000,
+};
+
+/// Constant product pair with sale tax
+pub const XYK_SALE_TAX_SWAP: SwapGasEstimate = SwapGasEstimate {
+    native: 210_000,
+    cw20: 280_000,
+};
+
+/// Stableswap pair
+pub const STABLE_SWAP: SwapGasEstimate = SwapGasEstimate {
+    native: 260_000,
+    cw20: 330_000,
+};
+
+/// Concentrated liquidity pair
+pub const CONCENTRATED_SWAP: SwapGasEstimate = SwapGasEstimate {
+    native: 320_000,
+    cw20: 390_000,
+};
+
+/// Transmuter pair
+pub const TRANSMUTER_SWAP: SwapGasEstimate = SwapGasEstimate {
+    native: 150_000,
+    cw20: 220_000,
+};
@@ -8,12 +8,15 @@ pub mod common;
 pub mod cosmwasm_ext;
 pub mod factory;
 pub mod fee_granter;
+pub mod gas_estimates;
 #[cfg(feature = "injective")]
 pub mod injective_ext;
 pub mod maker;
+pub mod math;
 pub mod native_coin_registry;
 pub mod observation;
 pub mod oracle;
+pub mod oracle_interface;
 pub mod pair;
 pub mod pair_concentrated;
 pub mod pair_concentrated_inj;
@@ -22,6 +25,8 @@ pub mod querier;
 pub mod restricted_vector;
 pub mod router;
 pub mod staking;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
 pub mod token;
 pub mod token_factory;
 pub mod tokenfactory_tracker;
@@ -0,0 +1,104 @@
+use cosmwasm_std::{Decimal, OverflowError, OverflowOperation, StdError, StdResult, Uint128};
+
+use crate::asset::AssetInfo;
+use crate::pair::PoolResponse;
+
+/// Number of days used to annualize daily fee income
+const DAYS_IN_YEAR: u128 = 365;
+
+/// Length of the volume window expected by [`estimate_fee_income`]
+pub const SECONDS_IN_DAY: u64 = 86400;
+
+/// Estimates the annual fee income rate of a constant product pool as
+/// `volume_24h * 365 * fee_rate / tvl`.
+///
+/// This is an approximation. It assumes the daily volume stays the same during the whole year and
+/// values TVL as twice the reserve of the asset `volume_24h` is denominated in. The latter holds only
+/// for constant product pools where both sides of the pool have the same value.
+///
+/// * **pool** current pool reserves.
+///
+/// * **volume_asset** asset in which `volume_24h` is denominated.
+///
+/// * **fee_rate** total swap fee rate.
+///
+/// * **volume_24h** swap volume over the last 24 hours.
+pub fn estimate_fee_income(
+    pool: &PoolResponse,
+    volume_asset: &AssetInfo,
+    fee_rate: Decimal,
+    volume_24h: Uint128,
+) -> StdResult<Decimal> {
+    let reserve = pool
+        .assets
+        .iter()
+        .find(|asset| asset.info == *volume_asset)
+        .map(|asset| asset.amount)
+        .ok_or_else(|| StdError::generic_err(format!("Asset {volume_asset} is not in the pool")))?;
+
+    if reserve.is_zero() {
+        return Err(StdError::generic_err("One of the pools is empty"));
+    }
+
+    let tvl = reserve.checked_mul(Uint128::new(2))?;
+    let annual_volume = volume_24h.checked_mul(Uint128::new(DAYS_IN_YEAR))?;
+
+    // The TVL is not zero, so the ratio can only fail if it doesn't fit into a Decimal
+    let turnover = Decimal::checked_from_ratio(annual_volume, tvl)
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, annual_volume, tvl))?;
+
+    Ok(turnover.checked_mul(fee_rate)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset::{native_asset_info, AssetInfoExt};
+
+    #[test]
+    fn test_estimate_fee_income() {
+        let uusd = native_asset_info("uusd".to_string());
+        let uluna = native_asset_info("uluna".to_string());
+        let pool = PoolResponse {
+            assets: vec![
+                uusd.with_balance(1_000_000_000000u128),
+                uluna.with_balance(500_000_000000u128),
+            ],
+            total_share: Uint128::new(700_000_000000),
+            external_liquidity: vec![],
+        };
+
+        // 100k daily volume on 2M TVL with 0.3% fee gives 0.3 * 365 / 20 = 5.475% per year
+        let rate = estimate_fee_income(
+            &pool,
+            &uusd,
+            Decimal::permille(3),
+            Uint128::new(100_000_000000),
+        )
+        .unwrap();
+        assert_eq!(rate, Decimal::from_ratio(5475u128, 100000u128));
+
+        // No volume, no income
+        let rate =
+            estimate_fee_income(&pool, &uluna, Decimal::permille(3), Uint128::zero()).unwrap();
+        assert_eq!(rate, Decimal::zero());
+
+        let err = estimate_fee_income(
+            &pool,
+            &native_asset_info("uatom".to_string()),
+            Decimal::permille(3),
+            Uint128::new(100),
+        )
+        .unwrap_err();
+        assert_eq!(err, StdError::generic_err("Asset uatom is not in the pool"));
+
+        let empty_pool = PoolResponse {
+            assets: vec![uusd.with_balance(0u8), uluna.with_balance(0u8)],
+            total_share: Uint128::zero(),
+            external_liquidity: vec![],
+        };
+        let err = estimate_fee_income(&empty_pool, &uusd, Decimal::permille(3), Uint128::new(100))
+            .unwrap_err();
+        assert_eq!(err, StdError::generic_err("One of the pools is empty"));
+    }
+}
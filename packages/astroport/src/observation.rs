@@ -5,12 +5,15 @@ use cosmwasm_std::{
 };
 use cw_storage_plus::Item;
 
-use astroport_circular_buffer::{BufferManager, CircularBuffer};
+use astroport_circular_buffer::error::BufferResult;
+use astroport_circular_buffer::{BufferManager, CircularBuffer, Timestamped};
 
 use crate::cosmwasm_ext::AbsDiff;
 
 /// Circular buffer size which stores observations
 pub const OBSERVATIONS_SIZE: u32 = 3000;
+/// The maximum number of observations returned by a single observation range query
+pub const MAX_OBSERVATION_RANGE_LIMIT: usize = 200;
 
 /// Stores trade size observations. We use it in orderbook integration
 /// and derive prices for external contracts/users.
@@ -23,6 +26,45 @@ pub struct Observation {
     pub price: Decimal,
     /// Price simple moving average (mean)
     pub price_sma: Decimal,
+    /// Price geometric mean over the same window as `price_sma`
+    #[serde(default)]
+    pub price_geo_mean: Decimal,
+    /// Cumulative base asset volume up to and including this observation
+    #[serde(default)]
+    pub base_volume: Uint128,
+    /// Cumulative quote asset volume up to and including this observation
+    #[serde(default)]
+    pub quote_volume: Uint128,
+}
+
+impl Timestamped for Observation {
+    fn timestamp(&self) -> u64 {
+        self.ts
+    }
+}
+
+impl Observation {
+    /// Returns the price geometric mean. Observations stored before the geometric mean was
+    /// tracked fall back to the arithmetic mean.
+    pub fn geo_mean(&self) -> Decimal {
+        if self.price_geo_mean.is_zero() {
+            self.price_sma
+        } else {
+            self.price_geo_mean
+        }
+    }
+
+    /// Returns cumulative base and quote volumes after adding the next observed swap amounts.
+    pub fn accumulate_volume(
+        &self,
+        base_amount: Uint128,
+        quote_amount: Uint128,
+    ) -> StdResult<(Uint128, Uint128)> {
+        Ok((
+            self.base_volume.checked_add(base_amount)?,
+            self.quote_volume.checked_add(quote_amount)?,
+        ))
+    }
 }
 
 #[cw_serde]
@@ -31,6 +73,37 @@ pub struct OracleObservation {
     pub price: Decimal,
 }
 
+/// This structure describes a price observation stored in the observations buffer.
+#[cw_serde]
+pub struct ObservationResponse {
+    /// Timestamp of the observation
+    pub timestamp: u64,
+    /// Observed price at this point
+    pub price: Decimal,
+    /// Price simple moving average (mean)
+    pub price_sma: Decimal,
+}
+
+/// This structure describes the volume traded within an observation window.
+#[cw_serde]
+#[derive(Default)]
+pub struct VolumeWindowResponse {
+    /// Base asset volume
+    pub base_volume: Uint128,
+    /// Quote asset volume
+    pub quote_volume: Uint128,
+}
+
+impl From<Observation> for ObservationResponse {
+    fn from(obs: Observation) -> Self {
+        Self {
+            timestamp: obs.ts,
+            price: obs.price,
+            price_sma: obs.price_sma,
+        }
+    }
+}
+
 /// Returns price observation at point that was 'seconds_ago' seconds ago.
 pub fn query_observation<C>(
     deps: Deps<C>,
@@ -116,6 +189,221 @@ where
     })
 }
 
+/// Returns the latest stored observation with `ts <= timestamp`.
+/// Returns `None` if the buffer is empty or the requested timestamp predates the buffer.
+pub fn query_observation_at<C>(
+    deps: Deps<C>,
+    observations: CircularBuffer<Observation>,
+    timestamp: u64,
+) -> StdResult<Option<Observation>>
+where
+    C: CustomQuery,
+{
+    let buffer = BufferManager::new(deps.storage, observations)?;
+
+    find_observation_at(deps.storage, &buffer, timestamp)
+}
+
+fn find_observation_at(
+    storage: &dyn Storage,
+    buffer: &BufferManager<Observation>,
+    timestamp: u64,
+) -> StdResult<Option<Observation>> {
+    let mut oldest_ind = buffer.head();
+    let mut newest_ind = buffer.head() + buffer.capacity() - 1;
+
+    if !buffer.exists(storage, oldest_ind) {
+        if buffer.head() > 0 {
+            oldest_ind = 0;
+            newest_ind %= buffer.capacity();
+        } else {
+            return Ok(None);
+        }
+    }
+
+    let newest_obs = buffer.read_single(storage, newest_ind)?.unwrap();
+    if timestamp >= newest_obs.ts {
+        return Ok(Some(newest_obs));
+    }
+    let oldest_obs = buffer.read_single(storage, oldest_ind)?.unwrap();
+    if timestamp <= oldest_obs.ts {
+        return Ok((timestamp == oldest_obs.ts).then_some(oldest_obs));
+    }
+
+    let (left, right) = binary_search(storage, buffer, timestamp, oldest_ind, newest_ind)?;
+
+    Ok(Some(if right.ts <= timestamp { right } else { left }))
+}
+
+/// Returns the base and quote volumes of the observations made within the last `window_seconds`
+/// seconds. Observations stored before volumes were tracked are counted as zero volume.
+pub fn query_volume_window<C>(
+    deps: Deps<C>,
+    env: Env,
+    observations: CircularBuffer<Observation>,
+    window_seconds: u64,
+) -> StdResult<VolumeWindowResponse>
+where
+    C: CustomQuery,
+{
+    let buffer = BufferManager::new(deps.storage, observations)?;
+
+    let Some(newest_obs) = buffer.read_last(deps.storage)? else {
+        return Ok(VolumeWindowResponse::default());
+    };
+
+    let window_start = env.block.time.seconds().saturating_sub(window_seconds);
+    let (base_before, quote_before) =
+        match find_observation_at(deps.storage, &buffer, window_start)? {
+            Some(obs) => (obs.base_volume, obs.quote_volume),
+            // The buffer still holds every observation ever made
+            None if !buffer.exists(deps.storage, buffer.head()) => {
+                (Uint128::zero(), Uint128::zero())
+            }
+            None => {
+                return Err(StdError::generic_err(
+                    "Volume window exceeds stored observations",
+                ))
+            }
+        };
+
+    Ok(VolumeWindowResponse {
+        base_volume: newest_obs.base_volume.saturating_sub(base_before),
+        quote_volume: newest_obs.quote_volume.saturating_sub(quote_before),
+    })
+}
+
+/// Returns the time weighted average of the observed prices within the last `window_seconds` seconds.
+/// The price is interpolated linearly between consecutive observations, so the period which
+/// straddles the window start is only weighted by the part that falls into the window.
+/// The latest observed price is assumed to hold until the current block.
+pub fn query_observation_window<C>(
+    deps: Deps<C>,
+    env: Env,
+    observations: CircularBuffer<Observation>,
+    window_seconds: u64,
+) -> StdResult<Decimal>
+where
+    C: CustomQuery,
+{
+    if window_seconds == 0 {
+        return Err(StdError::generic_err(
+            "Observation window must be greater than zero",
+        ));
+    }
+
+    let buffer = BufferManager::new(deps.storage, observations)?;
+    let mut observations = buffer.read_all(deps.storage)?;
+    observations.sort_by_key(|obs| obs.ts);
+
+    let Some(newest_obs) = observations.last() else {
+        return Err(StdError::generic_err("Buffer is empty"));
+    };
+
+    let now = env.block.time.seconds();
+    let window_start = now.saturating_sub(window_seconds);
+    if window_start < observations[0].ts {
+        return Err(StdError::generic_err(
+            "Observation window exceeds stored observations",
+        ));
+    }
+
+    let mut weighted_sum = Decimal256::zero();
+    for pair in observations.windows(2) {
+        let (obs_before, obs_after) = (&pair[0], &pair[1]);
+        if obs_after.ts <= window_start {
+            continue;
+        }
+
+        let from = obs_before.ts.max(window_start);
+        let price_from = interpolate_price(obs_before, obs_after, from);
+        weighted_sum += (Decimal256::from(price_from) + Decimal256::from(obs_after.price))
+            * Decimal256::from_ratio(obs_after.ts - from, 2u8);
+    }
+    weighted_sum += Decimal256::from(newest_obs.price)
+        * Decimal256::from_ratio(now.saturating_sub(newest_obs.ts.max(window_start)), 1u8);
+
+    try_dec256_into_dec(weighted_sum / Decimal256::from_ratio(now - window_start, 1u8))
+}
+
+/// Returns the observations within the inclusive `[start_ts, end_ts]` range in ascending time order.
+/// At most [`MAX_OBSERVATION_RANGE_LIMIT`] observations are returned. The next page is read by passing
+/// the timestamp of the last returned observation as `start_after`.
+pub fn query_observation_range(
+    storage: &dyn Storage,
+    observations: CircularBuffer<Observation>,
+    start_ts: u64,
+    end_ts: u64,
+    start_after: Option<u64>,
+) -> StdResult<Vec<Observation>> {
+    let start_ts = match start_after {
+        Some(start_after) => start_ts.max(start_after.saturating_add(1)),
+        None => start_ts,
+    };
+    if start_ts > end_ts {
+        return Ok(vec![]);
+    }
+
+    let buffer = BufferManager::new(storage, observations)?;
+    let mut observations = buffer.read_range(storage, start_ts, end_ts)?;
+    observations.truncate(MAX_OBSERVATION_RANGE_LIMIT);
+
+    Ok(observations)
+}
+
+/// Linearly interpolates the observed price at `ts` between two observations.
+/// Timestamps outside of the `[obs_before.ts, obs_after.ts]` range are clamped to it.
+pub fn interpolate_price(obs_before: &Observation, obs_after: &Observation, ts: u64) -> Decimal {
+    if ts <= obs_before.ts || obs_after.ts <= obs_before.ts {
+        return obs_before.price;
+    }
+    if ts >= obs_after.ts {
+        return obs_after.price;
+    }
+
+    let progress = Decimal::from_ratio(ts - obs_before.ts, obs_after.ts - obs_before.ts);
+    let price_delta = obs_after.price.diff(obs_before.price) * progress;
+    if obs_before.price > obs_after.price {
+        obs_before.price - price_delta
+    } else {
+        obs_before.price + price_delta
+    }
+}
+
+/// Returns the median price of the latest `window` observations. If the buffer holds fewer
+/// observations, all of them are used. Unlike the SMA, the median is not moved by a single outlier.
+pub fn compute_median_price(
+    storage: &dyn Storage,
+    observations: CircularBuffer<Observation>,
+    window: u32,
+) -> StdResult<Decimal> {
+    if window == 0 {
+        return Err(StdError::generic_err(
+            "Median window must be greater than zero",
+        ));
+    }
+
+    let buffer = BufferManager::new(storage, observations)?;
+    let capacity = buffer.capacity();
+    let mut prices = (1..=window.min(capacity))
+        .map(|i| buffer.read_single(storage, (buffer.head() + capacity - i) % capacity))
+        .map_while(|res| res.transpose())
+        .map(|res| res.map(|obs| obs.price))
+        .collect::<BufferResult<Vec<_>>>()?;
+
+    if prices.is_empty() {
+        return Err(StdError::generic_err("Buffer is empty"));
+    }
+
+    prices.sort_unstable();
+    let mid = prices.len() / 2;
+    if prices.len() % 2 == 1 {
+        Ok(prices[mid])
+    } else {
+        Ok(prices[mid - 1].checked_add(prices[mid])? / Uint128::new(2))
+    }
+}
+
 /// Performs binary search in circular buffer. Returns left and right bounds of target value.
 /// Either left or right bound may hit in target value.
 fn binary_search(
@@ -191,6 +479,22 @@ impl<'a> PrecommitObservation {
     pub fn may_load(storage: &dyn Storage) -> StdResult<Option<Self>> {
         Self::PRECOMMIT_OBSERVATION.may_load(storage)
     }
+
+    /// Drops the observation which wasn't committed yet
+    #[inline]
+    pub fn remove(storage: &mut dyn Storage) {
+        Self::PRECOMMIT_OBSERVATION.remove(storage)
+    }
+}
+
+/// Returns the price geometric mean of the latest observation or `None` if the buffer is empty.
+pub fn query_geo_twap(
+    storage: &dyn Storage,
+    observations: CircularBuffer<Observation>,
+) -> StdResult<Option<Decimal>> {
+    let buffer = BufferManager::new(storage, observations)?;
+
+    Ok(buffer.read_last(storage)?.map(|obs| obs.geo_mean()))
 }
 
 pub fn try_dec256_into_dec(val: Decimal256) -> StdResult<Decimal> {
@@ -234,11 +538,99 @@ pub fn safe_sma_buffer_not_full(
     try_dec256_into_dec(res)
 }
 
+/// ln(2) with 18 decimal places
+const LN_2: Decimal256 = Decimal256::raw(693_147_180_559_945_309);
+
+/// Returns the natural logarithm of the raw price representation (`price * 10^18`) which keeps the
+/// result non-negative. Zero price is treated as the smallest representable one since the
+/// logarithm is undefined at zero.
+fn ln_price(price: Decimal) -> Decimal256 {
+    let x = price.atomics().u128().max(1);
+
+    // x = m * 2^k where 1 <= m < 2
+    let k = 127 - x.leading_zeros();
+    let m = Decimal256::from_ratio(x, 1u128 << k);
+
+    // ln(m) = 2 * atanh((m - 1) / (m + 1)) = 2 * sum(z^(2i+1) / (2i+1)) where z < 1/3
+    let z = (m - Decimal256::one()) / (m + Decimal256::one());
+    let z_squared = z * z;
+    let mut power = z;
+    let mut sum = Decimal256::zero();
+    let mut i = 1u32;
+    while !power.is_zero() {
+        sum += power / Decimal256::from_ratio(i, 1u8);
+        power *= z_squared;
+        i += 2;
+    }
+
+    LN_2 * Decimal256::from_ratio(k, 1u8) + sum + sum
+}
+
+/// Inverse of [`ln_price`].
+fn exp_price(log: Decimal256) -> StdResult<Decimal> {
+    // log = k * ln(2) + r where 0 <= r < ln(2)
+    let k: Uint128 = ((log / LN_2).atomics() / Decimal256::one().atomics()).try_into()?;
+    let k = k.u128() as u32;
+    let r = log.saturating_sub(LN_2 * Decimal256::from_ratio(k, 1u8));
+
+    let mut term = Decimal256::one();
+    let mut exp_r = Decimal256::one();
+    let mut i = 1u32;
+    loop {
+        term = term * r / Decimal256::from_ratio(i, 1u8);
+        if term.is_zero() {
+            break;
+        }
+        exp_r += term;
+        i += 1;
+    }
+
+    let atomics: Uint128 = (Uint256::one()
+        .checked_shl(k)
+        .map_err(|_| StdError::generic_err("Price geometric mean overflow"))?
+        * exp_r)
+        .try_into()?;
+
+    Ok(Decimal::raw(atomics.u128()))
+}
+
+/// Calculates the new price geometric mean by replacing the oldest price in the running
+/// log-sum with the new one.
+/// Formula: exp((ln(geo_mean) * count + ln(new_price) - ln(oldest_price)) / count)
+pub fn safe_geo_sma_calculation(
+    price_geo_mean: Decimal,
+    oldest_price: Decimal,
+    count: u32,
+    new_price: Decimal,
+) -> StdResult<Decimal> {
+    let count = Decimal256::from_ratio(count, 1u8);
+    let log_sum = (ln_price(price_geo_mean) * count + ln_price(new_price))
+        .saturating_sub(ln_price(oldest_price));
+
+    exp_price(log_sum / count)
+}
+
+/// Same as [`safe_geo_sma_calculation`] but is being used when buffer is not full yet.
+/// Formula: exp((ln(geo_mean) * count + ln(new_price)) / (count + 1))
+pub fn safe_geo_sma_buffer_not_full(
+    price_geo_mean: Decimal,
+    count: u32,
+    new_price: Decimal,
+) -> StdResult<Decimal> {
+    let log_sum =
+        ln_price(price_geo_mean) * Decimal256::from_ratio(count, 1u8) + ln_price(new_price);
+
+    exp_price(log_sum / Decimal256::from_ratio(count + 1, 1u8))
+}
+
 #[cfg(test)]
 mod test {
-    use cosmwasm_std::to_json_binary;
+    use cosmwasm_std::{from_json, to_json_binary, Decimal, Uint128};
 
-    use crate::observation::Observation;
+    use crate::observation::{
+        exp_price, interpolate_price, ln_price, safe_geo_sma_buffer_not_full,
+        safe_geo_sma_calculation, Observation,
+    };
 
     #[test]
     fn check_observation_size() {
@@ -248,13 +640,96 @@ mod test {
             ts: 0,
             price: Default::default(),
             price_sma: Default::default(),
+            price_geo_mean: Default::default(),
+            base_volume: Default::default(),
+            quote_volume: Default::default(),
         };
 
         let storage_bytes = to_json_binary(&obs).unwrap().len();
-        assert_eq!(storage_bytes, 36); // in storage
+        assert_eq!(storage_bytes, 94); // in storage
 
         // https://github.com/cosmos/cosmos-sdk/blob/47f46643affd7ec7978329c42bac47275ac7e1cc/store/types/gas.go#L199
         println!("sdk gas cost per read {}", 1000 + storage_bytes * 3);
         println!("sdk gas cost per write {}", 2000 + storage_bytes * 30)
     }
+
+    #[test]
+    fn legacy_observation_has_zero_volume() {
+        let obs: Observation =
+            from_json(r#"{"ts":1,"price":"2","price_sma":"2","price_geo_mean":"2"}"#).unwrap();
+        assert_eq!(obs.base_volume, Uint128::zero());
+        assert_eq!(obs.quote_volume, Uint128::zero());
+
+        let (base_volume, quote_volume) = obs
+            .accumulate_volume(Uint128::new(10), Uint128::new(5))
+            .unwrap();
+        assert_eq!(base_volume.u128(), 10);
+        assert_eq!(quote_volume.u128(), 5);
+    }
+
+    #[test]
+    fn check_interpolate_price() {
+        let obs = |ts: u64, price: u64| Observation {
+            ts,
+            price: Decimal::from_ratio(price, 1u8),
+            price_sma: Default::default(),
+            price_geo_mean: Default::default(),
+            base_volume: Default::default(),
+            quote_volume: Default::default(),
+        };
+
+        let (obs_before, obs_after) = (obs(100, 2), obs(200, 4));
+        assert_eq!(
+            interpolate_price(&obs_before, &obs_after, 150),
+            Decimal::from_ratio(3u8, 1u8)
+        );
+        assert_eq!(
+            interpolate_price(&obs_before, &obs_after, 175),
+            Decimal::from_ratio(7u8, 2u8)
+        );
+        // Falling prices
+        assert_eq!(
+            interpolate_price(&obs_after, &obs(300, 1), 250),
+            Decimal::from_ratio(5u8, 2u8)
+        );
+        // Timestamps outside of the range are clamped
+        assert_eq!(
+            interpolate_price(&obs_before, &obs_after, 50),
+            obs_before.price
+        );
+        assert_eq!(
+            interpolate_price(&obs_before, &obs_after, 250),
+            obs_after.price
+        );
+    }
+
+    #[test]
+    fn check_geo_mean_calculation() {
+        let assert_close = |a: Decimal, b: Decimal| {
+            let diff = if a > b { a - b } else { b - a };
+            assert!(
+                diff <= b * Decimal::from_ratio(1u8, 1_000_000_000u64),
+                "{a} != {b}"
+            );
+        };
+
+        for price in ["0.000001", "0.5", "1", "2", "123456.789"] {
+            let price: Decimal = price.parse().unwrap();
+            assert_close(exp_price(ln_price(price)).unwrap(), price);
+        }
+
+        // Geometric mean of 1, 4 and 16 is 4
+        let geo_mean =
+            safe_geo_sma_buffer_not_full(Decimal::one(), 1, Decimal::from_ratio(4u8, 1u8)).unwrap();
+        assert_close(geo_mean, Decimal::from_ratio(2u8, 1u8));
+        let geo_mean =
+            safe_geo_sma_buffer_not_full(geo_mean, 2, Decimal::from_ratio(16u8, 1u8)).unwrap();
+        assert_close(geo_mean, Decimal::from_ratio(4u8, 1u8));
+
+        // Replacing 1 with 64 in a full window: geometric mean of 4, 16 and 64 is 16
+        let geo_mean =
+            safe_geo_sma_calculation(geo_mean, Decimal::one(), 3, Decimal::from_ratio(64u8, 1u8))
+                .unwrap();
+        assert_close(geo_mean, Decimal::from_ratio(16u8, 1u8));
+    }
 }
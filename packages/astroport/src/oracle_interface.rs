@@ -0,0 +1,39 @@
+use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{wasm_execute, CosmosMsg, StdResult};
+
+use crate::observation::{Observation, OracleObservation};
+
+/// This structure describes the execute messages an oracle contract has to implement
+/// in order to receive the observation history from a pair.
+#[cw_serde]
+pub enum OracleInterfaceExecuteMsg {
+    /// Receives the pair observation history. Observations are sorted from the oldest to the newest one.
+    ReceiveObservationHistory { observations: Vec<Observation> },
+}
+
+/// This structure describes the query messages an oracle contract has to implement
+/// in order to serve the observation history received from a pair.
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum OracleInterfaceQueryMsg {
+    /// Returns the price observed `seconds_ago` seconds before the current block
+    #[returns(OracleObservation)]
+    Observe { seconds_ago: u64 },
+}
+
+/// Builds a message which sends the observation history to the oracle contract.
+///
+/// * **oracle** is the oracle contract address.
+///
+/// * **observations** pair observations sorted from the oldest to the newest one.
+pub fn observation_history_msg(
+    oracle: impl Into<String>,
+    observations: Vec<Observation>,
+) -> StdResult<CosmosMsg> {
+    Ok(wasm_execute(
+        oracle,
+        &OracleInterfaceExecuteMsg::ReceiveObservationHistory { observations },
+        vec![],
+    )?
+    .into())
+}
@@ -1,9 +1,17 @@
-use crate::observation::OracleObservation;
+use std::str::FromStr;
+
+use crate::cosmwasm_ext::ConvertInto;
+use crate::observation::{
+    Observation, ObservationResponse, OracleObservation, VolumeWindowResponse,
+};
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
 use crate::asset::{Asset, AssetInfo, PairInfo};
 
-use cosmwasm_std::{Addr, Binary, Decimal, Decimal256, StdError, Uint128, Uint64};
+use cosmwasm_std::{
+    to_json_binary, Addr, Binary, CosmosMsg, Decimal, Decimal256, Fraction, StdError, StdResult,
+    Uint128, Uint256, Uint64, WasmMsg,
+};
 use cw20::Cw20ReceiveMsg;
 
 /// The default swap slippage
@@ -12,6 +20,17 @@ pub const DEFAULT_SLIPPAGE: &str = "0.005";
 pub const MAX_ALLOWED_SLIPPAGE: &str = "0.5";
 /// The maximum fee share allowed, 10%
 pub const MAX_FEE_SHARE_BPS: u16 = 1000;
+/// The maximum flash loan fee allowed, 10%
+pub const MAX_FLASH_LOAN_FEE_BPS: u16 = 1000;
+/// The maximum number of price levels returned by the liquidity depth query
+pub const MAX_DEPTH_LEVELS: u8 = 50;
+/// The period (in seconds) LP tokens minted by a liquidity request fulfillment stay locked, 1 day
+pub const LIQUIDITY_REQUEST_LOCK_PERIOD: u64 = 86400;
+/// The maximum number of blocks over which swapped volume is released by the swap volume limit
+pub const MAX_VOLUME_LIMIT_DECAY_BLOCKS: u64 = 100;
+
+/// The maximum deviation of a reserve ratio from 0.5 for a pool to be considered balanced
+pub const RESERVE_IMBALANCE_THRESHOLD: Decimal = Decimal::percent(10);
 
 /// Decimal precision for TWAP results
 pub const TWAP_PRECISION: u8 = 6;
@@ -49,6 +68,36 @@ pub enum ExecuteMsg {
         /// The receiver of LP tokens
         receiver: Option<String>,
         min_lp_to_receive: Option<Uint128>,
+        /// The liquidity request fulfilled by this provision. The minted LP tokens are locked for
+        /// [`LIQUIDITY_REQUEST_LOCK_PERIOD`] and released to the receiver together with the request bounty
+        /// by [`ExecuteMsg::ClaimLiquidityRequest`]. Auto staking is not supported in this case.
+        /// Currently only supported by the constant product pair.
+        request_id: Option<u64>,
+    },
+    /// Provides liquidity with a single asset. Part of `offer` is swapped to the other pool asset
+    /// so that the rest of the deposit and the swap return match the pool ratio.
+    /// Currently only supported by the constant product pair.
+    /// Only native offers are supported, a CW20 offer is rejected as there is no CW20 hook for it
+    ProvideLiquiditySingle {
+        /// The native asset to provide
+        offer: Asset,
+        /// The maximum allowed shortfall of minted LP tokens compared to a balanced provision
+        /// of the same value
+        slippage_tolerance: Option<Decimal>,
+        /// The receiver of LP tokens
+        receiver: Option<String>,
+    },
+    /// Provides `offer` together with the amount of the other pool asset matching the current
+    /// pool ratio. A CW20 counterpart is pulled using the sender's allowance while a native
+    /// counterpart must be attached, any surplus is refunded.
+    /// Currently only supported by the concentrated liquidity pair.
+    ProvideLiquidityBalanced {
+        /// The asset to provide
+        offer: Asset,
+        /// The slippage tolerance that allows liquidity provision only if the price in the pool doesn't move too much
+        slippage_tolerance: Option<Decimal>,
+        /// The receiver of LP tokens
+        receiver: Option<String>,
     },
     /// WithdrawLiquidity allows someone to withdraw liquidity from the pool
     WithdrawLiquidity {
@@ -62,10 +111,101 @@ pub enum ExecuteMsg {
         ask_asset_info: Option<AssetInfo>,
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
+        /// The minimum amount of ask assets to receive. Checked independently of `max_spread`
+        min_out: Option<Uint128>,
         to: Option<String>,
+        /// Optional message which is sent back to the sender wrapped in [`SwapCallbackExecuteMsg`]
+        /// once the swap is completed. Currently only supported by the constant product pair.
+        callback: Option<Binary>,
     },
     /// Update the pair configuration
     UpdateConfig { params: Binary },
+    /// Stakes the LP tokens sent along with the message in the Incentives contract on behalf of the recipient.
+    /// Moves LP tokens to another wallet and stakes them in a single transaction.
+    TransferLpAndStake {
+        /// The address on whose behalf LP tokens are staked
+        recipient: String,
+        /// The amount of LP tokens attached to the message
+        amount: Uint128,
+        /// The Incentives contract address
+        generator: String,
+    },
+    /// Donates assets to the pool without minting LP tokens. Donated assets benefit existing LPs
+    DonateToPool {
+        /// The assets to donate. They must be pool assets
+        assets: Vec<Asset>,
+    },
+    /// Replaces a pool asset with a new one, e.g. when a CW20 token is migrated to a new contract.
+    /// The pair must already hold at least the same reserve of the new asset.
+    /// The reserve of the old asset is sent to the caller and the factory re-registers the pair
+    ReplaceAsset {
+        /// The asset to replace
+        old_asset: AssetInfo,
+        /// The asset that takes its place
+        new_asset: AssetInfo,
+    },
+    /// Switches to a new oracle contract. If `carry_over_observations` is set, the stored observation
+    /// history is sent to the new oracle with [`crate::oracle_interface::OracleInterfaceExecuteMsg`].
+    /// Afterwards observations missing in the pair are queried from the new oracle with
+    /// [`crate::oracle_interface::OracleInterfaceQueryMsg`].
+    UpgradeOracle {
+        /// The new oracle contract address
+        new_oracle: String,
+        /// Whether to send the stored observations to the new oracle
+        carry_over_observations: bool,
+    },
+    /// Permanently freezes the pair configuration. Afterwards configuration updates, asset replacements,
+    /// external liquidity registrations and sweeps are rejected
+    FreezeConfig {},
+    /// Sends tokens which are not part of the pool (airdrops, accidentally sent funds) to `recipient`.
+    /// If `assets` is not set, all native balances except the pool assets and the LP token are swept.
+    Sweep {
+        /// The address receiving the swept tokens
+        recipient: String,
+        /// The assets to sweep
+        assets: Option<Vec<AssetInfo>>,
+    },
+    /// Signals demand for liquidity. The native tokens attached to the message are escrowed as a bounty
+    /// which is paid to whoever provides at least `amount` LP tokens worth of liquidity referencing
+    /// the request before `deadline` and keeps the minted LP tokens locked for [`LIQUIDITY_REQUEST_LOCK_PERIOD`]
+    RequestLiquidityProvision {
+        /// The minimum amount of LP tokens the provision has to mint
+        amount: Uint128,
+        /// The timestamp (in seconds) after which the request can no longer be fulfilled
+        deadline: u64,
+    },
+    /// Cancels an unfulfilled liquidity request and refunds the bounty to the requester
+    CancelLiquidityRequest { request_id: u64 },
+    /// Sends the LP tokens locked by a fulfilled liquidity request together with the request bounty
+    /// to the liquidity provider once the lock period is over
+    ClaimLiquidityRequest { request_id: u64 },
+    /// Lends pool assets to `receiver` within a single transaction. The assets are sent to `receiver`
+    /// which is then called with [`FlashLoanCallbackExecuteMsg`]. The whole transaction is reverted
+    /// unless the pool holds the lent assets plus the flash loan fee afterwards.
+    FlashLoan {
+        /// The assets to borrow. They must be pool assets
+        assets: Vec<Asset>,
+        /// The contract receiving the assets and the callback
+        receiver: String,
+        /// The message passed to the receiver in the callback
+        msg: Binary,
+    },
+    /// Sets the fee charged on flash loans. Only the factory owner can execute this
+    SetFlashLoanFee { fee: Decimal },
+    /// Sets the pair-level cap on provide liquidity slippage tolerances.
+    /// Only the factory owner can execute this
+    SetSlippageTolerance { max_slippage: Decimal },
+    /// Registers liquidity which is held by an external contract on behalf of the pool.
+    /// Zero amounts remove the record. Only the factory owner can execute this
+    RegisterExternalLiquidity {
+        external_contract: String,
+        amount: [Uint128; 2],
+    },
+    /// Halts swaps, liquidity provision and withdrawals.
+    /// Only the factory owner or the pause guardian can execute this
+    Pause {},
+    /// Resumes the operations halted by [`ExecuteMsg::Pause`]. Only the factory owner can execute this
+    Unpause {},
     /// ProposeNewOwner creates a proposal to change contract ownership.
     /// The validity period for the proposal is set in the `expires_in` variable.
     ProposeNewOwner {
@@ -80,6 +220,74 @@ pub enum ExecuteMsg {
     ClaimOwnership {},
 }
 
+/// This structure is sent back to the swap initiator if the swap was executed with a callback.
+#[cw_serde]
+pub struct SwapCallbackMsg {
+    /// The amount of ask assets sent to the receiver
+    pub return_amount: Uint128,
+    /// The asset that was swapped
+    pub offer_asset: Asset,
+    /// The callback message specified in the swap
+    pub msg: Binary,
+}
+
+impl SwapCallbackMsg {
+    /// Serializes the message wrapped in [`SwapCallbackExecuteMsg`]
+    pub fn into_json_binary(self) -> StdResult<Binary> {
+        to_json_binary(&SwapCallbackExecuteMsg::SwapCallback(self))
+    }
+
+    /// Creates a message to execute the callback on the specified contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: self.into_json_binary()?,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+/// The execute message a contract has to implement to receive swap callbacks.
+#[cw_serde]
+pub enum SwapCallbackExecuteMsg {
+    SwapCallback(SwapCallbackMsg),
+}
+
+/// This structure is sent to the flash loan receiver once the borrowed assets were transferred.
+#[cw_serde]
+pub struct FlashLoanCallbackMsg {
+    /// The borrowed assets
+    pub assets: Vec<Asset>,
+    /// The fees which have to be returned to the pair on top of the borrowed assets
+    pub fees: Vec<Asset>,
+    /// The message specified in the flash loan
+    pub msg: Binary,
+}
+
+impl FlashLoanCallbackMsg {
+    /// Serializes the message wrapped in [`FlashLoanCallbackExecuteMsg`]
+    pub fn into_json_binary(self) -> StdResult<Binary> {
+        to_json_binary(&FlashLoanCallbackExecuteMsg::FlashLoanCallback(self))
+    }
+
+    /// Creates a message to execute the callback on the specified contract
+    pub fn into_cosmos_msg<T: Into<String>>(self, contract_addr: T) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg: self.into_json_binary()?,
+            funds: vec![],
+        }
+        .into())
+    }
+}
+
+/// The execute message a contract has to implement to receive flash loans.
+#[cw_serde]
+pub enum FlashLoanCallbackExecuteMsg {
+    FlashLoanCallback(FlashLoanCallbackMsg),
+}
+
 /// This structure describes a CW20 hook message.
 #[cw_serde]
 pub enum Cw20HookMsg {
@@ -88,8 +296,21 @@ pub enum Cw20HookMsg {
         ask_asset_info: Option<AssetInfo>,
         belief_price: Option<Decimal>,
         max_spread: Option<Decimal>,
+        /// The minimum amount of ask assets to receive. Checked independently of `max_spread`
+        min_out: Option<Uint128>,
         to: Option<String>,
     },
+    /// Provides liquidity using the received CW20 tokens together with the counterpart asset
+    /// and stakes the minted LP tokens in the Incentives contract on behalf of the CW20 sender
+    StakeAndProvide {
+        /// The other pool asset. CW20 counterparts are pulled from the sender with `TransferFrom`.
+        /// Native counterparts must be attached to the hook call
+        counterpart: Asset,
+        /// The slippage tolerance that allows liquidity provision only if the price in the pool doesn't move too much
+        slippage_tolerance: Option<Decimal>,
+        /// The Incentives contract address
+        generator: String,
+    },
 }
 
 /// This structure describes the query messages available in the contract.
@@ -135,6 +356,28 @@ pub enum QueryMsg {
     /// Query price from observations
     #[returns(OracleObservation)]
     Observe { seconds_ago: u64 },
+    /// Returns the latest stored observation at or before the given timestamp
+    #[returns(ObservationResponse)]
+    ObservationAt { timestamp: u64 },
+    /// Returns the median price of the latest `window` observations
+    #[returns(Decimal)]
+    MedianPrice { window: u32 },
+    /// Returns the base and quote volumes traded within the last `window_seconds` seconds
+    #[returns(VolumeWindowResponse)]
+    VolumeWindow { window_seconds: u64 },
+    /// Returns the time weighted average price over the last `window_seconds` seconds
+    /// with the price interpolated between observations
+    #[returns(Decimal)]
+    ObservationWindow { window_seconds: u64 },
+    /// Returns the observations within the inclusive `[start_ts, end_ts]` range in ascending time order.
+    /// At most 200 observations are returned, the rest can be read by passing the timestamp
+    /// of the last returned observation as `start_after`
+    #[returns(Vec<Observation>)]
+    ObservationRange {
+        start_ts: u64,
+        end_ts: u64,
+        start_after: Option<u64>,
+    },
     /// Returns an estimation of assets received for the given amount of LP tokens
     #[returns(Vec<Asset>)]
     SimulateWithdraw { lp_amount: Uint128 },
@@ -144,6 +387,47 @@ pub enum QueryMsg {
         assets: Vec<Asset>,
         slippage_tolerance: Option<Decimal>,
     },
+    /// Returns the execution price for swapping the given offer asset.
+    /// The price is expressed as the amount of offer asset paid per one unit of ask asset received.
+    #[returns(Decimal)]
+    ExecPrice { offer_asset: Asset },
+    /// Checks whether a swap would succeed with the given slippage tolerance
+    #[returns(SlippageValidationResponse)]
+    Slippage {
+        offer_asset: Asset,
+        slippage_tolerance: Decimal,
+    },
+    /// Returns the allowance granted by `owner` to `spender` for the pool CW20 token
+    #[returns(cw20::AllowanceResponse)]
+    TokenAllowance { owner: String, spender: String },
+    /// Returns the minimum amount of each pool asset which can be swapped for a non-zero return
+    #[returns(MinSwapAmountResponse)]
+    MinSwapAmount {},
+    /// Returns the share of each pool asset in the total pool value
+    #[returns(ReserveRatioResponse)]
+    ReserveRatio {},
+    /// Returns execution prices for logarithmically spaced offer amounts up to `offer_asset.amount`
+    #[returns(NextPriceResponse)]
+    NextPriceWithSize { offer_asset: Asset },
+    /// Returns whether all liquidity has been withdrawn from the pool
+    #[returns(IsPoolDepletedResponse)]
+    IsPoolDepleted {},
+    /// Returns the cumulative amounts needed to move the price by `price_step` multiples
+    #[returns(LiquidityDepthResponse)]
+    LiquidityDepth { price_step: Decimal, levels: u8 },
+    /// Returns the largest swap into `ask_asset` which keeps its reserve at or above the minimum liquidity
+    #[returns(MaxSwapableResponse)]
+    MaxSwapable { ask_asset: AssetInfo },
+    /// Checks whether a swap would pass the `belief_price` and `max_spread` checks of the swap handler
+    #[returns(CheckSwapLimitsResponse)]
+    CheckSwapLimits {
+        offer_asset: Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+    },
+    /// Returns the protocol fees the pool sent to the Maker
+    #[returns(AccumulatedFeesResponse)]
+    AccumulatedFees {},
 }
 
 /// This struct is used to return a query result with the total amount of LP tokens and assets in a specific pool.
@@ -153,6 +437,9 @@ pub struct PoolResponse {
     pub assets: Vec<Asset>,
     /// The total amount of LP tokens currently issued
     pub total_share: Uint128,
+    /// Liquidity held by external contracts on behalf of the pool. Not included in `assets`
+    #[serde(default)]
+    pub external_liquidity: Vec<(Addr, [Asset; 2])>,
 }
 
 /// This struct is used to return a query result with the general contract configuration.
@@ -168,6 +455,30 @@ pub struct ConfigResponse {
     pub factory_addr: Addr,
     /// Tracker contract address
     pub tracker_addr: Option<Addr>,
+    /// Current liquidity concentration parameters. Set only for concentrated liquidity pairs
+    pub liquidity_concentration: Option<ConcentrationParams>,
+    /// Whether swaps, liquidity provision and withdrawals are halted
+    #[serde(default)]
+    pub is_paused: bool,
+    /// Approximate annual fee income relative to the pool TVL. Set only if the pair keeps track of
+    /// the swap volume over the last 24 hours. See [`crate::math::estimate_fee_income`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_annual_fee_rate: Option<Decimal>,
+}
+
+/// This structure holds the current liquidity concentration parameters of a concentrated liquidity pair.
+#[cw_serde]
+pub struct ConcentrationParams {
+    /// Current amplification coefficient
+    pub amp: Decimal,
+    /// Current gamma
+    pub gamma: Decimal,
+    /// Parameter that defines how gradual the fee changes from mid_fee to out_fee
+    pub fee_gamma: Decimal,
+    /// The minimum fee, charged when the pool is fully balanced
+    pub mid_fee: Decimal,
+    /// The maximum fee, charged when the pool is imbalanced
+    pub out_fee: Decimal,
 }
 
 /// Holds the configuration for fee sharing
@@ -179,15 +490,34 @@ pub struct FeeShareConfig {
     pub recipient: Addr,
 }
 
+/// Holds the configuration for oracle backed arbitrage protection
+#[cw_serde]
+pub struct ArbProtectionConfig {
+    /// The oracle contract used to validate the post-swap pool price
+    pub oracle: Addr,
+    /// Swaps moving the pool price by more than this ratio are validated against the oracle
+    pub threshold: Decimal,
+    /// The maximum allowed deviation of the post-swap pool price from the oracle price
+    pub tolerance: Decimal,
+}
+
 /// This structure holds the parameters that are returned from a swap simulation response
 #[cw_serde]
 pub struct SimulationResponse {
-    /// The amount of ask assets returned by the swap
+    /// The amount of ask assets returned by the swap. This is the value to pass as `min_out`
+    /// to guard the swap against any price movement until its execution
     pub return_amount: Uint128,
     /// The spread used in the swap operation
     pub spread_amount: Uint128,
     /// The amount of fees charged by the transaction
     pub commission_amount: Uint128,
+    /// The relative difference between the spot price and the execution price of the swap
+    #[serde(default)]
+    pub price_impact: Decimal,
+    /// A rough, non-binding estimate of the gas consumed by the swap for the pair type
+    /// and the offer asset type. See [`crate::gas_estimates`]
+    #[serde(default)]
+    pub gas_estimate: u64,
 }
 
 /// This structure holds the parameters that are returned from a reverse swap simulation response.
@@ -199,6 +529,128 @@ pub struct ReverseSimulationResponse {
     pub spread_amount: Uint128,
     /// The amount of fees charged by the transaction
     pub commission_amount: Uint128,
+    /// The relative difference between the spot price and the execution price of the swap
+    #[serde(default)]
+    pub price_impact: Decimal,
+}
+
+/// Returns the relative difference between the spot price and the execution price of a swap,
+/// i.e. `(spot_price - execution_price) / spot_price`. Both prices are denominated in ask assets
+/// per one offer asset and the execution price accounts for the commission.
+/// Zero-size swaps have no price impact while swaps against a pool without a spot price
+/// (e.g. an empty pool) saturate at one.
+pub fn calculate_price_impact(
+    spot_price: Decimal256,
+    offer_amount: Decimal256,
+    return_amount: Decimal256,
+) -> StdResult<Decimal> {
+    if offer_amount.is_zero() {
+        return Ok(Decimal::zero());
+    }
+    if spot_price.is_zero() {
+        return Ok(Decimal::one());
+    }
+
+    let execution_price = return_amount
+        .checked_div(offer_amount)
+        .unwrap_or(Decimal256::MAX);
+
+    (spot_price.saturating_sub(execution_price) / spot_price)
+        .min(Decimal256::one())
+        .conv()
+}
+
+/// This structure holds the minimum viable swap amounts of the pool assets.
+#[cw_serde]
+pub struct MinSwapAmountResponse {
+    /// The minimum offer amount for each pool asset, in the same order as the pool assets
+    pub min_amounts: Vec<Asset>,
+}
+
+/// This structure holds the share of each pool asset in the total pool value.
+#[cw_serde]
+pub struct ReserveRatioResponse {
+    /// The share of the first pool asset
+    pub ratio_0: Decimal,
+    /// The share of the second pool asset
+    pub ratio_1: Decimal,
+    /// Whether `ratio_0` deviates from 0.5 by less than [`RESERVE_IMBALANCE_THRESHOLD`]
+    pub is_balanced: bool,
+}
+
+/// This structure describes whether a pool has been fully drained by liquidity withdrawals.
+#[cw_serde]
+pub struct IsPoolDepletedResponse {
+    /// Whether only the locked minimum liquidity remains in the pool
+    pub is_depleted: bool,
+    /// The timestamp of the last withdrawal that left the pool without user liquidity
+    pub last_withdrawal_ts: Option<u64>,
+}
+
+/// This structure holds the price impact curve of a swap.
+#[cw_serde]
+pub struct NextPriceResponse {
+    /// Offer amounts together with execution prices, expressed as the amount of offer asset paid
+    /// per one unit of ask asset received. Sorted by offer amount in ascending order
+    pub price_levels: Vec<(Uint128, Decimal)>,
+}
+
+/// This structure describes a single price level of the pool depth.
+#[cw_serde]
+pub struct DepthLevel {
+    /// Price of the first pool asset denominated in the second one
+    pub price: Decimal,
+    /// Amount of the offer asset needed to move the pool price to `price`
+    pub cumulative_size: Uint128,
+}
+
+/// This structure holds the result of a liquidity depth query.
+#[cw_serde]
+pub struct LiquidityDepthResponse {
+    /// Levels below the current price. Sizes are denominated in the first pool asset
+    pub bids: Vec<DepthLevel>,
+    /// Levels above the current price. Sizes are denominated in the second pool asset
+    pub asks: Vec<DepthLevel>,
+}
+
+/// This structure holds the result of a max swapable query.
+#[cw_serde]
+pub struct MaxSwapableResponse {
+    /// The largest amount of the offer asset that can be swapped
+    pub max_offer: Uint128,
+    /// The amount of the ask asset returned for `max_offer`
+    pub max_return: Uint128,
+    /// Amount of the offer asset paid per one unit of the ask asset
+    pub effective_price: Decimal,
+}
+
+/// This structure holds the result of a slippage validation query.
+#[cw_serde]
+pub struct SlippageValidationResponse {
+    /// Whether the swap would pass the slippage tolerance check
+    pub would_succeed: bool,
+    /// The slippage caused by the swap
+    pub actual_slippage: Decimal,
+    /// The minimum amount of ask assets to receive with the given slippage tolerance
+    pub min_return: Uint128,
+}
+
+/// This structure holds the protocol fees a pool sent to the Maker.
+#[cw_serde]
+pub struct AccumulatedFeesResponse {
+    /// Protocol fees in the pool asset order
+    pub fees: Vec<Asset>,
+}
+
+/// This structure holds the result of a swap limits check.
+#[cw_serde]
+pub struct CheckSwapLimitsResponse {
+    /// Whether the swap would pass the max spread check
+    pub would_succeed: bool,
+    /// The spread the swap is checked against, relative to the return amount without spread
+    pub expected_spread: Decimal,
+    /// The amount of ask assets the swap would return
+    pub expected_return: Uint128,
 }
 
 /// This structure is used to return a cumulative prices query response.
@@ -210,12 +662,25 @@ pub struct CumulativePricesResponse {
     pub total_share: Uint128,
     /// The vector contains cumulative prices for each pair of assets in the pool
     pub cumulative_prices: Vec<(AssetInfo, AssetInfo, Uint128)>,
+    /// Total swapped volume of the first pool asset (offered and returned amounts)
+    #[serde(default)]
+    pub volume0_cumulative_last: Uint256,
+    /// Total swapped volume of the second pool asset (offered and returned amounts)
+    #[serde(default)]
+    pub volume1_cumulative_last: Uint256,
+    /// Price geometric mean over the observations window. None for pools without observations
+    #[serde(default)]
+    pub geo_twap: Option<Decimal>,
 }
 
 /// This structure describes a migration message.
-/// We currently take no arguments for migrations.
 #[cw_serde]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// The contract version the migration is expected to start from. Not checked if not set
+    pub from_version: Option<String>,
+    /// The contract version the migration is expected to end with. Must match the new code version
+    pub to_version: String,
+}
 
 /// This structure holds XYK pool parameters.
 #[cw_serde]
@@ -233,6 +698,23 @@ pub struct XYKPoolConfig {
     pub track_asset_balances: bool,
     // The config for swap fee sharing
     pub fee_share: Option<FeeShareConfig>,
+    /// The config for oracle backed arbitrage protection
+    pub arb_protection: Option<ArbProtectionConfig>,
+    /// The fee charged on flash loans relative to the borrowed amount
+    #[serde(default)]
+    pub flash_loan_fee: Decimal,
+    /// The cap applied to user provided slippage tolerances
+    #[serde(default)]
+    pub global_max_slippage: Option<Decimal>,
+    /// The maximum swap volume of each asset (in that asset's units) accepted within a block
+    #[serde(default)]
+    pub max_swap_volume_per_block: Option<Uint128>,
+    /// The number of blocks over which previously swapped volume is linearly released
+    #[serde(default)]
+    pub volume_limit_decay_blocks: u64,
+    /// The address which is allowed to pause the pair in addition to the factory owner
+    #[serde(default)]
+    pub pause_guardian: Option<Addr>,
 }
 
 /// This enum stores the option available to enable asset balances tracking over blocks.
@@ -246,6 +728,34 @@ pub enum XYKPoolUpdateParams {
         fee_share_address: String,
     },
     DisableFeeShare,
+    /// Enables validation of large swaps against an external price oracle.
+    EnableArbProtection {
+        /// The oracle contract address
+        arb_oracle: String,
+        /// Swaps moving the pool price by more than this ratio are validated against the oracle
+        arb_threshold: Decimal,
+        /// The maximum allowed deviation of the post-swap pool price from the oracle price
+        arb_tolerance: Decimal,
+    },
+    DisableArbProtection,
+    /// Sets the fee charged on flash loans relative to the borrowed amount.
+    SetFlashLoanFee {
+        fee: Decimal,
+    },
+    /// Limits the swap volume accepted within a block. The limit is removed if
+    /// `max_swap_volume_per_block` is not set.
+    SetSwapVolumeLimit {
+        /// The maximum swap volume of each asset (in that asset's units) accepted within a block
+        max_swap_volume_per_block: Option<Uint128>,
+        /// The number of blocks over which previously swapped volume is linearly released.
+        /// Values of 0 and 1 reset the volume at every block. Capped by [`MAX_VOLUME_LIMIT_DECAY_BLOCKS`]
+        volume_limit_decay_blocks: u64,
+    },
+    /// Sets the address which is allowed to pause the pair. The guardian is removed if
+    /// `pause_guardian` is not set.
+    SetPauseGuardian {
+        pause_guardian: Option<String>,
+    },
 }
 
 /// This structure holds stableswap pool parameters.
@@ -264,6 +774,12 @@ pub struct StablePoolConfig {
     pub amp: Decimal,
     // The config for swap fee sharing
     pub fee_share: Option<FeeShareConfig>,
+    /// The maximum swap volume of each asset (in that asset's units) accepted within a block
+    #[serde(default)]
+    pub max_swap_volume_per_block: Option<Uint128>,
+    /// The number of blocks over which previously swapped volume is linearly released
+    #[serde(default)]
+    pub volume_limit_decay_blocks: u64,
 }
 
 /// This enum stores the options available to start and stop changing a stableswap pool's amplification.
@@ -282,6 +798,15 @@ pub enum StablePoolUpdateParams {
         fee_share_address: String,
     },
     DisableFeeShare,
+    /// Limits the swap volume accepted within a block. The limit is removed if
+    /// `max_swap_volume_per_block` is not set.
+    SetSwapVolumeLimit {
+        /// The maximum swap volume of each asset (in that asset's units) accepted within a block
+        max_swap_volume_per_block: Option<Uint128>,
+        /// The number of blocks over which previously swapped volume is linearly released.
+        /// Values of 0 and 1 reset the volume at every block. Capped by [`MAX_VOLUME_LIMIT_DECAY_BLOCKS`]
+        volume_limit_decay_blocks: u64,
+    },
 }
 
 /// A `reply` call code ID used for sub-messages.
@@ -289,6 +814,7 @@ pub enum StablePoolUpdateParams {
 pub enum ReplyIds {
     CreateDenom = 1,
     InstantiateTrackingContract = 2,
+    FlashLoan = 3,
 }
 
 impl TryFrom<u64> for ReplyIds {
@@ -298,6 +824,7 @@ impl TryFrom<u64> for ReplyIds {
         match value {
             1 => Ok(ReplyIds::CreateDenom),
             2 => Ok(ReplyIds::InstantiateTrackingContract),
+            3 => Ok(ReplyIds::FlashLoan),
             _ => Err(StdError::ParseErr {
                 target_type: "ReplyIds".to_string(),
                 msg: "Failed to parse reply".to_string(),
@@ -306,6 +833,148 @@ impl TryFrom<u64> for ReplyIds {
     }
 }
 
+/// This enum describes the ways [`assert_max_spread`] can fail.
+#[derive(Debug, PartialEq)]
+pub enum SpreadAssertionError {
+    /// Invalid input or an arithmetic error
+    Std(StdError),
+    /// `max_spread` exceeds [`MAX_ALLOWED_SLIPPAGE`]
+    AllowedSpread,
+    /// The swap spread exceeds `max_spread`
+    MaxSpread,
+}
+
+impl From<StdError> for SpreadAssertionError {
+    fn from(err: StdError) -> Self {
+        SpreadAssertionError::Std(err)
+    }
+}
+
+/// Returns the spread a swap is checked against. If the belief price is set, the spread is
+/// calculated relative to the return expected at that price, otherwise the swap spread is used.
+///
+/// * **belief_price** belief price used in the swap.
+///
+/// * **offer_amount** amount of assets to swap.
+///
+/// * **return_amount** amount of assets to receive from the swap.
+///
+/// * **spread_amount** spread used in the swap.
+pub fn effective_spread_amount(
+    belief_price: Option<Decimal>,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+) -> StdResult<Uint128> {
+    match belief_price {
+        Some(belief_price) => {
+            if belief_price.is_zero() {
+                return Err(StdError::generic_err("Belief price must not be zero!"));
+            }
+            let expected_return: Uint128 = offer_amount
+                .full_mul(belief_price.denominator())
+                .checked_div(belief_price.numerator().into())?
+                .try_into()?;
+            Ok(expected_return.saturating_sub(return_amount))
+        }
+        None => Ok(spread_amount),
+    }
+}
+
+/// If `belief_price` and `max_spread` are both specified, we compute a new spread,
+/// otherwise we just use the swap spread to check `max_spread`.
+/// The spread is calculated relative to the amount a user would receive without any spread.
+///
+/// * **belief_price** belief price used in the swap.
+///
+/// * **max_spread** max spread allowed so that the swap can be executed successfully.
+/// [`DEFAULT_SLIPPAGE`] is used if not set.
+///
+/// * **offer_amount** amount of assets to swap.
+///
+/// * **return_amount** amount of assets to receive from the swap.
+///
+/// * **spread_amount** spread used in the swap.
+pub fn assert_max_spread(
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+) -> Result<(), SpreadAssertionError> {
+    let max_spread = match max_spread {
+        Some(max_spread) => max_spread,
+        None => Decimal::from_str(DEFAULT_SLIPPAGE)?,
+    };
+    if max_spread > Decimal::from_str(MAX_ALLOWED_SLIPPAGE)? {
+        return Err(SpreadAssertionError::AllowedSpread);
+    }
+
+    let spread_amount =
+        effective_spread_amount(belief_price, offer_amount, return_amount, spread_amount)?;
+
+    let expected_return = return_amount
+        .checked_add(spread_amount)
+        .map_err(StdError::from)?;
+    if !spread_amount.is_zero() && Decimal::from_ratio(spread_amount, expected_return) > max_spread
+    {
+        return Err(SpreadAssertionError::MaxSpread);
+    }
+
+    Ok(())
+}
+
+/// This structure describes a swap which returns less ask assets than the requested minimum.
+#[derive(Debug, PartialEq)]
+pub struct MinOutNotMet {
+    pub expected: Uint128,
+    pub actual: Uint128,
+}
+
+/// Ensures the swap returns at least `min_out` ask assets (if it was specified).
+///
+/// * **min_out** the minimum amount of ask assets to receive.
+///
+/// * **return_amount** amount of ask assets the swap returns.
+pub fn assert_min_out(
+    min_out: Option<Uint128>,
+    return_amount: Uint128,
+) -> Result<(), MinOutNotMet> {
+    match min_out {
+        Some(expected) if return_amount < expected => Err(MinOutNotMet {
+            expected,
+            actual: return_amount,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Returns the smallest amount in `1..=max_amount` for which `is_viable` holds.
+/// `is_viable` must be monotonic, i.e. once it holds for an amount it holds for every bigger amount.
+/// Fails if even `max_amount` is not viable.
+pub fn find_min_swap_amount(
+    max_amount: Uint128,
+    mut is_viable: impl FnMut(Uint128) -> StdResult<bool>,
+) -> StdResult<Uint128> {
+    if max_amount.is_zero() || !is_viable(max_amount)? {
+        return Err(StdError::generic_err("Pool liquidity is too low to swap"));
+    }
+
+    // Zero is never viable while max_amount is, so the answer always lies in (low, high]
+    let mut low = Uint128::zero();
+    let mut high = max_amount;
+    while high - low > Uint128::one() {
+        let mid = low + (high - low) / Uint128::new(2);
+        if is_viable(mid)? {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+
+    Ok(high)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -353,6 +1022,8 @@ mod tests {
                 to_json_binary(&StablePoolConfig {
                     amp: Decimal::one(),
                     fee_share: None,
+                    max_swap_volume_per_block: None,
+                    volume_limit_decay_blocks: 0,
                 })
                 .unwrap(),
             ),
@@ -363,4 +1034,152 @@ mod tests {
 
         let _: ConfigResponse = from_json(&ser_msg).unwrap();
     }
+
+    #[test]
+    fn test_assert_max_spread() {
+        let max_spread = Some(Decimal::percent(1));
+        let check = |return_amount: u128, spread_amount: u128, max_spread| {
+            assert_max_spread(
+                None,
+                max_spread,
+                Uint128::zero(),
+                return_amount.into(),
+                spread_amount.into(),
+            )
+        };
+
+        // Zero spread
+        check(100, 0, max_spread).unwrap();
+
+        // Spread right at the limit
+        check(99, 1, max_spread).unwrap();
+
+        // Max spread exceeded
+        assert_eq!(
+            check(98, 2, max_spread).unwrap_err(),
+            SpreadAssertionError::MaxSpread
+        );
+
+        // Max spread is above the allowed limit
+        assert_eq!(
+            check(100, 0, Some(Decimal::percent(51))).unwrap_err(),
+            SpreadAssertionError::AllowedSpread
+        );
+
+        // None max_spread falls back to the default slippage of 0.5%
+        check(995, 5, None).unwrap();
+        assert_eq!(
+            check(994, 6, None).unwrap_err(),
+            SpreadAssertionError::MaxSpread
+        );
+
+        // Zero return amount
+        check(0, 0, max_spread).unwrap();
+        assert_eq!(
+            check(0, 1, max_spread).unwrap_err(),
+            SpreadAssertionError::MaxSpread
+        );
+
+        // Overflows are reported instead of being hidden behind the spread check
+        assert!(matches!(
+            check(u128::MAX, 1, max_spread).unwrap_err(),
+            SpreadAssertionError::Std(StdError::Overflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_assert_max_spread_with_belief_price() {
+        let max_spread = Some(Decimal::percent(1));
+        let check = |belief_price, return_amount: u128| {
+            assert_max_spread(
+                Some(belief_price),
+                max_spread,
+                1000u128.into(),
+                return_amount.into(),
+                Uint128::zero(),
+            )
+        };
+
+        // The expected return at the belief price of 2 is 500
+        check(Decimal::percent(200), 495).unwrap();
+        assert_eq!(
+            check(Decimal::percent(200), 494).unwrap_err(),
+            SpreadAssertionError::MaxSpread
+        );
+        // A return above the expected one has no spread
+        check(Decimal::percent(200), 600).unwrap();
+
+        assert_eq!(
+            check(Decimal::zero(), 500).unwrap_err(),
+            SpreadAssertionError::Std(StdError::generic_err("Belief price must not be zero!"))
+        );
+
+        // The expected return doesn't fit into Uint128
+        assert!(matches!(
+            assert_max_spread(
+                Some(Decimal::from_ratio(1u8, 10u8)),
+                max_spread,
+                Uint128::MAX,
+                Uint128::zero(),
+                Uint128::zero(),
+            )
+            .unwrap_err(),
+            SpreadAssertionError::Std(StdError::ConversionOverflow { .. })
+        ));
+    }
+
+    #[test]
+    fn test_calculate_price_impact() {
+        let spot_price = Decimal256::percent(200);
+
+        // 1000 offered for 1900 returned instead of 2000 at the spot price
+        let impact = calculate_price_impact(
+            spot_price,
+            Decimal256::from_ratio(1000u16, 1u8),
+            Decimal256::from_ratio(1900u16, 1u8),
+        )
+        .unwrap();
+        assert_eq!(impact, Decimal::percent(5));
+
+        // Execution better than the spot price
+        let impact = calculate_price_impact(
+            spot_price,
+            Decimal256::from_ratio(1000u16, 1u8),
+            Decimal256::from_ratio(2100u16, 1u8),
+        )
+        .unwrap();
+        assert_eq!(impact, Decimal::zero());
+
+        // Zero-size swap
+        let impact =
+            calculate_price_impact(spot_price, Decimal256::zero(), Decimal256::zero()).unwrap();
+        assert_eq!(impact, Decimal::zero());
+
+        // Empty pool
+        let impact = calculate_price_impact(
+            Decimal256::zero(),
+            Decimal256::from_ratio(1000u16, 1u8),
+            Decimal256::zero(),
+        )
+        .unwrap();
+        assert_eq!(impact, Decimal::one());
+    }
+
+    #[test]
+    fn test_find_min_swap_amount() {
+        for threshold in [1u128, 2, 17, 999, 1000] {
+            let min_amount =
+                find_min_swap_amount(Uint128::new(1000), |amount| Ok(amount.u128() >= threshold))
+                    .unwrap();
+            assert_eq!(min_amount.u128(), threshold);
+        }
+
+        let err = find_min_swap_amount(Uint128::new(1000), |amount| Ok(amount.u128() > 1000))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Generic error: Pool liquidity is too low to swap"
+        );
+        find_min_swap_amount(Uint128::zero(), |_| Ok(true)).unwrap_err();
+    }
 }
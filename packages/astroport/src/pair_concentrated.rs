@@ -1,12 +1,14 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Decimal, Decimal256, Uint128, Uint64};
+use cosmwasm_std::{Addr, Decimal, Decimal256, Int128, Uint128, Uint64};
 
 use crate::asset::PairInfo;
 use crate::asset::{Asset, AssetInfo};
-use crate::observation::OracleObservation;
+use crate::observation::{
+    Observation, ObservationResponse, OracleObservation, VolumeWindowResponse,
+};
 use crate::pair::{
-    ConfigResponse, CumulativePricesResponse, FeeShareConfig, PoolResponse,
-    ReverseSimulationResponse, SimulationResponse,
+    AccumulatedFeesResponse, ConfigResponse, CumulativePricesResponse, FeeShareConfig,
+    MinSwapAmountResponse, PoolResponse, ReverseSimulationResponse, SimulationResponse,
 };
 
 /// This structure holds concentrated pool parameters.
@@ -76,6 +78,39 @@ pub enum ConcentratedPoolUpdateParams {
         fee_share_address: String,
     },
     DisableFeeShare,
+    /// Enables the swap fee derived from the volatility of the latest observed prices.
+    /// If the volatility fee is already enabled, its values are overwritten.
+    EnableVolatilityFee(VolatilityFeeConfig),
+    /// Switches the swap fee back to the one depending on the pool balance.
+    DisableVolatilityFee,
+    /// Limits the swap volume accepted within a block. The limit is removed if
+    /// `max_swap_volume_per_block` is not set.
+    SetSwapVolumeLimit {
+        /// The maximum swap volume of each asset (in that asset's units) accepted within a block
+        max_swap_volume_per_block: Option<Uint128>,
+        /// The number of blocks over which previously swapped volume is linearly released.
+        /// Values of 0 and 1 reset the volume at every block. Capped by [`crate::pair::MAX_VOLUME_LIMIT_DECAY_BLOCKS`]
+        volume_limit_decay_blocks: u64,
+    },
+    /// Sets whether traders are omitted from the recent swaps log.
+    SetHideSwapTraders {
+        hide_swap_traders: bool,
+    },
+    /// Sets the fee charged on flash loans relative to the borrowed amount
+    SetFlashLoanFee {
+        fee: Decimal,
+    },
+}
+
+/// Holds the configuration for the volatility based swap fee
+#[cw_serde]
+pub struct VolatilityFeeConfig {
+    /// The fee charged when the observed price is stable
+    pub min_fee_bps: u16,
+    /// The fee charged when the observed price volatility reaches the saturation level
+    pub max_fee_bps: u16,
+    /// The number of the latest observations used to estimate the price volatility
+    pub volatility_window: u8,
 }
 
 /// This structure stores a CL pool's configuration.
@@ -105,6 +140,21 @@ pub struct ConcentratedPoolConfig {
     pub track_asset_balances: bool,
     /// The config for swap fee sharing
     pub fee_share: Option<FeeShareConfig>,
+    /// The config for the volatility based swap fee
+    #[serde(default)]
+    pub volatility_fee: Option<VolatilityFeeConfig>,
+    /// The maximum swap volume of each asset (in that asset's units) accepted within a block
+    #[serde(default)]
+    pub max_swap_volume_per_block: Option<Uint128>,
+    /// The number of blocks over which previously swapped volume is linearly released
+    #[serde(default)]
+    pub volume_limit_decay_blocks: u64,
+    /// Whether traders are omitted from the recent swaps log
+    #[serde(default)]
+    pub hide_swap_traders: bool,
+    /// The fee charged on flash loans relative to the borrowed amount
+    #[serde(default)]
+    pub flash_loan_fee: Decimal,
 }
 
 /// This structure describes the query messages available in the contract.
@@ -151,9 +201,32 @@ pub enum QueryMsg {
         asset_info: AssetInfo,
         block_height: Uint64,
     },
-    /// Query price from observations
+    /// Query price from observations. Falls back to the external oracle if the
+    /// observation is not available in the pair
     #[returns(OracleObservation)]
     Observe { seconds_ago: u64 },
+    /// Returns the latest stored observation at or before the given timestamp
+    #[returns(ObservationResponse)]
+    ObservationAt { timestamp: u64 },
+    /// Returns the median price of the latest `window` observations
+    #[returns(Decimal)]
+    MedianPrice { window: u32 },
+    /// Returns the base and quote volumes traded within the last `window_seconds` seconds
+    #[returns(VolumeWindowResponse)]
+    VolumeWindow { window_seconds: u64 },
+    /// Returns the time weighted average price over the last `window_seconds` seconds
+    /// with the price interpolated between observations
+    #[returns(Decimal)]
+    ObservationWindow { window_seconds: u64 },
+    /// Returns the observations within the inclusive `[start_ts, end_ts]` range in ascending time order.
+    /// At most 200 observations are returned, the rest can be read by passing the timestamp
+    /// of the last returned observation as `start_after`
+    #[returns(Vec<Observation>)]
+    ObservationRange {
+        start_ts: u64,
+        end_ts: u64,
+        start_after: Option<u64>,
+    },
     /// Returns an estimation of shares received for the given amount of assets
     #[returns(Uint128)]
     SimulateProvide {
@@ -163,6 +236,175 @@ pub enum QueryMsg {
     /// Returns an estimation of assets received for the given amount of LP tokens
     #[returns(Vec<Asset>)]
     SimulateWithdraw { lp_amount: Uint128 },
+    /// Returns the latest swaps starting from the most recent one
+    #[returns(Vec<SwapRecord>)]
+    RecentSwaps { limit: u32 },
+    /// Returns the difference between the current value of an LP position and the value of
+    /// holding the assets it was entered with, denominated in the first pool asset.
+    /// A positive value means the position outperformed holding.
+    /// `entry_prices` holds the LP token virtual price and the price scale at entry.
+    #[returns(Int128)]
+    ImpermanentGain {
+        entry_lp_amount: Uint128,
+        entry_prices: [Decimal; 2],
+    },
+    /// Returns the external oracle contract set with `UpgradeOracle`, if any
+    #[returns(Option<Addr>)]
+    ExternalOracle {},
+    /// Returns the oracle configuration and price observation quality metrics
+    #[returns(OracleResponse)]
+    Oracle {},
+    /// Returns swap activity metrics aggregated over the recent swaps log within the given
+    /// inclusive time range
+    #[returns(PoolStatisticsResponse)]
+    PoolStatistics { from_ts: u64, to_ts: u64 },
+    /// Returns how far the latest observed price deviates from its simple moving average
+    /// over the last `window_secs` seconds
+    #[returns(PriceDeviationResponse)]
+    PriceDeviationFromSma { window_secs: u64 },
+    /// Returns price, volume, fee and LP token value statistics for the swaps in the recent swaps log
+    /// executed within the given inclusive block height range
+    #[returns(PairStatsResponse)]
+    PairStats { from_height: u64, to_height: u64 },
+    /// Returns the narrowest price range which contains `coverage_pct` of the pool liquidity
+    #[returns(LiquidityBandResponse)]
+    LiquidityBand { coverage_pct: Decimal },
+    /// Returns the fee rate which is currently charged on swaps
+    #[returns(Decimal)]
+    CurrentFee {},
+    /// Returns the amount of the other pool asset required to provide `asset` at the current
+    /// pool ratio along with the LP tokens such a balanced provision mints
+    #[returns(OptimalLiquidityResponse)]
+    OptimalLiquidityAmounts { asset: Asset },
+    /// Returns the minimum amount of each pool asset which can be swapped for a non-zero return
+    #[returns(MinSwapAmountResponse)]
+    MinSwapAmount {},
+    /// Returns the protocol fees the pool sent to the Maker
+    #[returns(AccumulatedFeesResponse)]
+    AccumulatedFees {},
+}
+
+/// This structure describes swap activity metrics aggregated over the recent swaps log.
+#[cw_serde]
+pub struct PoolStatisticsResponse {
+    /// Number of swaps within the time range
+    pub trade_count: u32,
+    /// Total amount of the first pool asset traded
+    pub total_volume_base: Uint128,
+    /// Total amount of the second pool asset traded
+    pub total_volume_quote: Uint128,
+    /// Average fee rate charged on the swaps
+    pub average_fee: Decimal,
+    /// The largest swap in the first pool asset units
+    pub max_single_trade_base: Uint128,
+    /// The smallest swap in the first pool asset units
+    pub min_single_trade_base: Uint128,
+}
+
+/// This structure describes price, volume and LP token value statistics over a block height range.
+/// Prices are swap execution prices in second asset units per one unit of the first asset.
+#[cw_serde]
+pub struct PairStatsResponse {
+    /// Execution price of the first swap within the range
+    pub open_price: Decimal,
+    /// Execution price of the last swap within the range
+    pub close_price: Decimal,
+    /// The highest execution price within the range
+    pub high_price: Decimal,
+    /// The lowest execution price within the range
+    pub low_price: Decimal,
+    /// Total amount traded in the first pool asset units
+    pub total_volume: Uint128,
+    /// Total fees charged in the first pool asset units
+    pub fee_income: Uint128,
+    /// Relative growth of the LP token virtual price between the first and the last swap within the range
+    pub lp_token_appreciation: Decimal,
+}
+
+/// Minimum number of stored observations for the pair to be considered a manipulation resistant oracle
+pub const MIN_ORACLE_OBSERVATIONS: u32 = 10;
+/// Maximum price volatility for the pair to be considered a manipulation resistant oracle (10%)
+pub const MAX_ORACLE_VOLATILITY: Decimal = Decimal::percent(10);
+
+/// This structure describes the pair's oracle configuration and quality metrics.
+#[cw_serde]
+pub struct OracleResponse {
+    /// "internal" when observations are only kept by the pair, "external" when they are also
+    /// forwarded to an oracle contract
+    pub oracle_type: String,
+    /// Number of stored observations
+    pub observations_count: u32,
+    /// Seconds elapsed since the latest observation
+    pub last_observation_age: u64,
+    /// Spread between the highest and the lowest observed price relative to the lowest one
+    pub price_volatility: Decimal,
+    /// Whether there are at least [`MIN_ORACLE_OBSERVATIONS`] observations and the price volatility
+    /// is below [`MAX_ORACLE_VOLATILITY`]
+    pub is_manipulation_resistant: bool,
+}
+
+/// Maximum deviation of the spot price from its moving average before it is considered anomalous (5%)
+pub const MAX_SMA_DEVIATION: Decimal = Decimal::percent(5);
+
+/// This structure describes the deviation of the spot price from its moving average.
+#[cw_serde]
+pub struct PriceDeviationResponse {
+    /// The latest observed price
+    pub spot_price: Decimal,
+    /// Simple moving average of the prices observed within the window
+    pub sma_price: Decimal,
+    /// Absolute difference between the spot price and the moving average relative to the moving average
+    pub deviation_pct: Decimal,
+    /// Whether the deviation exceeds [`MAX_SMA_DEVIATION`]
+    pub is_anomalous: bool,
+}
+
+/// This structure describes the price range holding a share of the pool liquidity.
+#[cw_serde]
+pub struct LiquidityBandResponse {
+    /// Lower bound of the second pool asset price denominated in the first pool asset
+    pub price_lower: Decimal,
+    /// Upper bound of the second pool asset price denominated in the first pool asset
+    pub price_upper: Decimal,
+    /// Pool liquidity within the band, valued in the first pool asset at the price scale
+    pub liquidity_in_band: Decimal256,
+}
+
+/// This structure describes a balanced liquidity provision.
+#[cw_serde]
+pub struct OptimalLiquidityResponse {
+    /// The asset given in the query
+    pub asset_a: Asset,
+    /// The amount of the other pool asset matching the current pool ratio
+    pub asset_b: Asset,
+    /// LP tokens minted by providing both assets
+    pub lp_tokens_estimated: Uint128,
+}
+
+/// Maximum number of swaps kept in the recent swaps log
+pub const MAX_RECENT_SWAPS: u32 = 50;
+
+/// This structure describes a swap stored in the recent swaps log.
+#[cw_serde]
+pub struct SwapRecord {
+    /// Block timestamp of the swap
+    pub ts: u64,
+    /// The asset offered by the trader
+    pub offer: Asset,
+    /// The asset returned by the pool
+    pub returned: Asset,
+    /// The spread amount in ask asset units
+    pub spread: Uint128,
+    /// The total fee charged in ask asset units
+    pub fee: Uint128,
+    /// The trader who initiated the swap. Not set if the pool hides swap traders
+    pub trader: Option<Addr>,
+    /// Block height of the swap
+    #[serde(default)]
+    pub height: u64,
+    /// LP token virtual price right after the swap
+    #[serde(default)]
+    pub virtual_price: Decimal256,
 }
 
 #[cw_serde]
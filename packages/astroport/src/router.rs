@@ -1,9 +1,9 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{Addr, Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
-use crate::asset::AssetInfo;
+use crate::asset::{Asset, AssetInfo};
 
 pub const MAX_SWAP_OPERATIONS: usize = 50;
 
@@ -101,6 +101,23 @@ pub enum QueryMsg {
         /// The swap operations to perform, each swap involving a specific pool
         operations: Vec<SwapOperation>,
     },
+    /// SimulateMultiHopSwap simulates multi-hop swap operations and returns the result of every hop
+    #[returns(MultiHopSimulationResponse)]
+    SimulateMultiHopSwap {
+        /// The asset to swap
+        offer_asset: Asset,
+        /// The swap operations to perform, each swap involving a specific pool
+        operations: Vec<SwapOperation>,
+    },
+    /// SimulateReverseMultiHopSwap simulates multi-hop swap operations backwards starting from the
+    /// asset to receive and returns the result of every hop
+    #[returns(ReverseMultiHopSimulationResponse)]
+    SimulateReverseMultiHopSwap {
+        /// The asset to receive from the last swap operation
+        ask_asset: Asset,
+        /// The swap operations to perform, each swap involving a specific pool
+        operations: Vec<SwapOperation>,
+    },
 }
 
 /// This structure describes a custom struct to return a query response containing the base contract configuration.
@@ -117,6 +134,47 @@ pub struct SimulateSwapOperationsResponse {
     pub amount: Uint128,
 }
 
+/// This structure describes the simulated result of a single swap operation.
+#[cw_serde]
+pub struct HopSimulation {
+    /// The pair contract performing the swap
+    pub pair: Addr,
+    /// The asset offered to the pair
+    pub offer_asset: Asset,
+    /// The asset returned by the pair
+    pub return_asset: Asset,
+    /// The spread charged by the pair (in ask asset units)
+    pub spread_amount: Uint128,
+    /// The commission charged by the pair (in ask asset units)
+    pub commission_amount: Uint128,
+}
+
+/// This structure describes a custom struct to return a query response containing the result of a
+/// multi-hop swap simulation.
+#[cw_serde]
+pub struct MultiHopSimulationResponse {
+    /// The amount of tokens received from the last swap operation
+    pub return_amount: Uint128,
+    /// The sum of spread amounts of all hops. Each hop's spread is denominated in its own ask
+    /// asset, thus this value is only indicative for paths with differently priced assets
+    pub spread_amount: Uint128,
+    /// The simulated result of every swap operation in the path order
+    pub hops: Vec<HopSimulation>,
+}
+
+/// This structure describes a custom struct to return a query response containing the result of a
+/// reverse multi-hop swap simulation.
+#[cw_serde]
+pub struct ReverseMultiHopSimulationResponse {
+    /// The amount of tokens to offer to the first swap operation
+    pub offer_amount: Uint128,
+    /// The sum of spread amounts of all hops. Each hop's spread is denominated in its own ask
+    /// asset, thus this value is only indicative for paths with differently priced assets
+    pub spread_amount: Uint128,
+    /// The simulated result of every swap operation in the path order
+    pub hops: Vec<HopSimulation>,
+}
+
 /// This structure describes a migration message.
 /// We currently take no arguments for migrations.
 #[cw_serde]
@@ -0,0 +1,180 @@
+//! Shared fixtures for pair contract unit tests.
+
+use cosmwasm_std::testing::{mock_env, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{Addr, Binary, BlockInfo, Env, Timestamp, Uint128};
+
+use crate::asset::{Asset, AssetInfo, PairInfo};
+use crate::factory::PairType;
+use crate::pair::{ConfigResponse, InstantiateMsg, PoolResponse};
+
+/// Returns a native asset with the given denom and amount.
+pub fn make_native_asset(denom: &str, amount: impl Into<Uint128>) -> Asset {
+    Asset {
+        info: AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        },
+        amount: amount.into(),
+    }
+}
+
+/// Returns a CW20 asset with the given contract address and amount.
+pub fn make_cw20_asset(contract_addr: &str, amount: impl Into<Uint128>) -> Asset {
+    Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked(contract_addr),
+        },
+        amount: amount.into(),
+    }
+}
+
+/// Returns a [`PoolResponse`] with the given reserves and LP token supply.
+pub fn mock_pool_response(assets: &[Asset], total_share: impl Into<Uint128>) -> PoolResponse {
+    PoolResponse {
+        assets: assets.to_vec(),
+        total_share: total_share.into(),
+        external_liquidity: vec![],
+    }
+}
+
+/// Returns a mocked environment at block height 1 with the given block time (in seconds).
+pub fn mock_env_with_block_time(time: u64) -> Env {
+    let mut env = mock_env();
+    env.block = BlockInfo {
+        height: 1,
+        time: Timestamp::from_seconds(time),
+        chain_id: "columbus".to_string(),
+    };
+    env
+}
+
+/// Builder describing the state of a mocked pair.
+#[derive(Clone, Debug)]
+pub struct MockPairSetup {
+    /// Pool assets
+    pub assets: Vec<AssetInfo>,
+    /// Pool reserves, in the same order as the assets
+    pub reserves: Vec<Uint128>,
+    /// Total LP token supply
+    pub lp_supply: Uint128,
+    /// Pair configuration
+    pub config: ConfigResponse,
+}
+
+impl Default for MockPairSetup {
+    fn default() -> Self {
+        Self {
+            assets: vec![],
+            reserves: vec![],
+            lp_supply: Uint128::zero(),
+            config: ConfigResponse {
+                block_time_last: 0,
+                params: None,
+                owner: Addr::unchecked("owner"),
+                factory_addr: Addr::unchecked("factory"),
+                tracker_addr: None,
+                liquidity_concentration: None,
+                is_paused: false,
+                estimated_annual_fee_rate: None,
+            },
+        }
+    }
+}
+
+impl MockPairSetup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a pool asset with the given reserve.
+    pub fn with_asset(mut self, asset: Asset) -> Self {
+        self.assets.push(asset.info);
+        self.reserves.push(asset.amount);
+        self
+    }
+
+    pub fn with_lp_supply(mut self, lp_supply: impl Into<Uint128>) -> Self {
+        self.lp_supply = lp_supply.into();
+        self
+    }
+
+    pub fn with_config(mut self, config: ConfigResponse) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_params(mut self, params: Binary) -> Self {
+        self.config.params = Some(params);
+        self
+    }
+
+    /// Returns pool reserves as a list of assets.
+    pub fn pool_assets(&self) -> Vec<Asset> {
+        self.assets
+            .iter()
+            .zip(self.reserves.iter())
+            .map(|(info, amount)| Asset {
+                info: info.clone(),
+                amount: *amount,
+            })
+            .collect()
+    }
+
+    pub fn pool_response(&self) -> PoolResponse {
+        mock_pool_response(&self.pool_assets(), self.lp_supply)
+    }
+
+    /// Returns the message instantiating the described pair. The LP token code id is not used
+    /// by token factory based pairs, so an arbitrary one is set.
+    pub fn instantiate_msg(&self) -> InstantiateMsg {
+        InstantiateMsg {
+            asset_infos: self.assets.clone(),
+            token_code_id: 10u64,
+            factory_addr: self.config.factory_addr.to_string(),
+            init_params: self.config.params.clone(),
+        }
+    }
+
+    /// Returns the pair info of a pair deployed at [`MOCK_CONTRACT_ADDR`].
+    pub fn pair_info(&self, pair_type: PairType) -> PairInfo {
+        PairInfo {
+            asset_infos: self.assets.clone(),
+            contract_addr: Addr::unchecked(MOCK_CONTRACT_ADDR),
+            liquidity_token: format!("factory/{MOCK_CONTRACT_ADDR}/astroport/share"),
+            pair_type,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_pair_setup() {
+        let setup = MockPairSetup::new()
+            .with_asset(make_native_asset("uusd", 100u128))
+            .with_asset(make_cw20_asset("asset0000", 200u128))
+            .with_lp_supply(150u128);
+
+        assert_eq!(
+            setup.pool_response(),
+            PoolResponse {
+                assets: vec![
+                    make_native_asset("uusd", 100u128),
+                    make_cw20_asset("asset0000", 200u128)
+                ],
+                total_share: Uint128::new(150),
+                external_liquidity: vec![],
+            }
+        );
+
+        let pair_info = setup.pair_info(PairType::Xyk {});
+        assert_eq!(pair_info.asset_infos, setup.assets);
+        assert_eq!(pair_info.contract_addr.as_str(), MOCK_CONTRACT_ADDR);
+
+        let msg = setup.instantiate_msg();
+        assert_eq!(msg.asset_infos, setup.assets);
+        assert_eq!(msg.factory_addr, "factory");
+        assert_eq!(msg.init_params, None);
+    }
+}
@@ -0,0 +1,23 @@
+use cosmwasm_std::Uint128;
+
+/// This structure describes a swap which exceeds the per block swap volume limit.
+#[derive(Debug, PartialEq)]
+pub struct BlockVolumeLimitExceeded {
+    pub volume: Uint128,
+    pub limit: Uint128,
+}
+
+/// This structure describes a message sent to a pair whose state was written by another code version.
+#[derive(Debug, PartialEq)]
+pub struct VersionMismatch {
+    pub expected: String,
+    pub got: String,
+}
+
+/// This structure describes a mismatch between the LP tokens attached to a message and the
+/// amount specified in it.
+#[derive(Debug, PartialEq)]
+pub struct LpAmountMismatch {
+    pub sent: Uint128,
+    pub expected: Uint128,
+}
@@ -0,0 +1,10 @@
+use cosmwasm_std::Uint128;
+use cw_storage_plus::{Item, Map};
+
+use astroport::asset::AssetInfo;
+
+/// Swapped volume by offer asset and block height. Used to enforce the per block swap volume limit
+pub const BLOCK_VOLUME: Map<(&AssetInfo, u64), Uint128> = Map::new("block_volume_by_asset");
+
+/// The code version which last wrote the pair state. Set on instantiation and migration
+pub const CODE_VERSION: Item<String> = Item::new("code_version");
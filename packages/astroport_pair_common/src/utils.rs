@@ -0,0 +1,199 @@
+use cosmwasm_std::{
+    attr, coin, ensure_eq, wasm_execute, Addr, Api, Coin, CosmosMsg, MessageInfo, Order, Response,
+    StdError, StdResult, Storage, Uint128,
+};
+use cw_storage_plus::Bound;
+use cw_utils::{one_coin, PaymentError};
+
+use astroport::asset::{Asset, AssetInfo};
+use astroport::factory::ExecuteMsg as FactoryExecuteMsg;
+use astroport::incentives::ExecuteMsg as IncentiveExecuteMsg;
+
+use crate::error::{BlockVolumeLimitExceeded, LpAmountMismatch, VersionMismatch};
+use crate::state::{BLOCK_VOLUME, CODE_VERSION};
+
+/// Accounts `volume` of `asset_info` against the per block swap volume limit if one is set.
+/// Volumes are tracked separately for every asset, so `limit` is denominated in `asset_info` units.
+/// Volume swapped in earlier blocks is released linearly over `decay_blocks` blocks,
+/// so the available volume recovers gradually instead of being fully reset at every block.
+///
+/// * **limit** the maximum volume accepted within a block.
+///
+/// * **decay_blocks** the number of blocks over which previously swapped volume is released.
+/// Values of 0 and 1 reset the volume at every block.
+pub fn accumulate_block_volume<E>(
+    storage: &mut dyn Storage,
+    height: u64,
+    limit: Option<Uint128>,
+    decay_blocks: u64,
+    asset_info: &AssetInfo,
+    volume: Uint128,
+) -> Result<(), E>
+where
+    E: From<StdError> + From<BlockVolumeLimitExceeded>,
+{
+    let Some(limit) = limit else {
+        return Ok(());
+    };
+
+    let decay_blocks = decay_blocks.max(1);
+    let oldest_height = height.saturating_sub(decay_blocks - 1);
+
+    // Volumes recorded before the decay period do not count anymore
+    let expired = BLOCK_VOLUME
+        .prefix(asset_info)
+        .keys(
+            storage,
+            None,
+            Some(Bound::exclusive(oldest_height)),
+            Order::Ascending,
+        )
+        .collect::<StdResult<Vec<_>>>()?;
+    for block in expired {
+        BLOCK_VOLUME.remove(storage, (asset_info, block));
+    }
+
+    let mut total_volume = volume;
+    for item in BLOCK_VOLUME.prefix(asset_info).range(
+        storage,
+        Some(Bound::inclusive(oldest_height)),
+        None,
+        Order::Ascending,
+    ) {
+        let (block, block_volume) = item?;
+        total_volume = total_volume
+            .checked_add(block_volume.multiply_ratio(decay_blocks - (height - block), decay_blocks))
+            .map_err(StdError::from)?;
+    }
+
+    if total_volume > limit {
+        return Err(BlockVolumeLimitExceeded {
+            volume: total_volume,
+            limit,
+        }
+        .into());
+    }
+
+    BLOCK_VOLUME.update(
+        storage,
+        (asset_info, height),
+        |block_volume| -> StdResult<_> {
+            Ok(block_volume.unwrap_or_default().checked_add(volume)?)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// Ensures the pair state was written by the compiled code version, i.e. the code was not upgraded
+/// without going through the migration entry point.
+///
+/// * **expected** the compiled contract version.
+pub fn assert_code_version<E>(storage: &dyn Storage, expected: &str) -> Result<(), E>
+where
+    E: From<StdError> + From<VersionMismatch>,
+{
+    let got = CODE_VERSION.may_load(storage)?.unwrap_or_default();
+    ensure_eq!(
+        got,
+        expected,
+        VersionMismatch {
+            expected: expected.to_string(),
+            got,
+        }
+    );
+
+    Ok(())
+}
+
+/// Stakes LP tokens sent along with the message in the Incentives contract on behalf of the recipient.
+/// Implements [`astroport::pair::ExecuteMsg::TransferLpAndStake`] for all pair types.
+///
+/// * **liquidity_token** the LP token denom of the pair.
+///
+/// * **recipient** address on whose behalf LP tokens are staked.
+///
+/// * **amount** amount of LP tokens attached to the message.
+///
+/// * **generator** Incentives contract address.
+pub fn transfer_lp_and_stake<E>(
+    api: &dyn Api,
+    info: MessageInfo,
+    liquidity_token: &str,
+    recipient: String,
+    amount: Uint128,
+    generator: String,
+) -> Result<Response, E>
+where
+    E: From<StdError> + From<PaymentError> + From<LpAmountMismatch>,
+{
+    let Coin {
+        amount: sent_amount,
+        denom,
+    } = one_coin(&info)?;
+
+    ensure_eq!(
+        denom,
+        liquidity_token,
+        PaymentError::MissingDenom(liquidity_token.to_string())
+    );
+    ensure_eq!(
+        sent_amount,
+        amount,
+        LpAmountMismatch {
+            sent: sent_amount,
+            expected: amount,
+        }
+    );
+
+    let recipient = api.addr_validate(&recipient)?;
+    let generator = api.addr_validate(&generator)?;
+
+    let deposit_msg = wasm_execute(
+        &generator,
+        &IncentiveExecuteMsg::Deposit {
+            recipient: Some(recipient.to_string()),
+        },
+        vec![coin(amount.u128(), denom)],
+    )?;
+
+    Ok(Response::new().add_message(deposit_msg).add_attributes([
+        attr("action", "transfer_lp_and_stake"),
+        attr("sender", info.sender),
+        attr("recipient", recipient),
+        attr("generator", generator),
+        attr("amount", amount),
+    ]))
+}
+
+/// Builds the messages which finalize [`astroport::pair::ExecuteMsg::ReplaceAsset`]: the reserve of
+/// the replaced asset is sent to `recipient` and the factory is asked to register the pair under
+/// its new assets.
+pub fn replace_asset_messages(
+    factory_addr: &Addr,
+    old_asset_infos: Vec<AssetInfo>,
+    old_asset: AssetInfo,
+    old_reserve: Uint128,
+    recipient: &Addr,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = vec![];
+    if !old_reserve.is_zero() {
+        messages.push(
+            Asset {
+                info: old_asset,
+                amount: old_reserve,
+            }
+            .into_msg(recipient)?,
+        );
+    }
+    messages.push(
+        wasm_execute(
+            factory_addr,
+            &FactoryExecuteMsg::UpdatePairKey { old_asset_infos },
+            vec![],
+        )?
+        .into(),
+    );
+
+    Ok(messages)
+}
@@ -8,6 +8,8 @@ pub const DEFAULT_SLIPPAGE: Decimal256 = Decimal256::raw(50000000000000000);
 pub const MAX_ALLOWED_SLIPPAGE: Decimal256 = Decimal256::raw(500000000000000000);
 /// Percentage of 1st pool volume used as offer amount to forecast last price (0.01% or 0.0001).
 pub const OFFER_PERCENT: Decimal256 = Decimal256::raw(100000000000000);
+/// Price volatility at which the volatility fee reaches its maximum (0.05)
+pub const VOLATILITY_FEE_SATURATION: Decimal256 = Decimal256::raw(50000000000000000);
 
 /// ## Internal constants
 /// Number of coins. (2.0)
@@ -45,6 +47,11 @@ pub const PRICE_SCALE_DELTA_MAX: Decimal = Decimal::one();
 
 pub const MA_HALF_TIME_LIMITS: RangeInclusive<u64> = 1..=(7 * 86400);
 
+/// Volatility fee bounds in bps. Matches [`MAX_FEE`]
+pub const VOLATILITY_FEE_BPS_LIMITS: RangeInclusive<u16> = 1..=100;
+/// At least two observations are needed to estimate the price volatility
+pub const VOLATILITY_WINDOW_LIMITS: RangeInclusive<u8> = 2..=u8::MAX;
+
 /// 0.1
 pub const AMP_MIN: Decimal = Decimal::raw(1e17 as u128);
 /// 100000
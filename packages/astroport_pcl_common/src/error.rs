@@ -1,6 +1,8 @@
 use cosmwasm_std::{Decimal, StdError};
 use thiserror::Error;
 
+use astroport::pair::SpreadAssertionError;
+
 use crate::consts::MIN_AMP_CHANGING_TIME;
 
 /// This enum describes pair contract errors
@@ -41,3 +43,22 @@ pub enum PclError {
     #[error("The asset {0} does not belong to the pair")]
     InvalidAsset(String),
 }
+
+impl From<SpreadAssertionError> for PclError {
+    fn from(err: SpreadAssertionError) -> Self {
+        match err {
+            SpreadAssertionError::Std(err) => PclError::Std(err),
+            SpreadAssertionError::AllowedSpread => PclError::AllowedSpreadAssertion {},
+            SpreadAssertionError::MaxSpread => PclError::MaxSpreadAssertion {},
+        }
+    }
+}
+
+impl From<PclError> for StdError {
+    fn from(err: PclError) -> Self {
+        match err {
+            PclError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
+}
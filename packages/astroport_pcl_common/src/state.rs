@@ -10,12 +10,13 @@ use cw_storage_plus::Map;
 use astroport::asset::{AssetInfo, PairInfo};
 use astroport::cosmwasm_ext::{AbsDiff, IntegerToDecimal};
 use astroport::pair::FeeShareConfig;
-use astroport::pair_concentrated::{PromoteParams, UpdatePoolParams};
+use astroport::pair_concentrated::{PromoteParams, UpdatePoolParams, VolatilityFeeConfig};
 
 use crate::consts::{
     AMP_MAX, AMP_MIN, FEE_GAMMA_MAX, FEE_GAMMA_MIN, FEE_TOL, GAMMA_MAX, GAMMA_MIN, MAX_CHANGE,
     MAX_FEE, MA_HALF_TIME_LIMITS, MIN_AMP_CHANGING_TIME, MIN_FEE, N_POW2, PRICE_SCALE_DELTA_MAX,
     PRICE_SCALE_DELTA_MIN, REPEG_PROFIT_THRESHOLD_MAX, REPEG_PROFIT_THRESHOLD_MIN, TWO,
+    VOLATILITY_FEE_BPS_LIMITS, VOLATILITY_WINDOW_LIMITS,
 };
 use crate::error::PclError;
 use crate::math::{calc_d, get_xcp, half_float_pow};
@@ -43,6 +44,21 @@ pub struct Config {
     pub fee_share: Option<FeeShareConfig>,
     /// The tracker contract address
     pub tracker_addr: Option<Addr>,
+    /// The config for the volatility based swap fee
+    #[serde(default)]
+    pub volatility_fee: Option<VolatilityFeeConfig>,
+    /// The maximum swap volume of each asset (in that asset's units) accepted within a block
+    #[serde(default)]
+    pub max_swap_volume_per_block: Option<Uint128>,
+    /// The number of blocks over which previously swapped volume is linearly released
+    #[serde(default)]
+    pub volume_limit_decay_blocks: u64,
+    /// Whether traders are omitted from the recent swaps log
+    #[serde(default)]
+    pub hide_swap_traders: bool,
+    /// The fee charged on flash loans relative to the borrowed amount
+    #[serde(default)]
+    pub flash_loan_fee: Decimal,
 }
 
 /// This structure stores the pool parameters which may be adjusted via the `update_pool_params`.
@@ -172,6 +188,28 @@ impl PoolParams {
     }
 }
 
+/// Validates the volatility fee bounds and the observations window.
+pub fn validate_volatility_fee(volatility_fee: &VolatilityFeeConfig) -> Result<(), PclError> {
+    validate_param(
+        "min_fee_bps",
+        volatility_fee.min_fee_bps,
+        *VOLATILITY_FEE_BPS_LIMITS.start(),
+        *VOLATILITY_FEE_BPS_LIMITS.end(),
+    )?;
+    validate_param(
+        "max_fee_bps",
+        volatility_fee.max_fee_bps,
+        volatility_fee.min_fee_bps,
+        *VOLATILITY_FEE_BPS_LIMITS.end(),
+    )?;
+    validate_param(
+        "volatility_window",
+        volatility_fee.volatility_window,
+        *VOLATILITY_WINDOW_LIMITS.start(),
+        *VOLATILITY_WINDOW_LIMITS.end(),
+    )
+}
+
 /// Structure which stores Amp and Gamma.
 #[cw_serde]
 #[derive(Default, Copy)]
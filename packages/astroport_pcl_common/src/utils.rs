@@ -7,12 +7,15 @@ use itertools::Itertools;
 use astroport::asset::{Asset, AssetInfo, Decimal256Ext, DecimalAsset};
 use astroport::cosmwasm_ext::AbsDiff;
 use astroport::incentives::ExecuteMsg as IncentiveExecuteMsg;
+use astroport::observation::try_dec256_into_dec;
+use astroport::pair_concentrated::VolatilityFeeConfig;
 use astroport::querier::query_factory_config;
 use astroport::token_factory::tf_mint_msg;
 use astroport_factory::state::pair_key;
 
 use crate::consts::{
     DEFAULT_SLIPPAGE, MAX_ALLOWED_SLIPPAGE, N, OFFER_PERCENT, TWAP_PRECISION_DEC, TWO,
+    VOLATILITY_FEE_SATURATION,
 };
 use crate::error::PclError;
 use crate::state::{Config, PoolParams, PriceState};
@@ -29,7 +32,7 @@ pub fn check_asset_infos(api: &dyn Api, asset_infos: &[AssetInfo]) -> Result<(),
 
     asset_infos
         .iter()
-        .try_for_each(|asset_info| asset_info.check(api))
+        .try_for_each(|asset_info| asset_info.validate(api))
         .map_err(Into::into)
 }
 
@@ -43,14 +46,16 @@ pub fn check_assets(api: &dyn Api, assets: &[Asset]) -> Result<(), PclError> {
 ///
 /// * **cw20_sender** is cw20 token address which is being checked.
 pub fn check_cw20_in_pool(config: &Config, cw20_sender: &Addr) -> Result<(), PclError> {
-    for asset_info in &config.pair_info.asset_infos {
-        match asset_info {
-            AssetInfo::Token { contract_addr } if contract_addr == cw20_sender => return Ok(()),
-            _ => {}
-        }
+    if config
+        .pair_info
+        .asset_infos
+        .iter()
+        .any(|asset_info| asset_info.token_contract() == Some(cw20_sender))
+    {
+        Ok(())
+    } else {
+        Err(PclError::Unauthorized {})
     }
-
-    Err(PclError::Unauthorized {})
 }
 
 /// Mint LP tokens for a beneficiary and auto stake the tokens in the Incentive contract (if auto staking is specified).
@@ -130,6 +135,8 @@ pub fn get_share_in_assets(
 
 /// If `belief_price` and `max_spread` are both specified, we compute a new spread,
 /// otherwise we just use the swap spread to check `max_spread`.
+/// Delegates to [`astroport::pair::assert_max_spread`] keeping the PCL [`DEFAULT_SLIPPAGE`]
+/// of 5% if `max_spread` is not set.
 ///
 /// * **belief_price** belief price used in the swap.
 ///
@@ -140,6 +147,10 @@ pub fn get_share_in_assets(
 /// * **return_amount** amount of assets  a user wants to receive from the swap.
 ///
 /// * **spread_amount** spread used in the swap.
+#[deprecated(
+    since = "2.1.0",
+    note = "use astroport::pair::assert_max_spread with an explicit max_spread"
+)]
 pub fn assert_max_spread(
     belief_price: Option<Decimal>,
     max_spread: Option<Decimal>,
@@ -147,29 +158,19 @@ pub fn assert_max_spread(
     return_amount: Uint128,
     spread_amount: Uint128,
 ) -> Result<(), PclError> {
-    let max_spread = max_spread.map(Decimal256::from).unwrap_or(DEFAULT_SLIPPAGE);
-    if max_spread > MAX_ALLOWED_SLIPPAGE {
-        return Err(PclError::AllowedSpreadAssertion {});
-    }
-
-    if let Some(belief_price) = belief_price {
-        let expected_return = offer_amount
-            * belief_price.inv().ok_or_else(|| {
-                StdError::generic_err("Invalid belief_price. Check the input values.")
-            })?;
-
-        let spread_amount = expected_return.saturating_sub(return_amount);
-
-        if return_amount < expected_return
-            && Decimal256::from_ratio(spread_amount, expected_return) > max_spread
-        {
-            return Err(PclError::MaxSpreadAssertion {});
-        }
-    } else if Decimal256::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
-        return Err(PclError::MaxSpreadAssertion {});
-    }
+    let max_spread = match max_spread {
+        Some(max_spread) => max_spread,
+        None => try_dec256_into_dec(DEFAULT_SLIPPAGE)?,
+    };
 
-    Ok(())
+    astroport::pair::assert_max_spread(
+        belief_price,
+        Some(max_spread),
+        offer_amount,
+        return_amount,
+        spread_amount,
+    )
+    .map_err(Into::into)
 }
 
 /// Checks whether it possible to make a swap or not.
@@ -205,6 +206,7 @@ impl SwapResult {
 }
 
 /// Performs swap simulation to calculate a price.
+/// If the volatility fee is enabled, the simulation is charged with its minimum.
 pub fn calc_last_prices(xs: &[Decimal256], config: &Config, env: &Env) -> StdResult<Decimal256> {
     let mut offer_amount = Decimal256::one().min(xs[0] * OFFER_PERCENT);
     if offer_amount.is_zero() {
@@ -256,6 +258,7 @@ pub fn accumulate_prices(env: &Env, config: &mut Config, last_real_price: Decima
 }
 
 /// Calculate swap result.
+/// If the volatility fee is enabled, the swap is charged with its minimum.
 pub fn compute_swap(
     xs: &[Decimal256],
     offer_amount: Decimal256,
@@ -264,6 +267,32 @@ pub fn compute_swap(
     env: &Env,
     maker_fee_share: Decimal256,
     share_fee_share: Decimal256,
+) -> StdResult<SwapResult> {
+    compute_swap_with_prices(
+        xs,
+        offer_amount,
+        ask_ind,
+        config,
+        env,
+        maker_fee_share,
+        share_fee_share,
+        &[],
+    )
+}
+
+/// Calculate swap result.
+///
+/// * **prices** the latest observed prices. Used only if the volatility fee is enabled.
+#[allow(clippy::too_many_arguments)]
+pub fn compute_swap_with_prices(
+    xs: &[Decimal256],
+    offer_amount: Decimal256,
+    ask_ind: usize,
+    config: &Config,
+    env: &Env,
+    maker_fee_share: Decimal256,
+    share_fee_share: Decimal256,
+    prices: &[Decimal],
 ) -> StdResult<SwapResult> {
     let offer_ind = 1 ^ ask_ind;
 
@@ -291,7 +320,7 @@ pub fn compute_swap(
         offer_amount.saturating_sub(dy / config.pool_state.price_state.oracle_price)
     };
 
-    let fee_rate = config.pool_params.fee(&ixs);
+    let fee_rate = compute_swap_fee(config, &ixs, prices)?;
     let total_fee = fee_rate * dy;
     dy -= total_fee;
 
@@ -307,12 +336,27 @@ pub fn compute_swap(
 }
 
 /// Returns an amount of offer assets for a specified amount of ask assets.
+/// If the volatility fee is enabled, the swap is charged with its minimum.
 pub fn compute_offer_amount(
+    xs: &[Decimal256],
+    want_amount: Decimal256,
+    ask_ind: usize,
+    config: &Config,
+    env: &Env,
+) -> StdResult<(Decimal256, Decimal256, Decimal256)> {
+    compute_offer_amount_with_prices(xs, want_amount, ask_ind, config, env, &[])
+}
+
+/// Returns an amount of offer assets for a specified amount of ask assets.
+///
+/// * **prices** the latest observed prices. Used only if the volatility fee is enabled.
+pub fn compute_offer_amount_with_prices(
     xs: &[Decimal256],
     mut want_amount: Decimal256,
     ask_ind: usize,
     config: &Config,
     env: &Env,
+    prices: &[Decimal],
 ) -> StdResult<(Decimal256, Decimal256, Decimal256)> {
     let offer_ind = 1 ^ ask_ind;
 
@@ -326,11 +370,13 @@ pub fn compute_offer_amount(
     let amp_gamma = config.pool_state.get_amp_gamma(env);
     let d = calc_d(&ixs, &amp_gamma)?;
 
-    // It's hard to predict fee rate thus we use maximum possible fee rate
-    let before_fee = want_amount
-        * (Decimal256::one() - Decimal256::from(config.pool_params.out_fee))
-            .inv()
-            .unwrap();
+    // It's hard to predict fee rate thus we use maximum possible fee rate.
+    // The volatility fee doesn't depend on the pool balance so it is known in advance
+    let fee_rate = match &config.volatility_fee {
+        Some(volatility_fee) => volatility_fee_rate(volatility_fee, prices)?,
+        None => config.pool_params.out_fee.into(),
+    };
+    let before_fee = want_amount * (Decimal256::one() - fee_rate).inv().unwrap();
     let mut fee = before_fee - want_amount;
 
     ixs[ask_ind] -= before_fee;
@@ -348,6 +394,56 @@ pub fn compute_offer_amount(
     Ok((dy, spread_fee, fee))
 }
 
+/// Returns the swap fee rate. If the volatility fee is enabled, the rate is derived from
+/// the given prices. Otherwise, it depends on the pool balance.
+/// * `ixs` - internal repr of pools after the swap
+pub fn compute_swap_fee(
+    config: &Config,
+    ixs: &[Decimal256],
+    prices: &[Decimal],
+) -> StdResult<Decimal256> {
+    match &config.volatility_fee {
+        Some(volatility_fee) => volatility_fee_rate(volatility_fee, prices),
+        None => Ok(config.pool_params.fee(ixs)),
+    }
+}
+
+/// Linearly interpolates the fee between its bounds by the price volatility.
+/// The maximum fee is reached once the volatility hits [`VOLATILITY_FEE_SATURATION`].
+pub fn volatility_fee_rate(
+    volatility_fee: &VolatilityFeeConfig,
+    prices: &[Decimal],
+) -> StdResult<Decimal256> {
+    let min_fee = Decimal256::from_ratio(volatility_fee.min_fee_bps, 10000u16);
+    let max_fee = Decimal256::from_ratio(volatility_fee.max_fee_bps, 10000u16);
+    let fee_multiplier =
+        (calc_price_volatility(prices)? / VOLATILITY_FEE_SATURATION).min(Decimal256::one());
+
+    Ok(min_fee + max_fee.saturating_sub(min_fee) * fee_multiplier)
+}
+
+/// Calculates the price volatility as the standard deviation of the prices relative to their mean.
+/// Returns zero if there are less than two prices.
+pub fn calc_price_volatility(prices: &[Decimal]) -> StdResult<Decimal256> {
+    if prices.len() < 2 {
+        return Ok(Decimal256::zero());
+    }
+
+    let count = Decimal256::from_ratio(prices.len() as u128, 1u8);
+    let mean = prices.iter().try_fold(Decimal256::zero(), |acc, price| {
+        acc.checked_add(Decimal256::from(*price))
+    })? / count;
+    if mean.is_zero() {
+        return Ok(Decimal256::zero());
+    }
+
+    let variance = prices.iter().try_fold(Decimal256::zero(), |acc, price| {
+        acc.checked_add(Decimal256::from(*price).diff(mean).checked_pow(2)?)
+    })? / count;
+
+    Ok(variance.sqrt() / mean)
+}
+
 /// Calculate provide fee applied on the amount of LP tokens. Only charged for imbalanced provide.
 /// * `deposits` - internal repr of deposit
 /// * `xp` - internal repr of pools
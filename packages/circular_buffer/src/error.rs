@@ -26,6 +26,9 @@ pub enum BufferError {
 
     #[error("Buffer already initialized")]
     BufferAlreadyInitialized {},
+
+    #[error("Buffer capacity must be greater than zero")]
+    ZeroCapacity {},
 }
 
 impl From<BufferError> for StdError {
@@ -34,6 +34,13 @@ use crate::error::{BufferError, BufferResult};
 
 pub mod error;
 
+/// Values which carry a timestamp. Required to read values by time range with
+/// [`BufferManager::read_range`].
+pub trait Timestamped {
+    /// Returns the timestamp (in seconds) of the value
+    fn timestamp(&self) -> u64;
+}
+
 #[cw_serde]
 pub struct BufferState {
     capacity: u32,
@@ -231,6 +238,59 @@ where
         }
     }
 
+    /// Changes buffer capacity. Pending values are committed first. Stored values are laid out again
+    /// in ring order starting from index 0, thus the head points right after the latest value.
+    /// When shrinking, the oldest values which don't fit into the new capacity are dropped.
+    /// All values are read before anything is written to storage.
+    pub fn resize(&mut self, store: &mut dyn Storage, new_capacity: u32) -> BufferResult<()> {
+        if new_capacity == 0 {
+            return Err(BufferError::ZeroCapacity {});
+        }
+
+        self.commit(store)?;
+
+        let values = self.read(store, self.ring_indexes(store), false)?;
+        let dropped = values.len().saturating_sub(new_capacity as usize);
+
+        self.clear_buffer(store);
+
+        let array_key = self.store_iface.array();
+        for (ind, value) in (0u32..).zip(&values[dropped..]) {
+            array_key.save(store, ind, value)?;
+        }
+
+        self.state = BufferState {
+            capacity: new_capacity,
+            head: (values.len() - dropped) as u32 % new_capacity,
+        };
+        self.store_iface.state().save(store, &self.state)?;
+
+        Ok(())
+    }
+
+    /// Removes all stored and pending values and moves the head back to index 0.
+    /// The capacity is preserved.
+    pub fn reset(&mut self, store: &mut dyn Storage) -> BufferResult<()> {
+        self.precommit_buffer.clear();
+        self.clear_buffer(store);
+
+        self.state.head = 0;
+        self.store_iface.state().save(store, &self.state)?;
+
+        Ok(())
+    }
+
+    /// Returns indexes of the stored values starting from the oldest one.
+    fn ring_indexes(&self, store: &dyn Storage) -> Vec<u32> {
+        // If the slot at head is occupied, the buffer has wrapped and head points to the oldest value
+        let head = self.state.head;
+        if self.exists(store, head) {
+            (head..self.state.capacity).chain(0..head).collect()
+        } else {
+            (0..head).collect()
+        }
+    }
+
     /// This operation is gas consuming. However, it might be helpful in rare cases.
     pub fn clear_buffer(&self, store: &mut dyn Storage) {
         let array_key = self.store_iface.array();
@@ -245,6 +305,36 @@ where
     }
 }
 
+impl<'a, V> BufferManager<'a, V>
+where
+    V: Serialize + DeserializeOwned + Timestamped + 'a,
+{
+    /// Reads values with timestamps within the inclusive `[start_ts, end_ts]` range.
+    /// The ring is walked from the oldest value and reading stops at the first value after `end_ts`,
+    /// thus values are returned in ascending time order. Returns an empty vector if nothing is in range.
+    pub fn read_range(
+        &self,
+        store: &dyn Storage,
+        start_ts: u64,
+        end_ts: u64,
+    ) -> BufferResult<Vec<V>> {
+        let mut values = vec![];
+        for ind in self.ring_indexes(store) {
+            let Some(value) = self.read_single(store, ind)? else {
+                break;
+            };
+            if value.timestamp() > end_ts {
+                break;
+            }
+            if value.timestamp() >= start_ts {
+                values.push(value);
+            }
+        }
+
+        Ok(values)
+    }
+}
+
 impl<V: Debug> Debug for BufferManager<'_, V> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("BufferManager")
@@ -350,4 +440,134 @@ mod tests {
             .collect::<Vec<_>>();
         assert_eq!(partial_read, vec![11, 13, 15, 7, 9]);
     }
+
+    #[test]
+    fn test_resize() {
+        let mut store = MockStorage::new();
+
+        BufferManager::init(&mut store, CIRCULAR_BUFFER, 10).unwrap();
+        let mut buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+
+        let err = buffer.resize(&mut store, 0).unwrap_err();
+        assert_eq!(err, BufferError::ZeroCapacity {});
+
+        let data = (1..=15u8).map(DataType::from).collect::<Vec<_>>();
+        buffer.push_many(&data);
+        buffer.commit(&mut store).unwrap();
+
+        buffer.resize(&mut store, 20).unwrap();
+        assert_eq!(buffer.capacity(), 20);
+        assert_eq!(buffer.head(), 10);
+        assert_eq!(buffer.read_last(&store).unwrap().unwrap().u128(), 15);
+
+        let data = (16..=20u8).map(DataType::from).collect::<Vec<_>>();
+        buffer.push_many(&data);
+        buffer.commit(&mut store).unwrap();
+
+        // Ring order is preserved after the resize
+        let buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+        let saved = buffer
+            .read_all(&store)
+            .unwrap()
+            .into_iter()
+            .map(|i| i.u128())
+            .collect::<Vec<_>>();
+        assert_eq!(saved, (6..=20).collect::<Vec<_>>());
+        assert_eq!(buffer.head(), 15);
+    }
+
+    #[test]
+    fn test_resize_shrink() {
+        let mut store = MockStorage::new();
+
+        BufferManager::init(&mut store, CIRCULAR_BUFFER, 10).unwrap();
+        let mut buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+
+        let data = (1..=15u8).map(DataType::from).collect::<Vec<_>>();
+        buffer.push_many(&data);
+        buffer.commit(&mut store).unwrap();
+
+        // The oldest values are dropped
+        buffer.resize(&mut store, 4).unwrap();
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(buffer.head(), 0);
+
+        let buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+        let err = buffer.read(&store, 0u32..10, true).unwrap_err();
+        assert_eq!(err, BufferError::ReadAheadError(4));
+        let saved = buffer
+            .read_all(&store)
+            .unwrap()
+            .into_iter()
+            .map(|i| i.u128())
+            .collect::<Vec<_>>();
+        assert_eq!(saved, vec![12, 13, 14, 15]);
+        assert_eq!(buffer.read_last(&store).unwrap().unwrap().u128(), 15);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut store = MockStorage::new();
+
+        BufferManager::init(&mut store, CIRCULAR_BUFFER, 10).unwrap();
+        let mut buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+
+        let data = (1..=15u8).map(DataType::from).collect::<Vec<_>>();
+        buffer.push_many(&data);
+        buffer.commit(&mut store).unwrap();
+
+        buffer.reset(&mut store).unwrap();
+
+        let mut buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+        assert_eq!(buffer.capacity(), 10);
+        assert_eq!(buffer.head(), 0);
+        assert_eq!(buffer.read_last(&store).unwrap(), None);
+        assert_eq!(buffer.read_all(&store).unwrap(), vec![]);
+
+        let val = DataType::from(16u128);
+        buffer.instant_push(&mut store, &val).unwrap();
+        assert_eq!(buffer.read_all(&store).unwrap(), vec![val]);
+    }
+
+    impl Timestamped for DataType {
+        fn timestamp(&self) -> u64 {
+            self.u128() as u64
+        }
+    }
+
+    #[test]
+    fn test_read_range() {
+        let mut store = MockStorage::new();
+
+        BufferManager::init(&mut store, CIRCULAR_BUFFER, 10).unwrap();
+        let mut buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+        assert_eq!(buffer.read_range(&store, 0, 100).unwrap(), vec![]);
+
+        let data = (1..=5u8).map(DataType::from).collect::<Vec<_>>();
+        buffer.push_many(&data);
+        buffer.commit(&mut store).unwrap();
+
+        let read_range =
+            |store: &MockStorage, buffer: &BufferManager<DataType>, start_ts, end_ts| {
+                buffer
+                    .read_range(store, start_ts, end_ts)
+                    .unwrap()
+                    .into_iter()
+                    .map(|i| i.u128())
+                    .collect::<Vec<_>>()
+            };
+        assert_eq!(read_range(&store, &buffer, 2, 4), vec![2, 3, 4]);
+        assert_eq!(read_range(&store, &buffer, 0, 100), vec![1, 2, 3, 4, 5]);
+        assert_eq!(read_range(&store, &buffer, 6, 100), Vec::<u128>::new());
+
+        // The buffer wraps, thus the ring is read starting from the head
+        let data = (6..=15u8).map(DataType::from).collect::<Vec<_>>();
+        let mut buffer = BufferManager::new(&store, CIRCULAR_BUFFER).unwrap();
+        buffer.push_many(&data);
+        buffer.commit(&mut store).unwrap();
+
+        assert_eq!(read_range(&store, &buffer, 0, 7), vec![6, 7]);
+        assert_eq!(read_range(&store, &buffer, 9, 12), vec![9, 10, 11, 12]);
+        assert_eq!(read_range(&store, &buffer, 14, 20), vec![14, 15]);
+    }
 }